@@ -8,7 +8,9 @@ mod models;
 mod utils;
 
 use adb_client::{
-    ADBDeviceExt, ADBServer, ADBServerDevice, ADBTcpDevice, ADBUSBDevice, MDNSDiscoveryService,
+    ADBDeviceExt, ADBPairing, ADBServer, ADBServerDevice, ADBTcpDevice, ADBUSBDevice,
+    BackupOptions, MDNSAddressFamily, MDNSBrowser, MDNSBrowserBackend, MDNSEvent, PathFilter,
+    PullOptions, QrPairing, SyncDirection, TcpConnectOptions,
 };
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
@@ -22,7 +24,8 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
-use utils::setup_logger;
+use std::time::Duration;
+use utils::{print_progress, setup_logger};
 
 fn main() -> Result<()> {
     let opts = Opts::parse();
@@ -55,12 +58,20 @@ fn main() -> Result<()> {
             }
         }
         MainCommand::Usb(usb_command) => {
-            let device = match (usb_command.vendor_id, usb_command.product_id) {
-                (Some(vid), Some(pid)) => match usb_command.path_to_private_key {
+            let device = match (
+                usb_command.serial,
+                usb_command.vendor_id,
+                usb_command.product_id,
+            ) {
+                (Some(serial), _, _) => match usb_command.path_to_private_key {
+                    Some(pk) => ADBUSBDevice::new_from_serial_with_custom_private_key(&serial, pk)?,
+                    None => ADBUSBDevice::new_from_serial(&serial)?,
+                },
+                (None, Some(vid), Some(pid)) => match usb_command.path_to_private_key {
                     Some(pk) => ADBUSBDevice::new_with_custom_private_key(vid, pid, pk)?,
                     None => ADBUSBDevice::new(vid, pid)?,
                 },
-                (None, None) => match usb_command.path_to_private_key {
+                (None, None, None) => match usb_command.path_to_private_key {
                     Some(pk) => ADBUSBDevice::autodetect_with_custom_private_key(pk)?,
                     None => ADBUSBDevice::autodetect()?,
                 },
@@ -73,25 +84,78 @@ fn main() -> Result<()> {
             (device.boxed(), usb_command.commands)
         }
         MainCommand::Tcp(tcp_command) => {
-            let device = ADBTcpDevice::new(tcp_command.address)?;
+            let connect_options = TcpConnectOptions {
+                proxy: tcp_command.proxy,
+                ..Default::default()
+            };
+            let device = ADBTcpDevice::new_with_options(tcp_command.address, connect_options)?;
             (device.boxed(), tcp_command.commands)
         }
-        MainCommand::Mdns => {
-            let mut service = MDNSDiscoveryService::new()?;
-
-            let (tx, rx) = std::sync::mpsc::channel();
-            service.start(tx)?;
+        #[cfg(all(feature = "vsock", target_os = "linux"))]
+        MainCommand::Vsock(vsock_command) => {
+            let device = adb_client::ADBVsockDevice::new(vsock_command.cid, vsock_command.port)?;
+            (device.boxed(), vsock_command.commands)
+        }
+        MainCommand::Mdns {
+            timeout,
+            interfaces,
+            ipv4,
+            ipv6,
+            server,
+        } => {
+            let interfaces: Vec<&str> = interfaces.iter().map(String::as_str).collect();
+            let mut browser = MDNSBrowser::new()
+                .with_interfaces(&interfaces)
+                .with_address_family(match (ipv4, ipv6) {
+                    (true, _) => MDNSAddressFamily::IPv4,
+                    (_, true) => MDNSAddressFamily::IPv6,
+                    _ => MDNSAddressFamily::Any,
+                });
+            if server {
+                browser = browser.with_backend(MDNSBrowserBackend::Server(None));
+            }
+            if let Some(timeout) = timeout {
+                browser = browser.with_timeout(Duration::from_secs(timeout));
+            }
 
             log::info!("Starting mdns discovery...");
-            while let Ok(device) = rx.recv() {
-                log::info!(
-                    "Found device {} with addresses {:?}",
-                    device.fullname,
-                    device.addresses
-                )
+            for event in browser.browse()? {
+                match event? {
+                    MDNSEvent::Added(device) => log::info!(
+                        "Found {:?} device {} at {:?}, port {}",
+                        device.service_type,
+                        device.name(),
+                        device.addresses,
+                        device.port
+                    ),
+                    MDNSEvent::Updated(device) => log::info!(
+                        "Updated {:?} device {} at {:?}, port {}",
+                        device.service_type,
+                        device.name(),
+                        device.addresses,
+                        device.port
+                    ),
+                    MDNSEvent::Removed(service_type, fullname) => {
+                        log::info!("Lost {service_type:?} device {fullname}")
+                    }
+                }
             }
 
-            return Ok(service.shutdown()?);
+            return Ok(());
+        }
+        MainCommand::Pair { address, code } => {
+            let guid = ADBPairing::new(address)?.pair(&code)?;
+            log::info!("Paired with device {guid}");
+            return Ok(());
+        }
+        MainCommand::PairQr { timeout } => {
+            let pairing = QrPairing::new();
+            // Payload is meant to be piped to a QR code generator, e.g. `qrencode -t ansiutf8`
+            println!("{}", pairing.payload());
+            log::info!("Waiting for device to scan QR code...");
+            let guid = pairing.pair(Duration::from_secs(timeout))?;
+            log::info!("Paired with device {guid}");
+            return Ok(());
         }
     };
 
@@ -119,36 +183,197 @@ fn main() -> Result<()> {
         DeviceCommands::Pull {
             source,
             destination,
+            sparse,
+            recursive,
+            include,
+            exclude,
+            progress,
+            compression,
+            symlinks,
+            preserve_times,
         } => {
-            let mut output = File::create(Path::new(&destination))?;
-            device.pull(&source, &mut output)?;
+            let options = PullOptions {
+                symlinks: symlinks.unwrap_or_default(),
+                preserve_times,
+            };
+            if recursive {
+                let filter = PathFilter { include, exclude };
+                let stats =
+                    device.pull_dir_with_options(&source, &destination, &filter, &options)?;
+                log::info!("Pulled {} bytes", stats.bytes);
+            } else if sparse {
+                device.pull_sparse(&source, &destination)?;
+            } else if symlinks.is_some() || preserve_times {
+                device.pull_path(&source, &destination, &options)?;
+            } else {
+                let mut output = File::create(Path::new(&destination))?;
+                if let Some(compression) = compression {
+                    device.pull_compressed(&source, &mut output, compression)?;
+                } else if progress {
+                    device.pull_with_progress(&source, &mut output, &mut print_progress)?;
+                    eprintln!();
+                } else {
+                    device.pull(&source, &mut output)?;
+                }
+            }
             log::info!("Downloaded {source} as {destination}");
         }
         DeviceCommands::Stat { path } => {
-            let stat_response = device.stat(&path)?;
+            let stat_response = device.stat_v2(&path)?;
             println!("{}", stat_response);
         }
+        DeviceCommands::List { path } => {
+            for entry in device.list_dir(&path)? {
+                println!("{entry}");
+            }
+        }
         DeviceCommands::Reboot { reboot_type } => {
             log::info!("Reboots device in mode {:?}", reboot_type);
             device.reboot(reboot_type.into())?
         }
-        DeviceCommands::Push { filename, path } => {
-            let mut input = File::open(Path::new(&filename))?;
-            device.push(&mut input, &path)?;
-            log::info!("Uploaded {filename} to {path}");
+        DeviceCommands::Push {
+            filename,
+            path,
+            progress,
+            compression,
+        } => {
+            let stats = if Path::new(&filename).is_dir() {
+                device.push_dir(&filename, &path)?
+            } else if let Some(compression) = compression {
+                let mut input = File::open(Path::new(&filename))?;
+                device.push_compressed(&mut input, &path, 0o777, compression)?
+            } else if progress {
+                let mut input = File::open(Path::new(&filename))?;
+                let total = input.metadata()?.len();
+                let stats = device.push_with_progress(
+                    &mut input,
+                    &path,
+                    Some(total),
+                    &mut print_progress,
+                )?;
+                eprintln!();
+                stats
+            } else {
+                device.push_file(&filename, &path)?
+            };
+            log::info!(
+                "Uploaded {filename} to {path} ({} bytes at {:.0} B/s)",
+                stats.bytes,
+                stats.throughput()
+            );
+        }
+        DeviceCommands::Sync {
+            local,
+            remote,
+            pull,
+        } => {
+            let direction = match pull {
+                true => SyncDirection::Pull,
+                false => SyncDirection::Push,
+            };
+            let stats = device.sync_dir(&local, &remote, direction)?;
+            log::info!(
+                "Synchronized {} and {remote} ({} bytes transferred)",
+                local.display(),
+                stats.bytes
+            );
         }
         DeviceCommands::Run { package, activity } => {
             let output = device.run_activity(&package, &activity)?;
             std::io::stdout().write_all(&output)?;
         }
-        DeviceCommands::Install { path } => {
+        DeviceCommands::Root => {
+            device.root()?;
+            log::info!("adbd is running as root");
+        }
+        DeviceCommands::Unroot => {
+            device.unroot()?;
+            log::info!("adbd is not running as root");
+        }
+        DeviceCommands::Remount { reboot } => {
+            if device.remount(reboot)? && !reboot {
+                log::warn!("Reboot device for remount to take effect");
+            }
+        }
+        DeviceCommands::DisableVerity { reboot } => {
+            if device.disable_verity(reboot)? && !reboot {
+                log::warn!("Reboot device for disabling verity to take effect");
+            }
+        }
+        DeviceCommands::EnableVerity { reboot } => {
+            if device.enable_verity(reboot)? && !reboot {
+                log::warn!("Reboot device for enabling verity to take effect");
+            }
+        }
+        DeviceCommands::Tcpip { port } => {
+            let port = device.tcpip(port)?;
+            log::info!("adbd is listening on TCP port {port}");
+        }
+        DeviceCommands::Usb => {
+            device.usb()?;
+            log::info!("adbd is listening on USB only");
+        }
+        DeviceCommands::Install {
+            path,
+            incremental,
+            flags,
+        } => {
             log::info!("Starting installation of APK {}...", path.display());
-            device.install(&path)?;
+            if incremental {
+                let install = device.install_incremental(&path, &flags.into())?;
+                if !install.is_finished() {
+                    log::info!("Streaming remaining blocks of APK...");
+                }
+                install.wait()?;
+            } else {
+                device.install_with_options(&path, &flags.into())?;
+            }
+        }
+        DeviceCommands::InstallMultiple { paths, flags } => {
+            log::info!("Starting installation of {} APKs...", paths.len());
+            device.install_multiple(&paths, &flags.into())?;
+        }
+        DeviceCommands::Sideload { path } => {
+            log::info!("Sideloading package {}...", path.display());
+            let stats = device.sideload(&path)?;
+            log::info!(
+                "Sideloaded {} ({} bytes at {:.0} B/s)",
+                path.display(),
+                stats.bytes,
+                stats.throughput()
+            );
         }
         DeviceCommands::Uninstall { package } => {
             log::info!("Uninstalling the package {}...", package);
             device.uninstall(&package)?;
         }
+        DeviceCommands::Backup {
+            path,
+            packages,
+            all,
+            apk,
+            shared,
+        } => {
+            let options = BackupOptions {
+                packages,
+                all,
+                apk,
+                shared,
+            };
+            let mut output = File::create(&path)?;
+            log::info!("Confirm backup on device...");
+            device.backup(&options, &mut output)?;
+            log::info!("Backup written to {}", path.display());
+        }
+        DeviceCommands::Restore { path } => {
+            let mut input = File::open(&path)?;
+            log::info!("Confirm restoration on device...");
+            device.restore(&mut input)?;
+        }
+        DeviceCommands::Screenshot { path } => {
+            std::fs::write(&path, device.screenshot_png()?)?;
+            log::info!("Successfully saved screenshot at path {}", path.display());
+        }
         DeviceCommands::Framebuffer { path } => {
             device.framebuffer(&path)?;
             log::info!("Successfully dumped framebuffer at path {path}");
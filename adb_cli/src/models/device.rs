@@ -1,19 +1,74 @@
 use std::path::PathBuf;
 
+use adb_client::{RustADBError, SymlinkPolicy, SyncCompression};
 use clap::Parser;
 
-use super::RebootTypeCommand;
+use super::{InstallFlags, RebootTypeCommand};
+
+fn parse_symlink_policy(value: &str) -> Result<SymlinkPolicy, RustADBError> {
+    SymlinkPolicy::try_from(value)
+}
+
+fn parse_sync_compression(value: &str) -> Result<SyncCompression, RustADBError> {
+    SyncCompression::try_from(value)
+}
 
 #[derive(Parser, Debug)]
 pub enum DeviceCommands {
     /// Spawn an interactive shell or run a list of commands on the device
     Shell { commands: Vec<String> },
     /// Pull a file from device
-    Pull { source: String, destination: String },
-    /// Push a file on device
-    Push { filename: String, path: String },
+    Pull {
+        source: String,
+        destination: String,
+        /// Do not write blocks only made of zeros, creating a sparse file (e.g. for partition images)
+        #[clap(long = "sparse")]
+        sparse: bool,
+        /// Pull a directory and all its content
+        #[clap(short = 'r', long = "recursive")]
+        recursive: bool,
+        /// Only pull files matching this pattern (e.g. "*.jpg"), when pulling a directory
+        #[clap(long = "include")]
+        include: Vec<String>,
+        /// Skip files and directories matching this pattern, when pulling a directory
+        #[clap(long = "exclude")]
+        exclude: Vec<String>,
+        /// Display transfer progress, when pulling a single file
+        #[clap(long = "progress")]
+        progress: bool,
+        /// Pull a single file using sync protocol v2, compressed using any, none, brotli, lz4 or zstd
+        #[clap(short = 'z', long = "compression", value_parser = parse_sync_compression, conflicts_with = "progress")]
+        compression: Option<SyncCompression>,
+        /// Follow, recreate or skip symbolic links, which are skipped by default when pulling a directory
+        #[clap(long = "symlinks", value_parser = parse_symlink_policy)]
+        symlinks: Option<SymlinkPolicy>,
+        /// Preserve modification time of pulled files
+        #[clap(short = 'a', long = "preserve-times")]
+        preserve_times: bool,
+    },
+    /// Push a file or a directory on device
+    Push {
+        filename: String,
+        path: String,
+        /// Display transfer progress, when pushing a single file
+        #[clap(long = "progress")]
+        progress: bool,
+        /// Push a single file using sync protocol v2, compressed using any, none, brotli, lz4 or zstd
+        #[clap(short = 'z', long = "compression", value_parser = parse_sync_compression, conflicts_with = "progress")]
+        compression: Option<SyncCompression>,
+    },
+    /// Only push files of a local directory which changed since last synchronization, like `adb sync`
+    Sync {
+        local: PathBuf,
+        remote: String,
+        /// Pull changed files of remote directory instead
+        #[clap(long = "pull")]
+        pull: bool,
+    },
     /// Stat a file on device
     Stat { path: String },
+    /// List a directory on device
+    List { path: String },
     /// Run an activity on device specified by the intent
     Run {
         /// The package whose activity is to be invoked
@@ -28,16 +83,90 @@ pub enum DeviceCommands {
         #[clap(subcommand)]
         reboot_type: RebootTypeCommand,
     },
+    /// Restart adbd with root permissions
+    Root,
+    /// Restart adbd without root permissions
+    Unroot,
+    /// Remount system partitions read-write, adbd running as root
+    Remount {
+        /// Reboot device if required for remount to take effect
+        #[clap(short = 'R', long = "reboot")]
+        reboot: bool,
+    },
+    /// Disable dm-verity on system partitions
+    DisableVerity {
+        /// Reboot device if required for change to take effect
+        #[clap(short = 'R', long = "reboot")]
+        reboot: bool,
+    },
+    /// Enable dm-verity on system partitions
+    EnableVerity {
+        /// Reboot device if required for change to take effect
+        #[clap(short = 'R', long = "reboot")]
+        reboot: bool,
+    },
+    /// Restart adbd listening on TCP port
+    Tcpip {
+        /// Port adbd listens on
+        #[clap(default_value_t = 5555)]
+        port: u16,
+    },
+    /// Restart adbd listening on USB only
+    Usb,
     /// Install an APK on device
     Install {
         /// Path to APK file. Extension must be ".apk"
         path: PathBuf,
+        /// Install incrementally, using v4 signature file next to APK, falling back to a regular installation
+        #[clap(long = "incremental")]
+        incremental: bool,
+        #[clap(flatten)]
+        flags: InstallFlags,
+    },
+    /// Install several APKs together, such as an app split into a base APK and its splits
+    InstallMultiple {
+        /// Paths to APK files. Extensions must be ".apk"
+        #[clap(required = true)]
+        paths: Vec<PathBuf>,
+        #[clap(flatten)]
+        flags: InstallFlags,
+    },
+    /// Sideload an OTA package on device, which must be in sideload mode
+    Sideload {
+        /// Path to OTA package
+        path: PathBuf,
     },
     /// Uninstall a package from the device
     Uninstall {
         /// Name of the package to uninstall
         package: String,
     },
+    /// Back up packages of device into an `adb backup` archive
+    Backup {
+        /// Archive destination path
+        path: PathBuf,
+        /// Packages to back up
+        packages: Vec<String>,
+        /// Back up all installed packages
+        #[clap(long = "all")]
+        all: bool,
+        /// Include APKs of packages
+        #[clap(long = "apk")]
+        apk: bool,
+        /// Include content of shared storage
+        #[clap(long = "shared")]
+        shared: bool,
+    },
+    /// Restore an `adb backup` archive on device
+    Restore {
+        /// Archive path
+        path: PathBuf,
+    },
+    /// Capture screen of device as a PNG image, using framebuffer if `screencap` fails
+    Screenshot {
+        /// PNG image destination path
+        path: PathBuf,
+    },
     /// Dump framebuffer of device
     Framebuffer {
         /// Framebuffer image destination path
@@ -0,0 +1,45 @@
+use adb_client::InstallOptions;
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct InstallFlags {
+    /// Replace app if already installed, keeping its data
+    #[clap(short = 'r', long = "replace")]
+    replace: bool,
+    /// Allow version code downgrade, only for debuggable apps
+    #[clap(short = 'd', long = "downgrade")]
+    downgrade: bool,
+    /// Grant all runtime permissions listed in app manifest
+    #[clap(short = 'g', long = "grant")]
+    grant: bool,
+    /// Allow installing test only apps
+    #[clap(short = 't', long = "test")]
+    test: bool,
+    /// Install as an instant app
+    #[clap(long = "instant")]
+    instant: bool,
+    /// Stage installation, applied on next reboot
+    #[clap(long = "staged")]
+    staged: bool,
+    /// Install for this user only
+    #[clap(long = "user")]
+    user: Option<u32>,
+    /// Package name recorded as installer of app
+    #[clap(short = 'i', long = "installer")]
+    installer: Option<String>,
+}
+
+impl From<InstallFlags> for InstallOptions {
+    fn from(value: InstallFlags) -> Self {
+        InstallOptions {
+            replace: value.replace,
+            allow_downgrade: value.downgrade,
+            grant_permissions: value.grant,
+            allow_test: value.test,
+            instant: value.instant,
+            staged: value.staged,
+            user: value.user,
+            installer_package_name: value.installer,
+        }
+    }
+}
@@ -1,17 +1,23 @@
 mod device;
 mod emu;
 mod host;
+mod install_flags;
 mod local;
 mod opts;
 mod reboot_type;
 mod tcp;
 mod usb;
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+mod vsock;
 
 pub use device::DeviceCommands;
 pub use emu::{EmuCommand, EmulatorCommand};
 pub use host::{HostCommand, MdnsCommand};
+pub use install_flags::InstallFlags;
 pub use local::{LocalCommand, LocalDeviceCommand};
 pub use opts::{MainCommand, Opts, ServerCommand};
 pub use reboot_type::RebootTypeCommand;
 pub use tcp::TcpCommand;
 pub use usb::UsbCommand;
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+pub use vsock::VsockCommand;
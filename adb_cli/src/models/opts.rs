@@ -1,7 +1,9 @@
-use std::net::SocketAddrV4;
+use std::net::{SocketAddr, SocketAddrV4};
 
 use clap::{Parser, Subcommand};
 
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+use super::VsockCommand;
 use super::{EmulatorCommand, HostCommand, LocalCommand, TcpCommand, UsbCommand};
 
 #[derive(Debug, Parser)]
@@ -25,8 +27,40 @@ pub enum MainCommand {
     Usb(UsbCommand),
     /// TCP device related commands
     Tcp(TcpCommand),
-    /// MDNS discovery related commands
-    Mdns,
+    /// Vsock device related commands (e.g. Cuttlefish virtual machines)
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    Vsock(VsockCommand),
+    /// Continuously browse for devices advertised over MDNS
+    Mdns {
+        /// Stop browsing after given number of seconds
+        #[clap(long = "timeout")]
+        timeout: Option<u64>,
+        /// Only browse on given network interface, can be repeated
+        #[clap(short = 'i', long = "interface")]
+        interfaces: Vec<String>,
+        /// Only browse over IPv4
+        #[clap(short = '4', long = "ipv4", conflicts_with = "ipv6")]
+        ipv4: bool,
+        /// Only browse over IPv6
+        #[clap(short = '6', long = "ipv6")]
+        ipv6: bool,
+        /// Get services discovered by local ADB server instead of browsing from this process
+        #[clap(long = "server")]
+        server: bool,
+    },
+    /// Pair with a device in wireless debugging mode, without ADB server
+    Pair {
+        /// Address of pairing service displayed by device (e.g. 192.168.1.23:37851)
+        address: SocketAddr,
+        /// Pairing code displayed by device
+        code: String,
+    },
+    /// Print a QR code payload to scan with a device in wireless debugging mode, and pair with it once scanned
+    PairQr {
+        /// Maximum time to wait for device to scan QR code, in seconds
+        #[clap(long = "timeout", default_value = "120")]
+        timeout: u64,
+    },
 }
 
 #[derive(Debug, Parser)]
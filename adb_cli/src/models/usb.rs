@@ -17,6 +17,9 @@ pub struct UsbCommand {
     /// Hexadecimal product id of this USB device
     #[clap(short = 'p', long = "product-id", value_parser=parse_hex_id, value_name="PID")]
     pub product_id: Option<u16>,
+    /// Serial number of this USB device, to tell apart several identical devices
+    #[clap(short = 's', long = "serial", conflicts_with_all = ["vendor_id", "product_id"])]
+    pub serial: Option<String>,
     /// Path to a custom private key to use for authentication
     #[clap(short = 'k', long = "private-key")]
     pub path_to_private_key: Option<PathBuf>,
@@ -0,0 +1,14 @@
+use clap::Parser;
+
+use super::DeviceCommands;
+
+#[derive(Parser, Debug)]
+pub struct VsockCommand {
+    /// Context identifier of virtual machine (e.g. 3 for first Cuttlefish instance)
+    pub cid: u32,
+    /// Port adbd listens on in virtual machine
+    #[clap(default_value = "5555")]
+    pub port: u32,
+    #[clap(subcommand)]
+    pub commands: DeviceCommands,
+}
@@ -11,3 +11,14 @@ pub fn setup_logger(debug: bool) {
 
     env_logger::init();
 }
+
+/// Display progress of a file transfer on a single, constantly rewritten, line of standard error
+pub fn print_progress(path: &str, transferred: u64, total: Option<u64>) {
+    match total {
+        Some(total) if total > 0 => eprint!(
+            "\r{path}: {transferred}/{total} bytes ({}%)",
+            transferred * 100 / total
+        ),
+        _ => eprint!("\r{path}: {transferred} bytes"),
+    }
+}
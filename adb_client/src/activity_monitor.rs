@@ -0,0 +1,210 @@
+use std::io::{BufRead, BufReader, Write};
+
+use crate::event_stream::EventSender;
+use crate::{ReadWriteStream, Result};
+
+/// Activity manager event, as reported by [`crate::ADBDeviceExt::monitor_activities`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityEvent {
+    /// An activity of `package` is starting
+    Starting {
+        /// Package of the activity
+        package: String,
+    },
+    /// An activity of `package` is resuming
+    Resuming {
+        /// Package of the activity
+        package: String,
+    },
+    /// Process crashed with an uncaught exception
+    Crashed {
+        /// Name of crashed process
+        process: String,
+        /// Identifier of crashed process
+        pid: Option<u32>,
+        /// Exception message (e.g. `java.lang.RuntimeException: message`)
+        message: String,
+        /// Stack trace of exception
+        stack_trace: String,
+    },
+    /// Process is not responding
+    NotResponding {
+        /// Name of process not responding
+        process: String,
+        /// Identifier of process not responding
+        pid: Option<u32>,
+        /// CPU usage of processes when not responding was detected
+        process_stats: String,
+    },
+}
+
+/// Error report being read from `am monitor` output
+#[derive(Debug, Default)]
+struct Report {
+    crash: bool,
+    process: String,
+    pid: Option<u32>,
+    message: String,
+    details: String,
+    /// Multi-line details (`stack:` or `processStats:`) are being read, up to a `#` line
+    in_details: bool,
+}
+
+impl Report {
+    fn into_event(self) -> ActivityEvent {
+        if self.crash {
+            ActivityEvent::Crashed {
+                process: self.process,
+                pid: self.pid,
+                message: self.message,
+                stack_trace: self.details,
+            }
+        } else {
+            ActivityEvent::NotResponding {
+                process: self.process,
+                pid: self.pid,
+                process_stats: self.details,
+            }
+        }
+    }
+}
+
+/// Parser of `am monitor` output, fed line by line.
+#[derive(Debug, Default)]
+struct MonitorParser {
+    report: Option<Report>,
+}
+
+impl MonitorParser {
+    /// Handle `line` of output, returning event it completes if any
+    fn feed(&mut self, line: &str) -> Option<ActivityEvent> {
+        if let Some(report) = self.report.as_mut().filter(|report| report.in_details) {
+            if line == "#" {
+                return self.report.take().map(Report::into_event);
+            }
+            report.details.push_str(line);
+            report.details.push('\n');
+            return None;
+        }
+
+        if let Some(package) = line.strip_prefix("** Activity starting: ") {
+            return Some(ActivityEvent::Starting {
+                package: package.to_string(),
+            });
+        }
+        if let Some(package) = line.strip_prefix("** Activity resuming: ") {
+            return Some(ActivityEvent::Resuming {
+                package: package.to_string(),
+            });
+        }
+        if line.starts_with("**") {
+            // Early ANR reports are ignored, a full report follows them
+            self.report = match line {
+                "** ERROR: PROCESS CRASHED" => Some(Report {
+                    crash: true,
+                    ..Default::default()
+                }),
+                "** ERROR: PROCESS NOT RESPONDING" => Some(Report::default()),
+                _ => None,
+            };
+            return None;
+        }
+
+        let report = self.report.as_mut()?;
+        match line.split_once(':').map(|(key, value)| (key, value.trim())) {
+            Some(("processName", value)) => report.process = value.to_string(),
+            Some(("processPid", value)) => report.pid = value.parse().ok(),
+            Some(("shortMsg", value)) => report.message = value.to_string(),
+            Some(("stack" | "processStats", _)) => report.in_details = true,
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Read events from `am monitor` running in `stream`, sending them to `sender`.
+pub(crate) fn monitor_activities(
+    stream: Box<dyn ReadWriteStream>,
+    sender: &EventSender<ActivityEvent>,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut parser = MonitorParser::default();
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        if let Some(event) = parser.feed(line) {
+            sender.send(event)?;
+        }
+        // Activity manager waits for a command after crashes and ANRs, let it handle them as usual
+        if line.starts_with("Waiting after") {
+            let stream = reader.get_mut();
+            stream.write_all(b"c\n")?;
+            stream.flush()?;
+        }
+    }
+}
+
+#[test]
+fn test_monitor_parser() {
+    let output = "Monitoring activity manager...  available commands:\n\
+        ** Activity starting: com.example\n\
+        ** Activity resuming: com.example\n\
+        ** ERROR: PROCESS CRASHED\n\
+        processName: com.example\n\
+        processPid: 1234\n\
+        shortMsg: java.lang.RuntimeException: boom\n\
+        longMsg: java.lang.RuntimeException: boom\n\
+        timeMillis: 1700000000000\n\
+        stack:\n\
+        java.lang.RuntimeException: boom\n\
+        \tat com.example.MainActivity.onCreate(MainActivity.java:12)\n\
+        #\n\
+        Waiting after crash...  available commands:\n\
+        ** ERROR: EARLY PROCESS NOT RESPONDING\n\
+        processName: com.example\n\
+        processPid: 1234\n\
+        annotation: Input dispatching timed out\n\
+        ** ERROR: PROCESS NOT RESPONDING\n\
+        processName: com.example\n\
+        processPid: 1234\n\
+        processStats:\n\
+        CPU usage from 0ms to 5000ms later\n\
+        #\n";
+
+    let mut parser = MonitorParser::default();
+    let events: Vec<_> = output
+        .lines()
+        .filter_map(|line| parser.feed(line))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            ActivityEvent::Starting {
+                package: "com.example".to_string()
+            },
+            ActivityEvent::Resuming {
+                package: "com.example".to_string()
+            },
+            ActivityEvent::Crashed {
+                process: "com.example".to_string(),
+                pid: Some(1234),
+                message: "java.lang.RuntimeException: boom".to_string(),
+                stack_trace: "java.lang.RuntimeException: boom\n\
+                    \tat com.example.MainActivity.onCreate(MainActivity.java:12)\n"
+                    .to_string(),
+            },
+            ActivityEvent::NotResponding {
+                process: "com.example".to_string(),
+                pid: Some(1234),
+                process_stats: "CPU usage from 0ms to 5000ms later\n".to_string(),
+            },
+        ]
+    );
+}
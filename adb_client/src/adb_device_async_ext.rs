@@ -0,0 +1,113 @@
+use std::future::Future;
+use std::io::Cursor;
+
+use futures_lite::{AsyncRead, AsyncWrite};
+use image::{ImageBuffer, ImageFormat, Rgba};
+
+use crate::models::AdbStatResponse;
+use crate::{InstallOptions, RebootType, Result};
+
+/// Asynchronous counterpart of [`crate::ADBDeviceExt`].
+///
+/// Returned futures are `Send`, so they can be spawned on multi-threaded executors (e.g. `tokio`) without wrapping every call in a blocking task.
+pub trait ADBDeviceAsyncExt: Send {
+    /// Runs command in a shell on the device, and write its output and error streams into output.
+    fn shell_command(
+        &mut self,
+        command: &[&str],
+        output: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Starts an interactive shell session on the device.
+    /// Input data is read from reader and write to writer.
+    ///
+    /// Session ends when either `reader` reaches EOF or the remote shell exits.
+    fn shell(
+        &mut self,
+        reader: &mut (dyn AsyncRead + Unpin + Send),
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Display the stat information for a remote file
+    fn stat(&mut self, remote_path: &str) -> impl Future<Output = Result<AdbStatResponse>> + Send;
+
+    /// Pull the remote file pointed to by `source` and write its contents into `output`
+    fn pull(
+        &mut self,
+        source: &str,
+        output: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Push `stream` to `path` on the device.
+    fn push(
+        &mut self,
+        stream: &mut (dyn AsyncRead + Unpin + Send),
+        path: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Reboot the device using given reboot type
+    fn reboot(&mut self, reboot_type: RebootType) -> impl Future<Output = Result<()>> + Send;
+
+    /// Run `activity` from `package` on device. Return the command output.
+    fn run_activity(
+        &mut self,
+        package: &str,
+        activity: &str,
+    ) -> impl Future<Output = Result<Vec<u8>>> + Send {
+        async move {
+            let mut output = Vec::new();
+            self.shell_command(
+                &["am", "start", &format!("{package}/{package}.{activity}")],
+                &mut output,
+            )
+            .await?;
+
+            Ok(output)
+        }
+    }
+
+    /// Install an APK read from `apk` on device. `size` must be the exact length in bytes of the APK.
+    ///
+    /// Unlike [`crate::ADBDeviceExt::install`], no path is taken as opening local files is left to the caller's runtime.
+    fn install(
+        &mut self,
+        apk: &mut (dyn AsyncRead + Unpin + Send),
+        size: u64,
+    ) -> impl Future<Output = Result<()>> + Send {
+        async move {
+            self.install_with_options(apk, size, &InstallOptions::default())
+                .await
+        }
+    }
+
+    /// Install an APK of `size` bytes read from `apk` on device, passing `options` to package manager.
+    ///
+    /// APK is streamed as it is read, e.g. straight from an HTTP response body, and reading stops after `size` bytes.
+    fn install_with_options(
+        &mut self,
+        apk: &mut (dyn AsyncRead + Unpin + Send),
+        size: u64,
+        options: &InstallOptions,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Uninstall the package `package` from device.
+    fn uninstall(&mut self, package: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Inner method requesting framebuffer from an Android device
+    fn framebuffer_inner(
+        &mut self,
+    ) -> impl Future<Output = Result<ImageBuffer<Rgba<u8>, Vec<u8>>>> + Send;
+
+    /// Dump framebuffer of this device and return corresponding bytes.
+    ///
+    /// Output data format is currently only `PNG`.
+    fn framebuffer_bytes(&mut self) -> impl Future<Output = Result<Vec<u8>>> + Send {
+        async move {
+            let img = self.framebuffer_inner().await?;
+            let mut vec = Cursor::new(Vec::new());
+            img.write_to(&mut vec, ImageFormat::Png)?;
+
+            Ok(vec.into_inner())
+        }
+    }
+}
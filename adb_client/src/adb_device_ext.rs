@@ -1,28 +1,356 @@
-use std::io::{Cursor, Read, Write};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, Write};
 use std::path::Path;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
-use image::{ImageBuffer, ImageFormat, Rgba};
+use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba};
 
-use crate::models::AdbStatResponse;
-use crate::{RebootType, Result};
+use crate::activity_monitor::{ActivityEvent, monitor_activities};
+use crate::adbd_restart::{tcpip, usb};
+#[cfg(any(feature = "tcp", feature = "usb"))]
+use crate::app_export::{apk_paths, export_app, import_app};
+#[cfg(any(feature = "tcp", feature = "usb"))]
+use crate::bundle::install_bundle;
+use crate::framebuffer_stream::FramebufferStream;
+use crate::getevent::{GETEVENT_SERVICE, InputEvent, read_input_events};
+#[cfg(any(feature = "tcp", feature = "usb"))]
+use crate::incremental_install::{IncrementalInstall, install_incremental};
+#[cfg(any(feature = "tcp", feature = "usb"))]
+use crate::install_session::install_multiple;
+use crate::jdwp::{TRACK_JDWP_SERVICE, track_jdwp};
+use crate::list_dir::list_dir;
+use crate::logcat::{LOGCAT_SERVICE, LogcatFilter, read_logcat};
+use crate::models::{
+    ActivityLaunch, AdbStatResponse, AdbStatV2Response, BackupOptions, BatteryStats, DirEntry,
+    InstallOptions, Intent, LogcatEntry, PathFilter, ProcessInfo, PullOptions, RawFramebuffer,
+    ScreenRecordOptions, ShellOptions, ShellOutput, Signal, SyncCompression, SyncDirection,
+    ThermalInfo, TransferStats, UidTraffic, VolumeUsage,
+};
+use crate::obb::{OBB_DIRECTORY, check_package_name, is_obb_file_name, parse_version_code};
+use crate::path_watcher::PathWatcher;
+use crate::process_kill::{kill_pid, pkill};
+use crate::progress::{CallbackReader, CallbackWriter};
+use crate::pull_dir::{pull_dir, pull_path};
+use crate::push_dir::{push_dir, push_file};
+use crate::push_resume::push_resume;
+use crate::screen_record::record_screen;
+use crate::screenshot::screencap;
+use crate::shell_protocol::{legacy_run_command, legacy_shell_command_output};
+use crate::sparse_file::SparseFileWriter;
+use crate::stat_v2::stat_v2;
+use crate::sync_dir::sync_dir;
+use crate::sync_v2::{pull_compressed, push_compressed};
+use crate::ui_automation::{UiNode, UiSelector, ui_dump, wait_for};
+use crate::{CancelToken, EventStream, RebootType, Result, RustADBError};
+
+/// Bidirectional byte stream, as returned by [`ADBDeviceExt::open_service`].
+pub trait ReadWriteStream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> ReadWriteStream for T {}
+
+/// Seekable source of bytes, as read by [`ADBDeviceExt::sideload_from_reader`].
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
 
 /// Trait representing all features available on both [`crate::ADBServerDevice`] and [`crate::ADBUSBDevice`]
 pub trait ADBDeviceExt {
     /// Runs command in a shell on the device, and write its output and error streams into output.
     fn shell_command(&mut self, command: &[&str], output: &mut dyn Write) -> Result<()>;
 
+    /// Runs command in a shell on the device like [`ADBDeviceExt::shell_command`], for at most `timeout`.
+    ///
+    /// If command is still running once `timeout` expired, its stream is closed and remote process group is killed.
+    /// Fails with an [`std::io::ErrorKind::TimedOut`] error in that case, output received so far being kept.
+    fn shell_command_with_timeout(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<()>;
+
+    /// Runs command in a shell on the device like [`ADBDeviceExt::shell_command`], writing its output and error
+    /// streams as requested by `output`, whatever the connection type.
+    ///
+    /// Devices supporting `shell,v2` send both streams separately. Legacy `shell:` service merges them: when they are to
+    /// be kept separate, error stream is redirected to a temporary file on device, which is written into `stderr` once
+    /// command ended.
+    fn shell_command_output(&mut self, command: &[&str], output: ShellOutput<'_>) -> Result<()> {
+        legacy_shell_command_output(self, command, output)
+    }
+
+    /// Runs command in a shell on the device like [`ADBDeviceExt::shell_command`], and return its exit code.
+    ///
+    /// Exit code is reported by devices supporting `shell,v2`, `legacy_exit_code` being ignored for them.
+    /// Legacy `shell:` service does not report exit codes. If `legacy_exit_code` is set, command is followed by
+    /// `echo __EXIT:$?` and exit code is parsed from the end of output, this marker line being stripped from it.
+    /// Fails with [`RustADBError::ExitCodeUnavailable`] otherwise.
+    fn run_command(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        legacy_exit_code: bool,
+    ) -> Result<u8> {
+        legacy_run_command(self, command, output, legacy_exit_code)
+    }
+
+    /// Runs command on the device using `exec:` service, writing its raw output into `output`.
+    ///
+    /// Unlike [`ADBDeviceExt::shell_command`], no pseudo-terminal is involved: binary output (e.g. `screencap -p`)
+    /// is received unaltered, without line endings being translated.
+    fn exec_out(&mut self, command: &[&str], output: &mut dyn Write) -> Result<()> {
+        let mut stream = self.open_service(&format!("exec:{}", command.join(" ")))?;
+        std::io::copy(&mut stream, output)?;
+        Ok(())
+    }
+
+    /// Runs command on the device using `exec:` service, sending everything read from `input` unaltered to its
+    /// standard input (e.g. `cat > /data/local/tmp/file`).
+    ///
+    /// Standard input of command is closed once `input` has been fully sent.
+    fn exec_in(&mut self, command: &[&str], input: &mut dyn Read) -> Result<()> {
+        let mut stream = self.open_service(&format!("exec:{}", command.join(" ")))?;
+        std::io::copy(input, &mut stream)?;
+        stream.flush()?;
+        // Closing stream is what closes standard input of command
+        drop(stream);
+        Ok(())
+    }
+
     /// Starts an interactive shell session on the device.
     /// Input data is read from reader and write to writer.
     fn shell(&mut self, reader: &mut dyn Read, writer: Box<(dyn Write + Send)>) -> Result<()>;
 
+    /// Starts an interactive shell session on the device like [`ADBDeviceExt::shell`], configured using `options` (`TERM`, mode, environment).
+    fn shell_with_options(
+        &mut self,
+        options: &ShellOptions,
+        reader: &mut dyn Read,
+        writer: Box<dyn Write + Send>,
+    ) -> Result<()>;
+
+    /// List features advertised by device, such as `shell_v2` or `sendrecv_v2`.
+    fn features(&mut self) -> Result<Vec<String>>;
+
     /// Display the stat information for a remote file
     fn stat(&mut self, remote_path: &str) -> Result<AdbStatResponse>;
 
+    /// Stat `remote_path` following symbolic links, with full metadata (owner, links, 64-bit size and times).
+    ///
+    /// Devices not advertising `stat_v2` feature are sent a legacy [`ADBDeviceExt::stat`] request, which does not follow
+    /// symbolic links and only reports mode, size and modification time.
+    fn stat_v2(&mut self, remote_path: &str) -> Result<AdbStatV2Response> {
+        stat_v2(self, remote_path, true)
+    }
+
+    /// Stat `remote_path` like [`ADBDeviceExt::stat_v2`], symbolic links being described themselves.
+    fn lstat_v2(&mut self, remote_path: &str) -> Result<AdbStatV2Response> {
+        stat_v2(self, remote_path, false)
+    }
+
+    /// List entries of remote directory `remote_path`, with their mode, size and modification time.
+    ///
+    /// Symbolic links are listed as such, without being followed. Listing a path which is not a readable directory
+    /// returns no entry.
+    fn list_dir(&mut self, remote_path: &str) -> Result<Vec<DirEntry>> {
+        let mut stream = self.open_service("sync:")?;
+        list_dir(&mut stream, remote_path)
+    }
+
     /// Pull the remote file pointed to by `source` and write its contents into `output`
-    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<()>;
+    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<TransferStats>;
+
+    /// Pull the remote file pointed to by `source` into `output` like [`ADBDeviceExt::pull`], calling `progress` with
+    /// `source`, bytes transferred so far and total size of file each time data is received.
+    ///
+    /// Total size is read using [`ADBDeviceExt::stat`] before pulling, it is thus `None` if file cannot be stat'ed.
+    fn pull_with_progress(
+        &mut self,
+        source: &dyn AsRef<str>,
+        output: &mut dyn Write,
+        progress: &mut dyn FnMut(&str, u64, Option<u64>),
+    ) -> Result<TransferStats> {
+        let source = source.as_ref();
+        let total = self.stat(source).ok().map(|stat| u64::from(stat.file_size));
+        let mut output = CallbackWriter::new(output, source, total, progress);
+        self.pull(&source, &mut output)
+    }
+
+    /// Pull the remote file pointed to by `source` into `output` using sync protocol v2, data being compressed using
+    /// `compression`.
+    ///
+    /// Devices not advertising `sendrecv_v2` feature are pulled from using [`ADBDeviceExt::pull`], unless a specific
+    /// algorithm is requested.
+    fn pull_compressed(
+        &mut self,
+        source: &dyn AsRef<str>,
+        output: &mut dyn Write,
+        compression: SyncCompression,
+    ) -> Result<TransferStats> {
+        pull_compressed(self, source.as_ref(), output, compression)
+    }
+
+    /// Pull the remote file pointed to by `source` into a sparse local file at `destination`, and return its size.
+    ///
+    /// Blocks only made of zeros are not written but left as holes, which saves disk space when pulling mostly empty
+    /// files such as partition images.
+    fn pull_sparse(
+        &mut self,
+        source: &dyn AsRef<str>,
+        destination: &dyn AsRef<Path>,
+    ) -> Result<u64> {
+        let mut writer = SparseFileWriter::new(File::create(destination)?);
+        self.pull(source, &mut writer)?;
+        Ok(writer.finish()?)
+    }
+
+    /// Pull remote directory `source` recursively into local directory `destination`, created if missing.
+    ///
+    /// Directory tree is recreated locally, pulling files selected by `filter`. Entries which are neither files nor
+    /// directories, such as symbolic links, are skipped.
+    fn pull_dir(
+        &mut self,
+        source: &str,
+        destination: &dyn AsRef<Path>,
+        filter: &PathFilter,
+    ) -> Result<TransferStats> {
+        self.pull_dir_with_options(source, destination, filter, &PullOptions::default())
+    }
+
+    /// Pull remote directory `source` recursively like [`ADBDeviceExt::pull_dir`], handling symbolic links and
+    /// modification times according to `options`.
+    ///
+    /// Followed links to directories are walked, unless they point to a directory already walked through a link.
+    fn pull_dir_with_options(
+        &mut self,
+        source: &str,
+        destination: &dyn AsRef<Path>,
+        filter: &PathFilter,
+        options: &PullOptions,
+    ) -> Result<TransferStats> {
+        pull_dir(self, source, destination.as_ref(), filter, options, false)
+    }
+
+    /// Pull the remote file pointed to by `source` into local file `destination`, handling it according to `options`.
+    ///
+    /// Unlike [`ADBDeviceExt::pull`], which always reads through symbolic links, `source` is first checked using
+    /// [`ADBDeviceExt::lstat_v2`]. Its modification time is applied to `destination` if `options.preserve_times` is set.
+    fn pull_path(
+        &mut self,
+        source: &str,
+        destination: &dyn AsRef<Path>,
+        options: &PullOptions,
+    ) -> Result<TransferStats> {
+        pull_path(self, source, destination.as_ref(), options)
+    }
 
     /// Push `stream` to `path` on the device.
-    fn push(&mut self, stream: &mut dyn Read, path: &dyn AsRef<str>) -> Result<()>;
+    fn push(&mut self, stream: &mut dyn Read, path: &dyn AsRef<str>) -> Result<TransferStats> {
+        self.push_with_mode(stream, path, 0o777)
+    }
+
+    /// Push `stream` to `path` on the device like [`ADBDeviceExt::push`], calling `progress` with `path`, bytes
+    /// transferred so far and `total` each time data is sent.
+    ///
+    /// `total` is only forwarded to `progress`, e.g. being the size of pushed file when known.
+    fn push_with_progress(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        total: Option<u64>,
+        progress: &mut dyn FnMut(&str, u64, Option<u64>),
+    ) -> Result<TransferStats> {
+        let mut stream = CallbackReader::new(stream, path.as_ref(), total, progress);
+        self.push(&mut stream, path)
+    }
+
+    /// Push `stream` to `path` on the device, setting permissions of remote file to `mode` (e.g. `0o644`).
+    fn push_with_mode(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        mode: u32,
+    ) -> Result<TransferStats> {
+        self.push_with_metadata(stream, path, mode, SystemTime::now())
+    }
+
+    /// Push `stream` to `path` on the device, setting permissions of remote file to `mode` and its modification time
+    /// to `mod_time`.
+    fn push_with_metadata(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<TransferStats>;
+
+    /// Push local file `local` to `remote` on the device, keeping its permissions and modification time like
+    /// `adb push`.
+    ///
+    /// Permissions are `0o644` on platforms without Unix permissions.
+    fn push_file(
+        &mut self,
+        local: &dyn AsRef<Path>,
+        remote: &dyn AsRef<str>,
+    ) -> Result<TransferStats> {
+        push_file(self, local.as_ref(), remote.as_ref())
+    }
+
+    /// Push `stream` to `path` on the device like [`ADBDeviceExt::push_with_mode`], using sync protocol v2 and
+    /// compressing data using `compression`.
+    ///
+    /// Compression mostly speeds up transfers over slow links (USB 2.0, Wi-Fi), of data which is not already compressed.
+    /// Devices not advertising `sendrecv_v2` feature are pushed to using [`ADBDeviceExt::push_with_mode`], unless a
+    /// specific algorithm is requested.
+    fn push_compressed(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        mode: u32,
+        compression: SyncCompression,
+    ) -> Result<TransferStats> {
+        push_compressed(self, stream, path.as_ref(), mode, compression)
+    }
+
+    /// Push local directory `source` recursively into remote directory `destination`, created if missing.
+    ///
+    /// Directory tree is recreated on device, including empty directories, and files are pushed using
+    /// [`ADBDeviceExt::push_file`], keeping their permissions and modification time. Entries which are neither files
+    /// nor directories, such as symbolic links, are skipped.
+    fn push_dir(&mut self, source: &dyn AsRef<Path>, destination: &str) -> Result<TransferStats> {
+        push_dir(self, source.as_ref(), destination, false)
+    }
+
+    /// Synchronize local directory `local` and remote directory `remote` in `direction`, like `adb sync`.
+    ///
+    /// Only files missing or differing in size or modification time at destination are transferred, modification
+    /// time being kept so that they are skipped by next synchronization. Files missing at source are not deleted.
+    fn sync_dir(
+        &mut self,
+        local: &dyn AsRef<Path>,
+        remote: &str,
+        direction: SyncDirection,
+    ) -> Result<TransferStats> {
+        sync_dir(self, local.as_ref(), remote, direction)
+    }
+
+    /// Push file `local` to `remote` on the device, resuming an interrupted previous push of it.
+    ///
+    /// Existing remote file is kept if it matches the beginning of `local` (checked using `cksum` on device), and only the
+    /// remaining data is uploaded, in parts appended to it. Otherwise, `local` is pushed from the start.
+    fn push_resume(&mut self, local: &dyn AsRef<Path>, remote: &dyn AsRef<str>) -> Result<()> {
+        push_resume(self, local.as_ref(), remote.as_ref())
+    }
+
+    /// Poll files under `remote_path` every `interval`, see [`PathWatcher`].
+    fn watch_path(&mut self, remote_path: &str, interval: Duration) -> PathWatcher<'_, Self>
+    where
+        Self: Sized,
+    {
+        PathWatcher::new(self, remote_path, interval)
+    }
 
     /// Reboot the device using given reboot type
     fn reboot(&mut self, reboot_type: RebootType) -> Result<()>;
@@ -38,15 +366,459 @@ pub trait ADBDeviceExt {
         Ok(output)
     }
 
+    /// Start `intent` and wait for launched activity to be drawn, returning launch status and timings.
+    ///
+    /// Relies on `am start -W`, reported times allowing to measure startup time of an app.
+    /// Fails with [`RustADBError::UnresolvedIntent`] if no activity handles `intent`.
+    fn start_activity_wait(&mut self, intent: &Intent) -> Result<ActivityLaunch> {
+        let mut command = vec!["am".to_string(), "start".to_string(), "-W".to_string()];
+        command.extend(intent.args());
+        let command: Vec<&str> = command.iter().map(String::as_str).collect();
+
+        let mut output = Vec::new();
+        self.shell_command(&command, &mut output)?;
+        ActivityLaunch::parse(&String::from_utf8(output)?)
+    }
+
+    /// Open `url` like a clicked link would, and return launch of activity handling it, `None` if no activity does.
+    ///
+    /// Allows to check which activity (or app) handles a deep link.
+    fn open_url(&mut self, url: &str) -> Result<Option<ActivityLaunch>> {
+        match self.start_activity_wait(&Intent::view(url)) {
+            Ok(launch) => Ok(Some(launch)),
+            Err(RustADBError::UnresolvedIntent(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Monitor activity manager using `am monitor`, streaming activity starts, crashes and ANRs as they happen.
+    ///
+    /// Crashes and ANRs are handled as usual by device once reported. Monitoring runs over its own stream,
+    /// and stops when returned [`EventStream`] is dropped.
+    fn monitor_activities(&mut self) -> Result<EventStream<ActivityEvent>> {
+        let cancel_token = CancelToken::new();
+        let stream = self.open_service_cancellable("shell:am monitor", &cancel_token)?;
+        Ok(
+            EventStream::spawn(move |sender| monitor_activities(stream, sender))
+                .cancel_on_drop(cancel_token),
+        )
+    }
+
+    /// Stream log messages using logcat, keeping those matching `filter_specs` (e.g. `ActivityManager:I` or `*:S`).
+    ///
+    /// Messages already in log buffers are streamed first, followed by new ones as they are logged. Messages are read
+    /// in logger binary format, which holds nanosecond timestamps and user identifiers. Logcat runs over its own stream,
+    /// and stops when returned [`EventStream`] is dropped.
+    fn logcat(&mut self, filter_specs: &[&str]) -> Result<EventStream<LogcatEntry>> {
+        let filter = LogcatFilter::new(filter_specs)?;
+        let cancel_token = CancelToken::new();
+        let stream = self.open_service_cancellable(LOGCAT_SERVICE, &cancel_token)?;
+        Ok(
+            EventStream::spawn(move |sender| read_logcat(stream, &filter, sender))
+                .cancel_on_drop(cancel_token),
+        )
+    }
+
+    /// Track processes available for debugging over JDWP, streaming their full pid list each time one starts or stops.
+    ///
+    /// Current list is streamed first. Tracking runs over its own stream, and stops when returned [`EventStream`] is dropped.
+    fn track_jdwp(&mut self) -> Result<EventStream<Vec<u32>>> {
+        let cancel_token = CancelToken::new();
+        let stream = self.open_service_cancellable(TRACK_JDWP_SERVICE, &cancel_token)?;
+        Ok(EventStream::spawn(move |sender| track_jdwp(stream, sender))
+            .cancel_on_drop(cancel_token))
+    }
+
+    /// Stream input events read by `getevent` (touches, key presses...), along with input devices being plugged or unplugged.
+    ///
+    /// Input devices already present are streamed first. Reading runs over its own stream, and stops when returned
+    /// [`EventStream`] is dropped.
+    fn getevent(&mut self) -> Result<EventStream<InputEvent>> {
+        let cancel_token = CancelToken::new();
+        let stream = self.open_service_cancellable(GETEVENT_SERVICE, &cancel_token)?;
+        Ok(
+            EventStream::spawn(move |sender| read_input_events(stream, sender))
+                .cancel_on_drop(cancel_token),
+        )
+    }
+
+    /// Read battery statistics of `package` since last charge (wakelocks, network usage, jobs), using `dumpsys batterystats --checkin`.
+    fn batterystats(&mut self, package: &str) -> Result<BatteryStats> {
+        check_package_name(package)?;
+
+        let mut output = Vec::new();
+        self.shell_command(
+            &["dumpsys", "batterystats", "--checkin", package],
+            &mut output,
+        )?;
+        Ok(BatteryStats::parse(&String::from_utf8(output)?))
+    }
+
+    /// Read network traffic of each user identifier since boot, untagged and summed over all interfaces.
+    ///
+    /// Traffic is read from `/proc/net/xt_qtaguid/stats` on devices providing it (before Android 9),
+    /// and from `dumpsys netstats` otherwise.
+    fn network_stats(&mut self) -> Result<Vec<UidTraffic>> {
+        let mut output = Vec::new();
+        self.shell_command(
+            &["cat", "/proc/net/xt_qtaguid/stats", "2>/dev/null"],
+            &mut output,
+        )?;
+        if let Some(traffic) = UidTraffic::parse_qtaguid(&String::from_utf8_lossy(&output)) {
+            return Ok(traffic);
+        }
+
+        output.clear();
+        self.shell_command(&["dumpsys", "netstats", "detail"], &mut output)?;
+        Ok(UidTraffic::parse_netstats(&String::from_utf8(output)?))
+    }
+
+    /// List processes running on device.
+    fn processes(&mut self) -> Result<Vec<ProcessInfo>> {
+        let mut output = Vec::new();
+        self.shell_command(&["ps", "-A", "-o", "PID,PPID,UID,RSS,NAME"], &mut output)?;
+        let mut output = String::from_utf8(output)?;
+
+        // Toolbox `ps` reads `-A` as a name filter, list all processes with its own columns instead
+        if !ProcessInfo::is_toybox_output(&output) {
+            let mut toolbox_output = Vec::new();
+            self.shell_command(&["ps"], &mut toolbox_output)?;
+            output = String::from_utf8(toolbox_output)?;
+        }
+
+        Ok(ProcessInfo::parse(&output))
+    }
+
+    /// Return identifiers of processes named `package`, empty if app is not running.
+    ///
+    /// Only main process of app is matched, other processes being named `<package>:<process>`.
+    fn pidof(&mut self, package: &str) -> Result<Vec<u32>> {
+        Ok(self
+            .processes()?
+            .into_iter()
+            .filter(|process| process.name == package)
+            .map(|process| process.pid)
+            .collect())
+    }
+
+    /// Send `signal` to process `pid`.
+    ///
+    /// Fails with [`RustADBError::PermissionDenied`] if process is not owned by shell user.
+    fn kill_pid(&mut self, pid: u32, signal: Signal) -> Result<()> {
+        kill_pid(self, pid, signal)
+    }
+
+    /// Kill processes whose name matches regular expression `pattern`, returning `false` if none matched.
+    ///
+    /// Fails with [`RustADBError::PermissionDenied`] if a matching process is not owned by shell user.
+    fn pkill(&mut self, pattern: &str) -> Result<bool> {
+        pkill(self, pattern)
+    }
+
+    /// Read temperatures of thermal zones and throttling status, e.g. to wait for a device to cool down between benchmarks.
+    ///
+    /// Thermal service is queried on devices providing it (Android 10 and later), thermal zones being read from
+    /// `/sys/class/thermal` otherwise, without any throttling status.
+    fn thermal_info(&mut self) -> Result<ThermalInfo> {
+        let mut output = Vec::new();
+        self.shell_command(&["dumpsys", "thermalservice"], &mut output)?;
+        if let Some(info) = ThermalInfo::parse_thermalservice(&String::from_utf8_lossy(&output)) {
+            return Ok(info);
+        }
+
+        output.clear();
+        self.shell_command(
+            &[
+                "for zone in /sys/class/thermal/thermal_zone*;",
+                r#"do echo "$(cat $zone/type) $(cat $zone/temp)";"#,
+                "done 2>/dev/null",
+            ],
+            &mut output,
+        )?;
+        Ok(ThermalInfo::parse_sysfs(&String::from_utf8(output)?))
+    }
+
+    /// Read total, used and available space of mounted volumes.
+    ///
+    /// Volumes are listed using `df`, falling back to `dumpsys diskstats` (`/data`, `/cache` and `/system` only).
+    fn storage_info(&mut self) -> Result<Vec<VolumeUsage>> {
+        let mut output = Vec::new();
+        self.shell_command(&["df", "-k"], &mut output)?;
+        let volumes = VolumeUsage::parse_df(&String::from_utf8_lossy(&output));
+        if !volumes.is_empty() {
+            return Ok(volumes);
+        }
+
+        output.clear();
+        self.shell_command(&["dumpsys", "diskstats"], &mut output)?;
+        Ok(VolumeUsage::parse_diskstats(&String::from_utf8(output)?))
+    }
+
+    /// Dump UI hierarchy currently displayed using `uiautomator`, returning its nodes in document order.
+    fn ui_dump(&mut self) -> Result<Vec<UiNode>> {
+        ui_dump(self)
+    }
+
+    /// Wait for at most `timeout` until a node selected by `selector` is displayed, and return it.
+    ///
+    /// Fails with [`RustADBError::UiNodeNotFound`] if no such node appeared in time.
+    fn wait_for(&mut self, selector: &UiSelector, timeout: Duration) -> Result<UiNode> {
+        wait_for(self, selector, timeout)
+    }
+
+    /// Tap center of first displayed node selected by `selector`.
+    ///
+    /// Fails with [`RustADBError::UiNodeNotFound`] if no such node is displayed.
+    fn tap_on(&mut self, selector: &UiSelector) -> Result<()> {
+        let node = wait_for(self, selector, Duration::ZERO)?;
+        let (x, y) = node.center();
+        self.shell_command(
+            &["input", "tap", &x.to_string(), &y.to_string()],
+            &mut std::io::sink(),
+        )
+    }
+
+    /// Restart adbd with root permissions, on builds allowing it (`userdebug` and `eng`).
+    ///
+    /// Device is connected again once adbd restarted, nothing being done if adbd already runs as root.
+    fn root(&mut self) -> Result<()>;
+
+    /// Restart adbd without root permissions, connecting device again as done by [`ADBDeviceExt::root`].
+    fn unroot(&mut self) -> Result<()>;
+
+    /// Remount system partitions read-write, disabling verity if needed, which requires adbd to run as root
+    /// (see [`ADBDeviceExt::root`]).
+    ///
+    /// Return `true` if device must be rebooted for remount to take effect. If `reboot` is set, device is then rebooted
+    /// and connected again once it booted.
+    fn remount(&mut self, reboot: bool) -> Result<bool>;
+
+    /// Disable dm-verity on system partitions, on unlocked devices, rebooting device if `reboot` is set as done by
+    /// [`ADBDeviceExt::remount`].
+    fn disable_verity(&mut self, reboot: bool) -> Result<bool>;
+
+    /// Enable dm-verity on system partitions, rebooting device if `reboot` is set as done by [`ADBDeviceExt::remount`].
+    fn enable_verity(&mut self, reboot: bool) -> Result<bool>;
+
+    /// Restart adbd listening on TCP `port` in addition to USB, returning port adbd confirmed.
+    ///
+    /// Current connection is lost as adbd restarts, see [`crate::switch_to_tcp`] to connect to device over TCP.
+    fn tcpip(&mut self, port: u16) -> Result<u16> {
+        tcpip(self, port)
+    }
+
+    /// Restart adbd listening on USB only, current connection being lost.
+    fn usb(&mut self) -> Result<()> {
+        usb(self)
+    }
+
     /// Install an APK pointed to by `apk_path` on device.
-    fn install(&mut self, apk_path: &dyn AsRef<Path>) -> Result<()>;
+    fn install(&mut self, apk_path: &dyn AsRef<Path>) -> Result<()> {
+        self.install_with_options(apk_path, &InstallOptions::default())
+    }
+
+    /// Install an APK pointed to by `apk_path` on device, passing `options` to package manager.
+    fn install_with_options(
+        &mut self,
+        apk_path: &dyn AsRef<Path>,
+        options: &InstallOptions,
+    ) -> Result<()>;
+
+    /// Install an APK of `size` bytes read from `reader` on device, without storing it on host.
+    ///
+    /// `size` must be known beforehand (e.g. from a `Content-Length` header), as package manager requires it.
+    fn install_from_reader(&mut self, reader: &mut dyn Read, size: u64) -> Result<()> {
+        self.install_from_reader_with_options(reader, size, &InstallOptions::default())
+    }
+
+    /// Install an APK of `size` bytes read from `reader` on device, passing `options` to package manager.
+    fn install_from_reader_with_options(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()>;
+
+    /// Install APK pointed to by `apk_path` incrementally, app being installed before all of it has been sent.
+    ///
+    /// This needs the APK Signature Scheme v4 file generated by `apksigner` next to APK (`<apk_path>.idsig`) and a
+    /// device supporting incremental file system (Android 11 and later). Installation otherwise falls back to a
+    /// regular one. Blocks not needed for installation are then streamed by returned [`IncrementalInstall`].
+    #[cfg(any(feature = "tcp", feature = "usb"))]
+    fn install_incremental(
+        &mut self,
+        apk_path: &dyn AsRef<Path>,
+        options: &InstallOptions,
+    ) -> Result<IncrementalInstall> {
+        install_incremental(self, apk_path.as_ref(), options)
+    }
+
+    /// Install APKs pointed to by `apk_paths` together on device, in a single package manager session.
+    ///
+    /// This is needed for apps split into several APKs (base APK and its configuration splits), which package
+    /// manager only accepts at once. Either all APKs are installed, or none of them.
+    #[cfg(any(feature = "tcp", feature = "usb"))]
+    fn install_multiple(&mut self, apk_paths: &[PathBuf], options: &InstallOptions) -> Result<()> {
+        install_multiple(self, apk_paths, options)
+    }
+
+    /// Sideload OTA package pointed to by `package_path` on device, which must be in sideload mode
+    /// (e.g. after [`RebootType::Sideload`]).
+    ///
+    /// Progress is reported to [`crate::AdbObserver::on_sideload_progress`].
+    fn sideload(&mut self, package_path: &dyn AsRef<Path>) -> Result<TransferStats> {
+        let mut package = File::open(package_path)?;
+        let size = package.metadata()?.len();
+        self.sideload_from_reader(&mut package, size)
+    }
+
+    /// Sideload OTA package of `size` bytes read from `reader` on device, which must be in sideload mode.
+    ///
+    /// Recovery requests blocks of package in any order, and usually more than once, hence `reader` must be seekable.
+    fn sideload_from_reader(
+        &mut self,
+        reader: &mut dyn ReadSeek,
+        size: u64,
+    ) -> Result<TransferStats>;
+
+    /// Install app bundle (`.apks` or `.xapk` archive) at `path` on device.
+    ///
+    /// Split APKs matching device ABI, screen density and language are selected using device properties, and installed
+    /// together in a single package manager session. Expansion files held by `.xapk` archives are then pushed.
+    #[cfg(any(feature = "tcp", feature = "usb"))]
+    fn install_bundle(&mut self, path: &dyn AsRef<Path>) -> Result<()> {
+        install_bundle(self, path.as_ref())
+    }
+
+    /// Push expansion file `local_obb` of `package` to its expected location on device, and return its remote path.
+    ///
+    /// File keeps its name if already named `main.<version-code>.<package>.obb` (or `patch.`), otherwise it is pushed
+    /// as main expansion file of currently installed version of `package`.
+    /// Push progress is reported to attached observer, and size of remote file is checked afterwards.
+    fn push_obb(&mut self, package: &str, local_obb: &dyn AsRef<Path>) -> Result<String> {
+        check_package_name(package)?;
+
+        let local_obb = local_obb.as_ref();
+        let mut obb_file = File::open(local_obb)?;
+        let file_size = obb_file.metadata()?.len();
+
+        let file_name = match local_obb.file_name().and_then(|name| name.to_str()) {
+            Some(name) if is_obb_file_name(name, package) => name.to_string(),
+            _ => {
+                let mut output = Vec::new();
+                self.shell_command(&["dumpsys", "package", package], &mut output)?;
+                format!("main.{}.{package}.obb", parse_version_code(&output)?)
+            }
+        };
+
+        let remote_path = format!("{OBB_DIRECTORY}/{package}/{file_name}");
+        self.push(&mut obb_file, &remote_path)?;
+
+        // Expansion files commonly exceed 4 GiB, which legacy stat cannot report
+        let remote_size = self.stat_v2(&remote_path)?.size;
+        if remote_size != file_size {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "{remote_path} has a size of {remote_size} bytes instead of {file_size}"
+            )));
+        }
+
+        Ok(remote_path)
+    }
+
+    /// Pull base APK of installed `package` and write its contents into `output`.
+    #[cfg(any(feature = "tcp", feature = "usb"))]
+    fn pull_apk(&mut self, package: &str, output: &mut dyn Write) -> Result<()> {
+        let base_apk = apk_paths(self, package)?.remove(0);
+        self.pull(&base_apk, output)?;
+        Ok(())
+    }
+
+    /// Back up what `options` select into `output`, as an `adb backup` archive (`.ab`) to be restored using
+    /// [`ADBDeviceExt::restore`].
+    ///
+    /// Backup must be confirmed on device, and packages disallowing backups are skipped. Archive is streamed as
+    /// device produces it, and is never held in memory.
+    fn backup(&mut self, options: &BackupOptions, output: &mut dyn Write) -> Result<()> {
+        let mut stream = self.open_service(&options.service()?)?;
+        std::io::copy(&mut stream, output)?;
+        Ok(())
+    }
+
+    /// Restore `adb backup` archive read from `input`, which must be confirmed on device.
+    fn restore(&mut self, input: &mut dyn Read) -> Result<()> {
+        let mut stream = self.open_service("restore:")?;
+        std::io::copy(input, &mut stream)?;
+        stream.flush()?;
+        // Restoration ends once stream gets closed
+        drop(stream);
+        Ok(())
+    }
+
+    /// Export `package` with its data into `writer`, as a ZIP archive to be imported using [`ADBDeviceExt::import_app`].
+    ///
+    /// Archive holds all APKs of `package` (base and splits) and its data, copied using `run-as` if package is debuggable.
+    /// Otherwise data is exported using `adb backup`, which must be confirmed on device and is ignored by packages
+    /// disallowing backups.
+    #[cfg(any(feature = "tcp", feature = "usb"))]
+    fn export_app(&mut self, package: &str, writer: &mut dyn Write) -> Result<()> {
+        export_app(self, package, writer)
+    }
+
+    /// Install package exported by [`ADBDeviceExt::export_app`] into archive at `path`, restore its data, and return its name.
+    #[cfg(any(feature = "tcp", feature = "usb"))]
+    fn import_app(&mut self, path: &dyn AsRef<Path>) -> Result<String> {
+        import_app(self, path.as_ref())
+    }
 
     /// Uninstall the package `package` from device.
     fn uninstall(&mut self, package: &str) -> Result<()>;
 
+    /// Open a raw stream to `service` on the device (e.g. `tcp:5555`, `shell:ls` or `jdwp:1234`).
+    ///
+    /// Data written to the stream is sent to the service, and its output can be read from it.
+    fn open_service(&mut self, service: &str) -> Result<Box<dyn ReadWriteStream>>;
+
+    /// Same as [`ADBDeviceExt::open_service`], returned stream being shut down once `cancel_token` gets cancelled.
+    ///
+    /// Allows to abort a read blocked on stream from another thread.
+    fn open_service_cancellable(
+        &mut self,
+        service: &str,
+        cancel_token: &CancelToken,
+    ) -> Result<Box<dyn ReadWriteStream>>;
+
     /// Inner method requesting framebuffer from an Android device
     fn framebuffer_inner(&mut self) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>>;
 
+    /// Request framebuffer like [`ADBDeviceExt::framebuffer_inner`], storing it into `buffer` to reuse its allocation
+    fn framebuffer_into(&mut self, buffer: Vec<u8>) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>>;
+
+    /// Request framebuffer along with its header, pixel data being left as sent by device.
+    ///
+    /// Unlike [`ADBDeviceExt::framebuffer_inner`], pixel layout is described by header, e.g. to feed pixel data to a
+    /// video encoder.
+    fn framebuffer_raw(&mut self) -> Result<RawFramebuffer>;
+
+    /// Capture framebuffer every `interval`, see [`FramebufferStream`].
+    fn framebuffer_stream(&mut self, interval: Duration) -> FramebufferStream<'_, Self>
+    where
+        Self: Sized,
+    {
+        FramebufferStream::new(self, interval)
+    }
+
+    /// Record screen of this device for `duration` (at most 3 minutes) into MP4 file `local_path`.
+    ///
+    /// Recording is done by `screenrecord` into a temporary file on device, which is pulled and then removed.
+    fn record_screen(
+        &mut self,
+        duration: Duration,
+        options: &ScreenRecordOptions,
+        local_path: &dyn AsRef<Path>,
+    ) -> Result<()> {
+        record_screen(self, duration, options, local_path.as_ref())
+    }
+
     /// Dump framebuffer of this device into given path
     fn framebuffer(&mut self, path: &dyn AsRef<Path>) -> Result<()> {
         // Big help from AOSP source code (<https://android.googlesource.com/platform/system/adb/+/refs/heads/main/framebuffer_service.cpp>)
@@ -65,6 +837,32 @@ pub trait ADBDeviceExt {
         Ok(vec.into_inner())
     }
 
+    /// Capture screen of this device using `screencap`, falling back to framebuffer service if it fails.
+    ///
+    /// Framebuffer service is unavailable on many recent devices, unlike `screencap`.
+    fn screenshot(&mut self) -> Result<DynamicImage> {
+        match screencap(self) {
+            Ok(png) => Ok(image::load_from_memory_with_format(&png, ImageFormat::Png)?),
+            Err(e) => {
+                log::debug!("{e}, falling back to framebuffer");
+                Ok(DynamicImage::ImageRgba8(self.framebuffer_inner()?))
+            }
+        }
+    }
+
+    /// Capture screen like [`ADBDeviceExt::screenshot`], returning it as PNG bytes.
+    ///
+    /// PNG encoded by `screencap` on device is returned as is, without being decoded and encoded again.
+    fn screenshot_png(&mut self) -> Result<Vec<u8>> {
+        match screencap(self) {
+            Ok(png) => Ok(png),
+            Err(e) => {
+                log::debug!("{e}, falling back to framebuffer");
+                self.framebuffer_bytes()
+            }
+        }
+    }
+
     /// Return a boxed instance representing this trait
     fn boxed(self) -> Box<dyn ADBDeviceExt>
     where
@@ -0,0 +1,81 @@
+use std::io::Read;
+
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Open `service` on `device` and return its answer, adbd restarting once it has been sent
+fn read_answer<D: ADBDeviceExt + ?Sized>(device: &mut D, service: &str) -> Result<String> {
+    let mut stream = device.open_service(service)?;
+    let mut output = Vec::new();
+    // adbd may exit before stream gets closed
+    if let Err(e) = stream.read_to_end(&mut output)
+        && output.is_empty()
+    {
+        return Err(e.into());
+    }
+
+    Ok(String::from_utf8_lossy(&output).trim().to_string())
+}
+
+/// Open `service` (`root:` or `unroot:`) on `device`, returning `true` if adbd restarts to apply it.
+///
+/// Restarting adbd drops all connections to device, which must then be connected again.
+pub(crate) fn run_root_service<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    service: &str,
+) -> Result<bool> {
+    adbd_restarts(&read_answer(device, service)?)
+}
+
+/// Restart adbd of `device` listening on TCP `port`, returning port adbd confirmed
+pub(crate) fn tcpip<D: ADBDeviceExt + ?Sized>(device: &mut D, port: u16) -> Result<u16> {
+    parse_tcpip_answer(&read_answer(device, &format!("tcpip:{port}"))?)
+}
+
+/// Restart adbd of `device` listening on USB only
+pub(crate) fn usb<D: ADBDeviceExt + ?Sized>(device: &mut D) -> Result<()> {
+    match read_answer(device, "usb:")?.as_str() {
+        "restarting in USB mode" => Ok(()),
+        answer => Err(RustADBError::ADBRequestFailed(answer.to_string())),
+    }
+}
+
+/// Parse port from `answer` of `tcpip:` service, e.g. `restarting in TCP mode port: 5555`
+fn parse_tcpip_answer(answer: &str) -> Result<u16> {
+    answer
+        .strip_prefix("restarting in TCP mode port: ")
+        .and_then(|port| port.parse().ok())
+        .ok_or_else(|| RustADBError::ADBRequestFailed(answer.to_string()))
+}
+
+/// Return `true` if adbd answered `output` before restarting, `false` if it already runs as requested
+fn adbd_restarts(output: &str) -> Result<bool> {
+    match output {
+        // "restarting adbd as root", "restarting adbd as non root"
+        output if output.starts_with("restarting adbd") => Ok(true),
+        "adbd is already running as root" | "adbd not running as root" => Ok(false),
+        "" => Err(RustADBError::ADBRequestFailed(
+            "adbd closed stream without answering".to_string(),
+        )),
+        // e.g. "adbd cannot run as root in production builds"
+        output => Err(RustADBError::ADBRequestFailed(output.to_string())),
+    }
+}
+
+#[test]
+fn test_adbd_restarts() {
+    assert!(adbd_restarts("restarting adbd as root").expect("cannot parse output"));
+    assert!(adbd_restarts("restarting adbd as non root").expect("cannot parse output"));
+    assert!(!adbd_restarts("adbd is already running as root").expect("cannot parse output"));
+    assert!(!adbd_restarts("adbd not running as root").expect("cannot parse output"));
+    assert!(adbd_restarts("adbd cannot run as root in production builds").is_err());
+    assert!(adbd_restarts("").is_err());
+}
+
+#[test]
+fn test_parse_tcpip_answer() {
+    assert_eq!(
+        parse_tcpip_answer("restarting in TCP mode port: 5555").expect("cannot parse answer"),
+        5555
+    );
+    assert!(parse_tcpip_answer("invalid port 0").is_err());
+}
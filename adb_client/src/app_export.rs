@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+
+use crate::bundle::install_entries;
+use crate::models::BackupOptions;
+use crate::obb::check_package_name;
+use crate::zip_archive::{ZipArchive, ZipWriter};
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Entry of an export archive holding name of exported package
+const PACKAGE_ENTRY: &str = "package";
+/// Directory of an export archive holding APKs of exported package
+const APK_DIRECTORY: &str = "apk/";
+/// Entry of an export archive holding data of a debuggable package, as a tar archive of its data directory
+const DATA_TAR_ENTRY: &str = "data.tar";
+/// Entry of an export archive holding data of a package, as an `adb backup` archive
+const BACKUP_ENTRY: &str = "backup.ab";
+
+/// Paths of APKs of installed `package` on device, base APK first
+pub(crate) fn apk_paths<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    package: &str,
+) -> Result<Vec<String>> {
+    check_package_name(package)?;
+
+    let mut output = Vec::new();
+    device.shell_command(&["pm", "path", package], &mut output)?;
+
+    // One `package:<path>` line per APK
+    let mut paths: Vec<String> = String::from_utf8(output)?
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("package:"))
+        .map(str::to_string)
+        .collect();
+    if paths.is_empty() {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "package {package} is not installed"
+        )));
+    }
+
+    paths.sort_by_key(|path| !path.ends_with("/base.apk"));
+    Ok(paths)
+}
+
+/// Return `true` if data of `package` can be accessed using `run-as`, i.e. if it is debuggable
+fn is_debuggable<D: ADBDeviceExt + ?Sized>(device: &mut D, package: &str) -> Result<bool> {
+    let mut output = Vec::new();
+    device.shell_command(&["run-as", package, "id"], &mut output)?;
+    Ok(output.starts_with(b"uid="))
+}
+
+/// Export `package` from `device` into `writer`, see [`ADBDeviceExt::export_app`].
+pub(crate) fn export_app<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    package: &str,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    let mut archive = ZipWriter::new(writer);
+    archive.add_entry(PACKAGE_ENTRY, |output| {
+        Ok(output.write_all(package.as_bytes())?)
+    })?;
+
+    for path in apk_paths(device, package)? {
+        let file_name = path.rsplit('/').next().unwrap_or(&path);
+        archive.add_entry(&format!("{APK_DIRECTORY}{file_name}"), |output| {
+            device.pull(&path, output)?;
+            Ok(())
+        })?;
+    }
+
+    if is_debuggable(device, package)? {
+        // `run-as` starts in data directory of package
+        let mut stream = device.open_service(&format!("exec:run-as {package} tar -cf - ."))?;
+        archive.add_entry(DATA_TAR_ENTRY, |output| {
+            std::io::copy(&mut stream, output)?;
+            Ok(())
+        })?;
+    } else {
+        log::info!("{package} is not debuggable, confirm its backup on device");
+        archive.add_entry(BACKUP_ENTRY, |output| {
+            device.backup(&BackupOptions::packages(&[package]), output)
+        })?;
+    }
+
+    archive.finish()?;
+    Ok(())
+}
+
+/// Import archive at `path` on `device`, see [`ADBDeviceExt::import_app`].
+pub(crate) fn import_app<D: ADBDeviceExt + ?Sized>(device: &mut D, path: &Path) -> Result<String> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let entries = archive.entries().to_vec();
+    let find_entry = |name: &str| entries.iter().find(|entry| entry.name == name);
+
+    let package_entry = find_entry(PACKAGE_ENTRY)
+        .ok_or_else(|| RustADBError::InvalidBundle(format!("{PACKAGE_ENTRY} entry is missing")))?;
+    let mut package = String::new();
+    archive.open(package_entry)?.read_to_string(&mut package)?;
+    check_package_name(&package)?;
+
+    let apks: Vec<_> = entries
+        .iter()
+        .filter(|entry| entry.name.starts_with(APK_DIRECTORY))
+        .cloned()
+        .collect();
+    install_entries(device, &mut archive, &apks)?;
+
+    if let Some(entry) = find_entry(DATA_TAR_ENTRY) {
+        let mut stream = device.open_service(&format!("exec:run-as {package} tar -xf -"))?;
+        std::io::copy(&mut archive.open(entry)?, &mut stream)?;
+        // tar exits once end of archive has been read, wait for it to be done extracting
+        std::io::copy(&mut stream, &mut std::io::sink())?;
+    } else if let Some(entry) = find_entry(BACKUP_ENTRY) {
+        log::info!("confirm restoration of {package} data on device");
+        device.restore(&mut archive.open(entry)?)?;
+    }
+
+    Ok(package)
+}
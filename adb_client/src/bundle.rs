@@ -0,0 +1,264 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+use crate::install_session::InstallSession;
+use crate::obb::OBB_DIRECTORY;
+use crate::zip_archive::{ZipArchive, ZipEntry};
+use crate::{ADBDeviceExt, InstallOptions, Result, RustADBError};
+
+/// ABIs which may qualify a split APK, as written in split names
+const SPLIT_ABIS: [&str; 7] = [
+    "armeabi",
+    "armeabi_v7a",
+    "arm64_v8a",
+    "x86",
+    "x86_64",
+    "mips",
+    "mips64",
+];
+
+/// Densities which may qualify a split APK, with their dots per inch
+const SPLIT_DENSITIES: [(&str, u32); 7] = [
+    ("ldpi", 120),
+    ("mdpi", 160),
+    ("tvdpi", 213),
+    ("hdpi", 240),
+    ("xhdpi", 320),
+    ("xxhdpi", 480),
+    ("xxxhdpi", 640),
+];
+
+/// Device configuration used to select split APKs of a bundle.
+#[derive(Debug, Default)]
+pub(crate) struct DeviceConfig {
+    /// Supported ABIs, preferred one first (e.g. `arm64_v8a`)
+    pub(crate) abis: Vec<String>,
+    /// Screen density, in dots per inch
+    pub(crate) density: Option<u32>,
+    /// Language of device locale (e.g. `en`)
+    pub(crate) language: Option<String>,
+}
+
+impl DeviceConfig {
+    /// Read configuration from device properties
+    pub(crate) fn read<D: ADBDeviceExt + ?Sized>(device: &mut D) -> Result<Self> {
+        let mut output = Vec::new();
+        // One property per line, locale being read from its legacy property on older devices
+        device.shell_command(
+            &[
+                "getprop ro.product.cpu.abilist;",
+                "getprop ro.sf.lcd_density;",
+                "getprop persist.sys.locale;",
+                "getprop ro.product.locale",
+            ],
+            &mut output,
+        )?;
+
+        let output = String::from_utf8(output)?;
+        let mut properties = output.lines().map(str::trim);
+        let abis = properties
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|abi| !abi.is_empty())
+            .map(|abi| abi.replace('-', "_"))
+            .collect();
+        let density = properties.next().and_then(|density| density.parse().ok());
+        let language = properties
+            .find(|locale| !locale.is_empty())
+            .and_then(|locale| locale.split(['-', '_']).next())
+            .map(str::to_lowercase);
+
+        Ok(Self {
+            abis,
+            density,
+            language,
+        })
+    }
+}
+
+/// Configuration qualifying a split APK
+#[derive(Debug, PartialEq)]
+enum SplitQualifier<'a> {
+    Abi(&'a str),
+    Density(u32),
+    Language(&'a str),
+    /// Base APK, or split not depending on device configuration (e.g. feature module)
+    Any,
+}
+
+/// Qualifier of split APK `name`, e.g. `splits/base-arm64_v8a.apk` (`.apks`) or `config.xxhdpi.apk` (`.xapk`)
+fn split_qualifier(name: &str) -> SplitQualifier<'_> {
+    let file_name = name.rsplit('/').next().unwrap_or(name);
+    let stem = file_name.strip_suffix(".apk").unwrap_or(file_name);
+    let qualifier = match stem.strip_prefix("config.") {
+        Some(qualifier) => qualifier,
+        None => stem
+            .rsplit_once('-')
+            .map_or(stem, |(_, qualifier)| qualifier),
+    };
+
+    if SPLIT_ABIS.contains(&qualifier) {
+        return SplitQualifier::Abi(qualifier);
+    }
+    if let Some((_, dpi)) = SPLIT_DENSITIES
+        .iter()
+        .find(|(density, _)| *density == qualifier)
+    {
+        return SplitQualifier::Density(*dpi);
+    }
+    if (2..=3).contains(&qualifier.len()) && qualifier.bytes().all(|b| b.is_ascii_lowercase()) {
+        return SplitQualifier::Language(qualifier);
+    }
+    SplitQualifier::Any
+}
+
+/// Select APKs among `names` to install on a device with configuration `config`, returning their indices.
+///
+/// Only the best ABI and density splits are kept, along with splits matching device language.
+fn select_splits(names: &[&str], config: &DeviceConfig) -> Vec<usize> {
+    let qualifiers: Vec<SplitQualifier> = names.iter().map(|name| split_qualifier(name)).collect();
+
+    // First ABI supported by device among available ones
+    let abi = config.abis.iter().find(|abi| {
+        qualifiers
+            .iter()
+            .any(|qualifier| *qualifier == SplitQualifier::Abi(abi))
+    });
+
+    // Lowest density not below device one, highest available otherwise
+    let densities = qualifiers.iter().filter_map(|qualifier| match qualifier {
+        SplitQualifier::Density(dpi) => Some(*dpi),
+        _ => None,
+    });
+    let density = match config.density {
+        Some(device_density) => densities
+            .clone()
+            .filter(|dpi| *dpi >= device_density)
+            .min()
+            .or_else(|| densities.max()),
+        None => densities.max(),
+    };
+
+    qualifiers
+        .iter()
+        .enumerate()
+        .filter(|(_, qualifier)| match qualifier {
+            SplitQualifier::Abi(split_abi) => abi.is_some_and(|abi| abi == split_abi),
+            SplitQualifier::Density(dpi) => density == Some(*dpi),
+            SplitQualifier::Language(language) => config.language.as_deref() == Some(*language),
+            SplitQualifier::Any => true,
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Install APKs stored as `entries` of `archive` together, in a single package manager session.
+pub(crate) fn install_entries<D, R>(
+    device: &mut D,
+    archive: &mut ZipArchive<R>,
+    entries: &[ZipEntry],
+) -> Result<()>
+where
+    D: ADBDeviceExt + ?Sized,
+    R: Read + Seek,
+{
+    let session = InstallSession::create(
+        device,
+        entries.iter().map(|entry| entry.size).sum(),
+        &InstallOptions::default(),
+    )?;
+    for entry in entries {
+        let file_name = entry.name.rsplit('/').next().unwrap_or(&entry.name);
+        let written = archive
+            .open(entry)
+            .and_then(|mut reader| session.write(device, file_name, entry.size, &mut reader));
+        if let Err(e) = written {
+            // Best effort, failure to write is what matters to caller
+            let _ = session.abandon(device);
+            return Err(e);
+        }
+    }
+    session.commit(device)
+}
+
+/// Install bundle at `path` on `device`, see [`ADBDeviceExt::install_bundle`].
+pub(crate) fn install_bundle<D: ADBDeviceExt + ?Sized>(device: &mut D, path: &Path) -> Result<()> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+
+    // `.apks` archives also hold standalone APKs for devices not supporting splits
+    let apks: Vec<_> = archive
+        .entries()
+        .iter()
+        .filter(|entry| entry.name.ends_with(".apk") && !entry.name.starts_with("standalones/"))
+        .cloned()
+        .collect();
+    if apks.is_empty() {
+        return Err(RustADBError::InvalidBundle(format!(
+            "no APK found in {}",
+            path.display()
+        )));
+    }
+
+    let config = DeviceConfig::read(device)?;
+    let names: Vec<&str> = apks.iter().map(|entry| entry.name.as_str()).collect();
+    let selected: Vec<_> = select_splits(&names, &config)
+        .into_iter()
+        .map(|index| apks[index].clone())
+        .collect();
+    log::debug!(
+        "installing {:?} from {}",
+        selected.iter().map(|entry| &entry.name).collect::<Vec<_>>(),
+        path.display()
+    );
+
+    install_entries(device, &mut archive, &selected)?;
+
+    // `.xapk` archives hold expansion files at their location on device
+    let obbs: Vec<_> = archive
+        .entries()
+        .iter()
+        .filter(|entry| entry.name.ends_with(".obb"))
+        .filter_map(|entry| {
+            let (_, relative_path) = entry.name.split_once("Android/obb/")?;
+            Some((entry.clone(), format!("{OBB_DIRECTORY}/{relative_path}")))
+        })
+        .collect();
+    for (entry, remote_path) in obbs {
+        log::debug!("pushing {} to {remote_path}", entry.name);
+        device.push(&mut archive.open(&entry)?, &remote_path)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_select_splits() {
+    let config = DeviceConfig {
+        abis: vec!["arm64_v8a".to_string(), "armeabi_v7a".to_string()],
+        density: Some(420),
+        language: Some("fr".to_string()),
+    };
+
+    let apks = [
+        "splits/base-master.apk",
+        "splits/base-armeabi_v7a.apk",
+        "splits/base-arm64_v8a.apk",
+        "splits/base-xhdpi.apk",
+        "splits/base-xxhdpi.apk",
+        "splits/base-xxxhdpi.apk",
+        "splits/base-en.apk",
+        "splits/base-fr.apk",
+        "splits/feature-master.apk",
+    ];
+    assert_eq!(select_splits(&apks, &config), [0, 2, 4, 7, 8]);
+
+    let xapk = [
+        "com.example.game.apk",
+        "config.armeabi_v7a.apk",
+        "config.hdpi.apk",
+        "config.de.apk",
+    ];
+    assert_eq!(select_splits(&xapk, &config), [0, 1, 2]);
+}
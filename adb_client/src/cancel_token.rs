@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::{Result, RustADBError};
+
+type CancelHook = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct CancelTokenInner {
+    cancelled: AtomicBool,
+    next_hook_id: AtomicU64,
+    /// Hooks aborting blocking operations currently in flight
+    hooks: Mutex<HashMap<u64, CancelHook>>,
+}
+
+/// Lightweight token used to cancel in-flight operations from another thread.
+///
+/// A token is attached to a device handle (e.g. using [`crate::ADBServerDevice::set_cancel_token`]) and is honored by
+/// shell sessions, file transfers, installs, logs and device tracking. Cancelled operations fail with [`RustADBError::Cancelled`].
+///
+/// Clones share the same state: cancelling one of them cancels all of them. A cancelled token stays cancelled.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelTokenInner>,
+}
+
+impl Debug for CancelToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancelToken {
+    /// Instantiates a new, not cancelled, [`CancelToken`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel all operations honoring this token
+    pub fn cancel(&self) {
+        if self.inner.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let hooks: Vec<CancelHook> = match self.inner.hooks.lock() {
+            Ok(mut hooks) => hooks.drain().map(|(_, hook)| hook).collect(),
+            Err(poisoned) => poisoned
+                .into_inner()
+                .drain()
+                .map(|(_, hook)| hook)
+                .collect(),
+        };
+        for hook in hooks {
+            hook();
+        }
+    }
+
+    /// Return `true` if this token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Return [`RustADBError::Cancelled`] if this token has been cancelled
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(RustADBError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Run `hook` when this token gets cancelled, as long as returned guard is alive.
+    ///
+    /// `hook` is run immediately if token is already cancelled.
+    pub(crate) fn on_cancel<F: FnOnce() + Send + 'static>(&self, hook: F) -> CancelGuard {
+        let id = self.inner.next_hook_id.fetch_add(1, Ordering::SeqCst);
+
+        if let Ok(mut hooks) = self.inner.hooks.lock() {
+            hooks.insert(id, Box::new(hook));
+        }
+
+        // Token may have been cancelled before hook was registered
+        if self.is_cancelled()
+            && let Some(hook) = self
+                .inner
+                .hooks
+                .lock()
+                .ok()
+                .and_then(|mut hooks| hooks.remove(&id))
+        {
+            hook();
+        }
+
+        CancelGuard {
+            token: self.clone(),
+            id,
+        }
+    }
+}
+
+/// Unregisters a hook set with [`CancelToken::on_cancel`] when dropped.
+#[derive(Debug)]
+pub(crate) struct CancelGuard {
+    token: CancelToken,
+    id: u64,
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if let Ok(mut hooks) = self.token.inner.hooks.lock() {
+            hooks.remove(&self.id);
+        }
+    }
+}
+
+#[test]
+fn test_cancel_token_hooks() {
+    use std::sync::atomic::AtomicUsize;
+
+    let token = CancelToken::new();
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let dropped_calls = calls.clone();
+    drop(token.on_cancel(move || {
+        dropped_calls.fetch_add(10, Ordering::SeqCst);
+    }));
+
+    let registered_calls = calls.clone();
+    let _guard = token.on_cancel(move || {
+        registered_calls.fetch_add(1, Ordering::SeqCst);
+    });
+
+    token.clone().cancel();
+    token.cancel();
+    assert!(token.check().is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
@@ -1,2 +1,56 @@
 #[cfg(any(feature = "tcp", feature = "usb"))]
 pub const BUFFER_SIZE: usize = 65536;
+/// Maximum payload size announced to devices, larger messages are rejected
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const MAX_PAYLOAD_SIZE: u32 = 1048576;
+/// Time given to a transport to start delivering a message when polling it
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const POLL_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(1);
+/// Time given to a USB device to send a message payload once its header has been received
+#[cfg(feature = "usb")]
+pub const PAYLOAD_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+/// Interval between two attempts to open a USB device while waiting for it
+#[cfg(feature = "usb")]
+pub const USB_WAIT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// Time given to clean a timed out shell command up, when closing its stream and killing it
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const SHELL_ABORT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+/// Interval between two checks of a [`crate::CancelToken`] while waiting for a message
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const CANCEL_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+/// Time given to a USB device to answer a string descriptor request
+#[cfg(feature = "usb")]
+pub const USB_STRING_DESCRIPTOR_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+/// USB interface protocol of adb interfaces (class `0xff`, subclass `0x42`)
+#[cfg(feature = "usb")]
+pub const ADB_INTERFACE_PROTOCOL: u8 = 0x01;
+/// USB interface protocol of fastboot interfaces (class `0xff`, subclass `0x42`)
+#[cfg(feature = "usb")]
+pub const FASTBOOT_INTERFACE_PROTOCOL: u8 = 0x03;
+/// Time given to a device in fastboot mode to answer a command, flashing or erasing large partitions taking minutes
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const FASTBOOT_RESPONSE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+/// Time given to a device to answer a connection health ping
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const PING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+/// Time given to a device to remove a reverse forward once it has been stopped
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const FORWARD_REMOVAL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+/// Time given to adbd to restart, e.g. after switching to root
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const ADBD_RESTART_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// Time given to a device to reboot, until adbd accepts connections again
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub const DEVICE_REBOOT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(180);
+/// Interval between two attempts to connect to a device over TCP while waiting for it
+#[cfg(feature = "tcp")]
+pub const TCP_WAIT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+/// Interval between two listings of services discovered by ADB server, when browsing mDNS through it
+#[cfg(feature = "tcp")]
+pub const MDNS_SERVER_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// Number of transfers kept in flight on each bulk endpoint of a USB device
+#[cfg(feature = "trans-nusb")]
+pub const USB_QUEUE_DEPTH: usize = 4;
+/// Size of transfers a message payload is split into when read from a USB device, a multiple of all packet sizes
+#[cfg(feature = "trans-nusb")]
+pub const USB_TRANSFER_CHUNK_SIZE: usize = 16384;
@@ -0,0 +1,46 @@
+use std::io::ErrorKind;
+
+use crate::{ADBDeviceExt, Result, RustADBError, TransferStats};
+
+/// Copy file at `src_path` on `src_device` to `dst_path` on `dst_device`.
+///
+/// File is streamed from a pull on source device directly into a push on destination device, without being stored on host.
+/// Both devices can use any kind of connection (e.g. copying from an [`crate::ADBServerDevice`] to an [`crate::ADBUSBDevice`]).
+///
+/// If pull fails while transferring, destination file may be left incomplete. Statistics of push are returned on success.
+pub fn copy_between<S, D>(
+    src_device: &mut S,
+    src_path: &str,
+    dst_device: &mut D,
+    dst_path: &str,
+) -> Result<TransferStats>
+where
+    S: ADBDeviceExt + Send + ?Sized,
+    D: ADBDeviceExt + ?Sized,
+{
+    let (mut reader, mut writer) = std::io::pipe()?;
+
+    let (pull_result, push_result) = std::thread::scope(|scope| {
+        // Writer is dropped once pull ends, which signals end of file to push
+        let pull = scope.spawn(move || src_device.pull(&src_path, &mut writer));
+
+        let push_result = dst_device.push(&mut reader, &dst_path);
+        // Unblocks pull if push stopped reading early
+        drop(reader);
+
+        let pull_result = pull
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+
+        (pull_result, push_result)
+    });
+
+    match (pull_result, push_result) {
+        // Pull only failed because push stopped reading, the latter knows why
+        (Err(RustADBError::IOError(e)), Err(push_error)) if e.kind() == ErrorKind::BrokenPipe => {
+            Err(push_error)
+        }
+        (Err(e), _) | (Ok(_), Err(e)) => Err(e),
+        (Ok(_), Ok(stats)) => Ok(stats),
+    }
+}
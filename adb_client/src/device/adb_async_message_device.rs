@@ -0,0 +1,364 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use rand::Rng;
+use std::io::{Cursor, ErrorKind, Seek};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::{
+    ADBAsyncMessageTransport, AdbStatResponse, Result, RustADBError,
+    constants::{BUFFER_SIZE, MAX_PAYLOAD_SIZE},
+};
+
+use super::adb_message_device::ADBSession;
+use super::adb_transport_message::{AUTH_RSAPUBLICKEY, AUTH_SIGNATURE, AUTH_TOKEN};
+use super::{
+    ADBRsaKey, ADBTransportMessage, MessageCommand, get_default_adb_key_path,
+    models::MessageSubcommand, read_adb_private_key,
+};
+
+/// Generic structure representing an ADB device reachable over an [`ADBAsyncMessageTransport`].
+/// Asynchronous counterpart of the message device used by [`crate::ADBTcpDevice`] and [`crate::ADBUSBDevice`].
+#[derive(Debug)]
+pub struct ADBAsyncMessageDevice<T: ADBAsyncMessageTransport> {
+    transport: T,
+    /// Private key used to authenticate, default one if `None`
+    private_key_path: Option<PathBuf>,
+    maximum_data_size: Option<usize>,
+}
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    /// Instantiate a new [`ADBAsyncMessageDevice`]. [`ADBAsyncMessageDevice::connect`] must be called before issuing commands.
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            private_key_path: None,
+            maximum_data_size: None,
+        }
+    }
+
+    /// Instantiate a new [`ADBAsyncMessageDevice`] authenticating using a custom private key path
+    pub fn new_with_custom_private_key(transport: T, private_key_path: PathBuf) -> Self {
+        Self {
+            private_key_path: Some(private_key_path),
+            ..Self::new(transport)
+        }
+    }
+
+    /// Send initial connect, authenticating if device requires it (TLS is not supported).
+    ///
+    /// A random key is generated if private key cannot be read, device then asking user to accept it.
+    pub async fn connect(&mut self) -> Result<()> {
+        self.transport.connect().await?;
+
+        let message = ADBTransportMessage::new(
+            MessageCommand::Cnxn,
+            0x01000000,
+            MAX_PAYLOAD_SIZE,
+            format!("host::{}\0", env!("CARGO_PKG_NAME")).as_bytes(),
+        );
+
+        self.transport.write_message(message).await?;
+
+        let message = self.transport.read_message().await?;
+        // Devices not requiring authentication directly answer with CNXN
+        if message.header().command() == MessageCommand::Cnxn {
+            return self.set_maximum_data_size(message.header().arg1());
+        }
+        message.assert_command(MessageCommand::Auth)?;
+
+        let auth_message = match message.header().arg0() {
+            AUTH_TOKEN => message,
+            v => {
+                return Err(RustADBError::ADBRequestFailed(format!(
+                    "Received AUTH message with type != 1 ({v})"
+                )));
+            }
+        };
+
+        let private_key = self.private_key()?;
+        let sign = private_key.sign(auth_message.into_payload())?;
+        let message = ADBTransportMessage::new(MessageCommand::Auth, AUTH_SIGNATURE, 0, &sign);
+        self.transport.write_message(message).await?;
+
+        let received_response = self.transport.read_message().await?;
+        if received_response.header().command() == MessageCommand::Cnxn {
+            self.set_maximum_data_size(received_response.header().arg1())?;
+            log::info!(
+                "Authentication OK, device info {}",
+                String::from_utf8(received_response.into_payload())?
+            );
+            return Ok(());
+        }
+
+        // Signature has been refused, device asks user to accept our public key
+        let mut pubkey = private_key.android_pubkey_encode()?.into_bytes();
+        pubkey.push(b'\0');
+        let message = ADBTransportMessage::new(MessageCommand::Auth, AUTH_RSAPUBLICKEY, 0, &pubkey);
+        self.transport.write_message(message).await?;
+
+        let response = self
+            .transport
+            .read_message_with_timeout(Duration::from_secs(10))
+            .await?;
+        response.assert_command(MessageCommand::Cnxn)?;
+        self.set_maximum_data_size(response.header().arg1())?;
+        log::info!(
+            "Authentication OK, device info {}",
+            String::from_utf8(response.into_payload())?
+        );
+
+        Ok(())
+    }
+
+    /// Read private key used to authenticate, generating a random one if it cannot be read
+    fn private_key(&self) -> Result<ADBRsaKey> {
+        let private_key_path = match &self.private_key_path {
+            Some(private_key_path) => private_key_path.clone(),
+            None => get_default_adb_key_path()?,
+        };
+        match read_adb_private_key(private_key_path)? {
+            Some(private_key) => Ok(private_key),
+            None => ADBRsaKey::new_random(),
+        }
+    }
+
+    pub(crate) fn get_transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub(crate) fn get_transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Receive a message and acknowledge it by replying with an `OKAY` command
+    pub(crate) async fn recv_and_reply_okay(
+        &mut self,
+        session: ADBSession,
+    ) -> Result<ADBTransportMessage> {
+        let message = self.transport.read_message().await?;
+        self.transport
+            .write_message(ADBTransportMessage::new(
+                MessageCommand::Okay,
+                session.local_id,
+                session.remote_id,
+                &[],
+            ))
+            .await?;
+        Ok(message)
+    }
+
+    /// Expect a message with an `OKAY` command after sending a message.
+    pub(crate) async fn send_and_expect_okay(
+        &mut self,
+        message: ADBTransportMessage,
+    ) -> Result<ADBTransportMessage> {
+        self.transport.write_message(message).await?;
+
+        let message = self.transport.read_message().await?;
+        message.assert_command(MessageCommand::Okay)?;
+        Ok(message)
+    }
+
+    pub(crate) async fn recv_file<W: AsyncWrite + Unpin + ?Sized>(
+        &mut self,
+        session: ADBSession,
+        output: &mut W,
+    ) -> Result<()> {
+        let mut len: Option<u64> = None;
+        loop {
+            let payload = self.recv_and_reply_okay(session).await?.into_payload();
+            let mut rdr = Cursor::new(&payload);
+            while rdr.position() != payload.len() as u64 {
+                match len.take() {
+                    Some(0) | None => {
+                        rdr.seek_relative(4)?;
+                        len.replace(rdr.read_u32::<LittleEndian>()? as u64);
+                    }
+                    Some(length) => {
+                        let position = rdr.position() as usize;
+                        let remaining_bytes = (payload.len() - position) as u64;
+                        if length < remaining_bytes {
+                            let end = position + length as usize;
+                            output.write_all(&payload[position..end]).await?;
+                            rdr.set_position(end as u64);
+                        } else {
+                            output.write_all(&payload[position..]).await?;
+                            len.replace(length - remaining_bytes);
+                            // this payload is now exhausted
+                            break;
+                        }
+                    }
+                }
+            }
+            // Last 8 bytes hold the subcommand ending this payload, and its argument
+            let last_subcommand = payload
+                .len()
+                .checked_sub(8)
+                .and_then(|start| payload.get(start..start + 4))
+                .ok_or_else(|| {
+                    RustADBError::InvalidMessage(format!("sync payload of {} bytes", payload.len()))
+                })?;
+            if Cursor::new(last_subcommand).read_u32::<LittleEndian>()?
+                == MessageSubcommand::Done as u32
+            {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn push_file<R: AsyncRead + Unpin + ?Sized>(
+        &mut self,
+        session: ADBSession,
+        reader: &mut R,
+    ) -> Result<()> {
+        let mut buffer = vec![0; BUFFER_SIZE];
+        // The max size of a data packet is the devices reported maximum data size
+        // minus 8 (the size of the sub command stuct before the data)
+        // or BUFFER_SIZE, whichever is smaller.
+        let max_read = self
+            .maximum_data_size
+            .map(|v| v - 8)
+            .unwrap_or(BUFFER_SIZE)
+            .min(BUFFER_SIZE);
+
+        loop {
+            let size = reader.read(&mut buffer[..max_read]).await?;
+            if size == 0 {
+                // Currently file mtime is not forwarded
+                let subcommand_data = MessageSubcommand::Done.with_arg(0);
+
+                let serialized_message = bincode::serialize(&subcommand_data)
+                    .map_err(|_e| RustADBError::ConversionError)?;
+
+                self.send_and_expect_okay(ADBTransportMessage::new(
+                    MessageCommand::Write,
+                    session.local_id,
+                    session.remote_id,
+                    &serialized_message,
+                ))
+                .await?;
+
+                // Command should end with a Write => Okay
+                let received = self.transport.read_message().await?;
+                return match received.header().command() {
+                    MessageCommand::Write => Ok(()),
+                    c => Err(RustADBError::ADBRequestFailed(format!(
+                        "Wrong command received {}",
+                        c
+                    ))),
+                };
+            }
+
+            let subcommand_data = MessageSubcommand::Data.with_arg(size as u32);
+
+            let mut serialized_message =
+                bincode::serialize(&subcommand_data).map_err(|_e| RustADBError::ConversionError)?;
+            serialized_message.extend_from_slice(&buffer[..size]);
+
+            self.send_and_expect_okay(ADBTransportMessage::new(
+                MessageCommand::Write,
+                session.local_id,
+                session.remote_id,
+                &serialized_message,
+            ))
+            .await?;
+        }
+    }
+
+    pub(crate) async fn begin_synchronization(&mut self) -> Result<ADBSession> {
+        self.open_session(b"sync:\0").await
+    }
+
+    pub(crate) async fn stat_with_explicit_ids(
+        &mut self,
+        session: ADBSession,
+        remote_path: &str,
+    ) -> Result<AdbStatResponse> {
+        let stat_buffer = MessageSubcommand::Stat.with_arg(remote_path.len() as u32);
+        let message = ADBTransportMessage::new(
+            MessageCommand::Write,
+            session.local_id,
+            session.remote_id,
+            &bincode::serialize(&stat_buffer).map_err(|_e| RustADBError::ConversionError)?,
+        );
+        self.send_and_expect_okay(message).await?;
+        self.send_and_expect_okay(ADBTransportMessage::new(
+            MessageCommand::Write,
+            session.local_id,
+            session.remote_id,
+            remote_path.as_bytes(),
+        ))
+        .await?;
+        let response = self.transport.read_message().await?;
+        AdbStatResponse::try_from(response.payload().as_slice())
+    }
+
+    pub(crate) async fn end_transaction(&mut self, session: ADBSession) -> Result<()> {
+        let quit_buffer = MessageSubcommand::Quit.with_arg(0u32);
+        let sb = bincode::serialize(&quit_buffer).map_err(|_e| RustADBError::ConversionError)?;
+        self.send_and_expect_okay(ADBTransportMessage::new(
+            MessageCommand::Write,
+            session.local_id,
+            session.remote_id,
+            &sb,
+        ))
+        .await?;
+        // HACK: some devices don't send a close message
+        match self
+            .transport
+            .read_message_with_timeout(Duration::from_millis(100))
+            .await
+        {
+            Err(RustADBError::IOError(e)) if e.kind() == ErrorKind::TimedOut => Ok(()),
+            Err(e) => Err(e),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    pub(crate) async fn open_session(&mut self, data: &[u8]) -> Result<ADBSession> {
+        let local_id: u32 = rand::rng().random();
+
+        let message = ADBTransportMessage::new(
+            MessageCommand::Open,
+            local_id, // Our 'local-id'
+            0,
+            data,
+        );
+        self.transport.write_message(message).await?;
+
+        let response = self.transport.read_message().await?;
+
+        if response.header().command() != MessageCommand::Okay {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "Open session failed: got {} in respone instead of OKAY",
+                response.header().command()
+            )));
+        }
+
+        if response.header().arg1() != local_id {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "Open session failed: respones used {} for our local_id instead of {local_id}",
+                response.header().arg1()
+            )));
+        }
+
+        Ok(ADBSession {
+            local_id,
+            remote_id: response.header().arg0(),
+        })
+    }
+
+    pub(crate) fn set_maximum_data_size(&mut self, maximum_data_size: u32) -> Result<()> {
+        self.maximum_data_size = Some(usize::try_from(maximum_data_size)?);
+        Ok(())
+    }
+
+    /// Maximum payload size accepted by the device for a single message, once connected.
+    pub(crate) fn maximum_payload_size(&self) -> usize {
+        self.maximum_data_size
+            .unwrap_or(BUFFER_SIZE)
+            .min(BUFFER_SIZE)
+    }
+}
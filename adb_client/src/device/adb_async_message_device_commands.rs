@@ -0,0 +1,68 @@
+use futures_lite::{AsyncRead, AsyncWrite};
+use image::{ImageBuffer, Rgba};
+
+use crate::{
+    ADBAsyncMessageTransport, ADBDeviceAsyncExt, InstallOptions, RebootType, Result,
+    models::AdbStatResponse,
+};
+
+use super::ADBAsyncMessageDevice;
+
+impl<T: ADBAsyncMessageTransport> ADBDeviceAsyncExt for ADBAsyncMessageDevice<T> {
+    async fn shell_command(
+        &mut self,
+        command: &[&str],
+        output: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<()> {
+        self.shell_command(command, output).await
+    }
+
+    async fn shell(
+        &mut self,
+        reader: &mut (dyn AsyncRead + Unpin + Send),
+        writer: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<()> {
+        self.shell(reader, writer).await
+    }
+
+    async fn stat(&mut self, remote_path: &str) -> Result<AdbStatResponse> {
+        self.stat(remote_path).await
+    }
+
+    async fn pull(
+        &mut self,
+        source: &str,
+        output: &mut (dyn AsyncWrite + Unpin + Send),
+    ) -> Result<()> {
+        self.pull(source, output).await
+    }
+
+    async fn push(
+        &mut self,
+        stream: &mut (dyn AsyncRead + Unpin + Send),
+        path: &str,
+    ) -> Result<()> {
+        self.push(stream, path).await
+    }
+
+    async fn reboot(&mut self, reboot_type: RebootType) -> Result<()> {
+        self.reboot(reboot_type).await
+    }
+
+    async fn install_with_options(
+        &mut self,
+        apk: &mut (dyn AsyncRead + Unpin + Send),
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.install(apk, size, options).await
+    }
+
+    async fn uninstall(&mut self, package: &str) -> Result<()> {
+        self.uninstall(package).await
+    }
+
+    async fn framebuffer_inner(&mut self) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        self.framebuffer_inner().await
+    }
+}
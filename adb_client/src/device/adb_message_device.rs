@@ -1,18 +1,36 @@
 use byteorder::{LittleEndian, ReadBytesExt};
 use rand::Rng;
 use std::io::{Cursor, ErrorKind, Read, Seek};
-use std::time::Duration;
-
-use crate::{ADBMessageTransport, AdbStatResponse, Result, RustADBError, constants::BUFFER_SIZE};
-
-use super::{ADBTransportMessage, MessageCommand, models::MessageSubcommand};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::observer::ObserverSlot;
+use crate::{
+    ADBMessageTransport, AdbObserver, AdbStatResponse, CancelToken, Result, RustADBError,
+    constants::BUFFER_SIZE,
+};
+
+#[cfg(feature = "tcp")]
+use super::adb_transport_message::STLS_VERSION;
+use super::message_dispatcher::MessageDispatcher;
+use super::{ADBTransportMessage, DeviceMode, MessageCommand, models::MessageSubcommand};
+#[cfg(feature = "tcp")]
+use crate::constants::MAX_PAYLOAD_SIZE;
 
 /// Generic structure representing an ADB device reachable over an [`ADBMessageTransport`].
 /// Structure is totally agnostic over which transport is truly used.
 #[derive(Debug)]
 pub struct ADBMessageDevice<T: ADBMessageTransport> {
     transport: T,
+    /// Shared with port forwards, whose messages may be read by device commands and conversely
+    dispatcher: Arc<MessageDispatcher>,
     maximum_data_size: Option<usize>,
+    mode: DeviceMode,
+    /// Features announced by device when connecting (e.g. `shell_v2`)
+    features: Vec<String>,
+    observer: ObserverSlot,
+    cancel_token: Option<CancelToken>,
+    transfer_rate_limit: Option<u64>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -22,12 +40,83 @@ pub struct ADBSession {
 }
 
 impl<T: ADBMessageTransport> ADBMessageDevice<T> {
-    /// Instantiate a new [`ADBMessageTransport`]
+    /// Instantiate a new [`ADBMessageDevice`] over given transport
     pub fn new(transport: T) -> Self {
         Self {
             transport,
+            dispatcher: Arc::default(),
             maximum_data_size: None,
+            mode: DeviceMode::default(),
+            features: Vec::new(),
+            observer: ObserverSlot::default(),
+            cancel_token: None,
+            transfer_rate_limit: None,
+        }
+    }
+
+    /// Attach a [`CancelToken`] honored by all following operations
+    pub fn set_cancel_token(&mut self, cancel_token: CancelToken) {
+        self.cancel_token = Some(cancel_token);
+    }
+
+    pub(crate) fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel_token.as_ref()
+    }
+
+    /// Read a message, giving up if attached [`CancelToken`] gets cancelled
+    pub(crate) fn read_message(&mut self) -> Result<ADBTransportMessage> {
+        self.dispatcher
+            .read_message(&mut self.transport, None, self.cancel_token.as_ref())
+    }
+
+    /// Same as [`ADBMessageDevice::read_message`], failing with [`ErrorKind::TimedOut`] once `deadline` is reached
+    pub(crate) fn read_message_before(&mut self, deadline: Instant) -> Result<ADBTransportMessage> {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::Error::from(ErrorKind::TimedOut).into());
+        }
+
+        self.dispatcher.read_message(
+            &mut self.transport,
+            Some(remaining),
+            self.cancel_token.as_ref(),
+        )
+    }
+
+    /// Same as [`ADBMessageDevice::read_message`], closing `session` on cancellation
+    pub(crate) fn read_session_message(
+        &mut self,
+        session: ADBSession,
+    ) -> Result<ADBTransportMessage> {
+        let result = self.read_message();
+        if let Err(RustADBError::Cancelled) = result {
+            // Best effort, remote end will stop sending data for this session
+            let _ = self.transport.write_message(ADBTransportMessage::new(
+                MessageCommand::Clse,
+                session.local_id,
+                session.remote_id,
+                &[],
+            ));
         }
+        result
+    }
+
+    /// Attach an [`AdbObserver`] notified by all following operations
+    pub fn set_observer(&mut self, observer: Arc<dyn AdbObserver>) {
+        self.observer.set(observer);
+    }
+
+    pub(crate) fn observer(&self) -> &ObserverSlot {
+        &self.observer
+    }
+
+    /// Limit rate of following file transfers (push and pull) to `bytes_per_second`, `None` or `0` meaning no limit
+    pub fn set_transfer_rate_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.transfer_rate_limit = bytes_per_second;
+    }
+
+    pub(crate) fn transfer_rate_limit(&self) -> Option<u64> {
+        self.transfer_rate_limit
     }
 
     pub(crate) fn get_transport(&mut self) -> &T {
@@ -38,12 +127,17 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         &mut self.transport
     }
 
+    /// Dispatcher of messages read from transport, to be used by anything reading it besides device itself
+    pub(crate) fn dispatcher(&self) -> &Arc<MessageDispatcher> {
+        &self.dispatcher
+    }
+
     /// Receive a message and acknowledge it by replying with an `OKAY` command
     pub(crate) fn recv_and_reply_okay(
         &mut self,
         session: ADBSession,
     ) -> Result<ADBTransportMessage> {
-        let message = self.transport.read_message()?;
+        let message = self.read_session_message(session)?;
         self.transport.write_message(ADBTransportMessage::new(
             MessageCommand::Okay,
             session.local_id,
@@ -58,9 +152,13 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         &mut self,
         message: ADBTransportMessage,
     ) -> Result<ADBTransportMessage> {
+        let session = ADBSession {
+            local_id: message.header().arg0(),
+            remote_id: message.header().arg1(),
+        };
         self.transport.write_message(message)?;
 
-        self.transport.read_message().and_then(|message| {
+        self.read_session_message(session).and_then(|message| {
             message.assert_command(MessageCommand::Okay)?;
             Ok(message)
         })
@@ -94,8 +192,15 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
                     }
                 }
             }
-            if Cursor::new(&payload[(payload.len() - 8)..(payload.len() - 4)])
-                .read_u32::<LittleEndian>()?
+            // Last 8 bytes hold the subcommand ending this payload, and its argument
+            let last_subcommand = payload
+                .len()
+                .checked_sub(8)
+                .and_then(|start| payload.get(start..start + 4))
+                .ok_or_else(|| {
+                    RustADBError::InvalidMessage(format!("sync payload of {} bytes", payload.len()))
+                })?;
+            if Cursor::new(last_subcommand).read_u32::<LittleEndian>()?
                 == MessageSubcommand::Done as u32
             {
                 break;
@@ -108,6 +213,7 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         &mut self,
         session: ADBSession,
         mut reader: R,
+        mod_time: u32,
     ) -> std::result::Result<(), RustADBError> {
         let mut buffer = [0; BUFFER_SIZE];
         // The max size of a data packet is the devices reported maximum data size
@@ -136,12 +242,15 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         self.send_and_expect_okay(message)?;
 
         loop {
+            if let Some(cancel_token) = &self.cancel_token {
+                cancel_token.check()?;
+            }
+
             let mut buffer = [0; BUFFER_SIZE];
 
             match reader.read(&mut buffer[..max_read]) {
                 Ok(0) => {
-                    // Currently file mtime is not forwarded
-                    let subcommand_data = MessageSubcommand::Done.with_arg(0);
+                    let subcommand_data = MessageSubcommand::Done.with_arg(mod_time);
 
                     let serialized_message = bincode::serialize(&subcommand_data)
                         .map_err(|_e| RustADBError::ConversionError)?;
@@ -156,7 +265,7 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
                     self.send_and_expect_okay(message)?;
 
                     // Command should end with a Write => Okay
-                    let received = self.transport.read_message()?;
+                    let received = self.read_session_message(session)?;
                     match received.header().command() {
                         MessageCommand::Write => return Ok(()),
                         c => {
@@ -214,11 +323,8 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
             session.remote_id,
             remote_path.as_bytes(),
         ))?;
-        let response = self.transport.read_message()?;
-        // Skip first 4 bytes as this is the literal "STAT".
-        // Interesting part starts right after
-        bincode::deserialize(&response.into_payload()[4..])
-            .map_err(|_e| RustADBError::ConversionError)
+        let response = self.read_session_message(session)?;
+        AdbStatResponse::try_from(response.payload().as_slice())
     }
 
     pub(crate) fn end_transaction(&mut self, session: ADBSession) -> Result<()> {
@@ -231,10 +337,11 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
             &sb,
         ))?;
         // HACK: some devices don't send a close message
-        match self
-            .transport
-            .read_message_with_timeout(Duration::from_millis(100))
-        {
+        match self.dispatcher.read_message(
+            &mut self.transport,
+            Some(Duration::from_millis(100)),
+            None,
+        ) {
             Err(RustADBError::IOError(e)) if e.kind() == ErrorKind::TimedOut => Ok(()),
             Err(e) => Err(e),
             Ok(_) => Ok(()),
@@ -245,6 +352,15 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         &mut self,
         data: &[u8],
     ) -> Result<ADBSession> {
+        // minadbd drops connection on unknown services instead of rejecting them
+        if !self.mode.allows_service(data) {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "service {} is not available in {} mode",
+                String::from_utf8_lossy(data).trim_end_matches('\0'),
+                self.mode
+            )));
+        }
+
         let mut rng = rand::rng();
         let local_id: u32 = rng.random();
 
@@ -256,7 +372,7 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         );
         self.get_transport_mut().write_message(message)?;
 
-        let response = self.get_transport_mut().read_message()?;
+        let response = self.read_message()?;
 
         if response.header().command() != MessageCommand::Okay {
             return Err(RustADBError::ADBRequestFailed(format!(
@@ -280,9 +396,139 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         Ok(session)
     }
 
-    #[cfg(feature = "usb")]
+    /// Maximum payload size accepted by the device for a single message.
+    pub(crate) fn maximum_payload_size(&self) -> usize {
+        self.maximum_data_size
+            .unwrap_or(BUFFER_SIZE)
+            .min(BUFFER_SIZE)
+    }
+
     pub(crate) fn set_maximum_data_size(&mut self, maximum_data_size: u32) -> Result<()> {
         self.maximum_data_size = Some(usize::try_from(maximum_data_size)?);
         Ok(())
     }
+
+    /// Mode announced by device when connecting
+    pub(crate) fn mode(&self) -> DeviceMode {
+        self.mode
+    }
+
+    /// Return `true` if device announced `feature` when connecting
+    pub(crate) fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// Features announced by device in its `CNXN` message
+    pub(crate) fn announced_features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// Send our `CNXN` message over freshly connected transport, and complete connection depending on device answer.
+    ///
+    /// Device either answers with its own `CNXN` message, or with `STLS` if connection has to be upgraded to TLS first.
+    #[cfg(feature = "tcp")]
+    pub(crate) fn handshake(&mut self) -> Result<()> {
+        let message = ADBTransportMessage::new(
+            MessageCommand::Cnxn,
+            0x01000000,
+            MAX_PAYLOAD_SIZE,
+            format!("host::{}\0", env!("CARGO_PKG_NAME")).as_bytes(),
+        );
+
+        self.transport.write_message(message)?;
+
+        let message = self.transport.read_message()?;
+
+        // Check if client is requesting a secure connection and upgrade it if necessary
+        match message.header().command() {
+            MessageCommand::Stls => {
+                self.handle_stls()?;
+                log::debug!("Connection successfully upgraded to TLS");
+            }
+            MessageCommand::Cnxn => {
+                self.handle_connection(&message)?;
+                log::debug!("Unencrypted connection established");
+            }
+            _ => {
+                return Err(RustADBError::WrongResponseReceived(
+                    "Expected CNXN or STLS command".to_string(),
+                    message.header().command().to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Complete connection to a device which answered our `CNXN` message with `STLS`.
+    ///
+    /// Connection is upgraded to TLS, authenticating using our private key, and device then sends its `CNXN` message.
+    #[cfg(feature = "tcp")]
+    pub(crate) fn handle_stls(&mut self) -> Result<()> {
+        let message = ADBTransportMessage::new(MessageCommand::Stls, STLS_VERSION, 0, &[]);
+        self.transport.write_message(message)?;
+        self.transport.upgrade_tls()?;
+
+        let message = self.transport.read_message()?;
+        message.assert_command(MessageCommand::Cnxn)?;
+        self.handle_connection(&message)?;
+        log::debug!(
+            "TLS connection established, device info {}",
+            String::from_utf8_lossy(message.payload())
+        );
+        Ok(())
+    }
+
+    /// Record maximum data size, mode and features announced by device in its `CNXN` message
+    pub(crate) fn handle_connection(&mut self, message: &ADBTransportMessage) -> Result<()> {
+        // Messages still kept from a previous connection are meaningless now
+        self.dispatcher.discard_pending()?;
+        self.set_maximum_data_size(message.header().arg1())?;
+        self.mode = DeviceMode::from_banner(message.payload());
+        self.features = parse_banner_features(message.payload());
+        if self.mode != DeviceMode::Normal {
+            log::debug!("device connected in {} mode", self.mode);
+        }
+        Ok(())
+    }
+}
+
+/// Parse features from a connection banner, e.g. `device::ro.product.name=sdk;features=shell_v2,cmd`
+fn parse_banner_features(banner: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(banner)
+        .trim_end_matches('\0')
+        .split(';')
+        .find_map(|property| property.split("::").last()?.strip_prefix("features="))
+        .map(|features| features.split(',').map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_parse_banner_features() {
+    assert_eq!(
+        parse_banner_features(b"device::ro.product.name=sdk;features=shell_v2,cmd\0"),
+        ["shell_v2", "cmd"]
+    );
+    assert!(parse_banner_features(b"sideload::").is_empty());
+}
+
+#[cfg(feature = "tcp")]
+#[test]
+fn test_handle_stls() {
+    use crate::MockTransport;
+
+    let transport = MockTransport::new();
+    transport.expect(MessageCommand::Stls).respond_with_args(
+        MessageCommand::Cnxn,
+        0x01000001,
+        4096,
+        b"device::ro.product.name=sdk;features=shell_v2\0",
+    );
+
+    let mut device = ADBMessageDevice::new(transport.clone());
+    device.handle_stls().expect("cannot handle STLS");
+    assert_eq!(transport.written()[0].header().arg0(), STLS_VERSION);
+    assert_eq!(device.maximum_payload_size(), 4096);
+    assert!(device.has_feature("shell_v2"));
+    assert!(transport.is_exhausted());
 }
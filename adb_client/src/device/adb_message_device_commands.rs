@@ -1,7 +1,12 @@
-use crate::{ADBDeviceExt, ADBMessageTransport, RebootType, Result, models::AdbStatResponse};
+use crate::shell_protocol::{legacy_run_command, legacy_shell_command_output};
+use crate::{
+    ADBDeviceExt, ADBMessageTransport, CancelToken, InstallOptions, ReadSeek, ReadWriteStream,
+    RebootType, Result, ShellOptions, ShellOutput, TransferStats, models::AdbStatResponse,
+};
 use std::{
     io::{Read, Write},
     path::Path,
+    time::{Duration, SystemTime},
 };
 
 use super::ADBMessageDevice;
@@ -11,35 +16,155 @@ impl<T: ADBMessageTransport> ADBDeviceExt for ADBMessageDevice<T> {
         self.shell_command(command, output)
     }
 
+    fn shell_command_with_timeout(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.shell_command_with_timeout(command, output, timeout)
+    }
+
+    fn shell_command_output(&mut self, command: &[&str], output: ShellOutput<'_>) -> Result<()> {
+        if !self.has_feature("shell_v2") {
+            return legacy_shell_command_output(self, command, output);
+        }
+        self.shell_command_v2(command, output)?;
+        Ok(())
+    }
+
+    fn run_command(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        legacy_exit_code: bool,
+    ) -> Result<u8> {
+        if !self.has_feature("shell_v2") {
+            return legacy_run_command(self, command, output, legacy_exit_code);
+        }
+        self.shell_command_v2(command, ShellOutput::Merged(output))
+    }
+
     fn shell(&mut self, reader: &mut dyn Read, writer: Box<(dyn Write + Send)>) -> Result<()> {
         self.shell(reader, writer)
     }
 
+    fn shell_with_options(
+        &mut self,
+        options: &ShellOptions,
+        reader: &mut dyn Read,
+        writer: Box<dyn Write + Send>,
+    ) -> Result<()> {
+        self.shell_with_options(options, reader, writer)
+    }
+
+    fn features(&mut self) -> Result<Vec<String>> {
+        Ok(self.announced_features().to_vec())
+    }
+
     fn stat(&mut self, remote_path: &str) -> Result<AdbStatResponse> {
         self.stat(remote_path)
     }
 
-    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<()> {
+    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<TransferStats> {
         self.pull(source, output)
     }
 
-    fn push(&mut self, stream: &mut dyn Read, path: &dyn AsRef<str>) -> Result<()> {
-        self.push(stream, path)
+    fn push_with_metadata(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<TransferStats> {
+        self.push(stream, path, mode, mod_time)
     }
 
     fn reboot(&mut self, reboot_type: RebootType) -> Result<()> {
         self.reboot(reboot_type)
     }
 
-    fn install(&mut self, apk_path: &dyn AsRef<Path>) -> Result<()> {
-        self.install(apk_path)
+    /// Restart adbd with root permissions, connection being lost if it restarts.
+    ///
+    /// Devices created from a transport only know how to establish a connection, see [`crate::ADBTcpDevice`] and
+    /// [`crate::ADBUSBDevice`].
+    fn root(&mut self) -> Result<()> {
+        self.restart_adbd("root:").map(|_| ())
+    }
+
+    /// Restart adbd without root permissions, connection being lost if it restarts.
+    fn unroot(&mut self) -> Result<()> {
+        self.restart_adbd("unroot:").map(|_| ())
+    }
+
+    /// Remount system partitions read-write, connection being lost if device reboots
+    fn remount(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("remount:", reboot)
+    }
+
+    /// Disable dm-verity, connection being lost if device reboots
+    fn disable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("disable-verity:", reboot)
+    }
+
+    /// Enable dm-verity, connection being lost if device reboots
+    fn enable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("enable-verity:", reboot)
+    }
+
+    fn install_with_options(
+        &mut self,
+        apk_path: &dyn AsRef<Path>,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.install(apk_path, options)
+    }
+
+    fn install_from_reader_with_options(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.install_from_reader(reader, size, options)
+    }
+
+    fn sideload_from_reader(
+        &mut self,
+        reader: &mut dyn ReadSeek,
+        size: u64,
+    ) -> Result<TransferStats> {
+        self.sideload_from_reader(reader, size)
     }
 
     fn uninstall(&mut self, package: &str) -> Result<()> {
         self.uninstall(package)
     }
 
+    fn open_service(&mut self, service: &str) -> Result<Box<dyn ReadWriteStream>> {
+        self.open_service(service)
+    }
+
+    fn open_service_cancellable(
+        &mut self,
+        service: &str,
+        cancel_token: &CancelToken,
+    ) -> Result<Box<dyn ReadWriteStream>> {
+        self.open_service_cancellable(service, cancel_token)
+    }
+
     fn framebuffer_inner(&mut self) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
         self.framebuffer_inner()
     }
+
+    fn framebuffer_into(
+        &mut self,
+        buffer: Vec<u8>,
+    ) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+        self.framebuffer_into(buffer)
+    }
+
+    fn framebuffer_raw(&mut self) -> Result<crate::RawFramebuffer> {
+        self.framebuffer_raw()
+    }
 }
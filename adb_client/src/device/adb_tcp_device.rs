@@ -1,23 +1,66 @@
-use std::io::Write;
+use std::io::{ErrorKind, Write};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 use std::{io::Read, net::SocketAddr};
 
-use super::ADBTransportMessage;
 use super::adb_message_device::ADBMessageDevice;
-use super::models::MessageCommand;
-use crate::{ADBDeviceExt, ADBMessageTransport, ADBTransport, Result, TcpTransport};
+#[cfg(feature = "ssh")]
+use crate::SshTunnelTransport;
+use crate::constants::DEVICE_REBOOT_TIMEOUT;
+use crate::{
+    ADBDeviceExt, ADBTransport, AdbObserver, CancelToken, InstallOptions, PortForward, ReadSeek,
+    ReadWriteStream, Result, ShellOptions, ShellOutput, TcpConnectOptions, TcpTransport,
+    TransferStats,
+};
+
+/// Policy followed by an [`ADBTcpDevice`] to reconnect once its connection has been lost.
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    /// Number of reconnection attempts before giving up
+    pub attempts: u32,
+    /// Delay between two attempts
+    pub delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            delay: Duration::from_secs(2),
+        }
+    }
+}
 
 /// Represent a device reached and available over USB.
 #[derive(Debug)]
 pub struct ADBTcpDevice {
     inner: ADBMessageDevice<TcpTransport>,
+    reconnect_policy: Option<ReconnectPolicy>,
+    /// SSH tunnel device is reached through, closed once device is dropped
+    #[cfg(feature = "ssh")]
+    tunnel: Option<SshTunnelTransport>,
 }
 
 impl ADBTcpDevice {
     /// Instantiate a new [`ADBTcpDevice`]
     pub fn new(address: SocketAddr) -> Result<Self> {
+        Self::new_with_options(address, TcpConnectOptions::default())
+    }
+
+    /// Instantiate a new [`ADBTcpDevice`], connecting to it using given options (local bind address, connect timeout, proxy).
+    pub fn new_with_options(
+        address: SocketAddr,
+        connect_options: TcpConnectOptions,
+    ) -> Result<Self> {
+        let mut transport = TcpTransport::new(address)?;
+        transport.set_connect_options(connect_options);
+
         let mut device = Self {
-            inner: ADBMessageDevice::new(TcpTransport::new(address)?),
+            inner: ADBMessageDevice::new(transport),
+            reconnect_policy: None,
+            #[cfg(feature = "ssh")]
+            tunnel: None,
         };
 
         device.connect()?;
@@ -25,53 +68,206 @@ impl ADBTcpDevice {
         Ok(device)
     }
 
+    /// Instantiate a new [`ADBTcpDevice`] reached through `tunnel`, forwarding adbd port of a remote device.
+    ///
+    /// Tunnel is connected if needed, and closed once device is dropped.
+    #[cfg(feature = "ssh")]
+    pub fn new_over_ssh(mut tunnel: SshTunnelTransport) -> Result<Self> {
+        if tunnel.local_address().is_err() {
+            tunnel.connect()?;
+        }
+
+        let mut device = Self::new(tunnel.local_address()?.into())?;
+        device.tunnel = Some(tunnel);
+        Ok(device)
+    }
+
     /// Send initial connect
     pub fn connect(&mut self) -> Result<()> {
         self.get_transport_mut().connect()?;
+        self.inner.handshake()
+    }
 
-        let message = ADBTransportMessage::new(
-            MessageCommand::Cnxn,
-            0x01000000,
-            1048576,
-            format!("host::{}\0", env!("CARGO_PKG_NAME")).as_bytes(),
-        );
+    /// Attach an [`AdbObserver`] notified by all following operations
+    pub fn set_observer(&mut self, observer: Arc<dyn AdbObserver>) {
+        self.inner.set_observer(observer);
+    }
 
-        self.get_transport_mut().write_message(message)?;
+    /// Attach a [`CancelToken`] honored by all following operations
+    pub fn set_cancel_token(&mut self, cancel_token: CancelToken) {
+        self.inner.set_cancel_token(cancel_token);
+    }
 
-        let message = self.get_transport_mut().read_message()?;
+    /// Limit rate of following file transfers (push and pull) to `bytes_per_second`, `None` or `0` meaning no limit
+    pub fn set_transfer_rate_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.inner.set_transfer_rate_limit(bytes_per_second);
+    }
 
-        // Check if client is requesting a secure connection and upgrade it if necessary
-        match message.header().command() {
-            MessageCommand::Stls => {
-                self.get_transport_mut()
-                    .write_message(ADBTransportMessage::new(MessageCommand::Stls, 1, 0, &[]))?;
-                self.get_transport_mut().upgrade_connection()?;
-                log::debug!("Connection successfully upgraded from TCP to TLS");
-            }
-            MessageCommand::Cnxn => {
-                log::debug!("Unencrypted connection established");
-            }
-            _ => {
-                return Err(crate::RustADBError::WrongResponseReceived(
-                    "Expected CNXN or STLS command".to_string(),
-                    message.header().command().to_string(),
-                ));
-            }
+    /// Check that connection is still alive, returning round-trip time, see [`ADBMessageDevice::ping`]
+    pub fn ping(&mut self) -> Result<Duration> {
+        self.inner.ping()
+    }
+
+    /// Forward connections to `local` address on host to `remote` socket on device, see [`ADBMessageDevice::forward`]
+    pub fn forward(&mut self, local: SocketAddr, remote: &str) -> Result<PortForward> {
+        self.inner.forward(local, remote)
+    }
+
+    /// Forward connections to `remote` socket on device to `local` address on host, see [`ADBMessageDevice::reverse`]
+    pub fn reverse(&mut self, remote: &str, local: SocketAddr) -> Result<PortForward> {
+        self.inner.reverse(remote, local)
+    }
+
+    /// Transparently reconnect and authenticate again once connection has been lost (e.g. on a Wi-Fi drop), following `reconnect_policy`.
+    ///
+    /// Operation which failed is started over when safe to do so (e.g. `stat`, `install`, or `pull` when nothing has been received yet),
+    /// otherwise its error is returned and only following operations use the new connection.
+    pub fn set_reconnect_policy(&mut self, reconnect_policy: Option<ReconnectPolicy>) {
+        self.reconnect_policy = reconnect_policy;
+    }
+
+    /// Run `operation`, reconnecting if it failed because connection has been lost.
+    ///
+    /// `operation` is run again once reconnected if `retry` is `true`.
+    fn reconnecting<R>(
+        &mut self,
+        retry: bool,
+        mut operation: impl FnMut(&mut ADBMessageDevice<TcpTransport>) -> Result<R>,
+    ) -> Result<R> {
+        let error = match operation(&mut self.inner) {
+            Err(e) if e.is_connection_lost() && self.reconnect_policy.is_some() => e,
+            result => return result,
+        };
+
+        log::warn!("connection to device lost ({error}), reconnecting...");
+        if let Err(e) = self.reconnect() {
+            log::error!("cannot reconnect to device: {e}");
+            return Err(error);
         }
 
+        if retry {
+            operation(&mut self.inner)
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Run `service` restarting adbd, and connect again to restarted adbd
+    fn restart_adbd(&mut self, service: &str) -> Result<()> {
+        if self.inner.restart_adbd(service)? {
+            self.reconnect()?;
+        }
         Ok(())
     }
 
+    /// Run `service` changing verity state, rebooting device and connecting again to it if `reboot` is set and required
+    fn apply_verity_service(&mut self, service: &str, reboot: bool) -> Result<bool> {
+        let reboot_required = self.inner.apply_verity_service(service, reboot)?;
+        if reboot && reboot_required {
+            self.reconnect_within(DEVICE_REBOOT_TIMEOUT)?;
+        }
+        Ok(reboot_required)
+    }
+
+    /// Connect again to device, trying until `timeout` expires (e.g. while it reboots)
+    fn reconnect_within(&mut self, timeout: Duration) -> Result<()> {
+        let delay = self.reconnect_policy.unwrap_or_default().delay;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let _ = self.get_transport_mut().disconnect();
+
+            match self.connect() {
+                Ok(()) => return Ok(()),
+                Err(e) if Instant::now() >= deadline => return Err(e),
+                Err(e) => log::debug!("waiting for device: {e}"),
+            }
+
+            std::thread::sleep(delay);
+        }
+    }
+
+    fn reconnect(&mut self) -> Result<()> {
+        let reconnect_policy = self.reconnect_policy.unwrap_or_default();
+
+        let mut attempt = 1;
+        loop {
+            // Previous connection is dead anyway
+            let _ = self.get_transport_mut().disconnect();
+
+            match self.connect() {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= reconnect_policy.attempts => return Err(e),
+                Err(e) => log::debug!("reconnection attempt {attempt} failed: {e}"),
+            }
+
+            attempt += 1;
+            std::thread::sleep(reconnect_policy.delay);
+        }
+    }
+
     #[inline]
     fn get_transport_mut(&mut self) -> &mut TcpTransport {
         self.inner.get_transport_mut()
     }
 }
 
+/// [`Write`] implementation counting bytes written to `inner`
+struct CountingWriter<'a> {
+    inner: &'a mut dyn Write,
+    written: u64,
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let size = self.inner.write(buf)?;
+        self.written += size as u64;
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 impl ADBDeviceExt for ADBTcpDevice {
     #[inline]
     fn shell_command(&mut self, command: &[&str], output: &mut dyn Write) -> Result<()> {
-        self.inner.shell_command(command, output)
+        self.reconnecting(false, |inner| inner.shell_command(command, &mut *output))
+    }
+
+    #[inline]
+    fn shell_command_output(
+        &mut self,
+        command: &[&str],
+        mut output: ShellOutput<'_>,
+    ) -> Result<()> {
+        self.reconnecting(false, |inner| {
+            inner.shell_command_output(command, output.reborrow())
+        })
+    }
+
+    #[inline]
+    fn run_command(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        legacy_exit_code: bool,
+    ) -> Result<u8> {
+        self.reconnecting(false, |inner| {
+            inner.run_command(command, &mut *output, legacy_exit_code)
+        })
+    }
+
+    #[inline]
+    fn shell_command_with_timeout(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.reconnecting(false, |inner| {
+            inner.shell_command_with_timeout(command, &mut *output, timeout)
+        })
     }
 
     #[inline]
@@ -79,19 +275,65 @@ impl ADBDeviceExt for ADBTcpDevice {
         self.inner.shell(reader, writer)
     }
 
+    #[inline]
+    fn shell_with_options(
+        &mut self,
+        options: &ShellOptions,
+        reader: &mut dyn Read,
+        writer: Box<dyn Write + Send>,
+    ) -> Result<()> {
+        self.inner.shell_with_options(options, reader, writer)
+    }
+
+    #[inline]
+    fn features(&mut self) -> Result<Vec<String>> {
+        self.inner.features()
+    }
+
     #[inline]
     fn stat(&mut self, remote_path: &str) -> Result<crate::AdbStatResponse> {
-        self.inner.stat(remote_path)
+        self.reconnecting(true, |inner| inner.stat(remote_path))
     }
 
     #[inline]
-    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<()> {
-        self.inner.pull(source, output)
+    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<TransferStats> {
+        let started = Instant::now();
+        let mut attempts = 0;
+        let mut output = CountingWriter {
+            inner: output,
+            written: 0,
+        };
+        let stats = self.reconnecting(true, |inner| {
+            // Pull can only be started over if nothing has been written yet
+            if output.written > 0 {
+                return Err(std::io::Error::new(
+                    ErrorKind::ConnectionReset,
+                    "connection lost while pulling",
+                )
+                .into());
+            }
+            attempts += 1;
+            inner.pull(source, &mut output)
+        })?;
+
+        Ok(TransferStats {
+            elapsed: started.elapsed(),
+            retries: attempts - 1,
+            ..stats
+        })
     }
 
     #[inline]
-    fn push(&mut self, stream: &mut dyn Read, path: &dyn AsRef<str>) -> Result<()> {
-        self.inner.push(stream, path)
+    fn push_with_metadata(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<TransferStats> {
+        self.reconnecting(false, |inner| {
+            inner.push(&mut *stream, path, mode, mod_time)
+        })
     }
 
     #[inline]
@@ -100,18 +342,103 @@ impl ADBDeviceExt for ADBTcpDevice {
     }
 
     #[inline]
-    fn install(&mut self, apk_path: &dyn AsRef<Path>) -> Result<()> {
-        self.inner.install(apk_path)
+    fn root(&mut self) -> Result<()> {
+        self.restart_adbd("root:")
+    }
+
+    #[inline]
+    fn unroot(&mut self) -> Result<()> {
+        self.restart_adbd("unroot:")
+    }
+
+    #[inline]
+    fn remount(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("remount:", reboot)
+    }
+
+    #[inline]
+    fn disable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("disable-verity:", reboot)
+    }
+
+    #[inline]
+    fn enable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("enable-verity:", reboot)
+    }
+
+    #[inline]
+    fn install_with_options(
+        &mut self,
+        apk_path: &dyn AsRef<Path>,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.reconnecting(true, |inner| inner.install(apk_path, options))
+    }
+
+    #[inline]
+    fn install_from_reader_with_options(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.reconnecting(false, |inner| {
+            inner.install_from_reader(&mut *reader, size, options)
+        })
+    }
+
+    #[inline]
+    fn sideload_from_reader(
+        &mut self,
+        reader: &mut dyn ReadSeek,
+        size: u64,
+    ) -> Result<TransferStats> {
+        self.reconnecting(false, |inner| {
+            inner.sideload_from_reader(&mut *reader, size)
+        })
     }
 
     #[inline]
     fn uninstall(&mut self, package: &str) -> Result<()> {
-        self.inner.uninstall(package)
+        self.reconnecting(false, |inner| inner.uninstall(package))
+    }
+
+    #[inline]
+    fn open_service(&mut self, service: &str) -> Result<Box<dyn ReadWriteStream>> {
+        self.reconnecting(true, |inner| inner.open_service(service))
+    }
+
+    #[inline]
+    fn open_service_cancellable(
+        &mut self,
+        service: &str,
+        cancel_token: &CancelToken,
+    ) -> Result<Box<dyn ReadWriteStream>> {
+        self.reconnecting(true, |inner| {
+            inner.open_service_cancellable(service, cancel_token)
+        })
     }
 
     #[inline]
     fn framebuffer_inner(&mut self) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
-        self.inner.framebuffer_inner()
+        self.reconnecting(true, |inner| inner.framebuffer_inner())
+    }
+
+    #[inline]
+    fn framebuffer_into(
+        &mut self,
+        buffer: Vec<u8>,
+    ) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+        // Buffer is only reused by first attempt
+        let mut buffer = Some(buffer);
+        self.reconnecting(true, |inner| {
+            inner.framebuffer_into(buffer.take().unwrap_or_default())
+        })
+    }
+
+    #[inline]
+    fn framebuffer_raw(&mut self) -> Result<crate::RawFramebuffer> {
+        self.reconnecting(true, |inner| inner.framebuffer_raw())
     }
 }
 
@@ -1,23 +1,28 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{Result, RustADBError};
+use crate::{Result, RustADBError, constants::MAX_PAYLOAD_SIZE};
 
 use super::models::MessageCommand;
 
-#[cfg(feature = "usb")]
+#[cfg(any(feature = "usb", feature = "async"))]
 pub const AUTH_TOKEN: u32 = 1;
-#[cfg(feature = "usb")]
+#[cfg(any(feature = "usb", feature = "async"))]
 pub const AUTH_SIGNATURE: u32 = 2;
-#[cfg(feature = "usb")]
+#[cfg(any(feature = "usb", feature = "async"))]
 pub const AUTH_RSAPUBLICKEY: u32 = 3;
+#[cfg(feature = "tcp")]
+/// TLS protocol version sent back to device in `STLS` message
+pub const STLS_VERSION: u32 = 0x01000000;
 
-#[derive(Debug)]
+/// Message exchanged with a device over an [`crate::ADBMessageTransport`], made of a header and a payload.
+#[derive(Debug, Clone)]
 pub struct ADBTransportMessage {
     header: ADBTransportMessageHeader,
     payload: Vec<u8>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Fixed-size header of an [`ADBTransportMessage`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[repr(C)]
 pub struct ADBTransportMessageHeader {
     command: MessageCommand, /* command identifier constant      */
@@ -29,6 +34,7 @@ pub struct ADBTransportMessageHeader {
 }
 
 impl ADBTransportMessageHeader {
+    /// Instantiates a new header describing a `command` message with `data` as payload
     pub fn new(command: MessageCommand, arg0: u32, arg1: u32, data: &[u8]) -> Self {
         Self {
             command,
@@ -40,22 +46,27 @@ impl ADBTransportMessageHeader {
         }
     }
 
+    /// Command of this message
     pub fn command(&self) -> MessageCommand {
         self.command
     }
 
+    /// First argument of this message
     pub fn arg0(&self) -> u32 {
         self.arg0
     }
 
+    /// Second argument of this message
     pub fn arg1(&self) -> u32 {
         self.arg1
     }
 
+    /// Length of the payload
     pub fn data_length(&self) -> u32 {
         self.data_length
     }
 
+    /// Checksum of the payload
     pub fn data_crc32(&self) -> u32 {
         self.data_crc32
     }
@@ -69,12 +80,14 @@ impl ADBTransportMessageHeader {
         command_u32 ^ 0xFFFFFFFF
     }
 
+    /// Serialize this header as sent on the wire
     pub fn as_bytes(&self) -> Result<Vec<u8>> {
         bincode::serialize(&self).map_err(|_e| RustADBError::ConversionError)
     }
 }
 
 impl ADBTransportMessage {
+    /// Instantiates a new `command` message with `data` as payload
     pub fn new(command: MessageCommand, arg0: u32, arg1: u32, data: &[u8]) -> Self {
         Self {
             header: ADBTransportMessageHeader::new(command, arg0, arg1, data),
@@ -82,15 +95,18 @@ impl ADBTransportMessage {
         }
     }
 
+    /// Instantiates a message from an already received header and its payload
     pub fn from_header_and_payload(header: ADBTransportMessageHeader, payload: Vec<u8>) -> Self {
         Self { header, payload }
     }
 
+    /// Return `true` if header magic and checksum match this message
     pub fn check_message_integrity(&self) -> bool {
         ADBTransportMessageHeader::compute_magic(self.header.command) == self.header.magic
             && ADBTransportMessageHeader::compute_crc32(&self.payload) == self.header.data_crc32
     }
 
+    /// Return an error if this message command is not `expected_command`
     pub fn assert_command(&self, expected_command: MessageCommand) -> Result<()> {
         let our_command = self.header().command();
         if expected_command == our_command {
@@ -103,14 +119,17 @@ impl ADBTransportMessage {
         ))
     }
 
+    /// Header of this message
     pub fn header(&self) -> &ADBTransportMessageHeader {
         &self.header
     }
 
+    /// Payload of this message
     pub fn payload(&self) -> &Vec<u8> {
         &self.payload
     }
 
+    /// Consume this message, returning its payload
     pub fn into_payload(self) -> Vec<u8> {
         self.payload
     }
@@ -120,6 +139,41 @@ impl TryFrom<[u8; 24]> for ADBTransportMessageHeader {
     type Error = RustADBError;
 
     fn try_from(value: [u8; 24]) -> Result<Self> {
-        bincode::deserialize(&value).map_err(|_e| RustADBError::ConversionError)
+        let header: Self =
+            bincode::deserialize(&value).map_err(|_e| RustADBError::ConversionError)?;
+
+        // Checked before reading payload, to avoid trusting garbage or oversized lengths
+        if header.magic != Self::compute_magic(header.command) {
+            return Err(RustADBError::InvalidMessage(format!(
+                "wrong magic {:#x} for {}",
+                header.magic, header.command
+            )));
+        }
+        if header.data_length > MAX_PAYLOAD_SIZE {
+            return Err(RustADBError::InvalidMessage(format!(
+                "payload length {} exceeds {MAX_PAYLOAD_SIZE}",
+                header.data_length
+            )));
+        }
+
+        Ok(header)
     }
 }
+
+#[test]
+fn test_header_rejects_malformed_input() {
+    let header = ADBTransportMessageHeader::new(MessageCommand::Write, 1, 2, b"data");
+    let mut bytes: [u8; 24] = header
+        .as_bytes()
+        .expect("cannot serialize header")
+        .try_into()
+        .expect("header is not 24 bytes long");
+    assert!(ADBTransportMessageHeader::try_from(bytes).is_ok());
+
+    // Oversized payload length
+    bytes[12..16].copy_from_slice(&(MAX_PAYLOAD_SIZE + 1).to_le_bytes());
+    assert!(ADBTransportMessageHeader::try_from(bytes).is_err());
+
+    // Unknown command
+    assert!(ADBTransportMessageHeader::try_from([0xff; 24]).is_err());
+}
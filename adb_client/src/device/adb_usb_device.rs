@@ -1,37 +1,38 @@
-use std::fs::read_to_string;
 use std::io::Read;
 use std::io::Write;
-use std::io::ErrorKind;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
 
 use super::adb_message_device::ADBMessageDevice;
-use super::get_default_adb_key_path;
 use super::models::MessageCommand;
 use super::{ADBRsaKey, ADBTransportMessage};
-use crate::search_adb_devices;
+use super::{get_default_adb_key_path, read_adb_private_key};
 use crate::ADBDeviceExt;
 use crate::ADBMessageTransport;
 use crate::ADBTransport;
+use crate::AdbObserver;
+use crate::CancelToken;
+use crate::DeviceMode;
+use crate::InstallOptions;
+use crate::PortForward;
+use crate::ShellOptions;
+use crate::ShellOutput;
+use crate::TransferStats;
+use crate::USBDeviceSelector;
+use crate::constants::ADBD_RESTART_TIMEOUT;
+use crate::constants::DEVICE_REBOOT_TIMEOUT;
+use crate::constants::MAX_PAYLOAD_SIZE;
+use crate::constants::USB_WAIT_POLL_INTERVAL;
 use crate::device::adb_transport_message::{AUTH_RSAPUBLICKEY, AUTH_SIGNATURE, AUTH_TOKEN};
+use crate::search_adb_devices;
+use crate::{ReadSeek, ReadWriteStream};
 use crate::{Result, RustADBError, USBTransport};
 
-pub fn read_adb_private_key<P: AsRef<Path>>(private_key_path: P) -> Result<Option<ADBRsaKey>> {
-    let pk = match read_to_string(private_key_path.as_ref()) {
-        Ok(contents) => contents,
-        Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
-        Err(e) => return Err(e.into()),
-    };
-    match ADBRsaKey::new_from_pkcs8(&pk) {
-        Ok(pk) => Ok(Some(pk)),
-        Err(e) => {
-            log::error!("Error while create RSA private key: {e}");
-            Ok(None)
-        }
-    }
-}
-
 /// Represent a device reached and available over USB.
 #[derive(Debug)]
 pub struct ADBUSBDevice {
@@ -54,6 +55,19 @@ impl ADBUSBDevice {
         Self::new_from_transport_inner(USBTransport::new(vendor_id, product_id)?, private_key_path)
     }
 
+    /// Instantiate a new [`ADBUSBDevice`] with given USB serial number, telling apart identical devices
+    pub fn new_from_serial(serial: &str) -> Result<Self> {
+        Self::new_from_serial_with_custom_private_key(serial, get_default_adb_key_path()?)
+    }
+
+    /// Instantiate a new [`ADBUSBDevice`] with given USB serial number using a custom private key path
+    pub fn new_from_serial_with_custom_private_key(
+        serial: &str,
+        private_key_path: PathBuf,
+    ) -> Result<Self> {
+        Self::new_from_transport_inner(USBTransport::new_from_serial(serial)?, private_key_path)
+    }
+
     /// Instantiate a new [`ADBUSBDevice`] from a [`USBTransport`] and an optional private key path.
     pub fn new_from_transport(
         transport: USBTransport,
@@ -103,6 +117,42 @@ impl ADBUSBDevice {
         }
     }
 
+    /// Wait until a device matching `selector` enumerates on USB, and establish a connection with it.
+    ///
+    /// Useful to reconnect to a device after a reboot. Devices are polled until `timeout` expires,
+    /// connection failures being retried meanwhile as device may not be ready yet.
+    pub fn wait_for_device(selector: USBDeviceSelector, timeout: Duration) -> Result<Self> {
+        let private_key_path = get_default_adb_key_path()?;
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let result = match &selector {
+                USBDeviceSelector::Any => {
+                    Self::autodetect_with_custom_private_key(private_key_path.clone())
+                }
+                USBDeviceSelector::VendorProduct(vendor_id, product_id) => {
+                    Self::new_with_custom_private_key(
+                        *vendor_id,
+                        *product_id,
+                        private_key_path.clone(),
+                    )
+                }
+                USBDeviceSelector::Serial(serial) => {
+                    Self::new_from_serial_with_custom_private_key(serial, private_key_path.clone())
+                }
+            };
+
+            match result {
+                Ok(device) => return Ok(device),
+                Err(e) if Instant::now() >= deadline => return Err(e),
+                Err(e) => log::debug!("waiting for USB device: {e}"),
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            std::thread::sleep(USB_WAIT_POLL_INTERVAL.min(remaining));
+        }
+    }
+
     /// Send initial connect
     pub fn connect(&mut self) -> Result<()> {
         self.get_transport_mut().connect()?;
@@ -110,7 +160,7 @@ impl ADBUSBDevice {
         let message = ADBTransportMessage::new(
             MessageCommand::Cnxn,
             0x01000000,
-            1048576,
+            MAX_PAYLOAD_SIZE,
             format!("host::{}\0", env!("CARGO_PKG_NAME")).as_bytes(),
         );
 
@@ -118,10 +168,9 @@ impl ADBUSBDevice {
 
         let message = self.get_transport_mut().read_message()?;
         // If the device returned CNXN instead of AUTH it does not require authentication,
-        // so we can skip the auth steps.
+        // so we can skip the auth steps. This is always the case of minadbd (sideload and rescue modes).
         if message.header().command() == MessageCommand::Cnxn {
-            self.inner.set_maximum_data_size(message.header().arg1())?;
-            return Ok(());
+            return self.inner.handle_connection(&message);
         }
         message.assert_command(MessageCommand::Auth)?;
 
@@ -144,6 +193,7 @@ impl ADBUSBDevice {
         let received_response = self.get_transport_mut().read_message()?;
 
         if received_response.header().command() == MessageCommand::Cnxn {
+            self.inner.handle_connection(&received_response)?;
             log::info!(
                 "Authentication OK, device info {}",
                 String::from_utf8(received_response.into_payload())?
@@ -157,13 +207,14 @@ impl ADBUSBDevice {
         let message = ADBTransportMessage::new(MessageCommand::Auth, AUTH_RSAPUBLICKEY, 0, &pubkey);
 
         self.get_transport_mut().write_message(message)?;
+        self.inner.observer().auth_pending();
 
         let response = self
             .get_transport_mut()
             .read_message_with_timeout(Duration::from_secs(10))
             .and_then(|message| {
                 message.assert_command(MessageCommand::Cnxn)?;
-                self.inner.set_maximum_data_size(message.header().arg1())?;
+                self.inner.handle_connection(&message)?;
                 Ok(message)
             })?;
 
@@ -175,6 +226,80 @@ impl ADBUSBDevice {
         Ok(())
     }
 
+    /// Attach an [`AdbObserver`] notified by all following operations.
+    ///
+    /// As connection is established on instantiation, [`ADBUSBDevice::connect`] must be called again to observe authentication.
+    pub fn set_observer(&mut self, observer: Arc<dyn AdbObserver>) {
+        self.inner.set_observer(observer);
+    }
+
+    /// Attach a [`CancelToken`] honored by all following operations
+    pub fn set_cancel_token(&mut self, cancel_token: CancelToken) {
+        self.inner.set_cancel_token(cancel_token);
+    }
+
+    /// Limit rate of following file transfers (push and pull) to `bytes_per_second`, `None` or `0` meaning no limit
+    pub fn set_transfer_rate_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.inner.set_transfer_rate_limit(bytes_per_second);
+    }
+
+    /// Check that connection is still alive, returning round-trip time, see [`ADBMessageDevice::ping`]
+    pub fn ping(&mut self) -> Result<Duration> {
+        self.inner.ping()
+    }
+
+    /// Forward connections to `local` address on host to `remote` socket on device, see [`ADBMessageDevice::forward`]
+    pub fn forward(&mut self, local: SocketAddr, remote: &str) -> Result<PortForward> {
+        self.inner.forward(local, remote)
+    }
+
+    /// Forward connections to `remote` socket on device to `local` address on host, see [`ADBMessageDevice::reverse`]
+    pub fn reverse(&mut self, remote: &str, local: SocketAddr) -> Result<PortForward> {
+        self.inner.reverse(remote, local)
+    }
+
+    /// Mode device is connected in, services available in recovery, sideload and rescue modes being restricted
+    pub fn mode(&self) -> DeviceMode {
+        self.inner.mode()
+    }
+
+    /// Run `service` restarting adbd, and connect again to restarted adbd
+    fn restart_adbd(&mut self, service: &str) -> Result<()> {
+        if self.inner.restart_adbd(service)? {
+            self.reconnect(ADBD_RESTART_TIMEOUT)?;
+        }
+        Ok(())
+    }
+
+    /// Run `service` changing verity state, rebooting device and connecting again to it if `reboot` is set and required
+    fn apply_verity_service(&mut self, service: &str, reboot: bool) -> Result<bool> {
+        let reboot_required = self.inner.apply_verity_service(service, reboot)?;
+        if reboot && reboot_required {
+            self.reconnect(DEVICE_REBOOT_TIMEOUT)?;
+        }
+        Ok(reboot_required)
+    }
+
+    /// Connect again to device once it enumerated again on USB, trying until `timeout` expires
+    fn reconnect(&mut self, timeout: Duration) -> Result<()> {
+        let (vendor_id, product_id) = self.get_transport_mut().vendor_product_ids()?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let result = USBTransport::new(vendor_id, product_id).and_then(|transport| {
+                *self.get_transport_mut() = transport;
+                self.connect()
+            });
+
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) if Instant::now() >= deadline => return Err(e),
+                Err(e) => log::debug!("waiting for USB device: {e}"),
+            }
+
+            std::thread::sleep(USB_WAIT_POLL_INTERVAL);
+        }
+    }
+
     #[inline]
     /// Get a reference to the underlying [`USBTransport`].
     pub fn get_transport_mut(&mut self) -> &mut USBTransport {
@@ -188,24 +313,71 @@ impl ADBDeviceExt for ADBUSBDevice {
         self.inner.shell_command(command, output)
     }
 
+    #[inline]
+    fn shell_command_output(&mut self, command: &[&str], output: ShellOutput<'_>) -> Result<()> {
+        self.inner.shell_command_output(command, output)
+    }
+
+    #[inline]
+    fn run_command(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        legacy_exit_code: bool,
+    ) -> Result<u8> {
+        self.inner.run_command(command, output, legacy_exit_code)
+    }
+
+    #[inline]
+    fn shell_command_with_timeout(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.inner
+            .shell_command_with_timeout(command, output, timeout)
+    }
+
     #[inline]
     fn shell<'a>(&mut self, reader: &mut dyn Read, writer: Box<(dyn Write + Send)>) -> Result<()> {
         self.inner.shell(reader, writer)
     }
 
+    #[inline]
+    fn shell_with_options(
+        &mut self,
+        options: &ShellOptions,
+        reader: &mut dyn Read,
+        writer: Box<dyn Write + Send>,
+    ) -> Result<()> {
+        self.inner.shell_with_options(options, reader, writer)
+    }
+
+    #[inline]
+    fn features(&mut self) -> Result<Vec<String>> {
+        self.inner.features()
+    }
+
     #[inline]
     fn stat(&mut self, remote_path: &str) -> Result<crate::AdbStatResponse> {
         self.inner.stat(remote_path)
     }
 
     #[inline]
-    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<()> {
+    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<TransferStats> {
         self.inner.pull(source, output)
     }
 
     #[inline]
-    fn push(&mut self, stream: &mut dyn Read, path: &dyn AsRef<str>) -> Result<()> {
-        self.inner.push(stream, path)
+    fn push_with_metadata(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<TransferStats> {
+        self.inner.push(stream, path, mode, mod_time)
     }
 
     #[inline]
@@ -214,8 +386,56 @@ impl ADBDeviceExt for ADBUSBDevice {
     }
 
     #[inline]
-    fn install(&mut self, apk_path: &dyn AsRef<Path>) -> Result<()> {
-        self.inner.install(apk_path)
+    fn root(&mut self) -> Result<()> {
+        self.restart_adbd("root:")
+    }
+
+    #[inline]
+    fn unroot(&mut self) -> Result<()> {
+        self.restart_adbd("unroot:")
+    }
+
+    #[inline]
+    fn remount(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("remount:", reboot)
+    }
+
+    #[inline]
+    fn disable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("disable-verity:", reboot)
+    }
+
+    #[inline]
+    fn enable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("enable-verity:", reboot)
+    }
+
+    #[inline]
+    fn install_with_options(
+        &mut self,
+        apk_path: &dyn AsRef<Path>,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.inner.install(apk_path, options)
+    }
+
+    #[inline]
+    fn install_from_reader_with_options(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.inner.install_from_reader(reader, size, options)
+    }
+
+    #[inline]
+    fn sideload_from_reader(
+        &mut self,
+        reader: &mut dyn ReadSeek,
+        size: u64,
+    ) -> Result<TransferStats> {
+        self.inner.sideload_from_reader(reader, size)
     }
 
     #[inline]
@@ -223,10 +443,37 @@ impl ADBDeviceExt for ADBUSBDevice {
         self.inner.uninstall(package)
     }
 
+    #[inline]
+    fn open_service(&mut self, service: &str) -> Result<Box<dyn ReadWriteStream>> {
+        self.inner.open_service(service)
+    }
+
+    #[inline]
+    fn open_service_cancellable(
+        &mut self,
+        service: &str,
+        cancel_token: &CancelToken,
+    ) -> Result<Box<dyn ReadWriteStream>> {
+        self.inner.open_service_cancellable(service, cancel_token)
+    }
+
     #[inline]
     fn framebuffer_inner(&mut self) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
         self.inner.framebuffer_inner()
     }
+
+    #[inline]
+    fn framebuffer_into(
+        &mut self,
+        buffer: Vec<u8>,
+    ) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+        self.inner.framebuffer_into(buffer)
+    }
+
+    #[inline]
+    fn framebuffer_raw(&mut self) -> Result<crate::RawFramebuffer> {
+        self.inner.framebuffer_raw()
+    }
 }
 
 impl Drop for ADBUSBDevice {
@@ -0,0 +1,288 @@
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::adb_message_device::ADBMessageDevice;
+use crate::constants::{ADBD_RESTART_TIMEOUT, DEVICE_REBOOT_TIMEOUT, TCP_WAIT_POLL_INTERVAL};
+use crate::{
+    ADBDeviceExt, ADBTransport, AdbObserver, CancelToken, DeviceMode, InstallOptions, PortForward,
+    ReadSeek, ReadWriteStream, Result, ShellOptions, ShellOutput, TransferStats, VsockTransport,
+};
+
+/// Represent a device reached over vsock, e.g. a virtual machine exposing adbd to its host.
+///
+/// Devices requiring authentication are not supported, virtual devices usually accepting any host.
+#[derive(Debug)]
+pub struct ADBVsockDevice {
+    inner: ADBMessageDevice<VsockTransport>,
+}
+
+impl ADBVsockDevice {
+    /// Instantiate a new [`ADBVsockDevice`] reached at `port` of virtual machine with context identifier `cid`
+    pub fn new(cid: u32, port: u32) -> Result<Self> {
+        let mut device = Self {
+            inner: ADBMessageDevice::new(VsockTransport::new(cid, port)),
+        };
+
+        device.connect()?;
+
+        Ok(device)
+    }
+
+    /// Send initial connect
+    pub fn connect(&mut self) -> Result<()> {
+        self.get_transport_mut().connect()?;
+        self.inner.handshake()
+    }
+
+    /// Attach an [`AdbObserver`] notified by all following operations
+    pub fn set_observer(&mut self, observer: Arc<dyn AdbObserver>) {
+        self.inner.set_observer(observer);
+    }
+
+    /// Attach a [`CancelToken`] honored by all following operations
+    pub fn set_cancel_token(&mut self, cancel_token: CancelToken) {
+        self.inner.set_cancel_token(cancel_token);
+    }
+
+    /// Limit rate of following file transfers (push and pull) to `bytes_per_second`, `None` or `0` meaning no limit
+    pub fn set_transfer_rate_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.inner.set_transfer_rate_limit(bytes_per_second);
+    }
+
+    /// Check that connection is still alive, returning round-trip time, see [`ADBMessageDevice::ping`]
+    pub fn ping(&mut self) -> Result<Duration> {
+        self.inner.ping()
+    }
+
+    /// Forward connections to `local` address on host to `remote` socket on device, see [`ADBMessageDevice::forward`]
+    pub fn forward(&mut self, local: SocketAddr, remote: &str) -> Result<PortForward> {
+        self.inner.forward(local, remote)
+    }
+
+    /// Forward connections to `remote` socket on device to `local` address on host, see [`ADBMessageDevice::reverse`]
+    pub fn reverse(&mut self, remote: &str, local: SocketAddr) -> Result<PortForward> {
+        self.inner.reverse(remote, local)
+    }
+
+    /// Mode device is connected in, services available in recovery, sideload and rescue modes being restricted
+    pub fn mode(&self) -> DeviceMode {
+        self.inner.mode()
+    }
+
+    /// Run `service` restarting adbd, and connect again to restarted adbd
+    fn restart_adbd(&mut self, service: &str) -> Result<()> {
+        if self.inner.restart_adbd(service)? {
+            self.reconnect(ADBD_RESTART_TIMEOUT)?;
+        }
+        Ok(())
+    }
+
+    /// Run `service` changing verity state, rebooting device and connecting again to it if `reboot` is set and required
+    fn apply_verity_service(&mut self, service: &str, reboot: bool) -> Result<bool> {
+        let reboot_required = self.inner.apply_verity_service(service, reboot)?;
+        if reboot && reboot_required {
+            self.reconnect(DEVICE_REBOOT_TIMEOUT)?;
+        }
+        Ok(reboot_required)
+    }
+
+    /// Connect again to device once adbd listens again, trying until `timeout` expires
+    fn reconnect(&mut self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let _ = self.get_transport_mut().disconnect();
+
+            match self.connect() {
+                Ok(()) => return Ok(()),
+                Err(e) if Instant::now() >= deadline => return Err(e),
+                Err(e) => log::debug!("waiting for device: {e}"),
+            }
+
+            std::thread::sleep(TCP_WAIT_POLL_INTERVAL);
+        }
+    }
+
+    #[inline]
+    fn get_transport_mut(&mut self) -> &mut VsockTransport {
+        self.inner.get_transport_mut()
+    }
+}
+
+impl ADBDeviceExt for ADBVsockDevice {
+    #[inline]
+    fn shell_command(&mut self, command: &[&str], output: &mut dyn Write) -> Result<()> {
+        self.inner.shell_command(command, output)
+    }
+
+    #[inline]
+    fn shell_command_output(&mut self, command: &[&str], output: ShellOutput<'_>) -> Result<()> {
+        self.inner.shell_command_output(command, output)
+    }
+
+    #[inline]
+    fn run_command(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        legacy_exit_code: bool,
+    ) -> Result<u8> {
+        self.inner.run_command(command, output, legacy_exit_code)
+    }
+
+    #[inline]
+    fn shell_command_with_timeout(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.inner
+            .shell_command_with_timeout(command, output, timeout)
+    }
+
+    #[inline]
+    fn shell(&mut self, reader: &mut dyn Read, writer: Box<(dyn Write + Send)>) -> Result<()> {
+        self.inner.shell(reader, writer)
+    }
+
+    #[inline]
+    fn shell_with_options(
+        &mut self,
+        options: &ShellOptions,
+        reader: &mut dyn Read,
+        writer: Box<dyn Write + Send>,
+    ) -> Result<()> {
+        self.inner.shell_with_options(options, reader, writer)
+    }
+
+    #[inline]
+    fn features(&mut self) -> Result<Vec<String>> {
+        self.inner.features()
+    }
+
+    #[inline]
+    fn stat(&mut self, remote_path: &str) -> Result<crate::AdbStatResponse> {
+        self.inner.stat(remote_path)
+    }
+
+    #[inline]
+    fn pull(&mut self, source: &dyn AsRef<str>, output: &mut dyn Write) -> Result<TransferStats> {
+        self.inner.pull(source, output)
+    }
+
+    #[inline]
+    fn push_with_metadata(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<TransferStats> {
+        self.inner.push(stream, path, mode, mod_time)
+    }
+
+    #[inline]
+    fn reboot(&mut self, reboot_type: crate::RebootType) -> Result<()> {
+        self.inner.reboot(reboot_type)
+    }
+
+    #[inline]
+    fn root(&mut self) -> Result<()> {
+        self.restart_adbd("root:")
+    }
+
+    #[inline]
+    fn unroot(&mut self) -> Result<()> {
+        self.restart_adbd("unroot:")
+    }
+
+    #[inline]
+    fn remount(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("remount:", reboot)
+    }
+
+    #[inline]
+    fn disable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("disable-verity:", reboot)
+    }
+
+    #[inline]
+    fn enable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("enable-verity:", reboot)
+    }
+
+    #[inline]
+    fn install_with_options(
+        &mut self,
+        apk_path: &dyn AsRef<Path>,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.inner.install(apk_path, options)
+    }
+
+    #[inline]
+    fn install_from_reader_with_options(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.inner.install_from_reader(reader, size, options)
+    }
+
+    #[inline]
+    fn sideload_from_reader(
+        &mut self,
+        reader: &mut dyn ReadSeek,
+        size: u64,
+    ) -> Result<TransferStats> {
+        self.inner.sideload_from_reader(reader, size)
+    }
+
+    #[inline]
+    fn uninstall(&mut self, package: &str) -> Result<()> {
+        self.inner.uninstall(package)
+    }
+
+    #[inline]
+    fn open_service(&mut self, service: &str) -> Result<Box<dyn ReadWriteStream>> {
+        self.inner.open_service(service)
+    }
+
+    #[inline]
+    fn open_service_cancellable(
+        &mut self,
+        service: &str,
+        cancel_token: &CancelToken,
+    ) -> Result<Box<dyn ReadWriteStream>> {
+        self.inner.open_service_cancellable(service, cancel_token)
+    }
+
+    #[inline]
+    fn framebuffer_inner(&mut self) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+        self.inner.framebuffer_inner()
+    }
+
+    #[inline]
+    fn framebuffer_into(
+        &mut self,
+        buffer: Vec<u8>,
+    ) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+        self.inner.framebuffer_into(buffer)
+    }
+
+    #[inline]
+    fn framebuffer_raw(&mut self) -> Result<crate::RawFramebuffer> {
+        self.inner.framebuffer_raw()
+    }
+}
+
+impl Drop for ADBVsockDevice {
+    fn drop(&mut self) {
+        // Best effort here
+        let _ = self.get_transport_mut().disconnect();
+    }
+}
@@ -0,0 +1,48 @@
+use std::io::{Cursor, Read};
+
+use image::{ImageBuffer, Rgba};
+
+use crate::{
+    ADBAsyncMessageTransport, Result,
+    device::{MessageCommand, adb_async_message_device::ADBAsyncMessageDevice},
+    models::{FrameBufferInfo, RawFramebuffer},
+};
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    pub(crate) async fn framebuffer_inner(&mut self) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        let session = self.open_session(b"framebuffer:\0").await?;
+
+        let response = self.recv_and_reply_okay(session).await?;
+
+        let mut payload_cursor = Cursor::new(response.payload());
+
+        let info = FrameBufferInfo::read(&mut payload_cursor)?;
+
+        let mut framebuffer_data = Vec::new();
+        payload_cursor.read_to_end(&mut framebuffer_data)?;
+
+        while framebuffer_data.len() as u32 != info.size() {
+            let response = self.recv_and_reply_okay(session).await?;
+
+            framebuffer_data.extend_from_slice(&response.into_payload());
+
+            log::debug!(
+                "received framebuffer data. new size {}",
+                framebuffer_data.len()
+            );
+        }
+
+        let img = RawFramebuffer {
+            info,
+            data: framebuffer_data,
+        }
+        .into_image()?;
+
+        self.get_transport_mut()
+            .read_message()
+            .await
+            .and_then(|message| message.assert_command(MessageCommand::Clse))?;
+
+        Ok(img)
+    }
+}
@@ -0,0 +1,57 @@
+use futures_lite::{AsyncRead, AsyncReadExt};
+
+use crate::{
+    ADBAsyncMessageTransport, InstallOptions, Result,
+    device::{
+        ADBTransportMessage, MessageCommand, adb_async_message_device::ADBAsyncMessageDevice,
+    },
+    utils::check_copied_size,
+};
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    pub(crate) async fn install<R: AsyncRead + Unpin + ?Sized>(
+        &mut self,
+        apk: &mut R,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        let session = self
+            .open_session(
+                format!("exec:cmd package 'install' {}\0", options.args(size)?).as_bytes(),
+            )
+            .await?;
+
+        // Package manager waits for exactly `size` bytes, never send more
+        let mut apk = apk.take(size);
+        let mut buffer = vec![0; self.maximum_payload_size()];
+        let mut copied = 0;
+        loop {
+            let amount_read = apk.read(&mut buffer).await?;
+            if amount_read == 0 {
+                break;
+            }
+            copied += amount_read as u64;
+
+            self.send_and_expect_okay(ADBTransportMessage::new(
+                MessageCommand::Write,
+                session.local_id,
+                session.remote_id,
+                &buffer[..amount_read],
+            ))
+            .await?;
+        }
+        check_copied_size(copied, size)?;
+
+        let final_status = self.get_transport_mut().read_message().await?;
+
+        match final_status.into_payload().as_slice() {
+            b"Success\n" => {
+                log::info!("APK file successfully installed");
+                Ok(())
+            }
+            d => Err(crate::RustADBError::ADBRequestFailed(String::from_utf8(
+                d.to_vec(),
+            )?)),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+mod framebuffer;
+mod install;
+mod pull;
+mod push;
+mod reboot;
+mod shell;
+mod stat;
+mod uninstall;
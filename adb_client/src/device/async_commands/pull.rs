@@ -0,0 +1,58 @@
+use futures_lite::AsyncWrite;
+
+use crate::{
+    ADBAsyncMessageTransport, Result, RustADBError,
+    device::{
+        ADBTransportMessage, MessageCommand, adb_async_message_device::ADBAsyncMessageDevice,
+        models::MessageSubcommand,
+    },
+};
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    pub(crate) async fn pull<W: AsyncWrite + Unpin + ?Sized>(
+        &mut self,
+        source: &str,
+        output: &mut W,
+    ) -> Result<()> {
+        let session = self.begin_synchronization().await?;
+
+        let adb_stat_response = self.stat_with_explicit_ids(session, source).await?;
+
+        if adb_stat_response.file_perm == 0 {
+            return Err(RustADBError::UnknownResponseType(
+                "mode is 0: source file does not exist".to_string(),
+            ));
+        }
+
+        self.get_transport_mut()
+            .write_message(ADBTransportMessage::new(
+                MessageCommand::Okay,
+                session.local_id,
+                session.remote_id,
+                &[],
+            ))
+            .await?;
+
+        let recv_buffer = MessageSubcommand::Recv.with_arg(source.len() as u32);
+        let recv_buffer =
+            bincode::serialize(&recv_buffer).map_err(|_e| RustADBError::ConversionError)?;
+        self.send_and_expect_okay(ADBTransportMessage::new(
+            MessageCommand::Write,
+            session.local_id,
+            session.remote_id,
+            &recv_buffer,
+        ))
+        .await?;
+        self.send_and_expect_okay(ADBTransportMessage::new(
+            MessageCommand::Write,
+            session.local_id,
+            session.remote_id,
+            source.as_bytes(),
+        ))
+        .await?;
+
+        self.recv_file(session, output).await?;
+        self.end_transaction(session).await?;
+        Ok(())
+    }
+}
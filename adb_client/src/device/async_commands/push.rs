@@ -0,0 +1,39 @@
+use futures_lite::AsyncRead;
+
+use crate::{
+    ADBAsyncMessageTransport, Result, RustADBError,
+    device::{
+        ADBTransportMessage, MessageCommand, MessageSubcommand,
+        adb_async_message_device::ADBAsyncMessageDevice,
+    },
+};
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    pub(crate) async fn push<R: AsyncRead + Unpin + ?Sized>(
+        &mut self,
+        stream: &mut R,
+        path: &str,
+    ) -> Result<()> {
+        let session = self.begin_synchronization().await?;
+
+        let path_header = format!("{},0777", path);
+
+        let send_buffer = MessageSubcommand::Send.with_arg(path_header.len() as u32);
+        let mut send_buffer =
+            bincode::serialize(&send_buffer).map_err(|_e| RustADBError::ConversionError)?;
+        send_buffer.append(&mut path_header.as_bytes().to_vec());
+
+        self.send_and_expect_okay(ADBTransportMessage::new(
+            MessageCommand::Write,
+            session.local_id,
+            session.remote_id,
+            &send_buffer,
+        ))
+        .await?;
+
+        self.push_file(session, stream).await?;
+        self.end_transaction(session).await?;
+
+        Ok(())
+    }
+}
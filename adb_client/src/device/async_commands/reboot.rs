@@ -0,0 +1,16 @@
+use crate::{
+    ADBAsyncMessageTransport, RebootType, Result,
+    device::{MessageCommand, adb_async_message_device::ADBAsyncMessageDevice},
+};
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    pub(crate) async fn reboot(&mut self, reboot_type: RebootType) -> Result<()> {
+        self.open_session(format!("reboot:{}\0", reboot_type).as_bytes())
+            .await?;
+
+        self.get_transport_mut()
+            .read_message()
+            .await
+            .and_then(|message| message.assert_command(MessageCommand::Okay))
+    }
+}
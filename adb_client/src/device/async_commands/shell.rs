@@ -0,0 +1,107 @@
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, future};
+
+use crate::{
+    ADBAsyncMessageTransport, Result, RustADBError,
+    device::{
+        ADBTransportMessage, MessageCommand, adb_async_message_device::ADBAsyncMessageDevice,
+    },
+};
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    /// Runs 'command' in a shell on the device, and write its output and error streams into output.
+    pub(crate) async fn shell_command<W: AsyncWrite + Unpin + ?Sized>(
+        &mut self,
+        command: &[&str],
+        output: &mut W,
+    ) -> Result<()> {
+        let session = self
+            .open_session(format!("shell:{}\0", command.join(" ")).as_bytes())
+            .await?;
+
+        loop {
+            let response = self.get_transport_mut().read_message().await?;
+            if response.header().command() != MessageCommand::Write {
+                break;
+            }
+
+            output.write_all(&response.into_payload()).await?;
+
+            self.get_transport_mut()
+                .write_message_with_timeout(
+                    ADBTransportMessage::new(
+                        MessageCommand::Okay,
+                        session.local_id,
+                        session.remote_id,
+                        &[],
+                    ),
+                    std::time::Duration::from_secs(4),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts an interactive shell session on the device.
+    /// Input data is read from [reader] and write to [writer].
+    pub(crate) async fn shell<R, W>(&mut self, reader: &mut R, writer: &mut W) -> Result<()>
+    where
+        R: AsyncRead + Unpin + ?Sized,
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let session = self.open_session(b"shell:\0").await?;
+        let maximum_payload_size = self.maximum_payload_size();
+
+        let mut transport = self.get_transport().clone();
+
+        // Reads responses from adbd
+        let device_to_writer = async move {
+            loop {
+                let message = transport.read_message().await?;
+
+                match message.header().command() {
+                    MessageCommand::Write => {
+                        // Acknowledge for more data
+                        transport
+                            .write_message(ADBTransportMessage::new(
+                                MessageCommand::Okay,
+                                session.local_id,
+                                session.remote_id,
+                                &[],
+                            ))
+                            .await?;
+                        writer.write_all(&message.into_payload()).await?;
+                        writer.flush().await?;
+                    }
+                    MessageCommand::Okay => continue,
+                    MessageCommand::Clse => return Ok(()),
+                    _ => return Err(RustADBError::ADBShellNotSupported),
+                }
+            }
+        };
+
+        let mut transport = self.get_transport().clone();
+
+        // Read from given reader (that could be stdin e.g), and write content to device adbd
+        let reader_to_device = async move {
+            let mut buffer = vec![0; maximum_payload_size];
+            loop {
+                let amount_read = reader.read(&mut buffer).await?;
+                if amount_read == 0 {
+                    return Ok(());
+                }
+
+                transport
+                    .write_message(ADBTransportMessage::new(
+                        MessageCommand::Write,
+                        session.local_id,
+                        session.remote_id,
+                        &buffer[..amount_read],
+                    ))
+                    .await?;
+            }
+        };
+
+        future::or(device_to_writer, reader_to_device).await
+    }
+}
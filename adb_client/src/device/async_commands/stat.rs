@@ -0,0 +1,13 @@
+use crate::{
+    ADBAsyncMessageTransport, AdbStatResponse, Result,
+    device::adb_async_message_device::ADBAsyncMessageDevice,
+};
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    pub(crate) async fn stat(&mut self, remote_path: &str) -> Result<AdbStatResponse> {
+        let session = self.begin_synchronization().await?;
+        let adb_stat_response = self.stat_with_explicit_ids(session, remote_path).await?;
+        self.end_transaction(session).await?;
+        Ok(adb_stat_response)
+    }
+}
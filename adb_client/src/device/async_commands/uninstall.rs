@@ -0,0 +1,22 @@
+use crate::{
+    ADBAsyncMessageTransport, Result, device::adb_async_message_device::ADBAsyncMessageDevice,
+};
+
+impl<T: ADBAsyncMessageTransport> ADBAsyncMessageDevice<T> {
+    pub(crate) async fn uninstall(&mut self, package_name: &str) -> Result<()> {
+        self.open_session(format!("exec:cmd package 'uninstall' {}\0", package_name).as_bytes())
+            .await?;
+
+        let final_status = self.get_transport_mut().read_message().await?;
+
+        match final_status.into_payload().as_slice() {
+            b"Success\n" => {
+                log::info!("Package {} successfully uninstalled", package_name);
+                Ok(())
+            }
+            d => Err(crate::RustADBError::ADBRequestFailed(String::from_utf8(
+                d.to_vec(),
+            )?)),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::io::Read;
+use std::net::{SocketAddr, TcpListener};
+
+use crate::{
+    ADBMessageTransport, Result, RustADBError,
+    device::{adb_message_device::ADBMessageDevice, port_forward::PortForward},
+};
+
+impl<T: ADBMessageTransport> ADBMessageDevice<T> {
+    /// Forward connections to `local` address on host to `remote` socket on device (e.g. `tcp:80` or `localabstract:name`).
+    ///
+    /// Each accepted connection is proxied over the transport of this device, as long as returned [`PortForward`] is alive.
+    /// Device can still be used meanwhile, including to start other forwards.
+    pub fn forward(&mut self, local: SocketAddr, remote: &str) -> Result<PortForward> {
+        if !self.mode().allows_service(remote.as_bytes()) {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "service {remote} is not available in {} mode",
+                self.mode()
+            )));
+        }
+
+        PortForward::start(
+            self.get_transport().clone(),
+            self.dispatcher().clone(),
+            TcpListener::bind(local)?,
+            remote,
+            self.maximum_payload_size(),
+        )
+    }
+
+    /// Forward connections to `remote` socket on device (e.g. `tcp:8080`) to `local` address on host.
+    ///
+    /// Connections opened by device are proxied as long as returned [`PortForward`] is alive, reverse forward being
+    /// removed from device once dropped. Device can still be used meanwhile.
+    pub fn reverse(&mut self, remote: &str, local: SocketAddr) -> Result<PortForward> {
+        // Device announces connections with the host socket given here, and may open one as soon as rule is installed
+        let host_socket = format!("tcp:{}", local.port());
+        let connections = self.dispatcher().register_reverse(&host_socket)?;
+        if let Err(e) = self.install_reverse(remote, &host_socket) {
+            self.dispatcher().unregister_reverse(&host_socket)?;
+            return Err(e);
+        }
+
+        PortForward::start_reverse(
+            self.get_transport().clone(),
+            self.dispatcher().clone(),
+            connections,
+            remote,
+            local,
+            &host_socket,
+            self.maximum_payload_size(),
+        )
+    }
+
+    /// Install rule reversing `remote` socket on device, announcing its connections to `host_socket`
+    fn install_reverse(&mut self, remote: &str, host_socket: &str) -> Result<()> {
+        let mut stream = self.open_service(&format!("reverse:forward:{remote};{host_socket}"))?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+
+        // Response is `OKAY`, or `FAIL` followed by a length-prefixed error message
+        if !response.starts_with(b"OKAY") {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "cannot reverse {remote}: {}",
+                String::from_utf8_lossy(response.get(8..).unwrap_or_default())
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_reverse_proxies_device_connection() {
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    use crate::MockTransport;
+    use crate::device::MessageCommand;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("cannot bind listener");
+    let local = listener.local_addr().expect("cannot read listener address");
+    let device_id = 42;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(format!("reverse:forward:tcp:8080;tcp:{}\0", local.port()).as_bytes())
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Write, b"OKAY");
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[])
+        // An app connects to reversed socket on device
+        .respond_with_args(
+            MessageCommand::Open,
+            device_id,
+            0,
+            format!("tcp:{}\0", local.port()).as_bytes(),
+        );
+    transport.expect(MessageCommand::Okay);
+    transport
+        .expect(MessageCommand::Write)
+        .with_payload(b"hello")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Clse, &[]);
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"reverse:killforward:tcp:8080\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Clse, &[]);
+
+    let reverse = ADBMessageDevice::new(transport.clone())
+        .reverse("tcp:8080", local)
+        .expect("cannot reverse socket");
+
+    let (mut socket, _) = listener.accept().expect("cannot accept connection");
+    socket.write_all(b"hello").expect("cannot write to device");
+    // Connection is closed once device closed its session
+    socket
+        .read_to_end(&mut Vec::new())
+        .expect("cannot read from device");
+
+    drop(reverse);
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while !transport.is_exhausted() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(transport.is_exhausted());
+}
+
+#[test]
+fn test_reverse_runs_alongside_shell_command() {
+    use std::time::{Duration, Instant};
+
+    use crate::MockTransport;
+    use crate::device::MessageCommand;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("cannot bind listener");
+    let local = listener.local_addr().expect("cannot read listener address");
+    let device_id = 42;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(format!("reverse:forward:tcp:8080;tcp:{}\0", local.port()).as_bytes())
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Write, b"OKAY");
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[]);
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"shell:echo hello\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Write, b"hello\n");
+    transport
+        .expect(MessageCommand::Okay)
+        // An app connects to reversed socket on device while shell command is still running
+        .respond_with_args(
+            MessageCommand::Open,
+            device_id,
+            0,
+            format!("tcp:{}\0", local.port()).as_bytes(),
+        )
+        .respond(MessageCommand::Clse, &[]);
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Write, b"ping");
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[]);
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"reverse:killforward:tcp:8080\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Clse, &[]);
+
+    let mut device = ADBMessageDevice::new(transport.clone());
+    let reverse = device
+        .reverse("tcp:8080", local)
+        .expect("cannot reverse socket");
+
+    let mut output = Vec::new();
+    device
+        .shell_command(&["echo", "hello"], &mut output)
+        .expect("cannot run shell command");
+    assert_eq!(output, b"hello\n");
+
+    let (mut socket, _) = listener.accept().expect("cannot accept connection");
+    let mut data = Vec::new();
+    socket
+        .read_to_end(&mut data)
+        .expect("cannot read from device");
+    assert_eq!(data, b"ping");
+
+    drop(reverse);
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while !transport.is_exhausted() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(transport.is_exhausted());
+}
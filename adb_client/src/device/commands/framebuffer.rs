@@ -1,98 +1,54 @@
 use std::io::{Cursor, Read};
 
-use byteorder::{LittleEndian, ReadBytesExt};
 use image::{ImageBuffer, Rgba};
 
 use crate::{
-    ADBMessageTransport, Result, RustADBError,
+    ADBMessageTransport, Result,
     device::{MessageCommand, adb_message_device::ADBMessageDevice},
-    models::{FrameBufferInfoV1, FrameBufferInfoV2},
+    models::{FrameBufferInfo, RawFramebuffer},
 };
 
 impl<T: ADBMessageTransport> ADBMessageDevice<T> {
     pub(crate) fn framebuffer_inner(&mut self) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
-        let session = self.open_session(b"framebuffer:\0")?;
-
-        let response = self.recv_and_reply_okay(session)?;
-
-        let mut payload_cursor = Cursor::new(response.payload());
-
-        let version = payload_cursor.read_u32::<LittleEndian>()?;
-
-        let img = match version {
-            // RGBA_8888
-            1 => {
-                let mut buf = [0u8; std::mem::size_of::<FrameBufferInfoV1>()];
-
-                payload_cursor.read_exact(&mut buf)?;
-
-                let framebuffer_info: FrameBufferInfoV1 = buf.try_into()?;
-
-                let mut framebuffer_data = Vec::new();
-                payload_cursor.read_to_end(&mut framebuffer_data)?;
-
-                loop {
-                    if framebuffer_data.len() as u32 == framebuffer_info.size {
-                        break;
-                    }
-
-                    let response = self.recv_and_reply_okay(session)?;
-
-                    framebuffer_data.extend_from_slice(&response.into_payload());
+        self.framebuffer_into(Vec::new())
+    }
 
-                    log::debug!(
-                        "received framebuffer data. new size {}",
-                        framebuffer_data.len()
-                    );
-                }
+    /// Capture framebuffer into `buffer`, reusing its allocation
+    pub(crate) fn framebuffer_into(
+        &mut self,
+        buffer: Vec<u8>,
+    ) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        self.framebuffer_raw_into(buffer)?.into_image()
+    }
 
-                ImageBuffer::<Rgba<u8>, Vec<u8>>::from_vec(
-                    framebuffer_info.width,
-                    framebuffer_info.height,
-                    framebuffer_data,
-                )
-                .ok_or_else(|| RustADBError::FramebufferConversionError)?
-            }
-            // RGBX_8888
-            2 => {
-                let mut buf = [0u8; std::mem::size_of::<FrameBufferInfoV2>()];
+    pub(crate) fn framebuffer_raw(&mut self) -> Result<RawFramebuffer> {
+        self.framebuffer_raw_into(Vec::new())
+    }
 
-                payload_cursor.read_exact(&mut buf)?;
+    /// Capture framebuffer as sent by device into `buffer`, reusing its allocation
+    fn framebuffer_raw_into(&mut self, mut buffer: Vec<u8>) -> Result<RawFramebuffer> {
+        let session = self.open_session(b"framebuffer:\0")?;
 
-                let framebuffer_info: FrameBufferInfoV2 = buf.try_into()?;
+        let response = self.recv_and_reply_okay(session)?;
 
-                let mut framebuffer_data = Vec::new();
-                payload_cursor.read_to_end(&mut framebuffer_data)?;
+        let mut payload_cursor = Cursor::new(response.payload());
 
-                loop {
-                    if framebuffer_data.len() as u32 == framebuffer_info.size {
-                        break;
-                    }
+        let info = FrameBufferInfo::read(&mut payload_cursor)?;
 
-                    let response = self.recv_and_reply_okay(session)?;
+        buffer.clear();
+        payload_cursor.read_to_end(&mut buffer)?;
 
-                    framebuffer_data.extend_from_slice(&response.into_payload());
+        while buffer.len() as u32 != info.size() {
+            let response = self.recv_and_reply_okay(session)?;
 
-                    log::debug!(
-                        "received framebuffer data. new size {}",
-                        framebuffer_data.len()
-                    );
-                }
+            buffer.extend_from_slice(&response.into_payload());
 
-                ImageBuffer::<Rgba<u8>, Vec<u8>>::from_vec(
-                    framebuffer_info.width,
-                    framebuffer_info.height,
-                    framebuffer_data,
-                )
-                .ok_or_else(|| RustADBError::FramebufferConversionError)?
-            }
-            v => return Err(RustADBError::UnimplementedFramebufferImageVersion(v)),
-        };
+            log::debug!("received framebuffer data. new size {}", buffer.len());
+        }
 
-        self.get_transport_mut()
-            .read_message()
+        self.read_session_message(session)
             .and_then(|message| message.assert_command(MessageCommand::Clse))?;
 
-        Ok(img)
+        Ok(RawFramebuffer { info, data: buffer })
     }
 }
@@ -1,46 +1,141 @@
-use std::{fs::File, path::Path};
-
-use rand::Rng;
+use std::{fs::File, io::Read, path::Path};
 
 use crate::{
-    ADBMessageTransport, Result,
-    device::{MessageWriter, adb_message_device::ADBMessageDevice},
-    utils::check_extension_is_apk,
+    ADBMessageTransport, InstallOptions, InstallPhase, Result,
+    device::{
+        ADBTransportMessage, MessageCommand, MessageWriter, adb_message_device::ADBMessageDevice,
+    },
+    utils::{InstallOutput, check_copied_size, check_extension_is_apk},
 };
 
 impl<T: ADBMessageTransport> ADBMessageDevice<T> {
-    pub(crate) fn install(&mut self, apk_path: &dyn AsRef<Path>) -> Result<()> {
+    pub(crate) fn install(
+        &mut self,
+        apk_path: &dyn AsRef<Path>,
+        options: &InstallOptions,
+    ) -> Result<()> {
         let mut apk_file = File::open(apk_path)?;
 
         check_extension_is_apk(apk_path)?;
 
         let file_size = apk_file.metadata()?.len();
 
-        let mut rng = rand::rng();
+        self.install_stream(
+            &mut apk_file,
+            file_size,
+            &apk_path.as_ref().to_string_lossy(),
+            options,
+        )
+    }
 
-        let local_id = rng.random();
+    pub(crate) fn install_from_reader(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.install_stream(reader, size, "APK stream", options)
+    }
 
-        self.open_session(format!("exec:cmd package 'install' -S {}\0", file_size).as_bytes())?;
+    /// Stream `size` bytes of APK from `reader` to package manager, `name` identifying it in progress and logs.
+    fn install_stream(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        name: &str,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        let session = self.open_session(
+            format!("exec:cmd package 'install' {}\0", options.args(size)?).as_bytes(),
+        )?;
 
         let transport = self.get_transport().clone();
 
-        let mut writer = MessageWriter::new(transport, local_id, 0);
-
-        std::io::copy(&mut apk_file, &mut writer)?;
+        let mut writer = MessageWriter::new(
+            transport,
+            self.dispatcher().clone(),
+            session.local_id,
+            session.remote_id,
+        );
 
-        let final_status = self.get_transport_mut().read_message()?;
+        self.observer().install_phase(InstallPhase::Uploading);
+        // Package manager waits for exactly `size` bytes, never send more
+        let mut apk_reader = self
+            .observer()
+            .install_reader(reader.take(size), name, size);
+        check_copied_size(std::io::copy(&mut apk_reader, &mut writer)?, size)?;
+        self.observer().install_phase(InstallPhase::Installing);
 
-        match final_status.into_payload().as_slice() {
-            b"Success\n" => {
-                log::info!(
-                    "APK file {} successfully installed",
-                    apk_path.as_ref().display()
-                );
-                Ok(())
+        let mut output = InstallOutput::default();
+        let status = loop {
+            let message = self.read_session_message(session)?;
+            if message.header().command() != MessageCommand::Write {
+                break output.finish(self.observer());
             }
-            d => Err(crate::RustADBError::ADBRequestFailed(String::from_utf8(
-                d.to_vec(),
-            )?)),
-        }
+            if let Some(status) = output.feed(message.payload(), self.observer()) {
+                break status;
+            }
+
+            self.get_transport_mut()
+                .write_message(ADBTransportMessage::new(
+                    MessageCommand::Okay,
+                    session.local_id,
+                    session.remote_id,
+                    &[],
+                ))?;
+        };
+
+        status?;
+        log::info!("APK {name} successfully installed");
+        self.observer().install_phase(InstallPhase::Completed);
+        Ok(())
     }
 }
+
+#[test]
+fn test_install_from_reader_sends_announced_size() {
+    use crate::MockTransport;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"exec:cmd package 'install' -S 4\0")
+        .respond(MessageCommand::Okay, &[]);
+    transport
+        .expect(MessageCommand::Write)
+        .with_payload(b"apk!")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Write, b"Success\n");
+
+    ADBMessageDevice::new(transport.clone())
+        .install_from_reader(
+            &mut b"apk!trailing".as_slice(),
+            4,
+            &InstallOptions::default(),
+        )
+        .expect("cannot install APK");
+
+    assert!(transport.is_exhausted());
+}
+
+#[test]
+fn test_install_from_reader_fails_on_short_reader() {
+    use crate::MockTransport;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"exec:cmd package 'install' -S 8\0")
+        .respond(MessageCommand::Okay, &[]);
+    transport
+        .expect(MessageCommand::Write)
+        .with_payload(b"apk!")
+        .respond(MessageCommand::Okay, &[]);
+
+    assert!(
+        ADBMessageDevice::new(transport.clone())
+            .install_from_reader(&mut b"apk!".as_slice(), 8, &InstallOptions::default())
+            .is_err()
+    );
+    assert!(transport.is_exhausted());
+}
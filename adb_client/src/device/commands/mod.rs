@@ -1,8 +1,14 @@
+mod forward;
 mod framebuffer;
 mod install;
+mod open_service;
+mod ping;
 mod pull;
 mod push;
 mod reboot;
+mod root;
 mod shell;
+mod sideload;
 mod stat;
 mod uninstall;
+mod verity;
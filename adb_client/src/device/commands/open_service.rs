@@ -0,0 +1,43 @@
+use crate::{
+    ADBMessageTransport, CancelToken, ReadWriteStream, Result,
+    device::{adb_message_device::ADBMessageDevice, service_stream::ServiceStream},
+};
+
+impl<T: ADBMessageTransport> ADBMessageDevice<T> {
+    pub(crate) fn open_service(&mut self, service: &str) -> Result<Box<dyn ReadWriteStream>> {
+        let session = self.open_session(format!("{service}\0").as_bytes())?;
+
+        Ok(Box::new(ServiceStream::new(
+            self.get_transport().clone(),
+            self.dispatcher().clone(),
+            session,
+            self.maximum_payload_size(),
+            self.cancel_token().cloned(),
+        )))
+    }
+
+    pub(crate) fn open_service_cancellable(
+        &mut self,
+        service: &str,
+        cancel_token: &CancelToken,
+    ) -> Result<Box<dyn ReadWriteStream>> {
+        let session = self.open_session(format!("{service}\0").as_bytes())?;
+
+        // Cancelling device still cancels stream
+        let cancel_guard = self.cancel_token().map(|device_token| {
+            let cancel_token = cancel_token.clone();
+            device_token.on_cancel(move || cancel_token.cancel())
+        });
+
+        Ok(Box::new(
+            ServiceStream::new(
+                self.get_transport().clone(),
+                self.dispatcher().clone(),
+                session,
+                self.maximum_payload_size(),
+                Some(cancel_token.clone()),
+            )
+            .with_cancel_guard(cancel_guard),
+        ))
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::{
+    ADBMessageTransport, Result,
+    constants::PING_TIMEOUT,
+    device::{ADBTransportMessage, MessageCommand, adb_message_device::ADBMessageDevice},
+};
+
+impl<T: ADBMessageTransport> ADBMessageDevice<T> {
+    /// Check that device still answers by opening and closing a `sync:` session, returning round-trip time.
+    ///
+    /// Fails with [`std::io::ErrorKind::TimedOut`] if device does not answer within a couple of seconds,
+    /// connection being likely dead.
+    pub fn ping(&mut self) -> Result<Duration> {
+        let started = Instant::now();
+        let deadline = started + PING_TIMEOUT;
+        let local_id: u32 = rand::rng().random();
+
+        self.get_transport_mut()
+            .write_message(ADBTransportMessage::new(
+                MessageCommand::Open,
+                local_id,
+                0,
+                b"sync:\0",
+            ))?;
+
+        // Device answering is all that matters, even by refusing service. Messages left by previous sessions are skipped.
+        let response = loop {
+            let message = self.read_message_before(deadline)?;
+            if message.header().arg1() == local_id {
+                break message;
+            }
+        };
+        let round_trip = started.elapsed();
+
+        if response.header().command() == MessageCommand::Okay {
+            self.get_transport_mut()
+                .write_message(ADBTransportMessage::new(
+                    MessageCommand::Clse,
+                    local_id,
+                    response.header().arg0(),
+                    &[],
+                ))?;
+            // Wait for device to acknowledge closing, not to leave its reply to following operations
+            while self.read_message_before(deadline)?.header().command() != MessageCommand::Clse {}
+        }
+
+        Ok(round_trip)
+    }
+}
+
+#[test]
+fn test_ping_closes_session() {
+    use crate::MockTransport;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"sync:\0")
+        .respond(MessageCommand::Okay, &[]);
+    transport
+        .expect(MessageCommand::Clse)
+        .respond(MessageCommand::Clse, &[]);
+
+    ADBMessageDevice::new(transport.clone())
+        .ping()
+        .expect("cannot ping device");
+
+    assert!(transport.is_exhausted());
+}
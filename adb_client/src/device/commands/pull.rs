@@ -1,15 +1,22 @@
 use std::io::Write;
+use std::time::Instant;
 
 use crate::{
-    ADBMessageTransport, Result, RustADBError,
+    ADBMessageTransport, Result, RustADBError, TransferStats,
     device::{
         ADBTransportMessage, MessageCommand, adb_message_device::ADBMessageDevice,
         models::MessageSubcommand,
     },
+    throttle::ThrottledWriter,
 };
 
 impl<T: ADBMessageTransport> ADBMessageDevice<T> {
-    pub(crate) fn pull<A: AsRef<str>, W: Write>(&mut self, source: A, output: W) -> Result<()> {
+    pub(crate) fn pull<A: AsRef<str>, W: Write>(
+        &mut self,
+        source: A,
+        output: W,
+    ) -> Result<TransferStats> {
+        let started = Instant::now();
         let session = self.begin_synchronization()?;
         let source = source.as_ref();
 
@@ -47,8 +54,18 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
             source.as_bytes(),
         ))?;
 
-        self.recv_file(session, output)?;
+        let output = ThrottledWriter::new(output, self.transfer_rate_limit());
+        let mut output = self.observer().progress_writer(
+            output,
+            source,
+            Some(adb_stat_response.file_size.into()),
+        );
+        self.recv_file(session, &mut output)?;
         self.end_transaction(session)?;
-        Ok(())
+        Ok(TransferStats {
+            bytes: output.transferred(),
+            elapsed: started.elapsed(),
+            retries: 0,
+        })
     }
 }
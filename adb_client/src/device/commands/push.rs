@@ -1,18 +1,29 @@
 use std::io::Read;
+use std::time::{Instant, SystemTime};
 
 use crate::{
-    ADBMessageTransport, Result, RustADBError,
+    ADBMessageTransport, Result, RustADBError, TransferStats,
     device::{
         ADBTransportMessage, MessageCommand, MessageSubcommand,
         adb_message_device::ADBMessageDevice,
     },
+    throttle::ThrottledReader,
+    utils::sync_mod_time,
 };
 
 impl<T: ADBMessageTransport> ADBMessageDevice<T> {
-    pub(crate) fn push<R: Read, A: AsRef<str>>(&mut self, stream: R, path: A) -> Result<()> {
+    pub(crate) fn push<R: Read, A: AsRef<str>>(
+        &mut self,
+        stream: R,
+        path: A,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<TransferStats> {
+        let started = Instant::now();
         let session = self.begin_synchronization()?;
 
-        let path_header = format!("{},0777", path.as_ref());
+        // Mode is parsed by device as a C integer literal, hence octal with a leading zero
+        let path_header = format!("{},0{mode:o}", path.as_ref());
 
         let send_buffer = MessageSubcommand::Send.with_arg(path_header.len() as u32);
         let mut send_buffer =
@@ -26,9 +37,15 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
             &send_buffer,
         ))?;
 
-        self.push_file(session, stream)?;
+        let stream = ThrottledReader::new(stream, self.transfer_rate_limit());
+        let mut stream = self.observer().progress_reader(stream, path.as_ref(), None);
+        self.push_file(session, &mut stream, sync_mod_time(mod_time))?;
         self.end_transaction(session)?;
 
-        Ok(())
+        Ok(TransferStats {
+            bytes: stream.transferred(),
+            elapsed: started.elapsed(),
+            retries: 0,
+        })
     }
 }
@@ -0,0 +1,34 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    ADBMessageTransport, Result, adbd_restart::run_root_service, constants::ADBD_RESTART_TIMEOUT,
+    device::adb_message_device::ADBMessageDevice,
+};
+
+impl<T: ADBMessageTransport> ADBMessageDevice<T> {
+    /// Run `service` (`root:` or `unroot:`), returning `true` once adbd closed connection to restart.
+    ///
+    /// Restarted adbd is only reached by a new connection, which must be established by caller.
+    pub(crate) fn restart_adbd(&mut self, service: &str) -> Result<bool> {
+        if !run_root_service(self, service)? {
+            return Ok(false);
+        }
+
+        self.wait_for_disconnection(ADBD_RESTART_TIMEOUT);
+        Ok(true)
+    }
+
+    /// Wait for at most `timeout` for device to close connection, e.g. as adbd exits.
+    ///
+    /// A new connection attempted meanwhile may still be accepted by exiting adbd.
+    pub(crate) fn wait_for_disconnection(&mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now())
+            && self
+                .dispatcher()
+                .clone()
+                .read_message(self.get_transport_mut(), Some(remaining), None)
+                .is_ok()
+        {}
+    }
+}
@@ -1,11 +1,17 @@
 use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
 
-use crate::Result;
+use crate::constants::SHELL_ABORT_TIMEOUT;
 use crate::device::ShellMessageWriter;
+use crate::shell_protocol::{ShellV2Demuxer, shell_v2_service};
+use crate::utils::{RemotePidFilter, kill_remote_pid, with_remote_pid};
 use crate::{
     ADBMessageTransport, RustADBError,
-    device::{ADBMessageDevice, ADBTransportMessage, MessageCommand},
+    device::{
+        ADBMessageDevice, ADBTransportMessage, MessageCommand, adb_message_device::ADBSession,
+    },
 };
+use crate::{Result, ShellOptions, ShellOutput};
 
 impl<T: ADBMessageTransport> ADBMessageDevice<T> {
     /// Runs 'command' in a shell on the device, and write its output and error streams into output.
@@ -14,7 +20,7 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
             self.open_session(format!("shell:{}\0", command.join(" "),).as_bytes())?;
 
         loop {
-            let response = self.get_transport_mut().read_message()?;
+            let response = self.read_session_message(session)?;
             if response.header().command() != MessageCommand::Write {
                 break;
             }
@@ -35,21 +41,163 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         Ok(())
     }
 
+    /// Runs 'command' using `shell,v2` service, writing its streams as requested by `output`, and return its exit code.
+    ///
+    /// Device must have announced `shell_v2` feature when connecting.
+    pub(crate) fn shell_command_v2(
+        &mut self,
+        command: &[&str],
+        output: ShellOutput<'_>,
+    ) -> Result<u8> {
+        let session = self.open_session(format!("{}\0", shell_v2_service(command)).as_bytes())?;
+        let mut demuxer = ShellV2Demuxer::new(output);
+
+        loop {
+            let response = self.read_session_message(session)?;
+            if response.header().command() != MessageCommand::Write {
+                break;
+            }
+
+            demuxer.write_all(&response.into_payload())?;
+
+            self.get_transport_mut()
+                .write_message(ADBTransportMessage::new(
+                    MessageCommand::Okay,
+                    session.local_id,
+                    session.remote_id,
+                    &[],
+                ))?;
+        }
+
+        demuxer.flush()?;
+        demuxer.finish()
+    }
+
+    /// Runs 'command' like [`ADBMessageDevice::shell_command`], killing it if still running after `timeout`.
+    pub(crate) fn shell_command_with_timeout(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<()> {
+        let mut output = RemotePidFilter::new(output);
+        let result = self.shell_command_until(
+            &with_remote_pid(command),
+            &mut output,
+            Instant::now() + timeout,
+        );
+
+        if let Err(e) = &result
+            && e.is_timeout()
+            && let Some(pid) = output.pid()
+        {
+            // Best effort, device may be unresponsive
+            let _ = self.shell_command_until(
+                &kill_remote_pid(pid),
+                &mut std::io::sink(),
+                Instant::now() + SHELL_ABORT_TIMEOUT,
+            );
+        }
+
+        result
+    }
+
+    /// Runs 'command_line' in a shell, closing its session if it does not end before `deadline`.
+    fn shell_command_until(
+        &mut self,
+        command_line: &str,
+        output: &mut dyn Write,
+        deadline: Instant,
+    ) -> Result<()> {
+        let session = self.open_session(format!("shell:{command_line}\0").as_bytes())?;
+
+        loop {
+            let response = match self.read_message_before(deadline) {
+                Err(e) if e.is_timeout() => {
+                    self.close_session(session);
+                    return Err(e);
+                }
+                response => response?,
+            };
+            if response.header().command() != MessageCommand::Write {
+                return Ok(());
+            }
+
+            output.write_all(&response.into_payload())?;
+
+            self.get_transport_mut()
+                .write_message(ADBTransportMessage::new(
+                    MessageCommand::Okay,
+                    session.local_id,
+                    session.remote_id,
+                    &[],
+                ))?;
+        }
+    }
+
+    /// Close `session`, and drop messages still in flight for it until device acknowledges.
+    fn close_session(&mut self, session: ADBSession) {
+        // Best effort here
+        if self
+            .get_transport_mut()
+            .write_message(ADBTransportMessage::new(
+                MessageCommand::Clse,
+                session.local_id,
+                session.remote_id,
+                &[],
+            ))
+            .is_err()
+        {
+            return;
+        }
+
+        let deadline = Instant::now() + SHELL_ABORT_TIMEOUT;
+        while let Ok(message) = self.read_message_before(deadline) {
+            if message.header().command() == MessageCommand::Clse
+                && message.header().arg1() == session.local_id
+            {
+                return;
+            }
+        }
+    }
+
     /// Starts an interactive shell session on the device.
     /// Input data is read from [reader] and write to [writer].
     pub(crate) fn shell(
         &mut self,
+        reader: &mut dyn Read,
+        writer: Box<(dyn Write + Send)>,
+    ) -> Result<()> {
+        self.interactive_shell("shell:", reader, writer)
+    }
+
+    /// Starts an interactive shell session on the device, configured using `options`.
+    pub(crate) fn shell_with_options(
+        &mut self,
+        options: &ShellOptions,
+        reader: &mut dyn Read,
+        writer: Box<dyn Write + Send>,
+    ) -> Result<()> {
+        self.interactive_shell(&options.service("")?, reader, writer)
+    }
+
+    fn interactive_shell(
+        &mut self,
+        service: &str,
         mut reader: &mut dyn Read,
-        mut writer: Box<(dyn Write + Send)>,
+        mut writer: Box<dyn Write + Send>,
     ) -> Result<()> {
-        let session = self.open_session(b"shell:\0")?;
+        let session = self.open_session(format!("{service}\0").as_bytes())?;
 
         let mut transport = self.get_transport().clone();
+        let dispatcher = self.dispatcher().clone();
+        let cancel_token = self.cancel_token().cloned();
 
         // Reading thread, reads response from adbd
         std::thread::spawn(move || -> Result<()> {
             loop {
-                let message = transport.read_message()?;
+                let message =
+                    dispatcher.read_message(&mut transport, None, cancel_token.as_ref())?;
 
                 // Acknowledge for more data
                 let response = ADBTransportMessage::new(
@@ -86,3 +234,68 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
         Ok(())
     }
 }
+
+#[test]
+fn test_shell_command_with_timeout_kills_remote_command() {
+    use crate::MockTransport;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"shell:echo $$; sleep 60\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Write, b"1234\nstarted\n");
+    transport.expect(MessageCommand::Okay);
+    transport
+        .expect(MessageCommand::Clse)
+        .respond(MessageCommand::Clse, &[]);
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"shell:kill -9 -- -1234 2>/dev/null || kill -9 1234\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Clse, &[]);
+
+    let mut output = Vec::new();
+    let error = ADBMessageDevice::new(transport.clone())
+        .shell_command_with_timeout(&["sleep", "60"], &mut output, Duration::from_secs(1))
+        .expect_err("command should have timed out");
+
+    assert!(error.is_timeout());
+    assert_eq!(output, b"started\n");
+    assert!(transport.is_exhausted());
+}
+
+#[test]
+fn test_shell_command_v2_separates_streams() {
+    use crate::MockTransport;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"shell,v2,raw:ls /missing\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(
+            MessageCommand::Write,
+            b"\x02\x08\0\0\0missing\n\x03\x01\0\0\0\x01",
+        );
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[]);
+
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let exit_code = ADBMessageDevice::new(transport.clone())
+        .shell_command_v2(
+            &["ls", "/missing"],
+            ShellOutput::Separate {
+                stdout: &mut stdout,
+                stderr: &mut stderr,
+            },
+        )
+        .expect("cannot run shell command");
+
+    assert_eq!(exit_code, 1);
+    assert!(stdout.is_empty());
+    assert_eq!(stderr, b"missing\n");
+    assert!(transport.is_exhausted());
+}
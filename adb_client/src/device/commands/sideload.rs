@@ -0,0 +1,22 @@
+use crate::{
+    ADBMessageTransport, ReadSeek, Result, TransferStats,
+    device::adb_message_device::ADBMessageDevice,
+    sideload::{sideload, sideload_service},
+};
+
+impl<T: ADBMessageTransport> ADBMessageDevice<T> {
+    pub(crate) fn sideload_from_reader(
+        &mut self,
+        reader: &mut dyn ReadSeek,
+        size: u64,
+    ) -> Result<TransferStats> {
+        let mut stream = self.open_service(&sideload_service(size))?;
+        sideload(
+            &mut stream,
+            reader,
+            size,
+            self.observer(),
+            self.transfer_rate_limit(),
+        )
+    }
+}
@@ -4,7 +4,7 @@ impl<T: ADBMessageTransport> ADBMessageDevice<T> {
     pub(crate) fn uninstall(&mut self, package_name: &str) -> Result<()> {
         self.open_session(format!("exec:cmd package 'uninstall' {}\0", package_name).as_bytes())?;
 
-        let final_status = self.get_transport_mut().read_message()?;
+        let final_status = self.read_message()?;
 
         match final_status.into_payload().as_slice() {
             b"Success\n" => {
@@ -0,0 +1,20 @@
+use crate::{
+    ADBMessageTransport, RebootType, Result, constants::DEVICE_REBOOT_TIMEOUT,
+    device::adb_message_device::ADBMessageDevice, verity::run_verity_service,
+};
+
+impl<T: ADBMessageTransport> ADBMessageDevice<T> {
+    /// Run `service` (`remount:`, `disable-verity:` or `enable-verity:`), returning `true` if a reboot is required.
+    ///
+    /// If `reboot` is set, device is rebooted when required, returning once it closed connection.
+    pub(crate) fn apply_verity_service(&mut self, service: &str, reboot: bool) -> Result<bool> {
+        let reboot_required = run_verity_service(self, service)?;
+
+        if reboot && reboot_required {
+            self.reboot(RebootType::System)?;
+            self.wait_for_disconnection(DEVICE_REBOOT_TIMEOUT);
+        }
+
+        Ok(reboot_required)
+    }
+}
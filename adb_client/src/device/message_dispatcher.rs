@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::ErrorKind;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Condvar, Mutex, TryLockError};
+use std::time::{Duration, Instant};
+
+use crate::constants::CANCEL_POLL_INTERVAL;
+use crate::{ADBMessageTransport, CancelToken, Result, RustADBError};
+
+use super::{ADBTransportMessage, MessageCommand};
+
+/// Dispatcher of messages read from the transport of a device, shared by its commands and port forwards.
+///
+/// Transport is read by a single thread at a time, whichever is waiting for a message. Each message read is handed over to
+/// the forward owning its session, or kept for device commands otherwise. Commands can then be issued while forwards run.
+///
+/// Sessions opened by device are handed over to the reverse forward of their host socket, or refused if there is none.
+#[derive(Debug, Default)]
+pub(crate) struct MessageDispatcher {
+    /// Held by the thread reading transport
+    reader: Mutex<()>,
+    state: Mutex<DispatcherState>,
+    /// Notified once a message has been kept for device commands
+    received: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct DispatcherState {
+    /// Sessions of forwards, by local id
+    sessions: HashMap<u32, Sender<ADBTransportMessage>>,
+    /// Sessions of forwards given up on host side, whose messages are dropped until device closes them too
+    closing: HashSet<u32>,
+    /// Reverse forwards, by host socket device opens sessions to (e.g. `tcp:8080`)
+    reverse_forwards: HashMap<String, Sender<ADBTransportMessage>>,
+    /// Messages kept for device commands, in order of reception
+    pending: VecDeque<ADBTransportMessage>,
+}
+
+impl DispatcherState {
+    /// Return `true` if all messages are for device commands
+    fn is_idle(&self) -> bool {
+        self.sessions.is_empty() && self.closing.is_empty() && self.reverse_forwards.is_empty()
+    }
+}
+
+impl MessageDispatcher {
+    /// Read next message for device commands, for at most `timeout` if any, giving up if `cancel_token` gets cancelled.
+    ///
+    /// Messages of forwards read meanwhile are handed over to them.
+    pub(crate) fn read_message<T: ADBMessageTransport>(
+        &self,
+        transport: &mut T,
+        timeout: Option<Duration>,
+        cancel_token: Option<&CancelToken>,
+    ) -> Result<ADBTransportMessage> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            let mut state = self.state.lock()?;
+            if let Some(message) = state.pending.pop_front() {
+                return Ok(message);
+            }
+            if let Some(cancel_token) = cancel_token {
+                cancel_token.check()?;
+            }
+            let remaining = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => Some(remaining),
+                    _ => return Err(std::io::Error::from(ErrorKind::TimedOut).into()),
+                },
+                None => None,
+            };
+            let poll_interval = remaining.map_or(CANCEL_POLL_INTERVAL, |remaining| {
+                remaining.min(CANCEL_POLL_INTERVAL)
+            });
+
+            let reader = match self.reader.try_lock() {
+                Ok(reader) => reader,
+                Err(TryLockError::WouldBlock) => {
+                    // Thread reading transport notifies us once it kept a message for commands
+                    drop(self.received.wait_timeout(state, poll_interval)?);
+                    continue;
+                }
+                Err(TryLockError::Poisoned(_)) => return Err(RustADBError::PoisonError),
+            };
+
+            // Nobody else waits for messages, wait for next one as previously
+            let message = if cancel_token.is_none() && state.is_idle() {
+                drop(state);
+                match remaining {
+                    Some(remaining) => transport.read_message_with_timeout(remaining)?,
+                    None => transport.read_message()?,
+                }
+            } else {
+                drop(state);
+                match transport.try_read_message()? {
+                    Some(message) => message,
+                    None => {
+                        drop(reader);
+                        drop(
+                            self.received
+                                .wait_timeout(self.state.lock()?, poll_interval)?,
+                        );
+                        continue;
+                    }
+                }
+            };
+
+            // Still holding reader, as no message kept meanwhile for commands can precede this one
+            if let Some(message) = self.dispatch(transport, message)? {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// Read a message if available and no other thread is reading transport, and dispatch it.
+    ///
+    /// Return `false` if no message has been read, to let caller wait a bit before polling again.
+    pub(crate) fn poll<T: ADBMessageTransport>(&self, transport: &mut T) -> Result<bool> {
+        let _reader = match self.reader.try_lock() {
+            Ok(reader) => reader,
+            Err(TryLockError::WouldBlock) => return Ok(false),
+            Err(TryLockError::Poisoned(_)) => return Err(RustADBError::PoisonError),
+        };
+
+        let Some(message) = transport.try_read_message()? else {
+            return Ok(false);
+        };
+        if let Some(message) = self.dispatch(transport, message)? {
+            self.state.lock()?.pending.push_back(message);
+            self.received.notify_all();
+        }
+        Ok(true)
+    }
+
+    /// Hand `message` over to the forward owning it, returning it if it is for device commands instead
+    fn dispatch<T: ADBMessageTransport>(
+        &self,
+        transport: &mut T,
+        message: ADBTransportMessage,
+    ) -> Result<Option<ADBTransportMessage>> {
+        let mut state = self.state.lock()?;
+        let command = message.header().command();
+        let local_id = message.header().arg1();
+
+        // Device opens a session for each connection to a reversed socket
+        if command == MessageCommand::Open && local_id == 0 {
+            let payload = String::from_utf8_lossy(message.payload());
+            let host_socket = payload.trim_end_matches('\0').to_string();
+            let message = match state.reverse_forwards.get(&host_socket) {
+                Some(reverse_forward) => match reverse_forward.send(message) {
+                    Ok(()) => return Ok(None),
+                    Err(mpsc::SendError(message)) => message,
+                },
+                None => message,
+            };
+            drop(state);
+
+            // Closing a session which is not opened yet refuses it
+            log::debug!("refusing session opened by device to {host_socket}");
+            transport.write_message(ADBTransportMessage::new(
+                MessageCommand::Clse,
+                0,
+                message.header().arg0(),
+                &[],
+            ))?;
+            return Ok(None);
+        }
+
+        if command == MessageCommand::Clse {
+            // Session is over for device, nothing else will be received
+            if state.closing.remove(&local_id) {
+                return Ok(None);
+            }
+            if let Some(session) = state.sessions.remove(&local_id) {
+                let _ = session.send(message);
+                return Ok(None);
+            }
+        }
+
+        if let Some(session) = state.sessions.get(&local_id) {
+            // Forward may already be gone, its connection being closed
+            let _ = session.send(message);
+            return Ok(None);
+        }
+        if state.closing.contains(&local_id) {
+            log::debug!("dropping {command} message received for closed session {local_id}");
+            return Ok(None);
+        }
+
+        Ok(Some(message))
+    }
+
+    /// Route messages of session `local_id` to returned receiver, until device closes session or it is unregistered
+    pub(crate) fn register_session(&self, local_id: u32) -> Result<Receiver<ADBTransportMessage>> {
+        let (sender, receiver) = mpsc::channel();
+        self.state.lock()?.sessions.insert(local_id, sender);
+        Ok(receiver)
+    }
+
+    /// Stop routing messages of session `local_id`, dropping the ones device may still send until it closes session
+    pub(crate) fn unregister_session(&self, local_id: u32) -> Result<()> {
+        let mut state = self.state.lock()?;
+        if state.sessions.remove(&local_id).is_some() {
+            state.closing.insert(local_id);
+        }
+        Ok(())
+    }
+
+    /// Return `true` if session `local_id` is routed, i.e. has not been closed by device yet
+    pub(crate) fn is_registered(&self, local_id: u32) -> Result<bool> {
+        Ok(self.state.lock()?.sessions.contains_key(&local_id))
+    }
+
+    /// Route `OPEN` messages of sessions opened by device to `host_socket` (e.g. `tcp:8080`) to returned receiver
+    pub(crate) fn register_reverse(
+        &self,
+        host_socket: &str,
+    ) -> Result<Receiver<ADBTransportMessage>> {
+        let mut state = self.state.lock()?;
+        if state.reverse_forwards.contains_key(host_socket) {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "{host_socket} is already the host side of a reverse forward"
+            )));
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        state
+            .reverse_forwards
+            .insert(host_socket.to_string(), sender);
+        Ok(receiver)
+    }
+
+    /// Stop routing sessions opened by device to `host_socket`
+    pub(crate) fn unregister_reverse(&self, host_socket: &str) -> Result<()> {
+        self.state.lock()?.reverse_forwards.remove(host_socket);
+        Ok(())
+    }
+
+    /// Drop messages kept for device commands, e.g. once connected again
+    pub(crate) fn discard_pending(&self) -> Result<()> {
+        self.state.lock()?.pending.clear();
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use std::io::{Error, ErrorKind, Result, Write};
+use std::sync::Arc;
 
 use crate::ADBMessageTransport;
 
+use super::message_dispatcher::MessageDispatcher;
 use super::{ADBTransportMessage, MessageCommand};
 
 /// [`Write`] trait implementation to hide underlying ADB protocol write logic.
@@ -9,14 +11,21 @@ use super::{ADBTransportMessage, MessageCommand};
 /// Read received responses to check that message has been correctly received.
 pub struct MessageWriter<T: ADBMessageTransport> {
     transport: T,
+    dispatcher: Arc<MessageDispatcher>,
     local_id: u32,
     remote_id: u32,
 }
 
 impl<T: ADBMessageTransport> MessageWriter<T> {
-    pub fn new(transport: T, local_id: u32, remote_id: u32) -> Self {
+    pub(crate) fn new(
+        transport: T,
+        dispatcher: Arc<MessageDispatcher>,
+        local_id: u32,
+        remote_id: u32,
+    ) -> Self {
         Self {
             transport,
+            dispatcher,
             local_id,
             remote_id,
         }
@@ -31,7 +40,10 @@ impl<T: ADBMessageTransport> Write for MessageWriter<T> {
             .write_message(message)
             .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
 
-        match self.transport.read_message() {
+        match self
+            .dispatcher
+            .read_message(&mut self.transport, None, None)
+        {
             Ok(response) => {
                 response
                     .assert_command(MessageCommand::Okay)
@@ -1,3 +1,7 @@
+#[cfg(feature = "async")]
+mod adb_async_message_device;
+#[cfg(feature = "async")]
+mod adb_async_message_device_commands;
 mod adb_message_device;
 mod adb_message_device_commands;
 #[cfg(feature = "tcp")]
@@ -5,25 +9,38 @@ mod adb_tcp_device;
 mod adb_transport_message;
 #[cfg(feature = "usb")]
 mod adb_usb_device;
+#[cfg(all(feature = "vsock", any(target_os = "linux", target_os = "android")))]
+mod adb_vsock_device;
+#[cfg(feature = "async")]
+mod async_commands;
 mod commands;
+mod message_dispatcher;
 mod message_writer;
 mod models;
+mod port_forward;
+mod service_stream;
 mod shell_message_writer;
 
 use std::path::PathBuf;
 
-use adb_message_device::ADBMessageDevice;
+#[cfg(feature = "async")]
+pub use adb_async_message_device::ADBAsyncMessageDevice;
+pub use adb_message_device::ADBMessageDevice;
 #[cfg(feature = "tcp")]
-pub use adb_tcp_device::ADBTcpDevice;
+pub use adb_tcp_device::{ADBTcpDevice, ReconnectPolicy};
 #[cfg(any(feature = "tcp", feature = "usb"))]
 pub use adb_transport_message::ADBTransportMessageHeader;
 pub use adb_transport_message::ADBTransportMessage;
 #[cfg(feature = "usb")]
 pub use adb_usb_device::ADBUSBDevice;
+#[cfg(all(feature = "vsock", any(target_os = "linux", target_os = "android")))]
+pub use adb_vsock_device::ADBVsockDevice;
 pub use message_writer::MessageWriter;
-pub use models::{MessageCommand, MessageSubcommand};
-#[cfg(feature = "usb")]
 pub use models::ADBRsaKey;
+#[cfg(feature = "usb")]
+pub use models::USBDeviceSelector;
+pub use models::{DeviceMode, MessageCommand, MessageSubcommand};
+pub use port_forward::PortForward;
 pub use shell_message_writer::ShellMessageWriter;
 
 use crate::{Result, RustADBError};
@@ -36,3 +53,21 @@ pub fn get_default_adb_key_path() -> Result<PathBuf> {
         .ok_or(RustADBError::NoHomeDirectory)
 }
 
+#[cfg(any(feature = "usb", feature = "async"))]
+pub fn read_adb_private_key<P: AsRef<std::path::Path>>(
+    private_key_path: P,
+) -> Result<Option<ADBRsaKey>> {
+    let pk = match std::fs::read_to_string(private_key_path.as_ref()) {
+        Ok(contents) => contents,
+        Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    match ADBRsaKey::new_from_pkcs8(&pk) {
+        Ok(pk) => Ok(Some(pk)),
+        Err(e) => {
+            log::error!("Error while create RSA private key: {e}");
+            Ok(None)
+        }
+    }
+}
+
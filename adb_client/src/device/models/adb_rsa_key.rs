@@ -3,9 +3,13 @@ use base64::{Engine, engine::general_purpose::STANDARD};
 use num_bigint::{BigUint, ModInverse};
 use num_traits::FromPrimitive;
 use num_traits::cast::ToPrimitive;
+#[cfg(any(feature = "usb", feature = "async"))]
+use rsa::Pkcs1v15Sign;
+use rsa::RsaPrivateKey;
 use rsa::pkcs8::DecodePrivateKey;
+#[cfg(feature = "tcp")]
+use rsa::pkcs8::{EncodePrivateKey, LineEnding};
 use rsa::traits::PublicKeyParts;
-use rsa::{Pkcs1v15Sign, RsaPrivateKey};
 
 const ADB_PRIVATE_KEY_SIZE: usize = 2048;
 const ANDROID_PUBKEY_MODULUS_SIZE_WORDS: u32 = 64;
@@ -61,6 +65,11 @@ impl ADBRsaKey {
         })
     }
 
+    #[cfg(feature = "tcp")]
+    pub fn to_pkcs8_pem(&self) -> Result<String> {
+        Ok(self.private_key.to_pkcs8_pem(LineEnding::LF)?.to_string())
+    }
+
     pub fn android_pubkey_encode(&self) -> Result<String> {
         // Helped from project: https://github.com/hajifkd/webadb
         // Source code: https://android.googlesource.com/platform/system/core/+/refs/heads/main/libcrypto_utils/android_pubkey.cpp
@@ -103,6 +112,7 @@ impl ADBRsaKey {
         encoded
     }
 
+    #[cfg(any(feature = "usb", feature = "async"))]
     pub fn sign(&self, msg: impl AsRef<[u8]>) -> Result<Vec<u8>> {
         Ok(self
             .private_key
@@ -0,0 +1,66 @@
+use std::fmt::Display;
+
+/// Mode a device announced in its connection banner, restricting services it provides.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeviceMode {
+    /// Android is running, all services are available
+    #[default]
+    Normal,
+    /// Device runs recovery, with `adbd` providing most services (shell, sync, reboot...)
+    Recovery,
+    /// Device runs `minadbd` in sideload mode, only accepting packages to sideload
+    Sideload,
+    /// Device runs `minadbd` in rescue mode, only accepting packages to install and properties requests
+    Rescue,
+}
+
+impl DeviceMode {
+    /// Parse mode from the banner sent by device along with `CNXN`, e.g. `sideload::`
+    pub(crate) fn from_banner(banner: &[u8]) -> Self {
+        let state = banner.split(|b| *b == b':').next().unwrap_or_default();
+        match state {
+            b"recovery" => Self::Recovery,
+            b"sideload" => Self::Sideload,
+            b"rescue" => Self::Rescue,
+            _ => Self::Normal,
+        }
+    }
+
+    /// Return `true` if a device in this mode accepts opening `service` (e.g. `shell:ls`)
+    pub(crate) fn allows_service(&self, service: &[u8]) -> bool {
+        let prefixes: &[&[u8]] = match self {
+            Self::Normal | Self::Recovery => return true,
+            Self::Sideload => &[b"sideload:", b"sideload-host:", b"reboot:"],
+            Self::Rescue => &[
+                b"rescue-install:",
+                b"rescue-getprop:",
+                b"rescue-wipe:",
+                b"reboot:",
+            ],
+        };
+        prefixes.iter().any(|prefix| service.starts_with(prefix))
+    }
+}
+
+impl Display for DeviceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeviceMode::Normal => write!(f, "device"),
+            DeviceMode::Recovery => write!(f, "recovery"),
+            DeviceMode::Sideload => write!(f, "sideload"),
+            DeviceMode::Rescue => write!(f, "rescue"),
+        }
+    }
+}
+
+#[test]
+fn test_device_mode() {
+    let mode = DeviceMode::from_banner(b"sideload::ro.product.name=sdk;\0");
+    assert_eq!(mode, DeviceMode::Sideload);
+    assert!(mode.allows_service(b"sideload-host:1024:65536\0"));
+    assert!(!mode.allows_service(b"shell:ls\0"));
+
+    let mode = DeviceMode::from_banner(b"device::ro.product.name=sdk;features=shell_v2");
+    assert_eq!(mode, DeviceMode::Normal);
+    assert!(mode.allows_service(b"sync:\0"));
+}
@@ -1,7 +1,13 @@
-#[cfg(feature = "usb")]
+#[cfg(any(feature = "tcp", feature = "usb"))]
 mod adb_rsa_key;
+mod device_mode;
 mod message_commands;
-
 #[cfg(feature = "usb")]
+mod usb_device_selector;
+
+#[cfg(any(feature = "tcp", feature = "usb"))]
 pub use adb_rsa_key::ADBRsaKey;
+pub use device_mode::DeviceMode;
 pub use message_commands::{MessageCommand, MessageSubcommand};
+#[cfg(feature = "usb")]
+pub use usb_device_selector::USBDeviceSelector;
@@ -0,0 +1,10 @@
+/// Selects which USB device to wait for, see [`crate::ADBUSBDevice::wait_for_device`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum USBDeviceSelector {
+    /// Only ADB device connected, detected using its interface class
+    Any,
+    /// Device with given vendor and product ids
+    VendorProduct(u16, u16),
+    /// Device with given USB serial number, telling apart several devices of the same model
+    Serial(String),
+}
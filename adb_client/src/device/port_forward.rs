@@ -0,0 +1,521 @@
+use std::collections::HashSet;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use rand::Rng;
+
+use crate::constants::{CANCEL_POLL_INTERVAL, FORWARD_REMOVAL_TIMEOUT};
+use crate::{ADBMessageTransport, CancelToken, Result, RustADBError};
+
+use super::adb_message_device::ADBSession;
+use super::message_dispatcher::MessageDispatcher;
+use super::{ADBTransportMessage, MessageCommand};
+
+/// Forward between a TCP port on host and a socket on device, proxied over the message transport of device.
+///
+/// Connections are proxied until forward is dropped, each of them into its own session. Dropping it also closes
+/// connections still being proxied, and removes rule from device for a reverse forward.
+///
+/// Device can still be used meanwhile, messages received being dispatched between its commands and forwards.
+#[derive(Debug)]
+pub struct PortForward {
+    local_addr: SocketAddr,
+    stop_token: CancelToken,
+}
+
+impl PortForward {
+    /// Accept connections on `listener`, proxying each of them to `remote` socket on device reachable over `transport`
+    pub(crate) fn start<T: ADBMessageTransport>(
+        transport: T,
+        dispatcher: Arc<MessageDispatcher>,
+        listener: TcpListener,
+        remote: &str,
+        maximum_payload_size: usize,
+    ) -> Result<Self> {
+        let local_addr = listener.local_addr()?;
+        listener.set_nonblocking(true)?;
+
+        let direction = Direction::Forward {
+            remote: remote.to_string(),
+        };
+        Ok(Self::spawn(
+            Forwarder::new(transport, dispatcher, direction, maximum_payload_size),
+            Some(listener),
+            None,
+            local_addr,
+        ))
+    }
+
+    /// Proxy connections opened by device reachable over `transport` on `remote` socket to `local` address on host.
+    ///
+    /// Rule must already have been installed on device, the `OPEN` messages of connections to `host_socket` being
+    /// received from `connections`.
+    pub(crate) fn start_reverse<T: ADBMessageTransport>(
+        transport: T,
+        dispatcher: Arc<MessageDispatcher>,
+        connections: Receiver<ADBTransportMessage>,
+        remote: &str,
+        local: SocketAddr,
+        host_socket: &str,
+        maximum_payload_size: usize,
+    ) -> Result<Self> {
+        let direction = Direction::Reverse {
+            remote: remote.to_string(),
+            local,
+            host_socket: host_socket.to_string(),
+        };
+        Ok(Self::spawn(
+            Forwarder::new(transport, dispatcher, direction, maximum_payload_size),
+            None,
+            Some(connections),
+            local,
+        ))
+    }
+
+    fn spawn<T: ADBMessageTransport>(
+        forwarder: Forwarder<T>,
+        listener: Option<TcpListener>,
+        connections: Option<Receiver<ADBTransportMessage>>,
+        local_addr: SocketAddr,
+    ) -> Self {
+        let stop_token = CancelToken::new();
+        let thread_stop_token = stop_token.clone();
+        std::thread::spawn(move || {
+            if let Err(e) =
+                forwarder.run(listener.as_ref(), connections.as_ref(), &thread_stop_token)
+            {
+                log::debug!("forward of {} stopped: {e}", forwarder.direction.remote());
+            }
+            if let Err(e) = forwarder.stop() {
+                log::debug!(
+                    "cannot stop forward of {}: {e}",
+                    forwarder.direction.remote()
+                );
+            }
+        });
+
+        Self {
+            local_addr,
+            stop_token,
+        }
+    }
+
+    /// Address of host side of forward: the one connections are accepted on (e.g. to know which port has been picked
+    /// when binding port `0`), or the one connections opened by device are proxied to for a reverse forward.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        self.stop_token.cancel();
+    }
+}
+
+/// Side initiating proxied connections
+#[derive(Debug, Clone)]
+enum Direction {
+    /// Connections accepted on host are proxied to `remote` socket on device
+    Forward { remote: String },
+    /// Connections opened by device on `remote` socket, announced as `host_socket` ones, are proxied to `local` address
+    Reverse {
+        remote: String,
+        local: SocketAddr,
+        host_socket: String,
+    },
+}
+
+impl Direction {
+    fn remote(&self) -> &str {
+        match self {
+            Direction::Forward { remote } | Direction::Reverse { remote, .. } => remote,
+        }
+    }
+}
+
+/// State shared by the thread polling for connections, and the ones proxying them.
+#[derive(Clone)]
+struct Forwarder<T: ADBMessageTransport> {
+    transport: T,
+    dispatcher: Arc<MessageDispatcher>,
+    /// Local ids of sessions of proxied connections
+    sessions: Arc<Mutex<HashSet<u32>>>,
+    direction: Direction,
+    maximum_payload_size: usize,
+}
+
+impl<T: ADBMessageTransport> Forwarder<T> {
+    fn new(
+        transport: T,
+        dispatcher: Arc<MessageDispatcher>,
+        direction: Direction,
+        maximum_payload_size: usize,
+    ) -> Self {
+        Self {
+            transport,
+            dispatcher,
+            sessions: Arc::default(),
+            direction,
+            maximum_payload_size,
+        }
+    }
+
+    /// Accept connections on `listener`, or the ones opened by device received from `connections`, until `stop_token`
+    /// is cancelled.
+    ///
+    /// Transport is polled meanwhile, as messages of proxied connections may not be read by device commands.
+    fn run(
+        &self,
+        listener: Option<&TcpListener>,
+        connections: Option<&Receiver<ADBTransportMessage>>,
+        stop_token: &CancelToken,
+    ) -> Result<()> {
+        let mut transport = self.transport.clone();
+
+        while !stop_token.is_cancelled() {
+            let mut idle = true;
+
+            if let Some(listener) = listener {
+                match listener.accept() {
+                    Ok((socket, peer)) => {
+                        idle = false;
+                        socket.set_nonblocking(false)?;
+                        let forwarder = self.clone();
+                        std::thread::spawn(move || {
+                            if let Err(e) = forwarder.proxy(socket, None) {
+                                log::debug!(
+                                    "connection from {peer} to {} ended: {e}",
+                                    forwarder.direction.remote()
+                                );
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            // Device opens a session for each connection to a reversed socket
+            while let Some(open) = connections.and_then(|connections| connections.try_recv().ok()) {
+                idle = false;
+                let remote_id = open.header().arg0();
+                let forwarder = self.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = forwarder.accept_reverse(remote_id) {
+                        log::debug!(
+                            "connection to {} from device ended: {e}",
+                            forwarder.direction.remote()
+                        );
+                    }
+                });
+            }
+
+            if self.dispatcher.poll(&mut transport)? {
+                idle = false;
+            }
+
+            if idle {
+                std::thread::sleep(CANCEL_POLL_INTERVAL);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stop routing connections and close the ones still being proxied, removing rule from device for a reverse forward
+    fn stop(&self) -> Result<()> {
+        if let Direction::Reverse { host_socket, .. } = &self.direction {
+            self.dispatcher.unregister_reverse(host_socket)?;
+        }
+        // Proxying threads stop once they cannot receive messages anymore
+        for local_id in self.sessions.lock()?.drain() {
+            self.dispatcher.unregister_session(local_id)?;
+        }
+        if let Direction::Reverse { remote, .. } = &self.direction {
+            self.remove_reverse(remote)?;
+        }
+        Ok(())
+    }
+
+    /// Proxy session opened by device as `remote_id` to a new connection to host side of reverse forward
+    fn accept_reverse(&self, remote_id: u32) -> Result<()> {
+        let socket = match &self.direction {
+            Direction::Reverse { local, .. } => TcpStream::connect(local),
+            Direction::Forward { .. } => Err(ErrorKind::ConnectionRefused.into()),
+        };
+
+        match socket {
+            Ok(socket) => self.proxy(socket, Some(remote_id)),
+            Err(e) => {
+                // Closing a session which is not opened yet refuses it
+                self.transport
+                    .clone()
+                    .write_message(ADBTransportMessage::new(
+                        MessageCommand::Clse,
+                        0,
+                        remote_id,
+                        &[],
+                    ))?;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Proxy `socket` into a new session, or into the one opened by device as `remote_id`, until either end closes it
+    fn proxy(&self, socket: TcpStream, remote_id: Option<u32>) -> Result<()> {
+        let local_id: u32 = rand::rng().random();
+        let receiver = self.dispatcher.register_session(local_id)?;
+        self.sessions.lock()?.insert(local_id);
+
+        let result = self
+            .open_session(local_id, remote_id, &receiver)
+            .and_then(|session| self.relay(session, &receiver, &socket));
+
+        self.sessions.lock()?.remove(&local_id);
+        self.dispatcher.unregister_session(local_id)?;
+        // Best effort, unblocks thread reading from socket
+        let _ = socket.shutdown(Shutdown::Both);
+        result
+    }
+
+    /// Open session `local_id` to remote socket, or accept the one opened by device as `remote_id`
+    fn open_session(
+        &self,
+        local_id: u32,
+        remote_id: Option<u32>,
+        receiver: &Receiver<ADBTransportMessage>,
+    ) -> Result<ADBSession> {
+        let mut transport = self.transport.clone();
+
+        if let Some(remote_id) = remote_id {
+            transport.write_message(ADBTransportMessage::new(
+                MessageCommand::Okay,
+                local_id,
+                remote_id,
+                &[],
+            ))?;
+            return Ok(ADBSession {
+                local_id,
+                remote_id,
+            });
+        }
+
+        let remote = self.direction.remote();
+        transport.write_message(ADBTransportMessage::new(
+            MessageCommand::Open,
+            local_id,
+            0,
+            format!("{remote}\0").as_bytes(),
+        ))?;
+
+        let response = receive(receiver)?;
+        if response.header().command() != MessageCommand::Okay {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "device refused to open {remote}"
+            )));
+        }
+        Ok(ADBSession {
+            local_id,
+            remote_id: response.header().arg0(),
+        })
+    }
+
+    /// Proxy data between `session` and `socket`, until either end closes it
+    fn relay(
+        &self,
+        session: ADBSession,
+        receiver: &Receiver<ADBTransportMessage>,
+        socket: &TcpStream,
+    ) -> Result<()> {
+        // Data read from socket is sent by another thread, each message waiting for the acknowledgment of previous one
+        let (acknowledgments, acknowledged) = mpsc::channel();
+        let reader = socket.try_clone()?;
+        let forwarder = self.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = forwarder.send_socket_data(session, reader, &acknowledged) {
+                log::debug!("cannot send data to {}: {e}", forwarder.direction.remote());
+            }
+        });
+
+        let result = self.relay_session_data(session, receiver, socket, &acknowledgments);
+        if result.is_err() {
+            // Best effort, device may not be reachable anymore
+            let _ = self
+                .transport
+                .clone()
+                .write_message(ADBTransportMessage::new(
+                    MessageCommand::Clse,
+                    session.local_id,
+                    session.remote_id,
+                    &[],
+                ));
+        }
+        result
+    }
+
+    /// Remove reverse forward of `remote` socket from device, once stopped
+    fn remove_reverse(&self, remote: &str) -> Result<()> {
+        let mut transport = self.transport.clone();
+        let local_id: u32 = rand::rng().random();
+        let receiver = self.dispatcher.register_session(local_id)?;
+        let result = self.kill_reverse(&mut transport, local_id, &receiver, remote);
+        self.dispatcher.unregister_session(local_id)?;
+        result
+    }
+
+    /// Run `reverse:killforward` service in session `local_id`, until device closes it
+    fn kill_reverse(
+        &self,
+        transport: &mut T,
+        local_id: u32,
+        receiver: &Receiver<ADBTransportMessage>,
+        remote: &str,
+    ) -> Result<()> {
+        transport.write_message(ADBTransportMessage::new(
+            MessageCommand::Open,
+            local_id,
+            0,
+            format!("reverse:killforward:{remote}\0").as_bytes(),
+        ))?;
+
+        // Wait for device to remove rule, which it reports by closing session
+        let deadline = Instant::now() + FORWARD_REMOVAL_TIMEOUT;
+        loop {
+            if Instant::now() >= deadline {
+                return Err(std::io::Error::from(ErrorKind::TimedOut).into());
+            }
+
+            // Forward is not polling transport anymore
+            let Ok(message) = receiver.try_recv() else {
+                if !self.dispatcher.poll(transport)? {
+                    std::thread::sleep(CANCEL_POLL_INTERVAL);
+                }
+                continue;
+            };
+            match message.header().command() {
+                MessageCommand::Write => transport.write_message(ADBTransportMessage::new(
+                    MessageCommand::Okay,
+                    local_id,
+                    message.header().arg0(),
+                    &[],
+                ))?,
+                MessageCommand::Clse => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Write data received in `session` into `socket`, until device closes session
+    fn relay_session_data(
+        &self,
+        session: ADBSession,
+        receiver: &Receiver<ADBTransportMessage>,
+        mut socket: &TcpStream,
+        acknowledgments: &Sender<()>,
+    ) -> Result<()> {
+        let mut transport = self.transport.clone();
+
+        loop {
+            let message = receive(receiver)?;
+            match message.header().command() {
+                MessageCommand::Write => {
+                    socket.write_all(message.payload())?;
+                    transport.write_message(ADBTransportMessage::new(
+                        MessageCommand::Okay,
+                        session.local_id,
+                        session.remote_id,
+                        &[],
+                    ))?;
+                }
+                MessageCommand::Okay => {
+                    let _ = acknowledgments.send(());
+                }
+                MessageCommand::Clse => return Ok(()),
+                command => log::debug!("ignoring unexpected {command} message"),
+            }
+        }
+    }
+
+    /// Send data read from `socket` into `session`, closing it once socket has been closed on host side
+    fn send_socket_data(
+        &self,
+        session: ADBSession,
+        mut socket: TcpStream,
+        acknowledged: &Receiver<()>,
+    ) -> Result<()> {
+        let mut transport = self.transport.clone();
+        let mut buffer = vec![0; self.maximum_payload_size];
+
+        loop {
+            let size = socket.read(&mut buffer)?;
+            if size == 0 {
+                break;
+            }
+            transport.write_message(ADBTransportMessage::new(
+                MessageCommand::Write,
+                session.local_id,
+                session.remote_id,
+                &buffer[..size],
+            ))?;
+            if acknowledged.recv().is_err() {
+                // Session has been closed meanwhile
+                return Ok(());
+            }
+        }
+
+        // Session is still registered if it has not been closed by device first
+        if self.dispatcher.is_registered(session.local_id)? {
+            transport.write_message(ADBTransportMessage::new(
+                MessageCommand::Clse,
+                session.local_id,
+                session.remote_id,
+                &[],
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+/// Wait for next message of a proxied session
+fn receive(receiver: &Receiver<ADBTransportMessage>) -> Result<ADBTransportMessage> {
+    receiver
+        .recv()
+        .map_err(|_| std::io::Error::new(ErrorKind::BrokenPipe, "forward has been stopped").into())
+}
+
+#[test]
+fn test_port_forward_proxies_connection() {
+    use crate::MockTransport;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"tcp:80\0")
+        .respond(MessageCommand::Okay, &[]);
+    transport
+        .expect(MessageCommand::Write)
+        .with_payload(b"ping")
+        .respond(MessageCommand::Write, b"pong")
+        .respond(MessageCommand::Okay, &[]);
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[]);
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("cannot bind listener");
+    let forward = PortForward::start(transport.clone(), Arc::default(), listener, "tcp:80", 4096)
+        .expect("cannot start forward");
+
+    let mut socket = TcpStream::connect(forward.local_addr()).expect("cannot connect to forward");
+    socket.write_all(b"ping").expect("cannot write to forward");
+    let mut output = Vec::new();
+    socket
+        .read_to_end(&mut output)
+        .expect("cannot read from forward");
+
+    assert_eq!(output, b"pong");
+    assert!(transport.is_exhausted());
+}
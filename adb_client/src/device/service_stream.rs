@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::sync::Arc;
+
+use crate::cancel_token::CancelGuard;
+use crate::{ADBMessageTransport, CancelToken, Result, RustADBError};
+
+use super::adb_message_device::ADBSession;
+use super::message_dispatcher::MessageDispatcher;
+use super::{ADBTransportMessage, MessageCommand};
+
+/// Raw bidirectional stream over an opened ADB session.
+///
+/// [`Read`] yields payloads of `WRTE` messages sent by device, while [`Write`] sends data as `WRTE` messages and waits for their `OKAY`.
+/// Session is closed when stream is dropped.
+#[derive(Debug)]
+pub(crate) struct ServiceStream<T: ADBMessageTransport> {
+    transport: T,
+    dispatcher: Arc<MessageDispatcher>,
+    session: ADBSession,
+    maximum_payload_size: usize,
+    cancel_token: Option<CancelToken>,
+    /// Forwards cancellation of device token to `cancel_token`, if distinct
+    cancel_guard: Option<CancelGuard>,
+    received: VecDeque<u8>,
+    acknowledged: bool,
+    closed: bool,
+}
+
+impl<T: ADBMessageTransport> ServiceStream<T> {
+    pub(crate) fn new(
+        transport: T,
+        dispatcher: Arc<MessageDispatcher>,
+        session: ADBSession,
+        maximum_payload_size: usize,
+        cancel_token: Option<CancelToken>,
+    ) -> Self {
+        Self {
+            transport,
+            dispatcher,
+            session,
+            maximum_payload_size,
+            cancel_token,
+            cancel_guard: None,
+            received: VecDeque::new(),
+            acknowledged: true,
+            closed: false,
+        }
+    }
+
+    /// Keep `cancel_guard` registered as long as this stream is alive
+    pub(crate) fn with_cancel_guard(mut self, cancel_guard: Option<CancelGuard>) -> Self {
+        self.cancel_guard = cancel_guard;
+        self
+    }
+
+    /// Handle next message received for this session
+    fn receive(&mut self) -> Result<()> {
+        let message =
+            self.dispatcher
+                .read_message(&mut self.transport, None, self.cancel_token.as_ref())?;
+        if message.header().arg1() != self.session.local_id {
+            log::debug!(
+                "ignoring {} message received for another session",
+                message.header().command()
+            );
+            return Ok(());
+        }
+
+        match message.header().command() {
+            MessageCommand::Write => {
+                self.transport.write_message(ADBTransportMessage::new(
+                    MessageCommand::Okay,
+                    self.session.local_id,
+                    self.session.remote_id,
+                    &[],
+                ))?;
+                self.received.extend(message.into_payload());
+            }
+            MessageCommand::Okay => self.acknowledged = true,
+            MessageCommand::Clse => self.closed = true,
+            command => log::debug!("ignoring unexpected {command} message"),
+        }
+
+        Ok(())
+    }
+}
+
+fn into_io_error(error: RustADBError) -> Error {
+    match error {
+        RustADBError::IOError(e) => e,
+        e => Error::other(e),
+    }
+}
+
+impl<T: ADBMessageTransport> Read for ServiceStream<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.received.is_empty() {
+            if self.closed {
+                return Ok(0);
+            }
+            self.receive().map_err(into_io_error)?;
+        }
+
+        let size = buf.len().min(self.received.len());
+        for (byte, received) in buf.iter_mut().zip(self.received.drain(..size)) {
+            *byte = received;
+        }
+        Ok(size)
+    }
+}
+
+impl<T: ADBMessageTransport> Write for ServiceStream<T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.closed {
+            return Err(Error::new(ErrorKind::BrokenPipe, "service has been closed"));
+        }
+
+        let size = buf.len().min(self.maximum_payload_size);
+        self.transport
+            .write_message(ADBTransportMessage::new(
+                MessageCommand::Write,
+                self.session.local_id,
+                self.session.remote_id,
+                &buf[..size],
+            ))
+            .map_err(into_io_error)?;
+
+        // Device may send data before acknowledging ours, it is kept for next reads
+        self.acknowledged = false;
+        while !self.acknowledged {
+            if self.closed {
+                return Err(Error::new(ErrorKind::BrokenPipe, "service has been closed"));
+            }
+            self.receive().map_err(into_io_error)?;
+        }
+
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: ADBMessageTransport> Drop for ServiceStream<T> {
+    fn drop(&mut self) {
+        if !self.closed {
+            // Best effort here
+            let _ = self.transport.write_message(ADBTransportMessage::new(
+                MessageCommand::Clse,
+                self.session.local_id,
+                self.session.remote_id,
+                &[],
+            ));
+        }
+    }
+}
+
+#[test]
+fn test_service_stream_read_write() {
+    use crate::{ADBMessageDevice, MockTransport};
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"tcp:5555\0")
+        .respond(MessageCommand::Okay, &[]);
+    transport
+        .expect(MessageCommand::Write)
+        .with_payload(b"ping")
+        .respond(MessageCommand::Write, b"pong")
+        .respond(MessageCommand::Okay, &[]);
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[]);
+
+    let mut device = ADBMessageDevice::new(transport.clone());
+    let mut stream = device
+        .open_service("tcp:5555")
+        .expect("cannot open service");
+    stream.write_all(b"ping").expect("cannot write to service");
+
+    let mut output = Vec::new();
+    stream
+        .read_to_end(&mut output)
+        .expect("cannot read from service");
+
+    assert_eq!(output, b"pong");
+    assert!(transport.is_exhausted());
+}
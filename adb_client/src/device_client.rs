@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+
+use crate::{ADBDeviceExt, AdbStatResponse, RebootType, Result, RustADBError, TransferStats};
+
+type DeviceJob = Box<dyn FnOnce(&mut dyn ADBDeviceExt) + Send>;
+
+/// Cloneable handle to a device owned by a dedicated worker thread.
+///
+/// Commands sent from any handle are queued and run one after another on the underlying device,
+/// which allows to share a single connection between threads (e.g. request handlers of a server).
+/// Worker thread stops once all handles have been dropped.
+#[derive(Debug, Clone)]
+pub struct DeviceClient {
+    jobs: Sender<DeviceJob>,
+}
+
+impl DeviceClient {
+    /// Move `device` to a new worker thread, and return a handle to it.
+    pub fn new<D: ADBDeviceExt + Send + 'static>(mut device: D) -> Self {
+        let (jobs, receiver) = mpsc::channel::<DeviceJob>();
+
+        std::thread::spawn(move || {
+            for job in receiver {
+                job(&mut device);
+            }
+        });
+
+        Self { jobs }
+    }
+
+    /// Run `f` on the worker thread with exclusive access to the device, and return its result.
+    ///
+    /// Fails with [`RustADBError::DeviceClientStopped`] if worker thread is not running anymore.
+    pub fn run<R, F>(&self, f: F) -> Result<R>
+    where
+        R: Send + 'static,
+        F: FnOnce(&mut dyn ADBDeviceExt) -> Result<R> + Send + 'static,
+    {
+        let (result_sender, result_receiver) = mpsc::sync_channel(1);
+
+        self.jobs
+            .send(Box::new(move |device| {
+                // Caller may have gone away, nobody is left to report to
+                let _ = result_sender.send(f(device));
+            }))
+            .map_err(|_| RustADBError::DeviceClientStopped)?;
+
+        result_receiver
+            .recv()
+            .map_err(|_| RustADBError::DeviceClientStopped)?
+    }
+
+    /// Runs command in a shell on the device, and return its output and error streams.
+    pub fn shell_command(&self, command: &[&str]) -> Result<Vec<u8>> {
+        let command: Vec<String> = command.iter().map(|arg| arg.to_string()).collect();
+        self.run(move |device| {
+            let command: Vec<&str> = command.iter().map(String::as_str).collect();
+            let mut output = Vec::new();
+            device.shell_command(&command, &mut output)?;
+            Ok(output)
+        })
+    }
+
+    /// Display the stat information for a remote file
+    pub fn stat(&self, remote_path: &str) -> Result<AdbStatResponse> {
+        let remote_path = remote_path.to_string();
+        self.run(move |device| device.stat(&remote_path))
+    }
+
+    /// Pull the remote file pointed to by `source` and return its contents
+    pub fn pull(&self, source: &str) -> Result<Vec<u8>> {
+        let source = source.to_string();
+        self.run(move |device| {
+            let mut output = Vec::new();
+            device.pull(&source, &mut output)?;
+            Ok(output)
+        })
+    }
+
+    /// Push `data` to `path` on the device.
+    pub fn push(&self, data: Vec<u8>, path: &str) -> Result<TransferStats> {
+        let path = path.to_string();
+        self.run(move |device| device.push(&mut data.as_slice(), &path))
+    }
+
+    /// Reboot the device using given reboot type
+    pub fn reboot(&self, reboot_type: RebootType) -> Result<()> {
+        self.run(move |device| device.reboot(reboot_type))
+    }
+
+    /// Install an APK pointed to by `apk_path` on device.
+    pub fn install<P: AsRef<Path>>(&self, apk_path: P) -> Result<()> {
+        let apk_path: PathBuf = apk_path.as_ref().to_path_buf();
+        self.run(move |device| device.install(&apk_path))
+    }
+
+    /// Uninstall the package `package` from device.
+    pub fn uninstall(&self, package: &str) -> Result<()> {
+        let package = package.to_string();
+        self.run(move |device| device.uninstall(&package))
+    }
+
+    /// Dump framebuffer of this device and return corresponding `PNG` bytes.
+    pub fn framebuffer_bytes(&self) -> Result<Vec<u8>> {
+        self.run(|device| device.framebuffer_bytes())
+    }
+}
+
+#[cfg(any(feature = "tcp", feature = "usb"))]
+#[test]
+fn test_device_client_shared_between_threads() {
+    use crate::{ADBMessageDevice, MessageCommand, MockTransport};
+
+    let transport = MockTransport::new();
+    for _ in 0..2 {
+        transport
+            .expect(MessageCommand::Open)
+            .with_payload(b"shell:id\0")
+            .respond(MessageCommand::Okay, &[])
+            .respond(MessageCommand::Write, b"uid=2000(shell)\n");
+        transport
+            .expect(MessageCommand::Okay)
+            .respond(MessageCommand::Clse, &[]);
+    }
+
+    let client = DeviceClient::new(ADBMessageDevice::new(transport.clone()));
+    let handle = client.clone();
+    let output = std::thread::spawn(move || handle.shell_command(&["id"]))
+        .join()
+        .expect("cannot join client thread")
+        .expect("cannot run shell command");
+
+    assert_eq!(output, b"uid=2000(shell)\n");
+    assert_eq!(
+        client
+            .shell_command(&["id"])
+            .expect("cannot run shell command"),
+        output
+    );
+    assert!(transport.is_exhausted());
+}
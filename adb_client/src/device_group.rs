@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use crate::{ADBDeviceExt, Result, TransferStats};
+
+/// Set of named devices on which commands are run concurrently, e.g. all devices of a test farm.
+///
+/// Each operation runs on all devices at once, one thread per device, and returns one result per device
+/// in the order devices were added. A failing device does not prevent others from completing.
+#[derive(Default)]
+pub struct DeviceGroup {
+    devices: Vec<(String, Box<dyn ADBDeviceExt + Send>)>,
+}
+
+impl std::fmt::Debug for DeviceGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeviceGroup")
+            .field("devices", &self.names())
+            .finish()
+    }
+}
+
+impl DeviceGroup {
+    /// Create an empty group
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `device` to this group, its results being reported under `name` (e.g. its serial)
+    pub fn add<D: ADBDeviceExt + Send + 'static>(&mut self, name: &str, device: D) -> &mut Self {
+        self.devices.push((name.to_string(), Box::new(device)));
+        self
+    }
+
+    /// Names of devices of this group
+    pub fn names(&self) -> Vec<&str> {
+        self.devices.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Number of devices in this group
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    /// Return `true` if this group has no device
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    /// Run `f` on all devices concurrently, and return its result for each device.
+    pub fn run<R, F>(&mut self, f: F) -> Vec<(String, Result<R>)>
+    where
+        R: Send,
+        F: Fn(&mut dyn ADBDeviceExt) -> Result<R> + Sync,
+    {
+        let f = &f;
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .devices
+                .iter_mut()
+                .map(|(name, device)| {
+                    let name = name.clone();
+                    let handle = scope.spawn(move || f(device.as_mut()));
+                    (name, handle)
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|(name, handle)| {
+                    let result = handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                    (name, result)
+                })
+                .collect()
+        })
+    }
+
+    /// Runs command in a shell on all devices, and return output and error streams of each of them.
+    pub fn shell_command(&mut self, command: &[&str]) -> Vec<(String, Result<Vec<u8>>)> {
+        self.run(|device| {
+            let mut output = Vec::new();
+            device.shell_command(command, &mut output)?;
+            Ok(output)
+        })
+    }
+
+    /// Push `data` to `path` on all devices.
+    pub fn push(&mut self, data: &[u8], path: &str) -> Vec<(String, Result<TransferStats>)> {
+        self.run(|device| device.push(&mut &data[..], &path))
+    }
+
+    /// Install an APK pointed to by `apk_path` on all devices.
+    pub fn install<P: AsRef<Path> + Sync>(&mut self, apk_path: P) -> Vec<(String, Result<()>)> {
+        self.run(|device| device.install(&apk_path.as_ref()))
+    }
+}
+
+#[cfg(any(feature = "tcp", feature = "usb"))]
+#[test]
+fn test_device_group_reports_each_device() {
+    use crate::{ADBMessageDevice, MessageCommand, MockTransport};
+
+    let working = MockTransport::new();
+    working
+        .expect(MessageCommand::Open)
+        .with_payload(b"shell:id\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Write, b"uid=2000(shell)\n");
+    working
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[]);
+
+    let refusing = MockTransport::new();
+    refusing
+        .expect(MessageCommand::Open)
+        .with_payload(b"shell:id\0")
+        .respond(MessageCommand::Clse, &[]);
+
+    let mut group = DeviceGroup::new();
+    group
+        .add("working", ADBMessageDevice::new(working.clone()))
+        .add("refusing", ADBMessageDevice::new(refusing.clone()));
+
+    let results = group.shell_command(&["id"]);
+    assert_eq!(group.names(), ["working", "refusing"]);
+    assert_eq!(results[0].0, "working");
+    assert_eq!(
+        results[0].1.as_ref().expect("cannot run shell command"),
+        b"uid=2000(shell)\n"
+    );
+    assert_eq!(results[1].0, "refusing");
+    assert!(results[1].1.is_err());
+    assert!(working.is_exhausted() && refusing.is_exhausted());
+}
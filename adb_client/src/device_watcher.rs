@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "usb")]
+use std::collections::BTreeSet;
+use std::net::SocketAddrV4;
+use std::sync::mpsc;
+#[cfg(feature = "usb")]
+use std::time::Duration;
+
+use crate::{ADBServer, CancelToken, DeviceState, EventStream, Result};
+
+/// Device seen by a [`DeviceWatcher`], whatever its connection path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchedDevice {
+    /// Device serial number, as reported by ADB server or USB descriptors
+    pub serial: String,
+    /// State reported by ADB server, `None` if device is unknown to it
+    pub server_state: Option<DeviceState>,
+    /// Device is directly reachable over USB
+    pub usb: bool,
+}
+
+/// Change in the list of devices, as reported by a [`DeviceWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// Device appeared through any connection path
+    Added(WatchedDevice),
+    /// Device connection paths or state changed
+    Changed(WatchedDevice),
+    /// Device is not reachable anymore, identified by its serial
+    Removed(String),
+}
+
+/// Update received from a watched source, listing all devices it currently sees
+enum SourceUpdate {
+    Server(Vec<(String, DeviceState)>),
+    #[cfg(feature = "usb")]
+    Usb(BTreeSet<String>),
+}
+
+/// Watches devices available through ADB server and directly over USB, merging both into a single list keyed by serial.
+///
+/// A device plugged over USB while ADB server is running is thus reported once, with both connection paths.
+#[derive(Debug, Clone)]
+pub struct DeviceWatcher {
+    server: Option<Option<SocketAddrV4>>,
+    #[cfg(feature = "usb")]
+    usb_poll_interval: Option<Duration>,
+}
+
+impl Default for DeviceWatcher {
+    fn default() -> Self {
+        Self {
+            server: Some(None),
+            #[cfg(feature = "usb")]
+            usb_poll_interval: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+impl DeviceWatcher {
+    /// Instantiate a new [`DeviceWatcher`], watching default ADB server and USB devices
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch ADB server listening on `server_addr`, or none if `None`
+    pub fn with_server(mut self, server_addr: Option<SocketAddrV4>) -> Self {
+        self.server = server_addr.map(Some);
+        self
+    }
+
+    /// Watch devices directly connected over USB, enumerating them every `poll_interval`, or none if `None`
+    #[cfg(feature = "usb")]
+    pub fn with_usb(mut self, poll_interval: Option<Duration>) -> Self {
+        self.usb_poll_interval = poll_interval;
+        self
+    }
+
+    /// Start watching, and return an [`EventStream`] of changes.
+    ///
+    /// All currently available devices are first reported as [`DeviceEvent::Added`].
+    /// Stream fails as soon as a watched source does. Dropping it stops watching sources.
+    pub fn watch(&self) -> Result<EventStream<DeviceEvent>> {
+        let (updates, receiver) = mpsc::channel();
+        let stream_token = CancelToken::new();
+        let mut shutdown_guard = None;
+
+        if let Some(server_addr) = self.server {
+            let mut transport = match server_addr {
+                Some(server_addr) => ADBServer::new(server_addr),
+                None => ADBServer::default(),
+            }
+            .track_devices_connection()?;
+
+            // Shutting tracking connection down unblocks the source waiting for next update
+            let stream = transport.get_raw_connection()?.try_clone()?;
+            shutdown_guard = Some(stream_token.on_cancel(move || {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }));
+
+            let updates = updates.clone();
+            std::thread::spawn(move || {
+                loop {
+                    let update = ADBServer::read_devices_update(&mut transport).map(|devices| {
+                        SourceUpdate::Server(
+                            devices
+                                .into_iter()
+                                .map(|device| (device.identifier, device.state))
+                                .collect(),
+                        )
+                    });
+                    let failed = update.is_err();
+                    // Watcher is gone, or has been told about the error
+                    if updates.send(update).is_err() || failed {
+                        return;
+                    }
+                }
+            });
+        }
+
+        #[cfg(feature = "usb")]
+        if let Some(poll_interval) = self.usb_poll_interval {
+            let updates = updates.clone();
+            let stream_token = stream_token.clone();
+            std::thread::spawn(move || watch_usb(&updates, poll_interval, &stream_token));
+        }
+
+        // Updates are only sent by sources, channel closes once they all stopped
+        drop(updates);
+
+        Ok(EventStream::spawn(move |sender| {
+            let _shutdown_guard = shutdown_guard;
+            let mut devices = DeviceTable::default();
+            for update in receiver {
+                for event in devices.update(update?) {
+                    sender.send(event)?;
+                }
+            }
+            Ok(())
+        })
+        .cancel_on_drop(stream_token))
+    }
+}
+
+#[cfg(feature = "usb")]
+fn watch_usb(
+    updates: &mpsc::Sender<Result<SourceUpdate>>,
+    poll_interval: Duration,
+    cancel_token: &CancelToken,
+) {
+    let mut previous = None;
+    while !cancel_token.is_cancelled() {
+        match crate::transports::list_adb_device_serials() {
+            Ok(serials) => {
+                let serials: BTreeSet<String> = serials.into_iter().collect();
+                // Only report actual changes, watcher would ignore the others anyway
+                if previous.as_ref() != Some(&serials) {
+                    previous = Some(serials.clone());
+                    if updates.send(Ok(SourceUpdate::Usb(serials))).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) => {
+                let _ = updates.send(Err(e));
+                return;
+            }
+        }
+        std::thread::sleep(poll_interval);
+    }
+}
+
+/// Merged view of devices seen by all sources
+#[derive(Debug, Default)]
+struct DeviceTable {
+    devices: BTreeMap<String, WatchedDevice>,
+}
+
+impl DeviceTable {
+    /// Apply `update`, and return resulting changes
+    fn update(&mut self, update: SourceUpdate) -> Vec<DeviceEvent> {
+        let mut next = self.devices.clone();
+        match update {
+            SourceUpdate::Server(devices) => {
+                next.values_mut()
+                    .for_each(|device| device.server_state = None);
+                for (serial, state) in devices {
+                    next.entry(serial.clone())
+                        .or_insert_with(|| WatchedDevice {
+                            serial,
+                            server_state: None,
+                            usb: false,
+                        })
+                        .server_state = Some(state);
+                }
+            }
+            #[cfg(feature = "usb")]
+            SourceUpdate::Usb(serials) => {
+                next.values_mut().for_each(|device| device.usb = false);
+                for serial in serials {
+                    next.entry(serial.clone())
+                        .or_insert_with(|| WatchedDevice {
+                            serial,
+                            server_state: None,
+                            usb: false,
+                        })
+                        .usb = true;
+                }
+            }
+        }
+        next.retain(|_, device| device.server_state.is_some() || device.usb);
+
+        let mut events = Vec::new();
+        for serial in self.devices.keys() {
+            if !next.contains_key(serial) {
+                events.push(DeviceEvent::Removed(serial.clone()));
+            }
+        }
+        for (serial, device) in &next {
+            match self.devices.get(serial) {
+                None => events.push(DeviceEvent::Added(device.clone())),
+                Some(previous) if previous != device => {
+                    events.push(DeviceEvent::Changed(device.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+
+        self.devices = next;
+        events
+    }
+}
+
+#[cfg(feature = "usb")]
+#[test]
+fn test_device_table_merges_sources() {
+    let mut table = DeviceTable::default();
+    let mut device = WatchedDevice {
+        serial: "abc".to_string(),
+        server_state: None,
+        usb: true,
+    };
+
+    let events = table.update(SourceUpdate::Usb(BTreeSet::from(["abc".to_string()])));
+    assert_eq!(events, vec![DeviceEvent::Added(device.clone())]);
+
+    let server_update = || SourceUpdate::Server(vec![("abc".to_string(), DeviceState::Device)]);
+    device.server_state = Some(DeviceState::Device);
+    assert_eq!(
+        table.update(server_update()),
+        vec![DeviceEvent::Changed(device)]
+    );
+    // Unchanged devices are not reported again
+    assert!(table.update(server_update()).is_empty());
+
+    table.update(SourceUpdate::Usb(BTreeSet::new()));
+    let events = table.update(SourceUpdate::Server(Vec::new()));
+    assert_eq!(events, vec![DeviceEvent::Removed("abc".to_string())]);
+}
@@ -68,7 +68,11 @@ impl TryFrom<ADBServerDevice> for ADBEmulatorDevice {
         match &value.identifier {
             Some(device_identifier) => ADBEmulatorDevice::new(
                 device_identifier.clone(),
-                Some(*value.transport.get_socketaddr().ip()),
+                value
+                    .transport
+                    .get_address()
+                    .socket_addr()
+                    .map(|addr| *addr.ip()),
             ),
             None => Err(RustADBError::DeviceNotFound(
                 "cannot connect to an emulator device without knowing its identifier".to_string(),
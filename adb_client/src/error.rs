@@ -115,18 +115,96 @@ pub enum RustADBError {
     /// Cannot upgrade connection from TCP to TLS
     #[error("upgrade error: {0}")]
     UpgradeError(String),
+    /// Wireless pairing with a device failed
+    #[error("pairing error: {0}")]
+    PairingError(String),
     /// An error occurred while getting mdns devices
     #[error(transparent)]
     MDNSError(#[from] mdns_sd::Error),
-    /// An error occurred while sending data to channel
-    #[error(transparent)]
-    SendError(#[from] std::sync::mpsc::SendError<crate::MDNSDevice>),
     /// An unknown transport has been provided
     #[error("unknown transport: {0}")]
     UnknownTransport(String),
     /// An error occurred while trying to convert integer sizes to one another
     #[error(transparent)]
     IntConvError(#[from] std::num::TryFromIntError),
+    /// A message written to a [`crate::MockTransport`] does not match its script
+    #[error("unexpected message on mock transport: {0}")]
+    UnexpectedMockMessage(String),
+    /// A message written to a [`crate::ReplayTransport`] does not match recorded session
+    #[error("replayed session mismatch: {0}")]
+    ReplayMismatch(String),
+    /// Consumer of an [`crate::EventStream`] has gone away
+    #[error("event stream has been closed")]
+    EventStreamClosed,
+    /// Operation has been cancelled using a [`crate::CancelToken`]
+    #[error("operation has been cancelled")]
+    Cancelled,
+    /// Message received from device or server is malformed
+    #[error("malformed message received: {0}")]
+    InvalidMessage(String),
+    /// Worker thread of a [`crate::DeviceClient`] is not running anymore
+    #[error("device client worker has stopped")]
+    DeviceClientStopped,
+    /// Package name contains characters not allowed by Android
+    #[error("invalid package name: {0}")]
+    InvalidPackageName(String),
+    /// Exit code of a command has not been reported by device
+    #[error("exit code not reported by device")]
+    ExitCodeUnavailable,
+    /// Known devices registry cannot be parsed or serialized
+    #[error("invalid known devices registry: {0}")]
+    InvalidRegistry(String),
+    /// App bundle (`.apks`, `.xapk`) cannot be read
+    #[error("invalid app bundle: {0}")]
+    InvalidBundle(String),
+    /// Device refused operation to shell user
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    /// No node of UI hierarchy matches selector
+    #[error("no UI node matches {0}")]
+    UiNodeNotFound(String),
+    /// No activity on device handles intent
+    #[error("no activity handles intent: {0}")]
+    UnresolvedIntent(String),
+    /// Device in fastboot mode replied to a command with `FAIL`
+    #[error("fastboot command failed: {0}")]
+    FastbootFailure(String),
+    /// ADB server socket specification cannot be parsed
+    #[error("invalid adb server address: {0}")]
+    InvalidServerAddress(String),
+    /// Proxy cannot be parsed, or failed to open a tunnel
+    #[error("proxy error: {0}")]
+    ProxyError(String),
+    /// SSH library error
+    #[cfg(feature = "ssh")]
+    #[error(transparent)]
+    SshError(#[from] ssh2::Error),
+    /// SSH tunnel cannot be established
+    #[error("SSH tunnel error: {0}")]
+    SshTunnelError(String),
+}
+
+#[cfg(any(feature = "tcp", feature = "usb"))]
+impl RustADBError {
+    /// Return `true` if this error comes from an I/O operation that timed out
+    pub(crate) fn is_timeout(&self) -> bool {
+        matches!(self, RustADBError::IOError(e) if matches!(e.kind(), std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock))
+    }
+
+    /// Return `true` if this error means that connection with device has been lost
+    #[cfg(feature = "tcp")]
+    pub(crate) fn is_connection_lost(&self) -> bool {
+        use std::io::ErrorKind;
+
+        matches!(self, RustADBError::IOError(e) if matches!(
+            e.kind(),
+            ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::BrokenPipe
+                | ErrorKind::UnexpectedEof
+                | ErrorKind::NotConnected
+        ))
+    }
 }
 
 impl<T> From<std::sync::PoisonError<T>> for RustADBError {
@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use crate::{CancelToken, Result, RustADBError};
+
+#[derive(Debug)]
+struct EventQueue<T> {
+    events: VecDeque<Result<T>>,
+    /// Producer has finished, no more events will be pushed
+    closed: bool,
+    /// Consumer has been dropped, events are not wanted anymore
+    abandoned: bool,
+    #[cfg(feature = "async")]
+    waker: Option<std::task::Waker>,
+}
+
+#[derive(Debug)]
+struct EventChannel<T> {
+    queue: Mutex<EventQueue<T>>,
+    available: Condvar,
+}
+
+impl<T> EventChannel<T> {
+    fn lock(&self) -> MutexGuard<'_, EventQueue<T>> {
+        // Queue is always left consistent, even if a panic occurred while it was locked
+        self.queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn push(&self, event: Result<T>) -> bool {
+        let mut queue = self.lock();
+        if queue.abandoned {
+            return false;
+        }
+        queue.events.push_back(event);
+        Self::notify(queue);
+        self.available.notify_one();
+        true
+    }
+
+    fn close(&self) {
+        let mut queue = self.lock();
+        queue.closed = true;
+        Self::notify(queue);
+        self.available.notify_one();
+    }
+
+    #[cfg(feature = "async")]
+    fn notify(mut queue: MutexGuard<'_, EventQueue<T>>) {
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn notify(_queue: MutexGuard<'_, EventQueue<T>>) {}
+}
+
+/// Stream of events produced by a long-running command (device tracking, logs...).
+///
+/// Events are produced in a background thread, and can be consumed either as a blocking [`Iterator`],
+/// or, with the `async` feature, as a [`futures_lite::Stream`].
+/// Iteration ends when the underlying command ends, an error being yielded as last item if it failed.
+///
+/// Dropping this stream shuts down the connection events are read from, which stops the background producer.
+#[derive(Debug)]
+pub struct EventStream<T> {
+    channel: Arc<EventChannel<T>>,
+    /// Cancelled on drop, aborting reads of producer
+    cancel_token: Option<CancelToken>,
+}
+
+/// Producing half of an [`EventStream`].
+#[derive(Debug)]
+pub(crate) struct EventSender<T> {
+    channel: Arc<EventChannel<T>>,
+}
+
+impl<T: Send + 'static> EventStream<T> {
+    /// Run `producer` in a background thread, streaming events it sends.
+    pub(crate) fn spawn<F>(producer: F) -> Self
+    where
+        F: FnOnce(&EventSender<T>) -> Result<()> + Send + 'static,
+    {
+        let channel = Arc::new(EventChannel {
+            queue: Mutex::new(EventQueue {
+                events: VecDeque::new(),
+                closed: false,
+                abandoned: false,
+                #[cfg(feature = "async")]
+                waker: None,
+            }),
+            available: Condvar::new(),
+        });
+
+        let sender = EventSender {
+            channel: channel.clone(),
+        };
+        std::thread::spawn(move || {
+            match producer(&sender) {
+                // Consumer going away is the normal way to stop an infinite producer
+                Ok(()) | Err(RustADBError::EventStreamClosed) => {}
+                Err(e) => {
+                    sender.channel.push(Err(e));
+                }
+            }
+            sender.channel.close();
+        });
+
+        Self {
+            channel,
+            cancel_token: None,
+        }
+    }
+
+    /// Cancel `cancel_token` once this stream is dropped, producer reads being aborted by it.
+    pub(crate) fn cancel_on_drop(mut self, cancel_token: CancelToken) -> Self {
+        self.cancel_token = Some(cancel_token);
+        self
+    }
+}
+
+impl<T> EventSender<T> {
+    /// Send an event to the consumer.
+    ///
+    /// Fails with [`RustADBError::EventStreamClosed`] if the consumer has dropped its [`EventStream`].
+    pub(crate) fn send(&self, event: T) -> Result<()> {
+        if self.channel.push(Ok(event)) {
+            Ok(())
+        } else {
+            Err(RustADBError::EventStreamClosed)
+        }
+    }
+}
+
+impl<T> Iterator for EventStream<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut queue = self.channel.lock();
+        loop {
+            if let Some(event) = queue.events.pop_front() {
+                return Some(event);
+            }
+            if queue.closed {
+                return None;
+            }
+            queue = self
+                .channel
+                .available
+                .wait(queue)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> futures_lite::Stream for EventStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let mut queue = self.channel.lock();
+        if let Some(event) = queue.events.pop_front() {
+            return std::task::Poll::Ready(Some(event));
+        }
+        if queue.closed {
+            return std::task::Poll::Ready(None);
+        }
+        queue.waker = Some(cx.waker().clone());
+        std::task::Poll::Pending
+    }
+}
+
+impl<T> Drop for EventStream<T> {
+    fn drop(&mut self) {
+        let mut queue = self.channel.lock();
+        queue.abandoned = true;
+        queue.events.clear();
+        drop(queue);
+
+        if let Some(cancel_token) = &self.cancel_token {
+            cancel_token.cancel();
+        }
+    }
+}
+
+#[test]
+fn test_event_stream_iterator() {
+    let stream = EventStream::spawn(|sender| {
+        for i in 0..3 {
+            sender.send(i)?;
+        }
+        Err(RustADBError::ConversionError)
+    });
+
+    let events: Vec<Result<u32>> = stream.collect();
+    assert_eq!(events.len(), 4);
+    assert!(matches!(events[..3], [Ok(0), Ok(1), Ok(2)]));
+    assert!(matches!(events[3], Err(RustADBError::ConversionError)));
+}
+
+#[test]
+fn test_event_stream_cancel_on_drop() {
+    let cancel_token = CancelToken::new();
+    let (started, wait_started) = std::sync::mpsc::channel();
+    let producer_token = cancel_token.clone();
+    let stream = EventStream::spawn(move |sender: &EventSender<()>| {
+        sender.send(())?;
+        let _ = started.send(());
+        // Stands for a read blocked until its connection is shut down
+        while !producer_token.is_cancelled() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        Err(RustADBError::Cancelled)
+    })
+    .cancel_on_drop(cancel_token.clone());
+
+    wait_started.recv().expect("producer did not start");
+    drop(stream);
+    assert!(cancel_token.is_cancelled());
+}
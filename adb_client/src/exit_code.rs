@@ -0,0 +1,84 @@
+use std::io::Write;
+
+use crate::{Result, RustADBError};
+
+/// Marker printed with exit code of command by [`with_exit_code`].
+const EXIT_CODE_MARKER: &[u8] = b"__EXIT:";
+/// Bytes held back until output ends, enough for marker, a 3 digits exit code and a `\r\n` line ending.
+const TAIL_SIZE: usize = EXIT_CODE_MARKER.len() + 5;
+
+/// Wrap `command` so that remote shell prints its exit code once it ended, for devices not supporting `shell_v2`.
+pub(crate) fn with_exit_code(command: &[&str]) -> String {
+    format!("{}; echo __EXIT:$?", command.join(" "))
+}
+
+/// [`Write`] implementation forwarding output of a command wrapped by [`with_exit_code`], without the exit code marker.
+pub(crate) struct ExitCodeFilter<W: Write> {
+    inner: W,
+    /// Last bytes received, which may belong to marker
+    tail: Vec<u8>,
+}
+
+impl<W: Write> ExitCodeFilter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            tail: Vec::with_capacity(2 * TAIL_SIZE),
+        }
+    }
+
+    /// Parse exit code from end of output, and forward remaining bytes before it.
+    pub(crate) fn finish(mut self) -> Result<u8> {
+        let marker_position = self
+            .tail
+            .windows(EXIT_CODE_MARKER.len())
+            .rposition(|window| window == EXIT_CODE_MARKER)
+            .ok_or(RustADBError::ExitCodeUnavailable)?;
+
+        let exit_code = std::str::from_utf8(&self.tail[marker_position + EXIT_CODE_MARKER.len()..])
+            .ok()
+            .and_then(|exit_code| exit_code.trim_end().parse().ok())
+            .ok_or(RustADBError::ExitCodeUnavailable)?;
+
+        self.inner.write_all(&self.tail[..marker_position])?;
+        self.inner.flush()?;
+        Ok(exit_code)
+    }
+}
+
+impl<W: Write> Write for ExitCodeFilter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.tail.extend_from_slice(buf);
+        if self.tail.len() > TAIL_SIZE {
+            let forwarded = self.tail.len() - TAIL_SIZE;
+            self.inner.write_all(&self.tail[..forwarded])?;
+            self.tail.drain(..forwarded);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+fn test_exit_code_filter() {
+    let mut output = Vec::new();
+    let mut filter = ExitCodeFilter::new(&mut output);
+    for chunk in [&b"line 1\nline 2"[..], b"\n__EX", b"IT:12", b"7\r\n"] {
+        filter.write_all(chunk).expect("cannot write to filter");
+    }
+    assert_eq!(filter.finish().expect("cannot parse exit code"), 127);
+    assert_eq!(output, b"line 1\nline 2\n");
+
+    let mut filter = ExitCodeFilter::new(Vec::new());
+    filter
+        .write_all(b"no marker in output\n")
+        .expect("cannot write to filter");
+    assert!(matches!(
+        filter.finish(),
+        Err(RustADBError::ExitCodeUnavailable)
+    ));
+}
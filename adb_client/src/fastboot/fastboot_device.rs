@@ -0,0 +1,252 @@
+use std::io::Read;
+use std::sync::Arc;
+
+use crate::observer::ObserverSlot;
+use crate::{AdbObserver, Result, RustADBError};
+
+use super::FastbootTransport;
+
+/// Size of packets data is sent in when downloading it to device
+const DOWNLOAD_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Packet sent by device in response to a command
+#[derive(Debug, PartialEq)]
+enum FastbootResponse {
+    /// Command succeeded, with an optional value (e.g. variable requested by `getvar`)
+    Okay(String),
+    /// Command failed, with a reason
+    Fail(String),
+    /// Device is ready to receive given number of bytes
+    Data(u32),
+    /// Informative message, command is still running
+    Info(String),
+    /// Text to display as is, command is still running
+    Text(String),
+}
+
+impl FastbootResponse {
+    fn parse(packet: &[u8]) -> Result<Self> {
+        let (status, payload) = packet.split_at(packet.len().min(4));
+        let payload = String::from_utf8_lossy(payload).to_string();
+        match status {
+            b"OKAY" => Ok(Self::Okay(payload)),
+            b"FAIL" => Ok(Self::Fail(payload)),
+            b"DATA" => Ok(Self::Data(
+                u32::from_str_radix(&payload, 16).map_err(|_| RustADBError::ConversionError)?,
+            )),
+            b"INFO" => Ok(Self::Info(payload)),
+            b"TEXT" => Ok(Self::Text(payload)),
+            _ => Err(RustADBError::WrongResponseReceived(
+                "OKAY, FAIL, DATA, INFO or TEXT".to_string(),
+                String::from_utf8_lossy(packet).to_string(),
+            )),
+        }
+    }
+}
+
+/// Represent a device in fastboot mode (bootloader or `fastbootd`), reachable over a [`FastbootTransport`].
+///
+/// Devices can be rebooted to fastboot mode using [`crate::ADBDeviceExt::reboot`] with [`crate::RebootType::Bootloader`],
+/// then found again using [`crate::search_fastboot_devices`].
+#[derive(Debug)]
+pub struct FastbootDevice<T: FastbootTransport> {
+    transport: T,
+    observer: ObserverSlot,
+}
+
+#[cfg(feature = "usb")]
+impl FastbootDevice<super::USBFastbootTransport> {
+    /// Connect to USB device in fastboot mode with given vendor and product ids
+    pub fn new_usb(vendor_id: u16, product_id: u16) -> Result<Self> {
+        Self::new(super::USBFastbootTransport::new(vendor_id, product_id)?)
+    }
+
+    /// Connect to the only USB device in fastboot mode
+    pub fn autodetect_usb() -> Result<Self> {
+        match crate::search_fastboot_devices()? {
+            Some((vendor_id, product_id)) => Self::new_usb(vendor_id, product_id),
+            None => Err(RustADBError::DeviceNotFound(
+                "cannot find USB devices in fastboot mode".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "tcp")]
+impl FastbootDevice<super::TcpFastbootTransport> {
+    /// Connect to device in fastboot mode listening on `address`, usually on port 5554
+    pub fn new_tcp(address: std::net::SocketAddr) -> Result<Self> {
+        Self::new(super::TcpFastbootTransport::new(address))
+    }
+}
+
+impl<T: FastbootTransport> FastbootDevice<T> {
+    /// Connect to device over `transport`
+    pub fn new(mut transport: T) -> Result<Self> {
+        transport.connect()?;
+        Ok(Self {
+            transport,
+            observer: ObserverSlot::default(),
+        })
+    }
+
+    /// Attach an [`AdbObserver`] notified of progress of following downloads
+    pub fn set_observer(&mut self, observer: Arc<dyn AdbObserver>) {
+        self.observer.set(observer);
+    }
+
+    /// Get a reference to the underlying transport
+    pub fn get_transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Read responses until command ends, returning value of `OKAY` or size of `DATA` requested
+    fn read_response(&mut self, info: &mut Vec<String>) -> Result<FastbootResponse> {
+        loop {
+            match FastbootResponse::parse(&self.transport.read_packet()?)? {
+                FastbootResponse::Info(message) => {
+                    log::info!("(bootloader) {message}");
+                    info.push(message);
+                }
+                FastbootResponse::Text(text) => {
+                    log::info!("{text}");
+                    info.push(text);
+                }
+                FastbootResponse::Fail(reason) => {
+                    return Err(RustADBError::FastbootFailure(reason));
+                }
+                response => return Ok(response),
+            }
+        }
+    }
+
+    /// Run `command`, returning value of its `OKAY` response along with informative messages sent meanwhile
+    fn command(&mut self, command: &str) -> Result<(String, Vec<String>)> {
+        log::debug!("fastboot command {command}");
+        self.transport.write_packet(command.as_bytes())?;
+
+        let mut info = Vec::new();
+        match self.read_response(&mut info)? {
+            FastbootResponse::Okay(value) => Ok((value, info)),
+            response => Err(RustADBError::WrongResponseReceived(
+                "OKAY".to_string(),
+                format!("{response:?}"),
+            )),
+        }
+    }
+
+    /// Send `size` bytes read from `reader` to device memory, to be used by a following `flash` or `boot` command
+    fn download(&mut self, name: &str, reader: &mut dyn Read, size: u64) -> Result<()> {
+        let size = u32::try_from(size)?;
+        if let Ok(max_download_size) = self.getvar("max-download-size") {
+            let max_download_size = match max_download_size.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => max_download_size.parse(),
+            }
+            .unwrap_or(u64::MAX);
+            if u64::from(size) > max_download_size {
+                return Err(RustADBError::FastbootFailure(format!(
+                    "{name} is {size} bytes, device accepts at most {max_download_size} bytes"
+                )));
+            }
+        }
+
+        self.transport
+            .write_packet(format!("download:{size:08x}").as_bytes())?;
+        let mut info = Vec::new();
+        match self.read_response(&mut info)? {
+            FastbootResponse::Data(requested) if requested == size => {}
+            response => {
+                return Err(RustADBError::WrongResponseReceived(
+                    format!("DATA{size:08x}"),
+                    format!("{response:?}"),
+                ));
+            }
+        }
+
+        let mut buffer = vec![0; DOWNLOAD_CHUNK_SIZE];
+        let mut reader = reader.take(u64::from(size));
+        let mut transferred = 0;
+        while transferred < u64::from(size) {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            self.transport.write_packet(&buffer[..read])?;
+            transferred += read as u64;
+            self.observer
+                .transfer_progress(name, transferred, Some(u64::from(size)));
+        }
+
+        match self.read_response(&mut info)? {
+            FastbootResponse::Okay(_) => Ok(()),
+            response => Err(RustADBError::WrongResponseReceived(
+                "OKAY".to_string(),
+                format!("{response:?}"),
+            )),
+        }
+    }
+
+    /// Read bootloader variable `name` (e.g. `product`, `current-slot`, `max-download-size`)
+    pub fn getvar(&mut self, name: &str) -> Result<String> {
+        self.command(&format!("getvar:{name}"))
+            .map(|(value, _)| value)
+    }
+
+    /// Write `size` bytes read from `reader` to `partition` (e.g. `boot`, `vendor_boot_a`)
+    ///
+    /// Image must fit in device download buffer, as reported by `max-download-size` variable.
+    pub fn flash(&mut self, partition: &str, reader: &mut dyn Read, size: u64) -> Result<()> {
+        self.download(partition, reader, size)?;
+        self.command(&format!("flash:{partition}"))?;
+        Ok(())
+    }
+
+    /// Erase `partition`
+    pub fn erase(&mut self, partition: &str) -> Result<()> {
+        self.command(&format!("erase:{partition}"))?;
+        Ok(())
+    }
+
+    /// Boot `size` bytes boot image read from `reader`, without flashing it
+    pub fn boot(&mut self, reader: &mut dyn Read, size: u64) -> Result<()> {
+        self.download("boot", reader, size)?;
+        self.command("boot")?;
+        Ok(())
+    }
+
+    /// Reboot device to Android
+    pub fn reboot(&mut self) -> Result<()> {
+        self.command("reboot")?;
+        Ok(())
+    }
+
+    /// Reboot device to bootloader
+    pub fn reboot_bootloader(&mut self) -> Result<()> {
+        self.command("reboot-bootloader")?;
+        Ok(())
+    }
+
+    /// Run OEM specific `command` (e.g. `device-info`), returning informative lines it printed
+    pub fn oem(&mut self, command: &str) -> Result<Vec<String>> {
+        self.command(&format!("oem {command}"))
+            .map(|(_, info)| info)
+    }
+}
+
+#[test]
+fn test_parse_response() {
+    assert_eq!(
+        FastbootResponse::parse(b"OKAY0x20000000").unwrap(),
+        FastbootResponse::Okay("0x20000000".to_string())
+    );
+    assert_eq!(
+        FastbootResponse::parse(b"DATA00001000").unwrap(),
+        FastbootResponse::Data(4096)
+    );
+    assert_eq!(
+        FastbootResponse::parse(b"FAILpartition does not exist").unwrap(),
+        FastbootResponse::Fail("partition does not exist".to_string())
+    );
+    assert!(FastbootResponse::parse(b"NOPE").is_err());
+}
@@ -0,0 +1,13 @@
+use crate::Result;
+
+/// Transport carrying fastboot packets, commands and data being sent as packets and responses read back one packet at a time.
+pub trait FastbootTransport {
+    /// Establish connection with device
+    fn connect(&mut self) -> Result<()>;
+
+    /// Send `data` as a single packet
+    fn write_packet(&mut self, data: &[u8]) -> Result<()>;
+
+    /// Read a single packet, waiting long enough for slow commands such as erasing a large partition
+    fn read_packet(&mut self) -> Result<Vec<u8>>;
+}
@@ -0,0 +1,13 @@
+mod fastboot_device;
+mod fastboot_transport;
+#[cfg(feature = "tcp")]
+mod tcp_fastboot_transport;
+#[cfg(feature = "usb")]
+mod usb_fastboot_transport;
+
+pub use fastboot_device::FastbootDevice;
+pub use fastboot_transport::FastbootTransport;
+#[cfg(feature = "tcp")]
+pub use tcp_fastboot_transport::TcpFastbootTransport;
+#[cfg(feature = "usb")]
+pub use usb_fastboot_transport::USBFastbootTransport;
@@ -0,0 +1,76 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::constants::FASTBOOT_RESPONSE_TIMEOUT;
+use crate::{Result, RustADBError};
+
+use super::FastbootTransport;
+
+/// Handshake exchanged once connected, announcing protocol version 1
+const HANDSHAKE: &[u8; 4] = b"FB01";
+
+/// Fastboot transport running on TCP, as provided by `fastbootd` and some bootloaders.
+///
+/// Each packet is prefixed by its length, as a big endian 64 bits integer.
+#[derive(Debug)]
+pub struct TcpFastbootTransport {
+    address: SocketAddr,
+    stream: Option<TcpStream>,
+}
+
+impl TcpFastbootTransport {
+    /// Instantiate a new [`TcpFastbootTransport`], fastboot usually listening on port 5554
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            stream: None,
+        }
+    }
+
+    fn get_stream(&mut self) -> Result<&mut TcpStream> {
+        self.stream
+            .as_mut()
+            .ok_or(RustADBError::IOError(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "not connected",
+            )))
+    }
+}
+
+impl FastbootTransport for TcpFastbootTransport {
+    fn connect(&mut self) -> Result<()> {
+        let mut stream = TcpStream::connect(self.address)?;
+        stream.set_read_timeout(Some(FASTBOOT_RESPONSE_TIMEOUT))?;
+
+        stream.write_all(HANDSHAKE)?;
+        let mut handshake = [0; 4];
+        stream.read_exact(&mut handshake)?;
+        // Only major version is checked, device may announce a higher one
+        if &handshake[..2] != b"FB" {
+            return Err(RustADBError::WrongResponseReceived(
+                String::from_utf8_lossy(HANDSHAKE).to_string(),
+                String::from_utf8_lossy(&handshake).to_string(),
+            ));
+        }
+
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn write_packet(&mut self, data: &[u8]) -> Result<()> {
+        let stream = self.get_stream()?;
+        stream.write_u64::<BigEndian>(data.len() as u64)?;
+        stream.write_all(data)?;
+        Ok(())
+    }
+
+    fn read_packet(&mut self) -> Result<Vec<u8>> {
+        let stream = self.get_stream()?;
+        let length = stream.read_u64::<BigEndian>()?;
+        let mut packet = vec![0; usize::try_from(length)?];
+        stream.read_exact(&mut packet)?;
+        Ok(packet)
+    }
+}
@@ -0,0 +1,55 @@
+use crate::constants::{FASTBOOT_INTERFACE_PROTOCOL, FASTBOOT_RESPONSE_TIMEOUT};
+use crate::{ADBTransport, Result, USBTransport};
+
+use super::FastbootTransport;
+
+/// Maximum size of a response packet
+const MAX_RESPONSE_SIZE: usize = 256;
+
+/// Fastboot transport running on USB, using fastboot interface of device.
+#[derive(Debug)]
+pub struct USBFastbootTransport {
+    inner: USBTransport,
+}
+
+impl USBFastbootTransport {
+    /// Instantiate a new [`USBFastbootTransport`].
+    /// Only the first device with given vendor_id and product_id is returned.
+    pub fn new(vendor_id: u16, product_id: u16) -> Result<Self> {
+        Ok(Self::new_from_transport(USBTransport::new(
+            vendor_id, product_id,
+        )?))
+    }
+
+    /// Instantiate a new [`USBFastbootTransport`] from a [`USBTransport`] not connected yet.
+    pub fn new_from_transport(transport: USBTransport) -> Self {
+        Self {
+            inner: transport.with_interface_protocol(FASTBOOT_INTERFACE_PROTOCOL),
+        }
+    }
+}
+
+impl FastbootTransport for USBFastbootTransport {
+    fn connect(&mut self) -> Result<()> {
+        self.inner.connect()
+    }
+
+    fn write_packet(&mut self, data: &[u8]) -> Result<()> {
+        let mut total_written = 0;
+        while total_written < data.len() {
+            total_written += self
+                .inner
+                .bulk_write(&data[total_written..], FASTBOOT_RESPONSE_TIMEOUT)?;
+        }
+        Ok(())
+    }
+
+    fn read_packet(&mut self) -> Result<Vec<u8>> {
+        let mut buffer = vec![0; MAX_RESPONSE_SIZE];
+        let read = self
+            .inner
+            .bulk_read(&mut buffer, FASTBOOT_RESPONSE_TIMEOUT)?;
+        buffer.truncate(read);
+        Ok(buffer)
+    }
+}
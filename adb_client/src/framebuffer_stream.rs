@@ -0,0 +1,164 @@
+use std::fmt::Debug;
+use std::io::Write;
+use std::time::{Duration, Instant, SystemTime};
+
+use image::{ImageBuffer, Rgba};
+
+use crate::adb_device_ext::ReadWriteStream;
+use crate::screenshot::{decode_png_into, read_png};
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Command run by the service kept open by a [`FramebufferStream`], capturing a frame for each line it reads
+const SCREENCAP_LOOP: &str = "exec:sh -c 'while read -r _; do screencap -p; done'";
+
+/// Frame captured by a [`FramebufferStream`].
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Time at which frame has been received
+    pub captured_at: SystemTime,
+    /// Content of framebuffer
+    pub image: ImageBuffer<Rgba<u8>, Vec<u8>>,
+}
+
+/// Iterator capturing framebuffer of a device at a fixed interval, e.g. for a low frame rate live view.
+///
+/// Iteration never ends by itself, failed captures being yielded as errors. Frames given back using
+/// [`FramebufferStream::recycle`] have their buffer reused for next captures, which avoids allocating a buffer per frame.
+///
+/// `framebuffer:` service closes its stream after sending a single frame, hence frames are captured by `screencap`
+/// instead, run in a loop by a single service kept open. It is opened again by next capture if one fails.
+pub struct FramebufferStream<'a, D: ADBDeviceExt + ?Sized> {
+    device: &'a mut D,
+    interval: Duration,
+    next_capture: Option<Instant>,
+    spare_buffer: Vec<u8>,
+    /// Service capturing frames, once opened
+    service: Option<Box<dyn ReadWriteStream>>,
+    /// PNG encoded by `screencap`, kept to reuse its allocation
+    png: Vec<u8>,
+}
+
+impl<'a, D: ADBDeviceExt + ?Sized> FramebufferStream<'a, D> {
+    /// Capture framebuffer of `device` every `interval`, first frame being captured immediately.
+    ///
+    /// If a capture takes longer than `interval`, next one starts right after it.
+    pub fn new(device: &'a mut D, interval: Duration) -> Self {
+        Self {
+            device,
+            interval,
+            next_capture: None,
+            spare_buffer: Vec::new(),
+            service: None,
+            png: Vec::new(),
+        }
+    }
+
+    /// Give `frame` back once not needed anymore, its buffer being reused by next capture.
+    pub fn recycle(&mut self, frame: Frame) {
+        self.spare_buffer = frame.image.into_raw();
+    }
+
+    /// Capture next frame over service, opening it first if needed
+    fn capture(&mut self) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        let service = match &mut self.service {
+            Some(service) => service,
+            None => self
+                .service
+                .insert(self.device.open_service(SCREENCAP_LOOP)?),
+        };
+
+        service.write_all(b"\n")?;
+        read_png(service, &mut self.png)?;
+        if self.png.is_empty() {
+            return Err(RustADBError::ADBRequestFailed(
+                "screencap service has been closed".to_string(),
+            ));
+        }
+
+        decode_png_into(&self.png, std::mem::take(&mut self.spare_buffer))
+    }
+}
+
+impl<D: ADBDeviceExt + ?Sized> Iterator for FramebufferStream<'_, D> {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(next_capture) = self.next_capture {
+            std::thread::sleep(next_capture.saturating_duration_since(Instant::now()));
+        }
+        let started = Instant::now();
+        self.next_capture = Some(started + self.interval);
+
+        let result = self.capture();
+        if result.is_err() {
+            // Output of service cannot be relied on anymore
+            self.service = None;
+        }
+        Some(result.map(|image| Frame {
+            captured_at: SystemTime::now(),
+            image,
+        }))
+    }
+}
+
+impl<D: ADBDeviceExt + ?Sized> Debug for FramebufferStream<'_, D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FramebufferStream")
+            .field("interval", &self.interval)
+            .field("next_capture", &self.next_capture)
+            .field("opened", &self.service.is_some())
+            .finish()
+    }
+}
+
+#[cfg(any(feature = "tcp", feature = "usb"))]
+#[test]
+fn test_framebuffer_stream_keeps_service_open() {
+    use std::io::Cursor;
+
+    use image::ImageFormat;
+
+    use crate::{ADBMessageDevice, MessageCommand, MockTransport};
+
+    let image = ImageBuffer::from_pixel(2, 2, Rgba([1, 2, 3, 255]));
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .expect("cannot encode frame");
+    let png = png.into_inner();
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(format!("{SCREENCAP_LOOP}\0").as_bytes())
+        .respond(MessageCommand::Okay, &[]);
+    for _ in 0..3 {
+        transport
+            .expect(MessageCommand::Write)
+            .with_payload(b"\n")
+            .respond(MessageCommand::Okay, &[])
+            .respond(MessageCommand::Write, &png);
+        transport.expect(MessageCommand::Okay);
+    }
+    transport.expect(MessageCommand::Clse);
+
+    let mut device = ADBMessageDevice::new(transport.clone());
+    let mut stream = device.framebuffer_stream(Duration::ZERO);
+    for _ in 0..3 {
+        let frame = stream
+            .next()
+            .expect("stream ended")
+            .expect("cannot capture frame");
+        assert_eq!(frame.image, image);
+        stream.recycle(frame);
+    }
+    drop(stream);
+
+    let opened = transport
+        .written()
+        .iter()
+        .filter(|message| message.header().command() == MessageCommand::Open)
+        .count();
+    assert_eq!(opened, 1);
+    assert!(transport.is_exhausted());
+}
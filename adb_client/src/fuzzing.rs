@@ -0,0 +1,43 @@
+//! Entry points of fuzz targets, feeding untrusted input to protocol parsers.
+//!
+//! Not part of the public API, only available with the `fuzzing` feature.
+
+use std::io::Read;
+
+use crate::device::ADBTransportMessageHeader;
+use crate::server_device::ADBRecvCommandReader;
+use crate::transports::read_adb_response;
+use crate::{AdbStatResponse, AdbStatV2Response, DeviceLong, DeviceShort};
+
+/// Parse a message header received from a device
+pub fn message_header(data: &[u8]) {
+    if let Ok(header) = <[u8; 24]>::try_from(data) {
+        let _ = ADBTransportMessageHeader::try_from(header);
+    }
+}
+
+/// Parse a sync `STAT`, `STA2` or `LST2` response
+pub fn sync_stat(data: &[u8]) {
+    let _ = AdbStatResponse::try_from(data);
+    let _ = AdbStatV2Response::try_from(data);
+}
+
+/// Read a file content sent as sync `DATA` chunks
+pub fn sync_recv(data: &[u8]) {
+    let mut reader = ADBRecvCommandReader::new(data);
+    let mut buffer = [0; 64];
+    while let Ok(1..) = reader.read(&mut buffer) {}
+}
+
+/// Parse a smart-socket response sent by ADB server
+pub fn smart_socket_response(data: &[u8]) {
+    let _ = read_adb_response(data);
+}
+
+/// Parse device lists sent by ADB server
+pub fn devices(data: &[u8]) {
+    for line in data.split(|byte| *byte == b'\n') {
+        let _ = DeviceShort::try_from(line.to_vec());
+        let _ = DeviceLong::try_from(line.to_vec());
+    }
+}
@@ -0,0 +1,170 @@
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+
+use crate::event_stream::EventSender;
+use crate::{ReadWriteStream, Result};
+
+/// Service printing input events with their timestamp, after the list of input devices
+pub(crate) const GETEVENT_SERVICE: &str = "shell:getevent -t";
+
+/// Input event, as reported by [`crate::ADBDeviceExt::getevent`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputEvent {
+    /// Input device has been plugged (e.g. a USB keyboard), devices already present being reported first
+    DeviceAdded {
+        /// Device node (e.g. `/dev/input/event2`)
+        path: String,
+        /// Device name (e.g. `gpio-keys`)
+        name: String,
+    },
+    /// Input device has been unplugged
+    DeviceRemoved {
+        /// Device node (e.g. `/dev/input/event2`)
+        path: String,
+    },
+    /// Raw event sent by an input device
+    Event {
+        /// Node of device which sent event
+        path: String,
+        /// Time of event, since device boot
+        timestamp: Duration,
+        /// Event type (e.g. `1` for `EV_KEY`)
+        event_type: u16,
+        /// Event code (e.g. `116` for `KEY_POWER`)
+        code: u16,
+        /// Event value (e.g. `1` for a key press)
+        value: i32,
+    },
+}
+
+/// Incremental parser of `getevent -t` output, fed line by line
+#[derive(Debug, Default)]
+struct GeteventParser {
+    /// Device being added, reported once its name is read on next line
+    added: Option<String>,
+}
+
+impl GeteventParser {
+    fn feed(&mut self, line: &str) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+
+        if let Some(name) = line.trim_start().strip_prefix("name:") {
+            if let Some(path) = self.added.take() {
+                events.push(InputEvent::DeviceAdded {
+                    path,
+                    name: name.trim().trim_matches('"').to_string(),
+                });
+            }
+            return events;
+        }
+        // Name line is missing, do not lose device
+        if let Some(path) = self.added.take() {
+            events.push(InputEvent::DeviceAdded {
+                path,
+                name: String::new(),
+            });
+        }
+
+        if let Some((_, path)) = line
+            .strip_prefix("add device ")
+            .and_then(|device| device.split_once(": "))
+        {
+            self.added = Some(path.trim().to_string());
+        } else if let Some((_, path)) = line
+            .strip_prefix("remove device ")
+            .and_then(|device| device.split_once(": "))
+        {
+            events.push(InputEvent::DeviceRemoved {
+                path: path.trim().to_string(),
+            });
+        } else if let Some(event) = parse_event(line) {
+            events.push(event);
+        }
+
+        events
+    }
+}
+
+/// Parse an event line, e.g. `[   1234.567890] /dev/input/event0: 0001 0074 00000001`
+fn parse_event(line: &str) -> Option<InputEvent> {
+    let (timestamp, event) = line.strip_prefix('[')?.split_once(']')?;
+    let (seconds, micros) = timestamp.trim().split_once('.')?;
+    let timestamp =
+        Duration::from_secs(seconds.parse().ok()?) + Duration::from_micros(micros.parse().ok()?);
+
+    let (path, fields) = event.trim().split_once(": ")?;
+    let mut fields = fields.split_whitespace();
+    let event_type = u16::from_str_radix(fields.next()?, 16).ok()?;
+    let code = u16::from_str_radix(fields.next()?, 16).ok()?;
+    // Value is signed, but printed as 8 hexadecimal digits
+    let value = u32::from_str_radix(fields.next()?, 16).ok()? as i32;
+
+    Some(InputEvent::Event {
+        path: path.to_string(),
+        timestamp,
+        event_type,
+        code,
+        value,
+    })
+}
+
+/// Read events from `getevent` running in `stream`, sending them to `sender`.
+pub(crate) fn read_input_events(
+    stream: Box<dyn ReadWriteStream>,
+    sender: &EventSender<InputEvent>,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut parser = GeteventParser::default();
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        let line = String::from_utf8_lossy(&line);
+
+        for event in parser.feed(line.trim_end_matches(['\r', '\n'])) {
+            sender.send(event)?;
+        }
+    }
+}
+
+#[test]
+fn test_getevent_parser() {
+    let output = "add device 1: /dev/input/event1\n  \
+        name:     \"gpio-keys\"\n\
+        [    1234.000500] /dev/input/event1: 0001 0074 00000001\n\
+        [    1234.100000] /dev/input/event1: 0003 0035 ffffffff\n\
+        could not get driver version for /dev/input/mouse0, Not a typewriter\n\
+        remove device 1: /dev/input/event1\n";
+
+    let mut parser = GeteventParser::default();
+    let events: Vec<InputEvent> = output.lines().flat_map(|line| parser.feed(line)).collect();
+    assert_eq!(
+        events,
+        [
+            InputEvent::DeviceAdded {
+                path: "/dev/input/event1".to_string(),
+                name: "gpio-keys".to_string(),
+            },
+            InputEvent::Event {
+                path: "/dev/input/event1".to_string(),
+                timestamp: Duration::from_micros(1_234_000_500),
+                event_type: 1,
+                code: 116,
+                value: 1,
+            },
+            InputEvent::Event {
+                path: "/dev/input/event1".to_string(),
+                timestamp: Duration::from_micros(1_234_100_000),
+                event_type: 3,
+                code: 53,
+                value: -1,
+            },
+            InputEvent::DeviceRemoved {
+                path: "/dev/input/event1".to_string(),
+            },
+        ]
+    );
+}
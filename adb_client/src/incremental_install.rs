@@ -0,0 +1,424 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::observer::ObserverSlot;
+use crate::utils::{InstallOutput, check_extension_is_apk, shell_quote};
+use crate::{ADBDeviceExt, InstallOptions, ReadWriteStream, Result, RustADBError};
+
+/// Size of blocks of incremental file system, served one at a time
+const INCREMENTAL_BLOCK_SIZE: u64 = 4096;
+/// Magic prefixing requests of device data loader, telling them apart from package manager output
+const REQUEST_MAGIC: &[u8; 4] = b"INCR";
+/// Size of requests of device data loader, following their magic
+const REQUEST_SIZE: usize = 8;
+/// First SDK version supporting incremental installs (Android 11)
+const INCREMENTAL_MIN_SDK: u32 = 30;
+
+// Requests of device data loader, as defined by `incremental_server.cpp`
+const REQUEST_EXIT: i16 = 0;
+const REQUEST_BLOCK_MISSING: i16 = 1;
+const REQUEST_PREFETCH: i16 = 2;
+const REQUEST_DESTROY: i16 = 3;
+
+// Kinds of served blocks
+const BLOCK_TYPE_DATA: i16 = 0;
+const BLOCK_TYPE_TREE: i16 = 1;
+
+/// Blocks are always served uncompressed
+const COMPRESSION_NONE: i16 = 0;
+
+/// APK Signature Scheme v4 signature, read from the `.idsig` file generated by `apksigner` next to an APK.
+///
+/// File holds signature headers, followed by the Merkle tree of APK whose blocks are served to device along with
+/// APK blocks.
+#[derive(Debug)]
+struct V4Signature {
+    /// Version, hashing and signing info, as given to package manager
+    headers: Vec<u8>,
+    /// Merkle tree of APK
+    tree: Vec<u8>,
+}
+
+impl V4Signature {
+    /// Parse `idsig` content, made of little-endian sizes followed by their data
+    fn parse(idsig: &[u8]) -> Result<Self> {
+        let invalid = || RustADBError::ADBRequestFailed("invalid v4 signature file".to_string());
+        let read_size = |offset: usize| -> Result<usize> {
+            let size = idsig
+                .get(offset..offset + 4)
+                .and_then(|size| size.try_into().ok())
+                .map(i32::from_le_bytes)
+                .ok_or_else(invalid)?;
+            usize::try_from(size).map_err(|_| invalid())
+        };
+
+        // Version, then hashing info and signing info prefixed by their size
+        let mut offset = 4;
+        for _ in 0..2 {
+            offset += 4 + read_size(offset)?;
+        }
+        let tree_size = read_size(offset)?;
+        let tree = idsig
+            .get(offset + 4..offset + 4 + tree_size)
+            .ok_or_else(invalid)?;
+
+        Ok(Self {
+            headers: idsig[..offset].to_vec(),
+            tree: tree.to_vec(),
+        })
+    }
+}
+
+/// Request of device data loader
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    kind: i16,
+    file_id: i16,
+    block_index: i32,
+}
+
+/// Take first request held by `pending` bytes received from device, moving package manager output preceding it into
+/// `output`. Return `None` if no complete request has been received yet.
+fn take_request(pending: &mut Vec<u8>, output: &mut Vec<u8>) -> Option<Request> {
+    let Some(start) = pending
+        .windows(REQUEST_MAGIC.len())
+        .position(|window| window == REQUEST_MAGIC)
+    else {
+        // Keep end of received bytes if it may be the start of a magic
+        let kept = (1..REQUEST_MAGIC.len())
+            .rev()
+            .find(|length| pending.ends_with(&REQUEST_MAGIC[..*length]))
+            .unwrap_or(0);
+        output.extend(pending.drain(..pending.len() - kept));
+        return None;
+    };
+
+    output.extend(pending.drain(..start));
+    let request = pending.get(REQUEST_MAGIC.len()..REQUEST_MAGIC.len() + REQUEST_SIZE)?;
+    let request = Request {
+        kind: i16::from_be_bytes([request[0], request[1]]),
+        file_id: i16::from_be_bytes([request[2], request[3]]),
+        block_index: i32::from_be_bytes([request[4], request[5], request[6], request[7]]),
+    };
+    pending.drain(..REQUEST_MAGIC.len() + REQUEST_SIZE);
+    Some(request)
+}
+
+/// Input received from device while serving blocks
+enum ServerInput {
+    Request(Request),
+    /// Package manager output has been received
+    Output(Vec<u8>),
+    /// Device closed stream
+    Closed,
+}
+
+/// Server of APK blocks requested by device data loader, in place of `adb inc-server`.
+///
+/// Device sends requests prefixed by [`REQUEST_MAGIC`], interleaved with output of package manager. Each served block
+/// is sent as a big-endian chunk: its size, followed by a header (file, compression, block type, index and size)
+/// and block data.
+struct IncrementalServer {
+    stream: Box<dyn ReadWriteStream>,
+    apk: File,
+    size: u64,
+    tree: Vec<u8>,
+    /// Bytes received from device, not parsed yet
+    pending: Vec<u8>,
+    /// First block of APK whose prefetch has been requested, served once installation is complete
+    prefetch: Option<u64>,
+}
+
+impl IncrementalServer {
+    fn read_input(&mut self) -> Result<ServerInput> {
+        let mut output = Vec::new();
+        loop {
+            if let Some(request) = take_request(&mut self.pending, &mut output) {
+                return Ok(ServerInput::Request(request));
+            }
+            if !output.is_empty() {
+                return Ok(ServerInput::Output(output));
+            }
+
+            let mut buffer = [0; 4096];
+            let read_amount = self.stream.read(&mut buffer)?;
+            if read_amount == 0 {
+                return Ok(ServerInput::Closed);
+            }
+            self.pending.extend_from_slice(&buffer[..read_amount]);
+        }
+    }
+
+    fn data_blocks(&self) -> u64 {
+        self.size.div_ceil(INCREMENTAL_BLOCK_SIZE)
+    }
+
+    fn send_block(&mut self, block_type: i16, index: u64) -> Result<()> {
+        let offset = index * INCREMENTAL_BLOCK_SIZE;
+        let mut data = Vec::with_capacity(INCREMENTAL_BLOCK_SIZE as usize);
+        if block_type == BLOCK_TYPE_TREE {
+            let end = self
+                .tree
+                .len()
+                .min((offset + INCREMENTAL_BLOCK_SIZE) as usize);
+            data.extend_from_slice(&self.tree[offset as usize..end]);
+        } else {
+            self.apk.seek(SeekFrom::Start(offset))?;
+            (&mut self.apk)
+                .take(INCREMENTAL_BLOCK_SIZE)
+                .read_to_end(&mut data)?;
+        }
+
+        // Chunk size is followed by a 12 bytes header
+        let chunk = [
+            &((12 + data.len()) as u32).to_be_bytes()[..],
+            &0i16.to_be_bytes(),
+            &COMPRESSION_NONE.to_be_bytes(),
+            &block_type.to_be_bytes(),
+            &(index as i32).to_be_bytes(),
+            &(data.len() as i16).to_be_bytes(),
+            &data,
+        ]
+        .concat();
+        self.stream.write_all(&chunk)?;
+        Ok(())
+    }
+
+    /// Serve `request`, returning `false` once device does not need blocks anymore
+    fn handle(&mut self, request: &Request) -> Result<bool> {
+        let index = u64::try_from(request.block_index).unwrap_or_default();
+        match request.kind {
+            REQUEST_EXIT | REQUEST_DESTROY => return Ok(false),
+            REQUEST_BLOCK_MISSING if request.file_id == 0 && index < self.data_blocks() => {
+                self.send_block(BLOCK_TYPE_DATA, index)?
+            }
+            REQUEST_PREFETCH if request.file_id == 0 => {
+                self.prefetch = Some(self.prefetch.map_or(index, |start| start.min(index)))
+            }
+            _ => log::debug!("ignoring incremental request {request:?}"),
+        }
+        Ok(true)
+    }
+
+    /// Serve blocks until package manager reports installation status, returning whether a request was received
+    fn serve_installation(&mut self) -> Result<(bool, Result<()>)> {
+        self.stream.write_all(b"OKAY")?;
+        for index in 0..(self.tree.len() as u64).div_ceil(INCREMENTAL_BLOCK_SIZE) {
+            self.send_block(BLOCK_TYPE_TREE, index)?;
+        }
+
+        let observer = ObserverSlot::default();
+        let mut output = InstallOutput::default();
+        let mut requested = false;
+        loop {
+            match self.read_input()? {
+                ServerInput::Request(request) => {
+                    requested = true;
+                    if !self.handle(&request)? {
+                        return Ok((requested, output.finish(&observer)));
+                    }
+                }
+                ServerInput::Output(data) => {
+                    if let Some(status) = output.feed(&data, &observer) {
+                        return Ok((requested, status));
+                    }
+                }
+                ServerInput::Closed => return Ok((requested, output.finish(&observer))),
+            }
+        }
+    }
+
+    /// Serve all blocks of APK following the first one whose prefetch has been requested
+    fn serve_prefetch(&mut self) -> Result<()> {
+        if let Some(start) = self.prefetch.take() {
+            for index in start..self.data_blocks() {
+                self.send_block(BLOCK_TYPE_DATA, index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serve remaining blocks once app is installed, until device stops requesting them
+    fn serve_remaining(mut self) -> Result<()> {
+        self.serve_prefetch()?;
+        loop {
+            match self.read_input()? {
+                ServerInput::Request(request) => {
+                    if !self.handle(&request)? {
+                        return Ok(());
+                    }
+                    self.serve_prefetch()?;
+                }
+                ServerInput::Output(_) => {}
+                ServerInput::Closed => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Incremental installation of an APK, as returned by [`crate::ADBDeviceExt::install_incremental`].
+///
+/// App is installed once returned, but only the blocks it needed so far have been sent: remaining ones are streamed
+/// in a background thread, which runs until device has all of them. App stalls on reading blocks not received yet,
+/// hence a process exiting early should first call [`IncrementalInstall::wait`].
+#[derive(Debug)]
+pub struct IncrementalInstall {
+    server: Option<JoinHandle<Result<()>>>,
+}
+
+impl IncrementalInstall {
+    /// Return `true` once all blocks have been streamed, which is immediate if installation fell back to a regular one
+    pub fn is_finished(&self) -> bool {
+        self.server.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    /// Wait until all blocks have been streamed to device
+    pub fn wait(self) -> Result<()> {
+        match self.server {
+            Some(server) => server.join().unwrap_or_else(|_| {
+                Err(RustADBError::ADBRequestFailed(
+                    "incremental server panicked".to_string(),
+                ))
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Read SDK version of `device`
+fn sdk_version<D: ADBDeviceExt + ?Sized>(device: &mut D) -> Result<u32> {
+    let mut output = Vec::new();
+    device.shell_command(&["getprop ro.build.version.sdk"], &mut output)?;
+    String::from_utf8_lossy(&output)
+        .trim()
+        .parse()
+        .map_err(|_| RustADBError::ADBRequestFailed("cannot read device SDK version".to_string()))
+}
+
+/// Install APK at `apk_path` on `device` incrementally, see [`ADBDeviceExt::install_incremental`].
+pub(crate) fn install_incremental<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    apk_path: &Path,
+    options: &InstallOptions,
+) -> Result<IncrementalInstall> {
+    check_extension_is_apk(apk_path)?;
+
+    let mut idsig_path = PathBuf::from(apk_path).into_os_string();
+    idsig_path.push(".idsig");
+    let idsig = match std::fs::read(&idsig_path) {
+        Ok(idsig) => idsig,
+        Err(e) => {
+            log::info!("cannot read v4 signature ({e}), falling back to regular installation");
+            device.install_with_options(&apk_path, options)?;
+            return Ok(IncrementalInstall { server: None });
+        }
+    };
+    let signature = V4Signature::parse(&idsig)?;
+
+    let sdk = sdk_version(device)?;
+    if sdk < INCREMENTAL_MIN_SDK {
+        log::info!(
+            "device SDK version {sdk} does not support incremental installation, falling back to regular installation"
+        );
+        device.install_with_options(&apk_path, options)?;
+        return Ok(IncrementalInstall { server: None });
+    }
+
+    let apk = File::open(apk_path)?;
+    let size = apk.metadata()?.len();
+    let name = apk_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+
+    // Files are described as `name:size:file_id:signature:streaming_version`
+    let mut args = options.flags()?;
+    args.push(format!(
+        "{}:{size}:0:{}:1",
+        shell_quote(&name),
+        STANDARD.encode(&signature.headers)
+    ));
+    let stream = device.open_service(&format!(
+        "exec:cmd package install-incremental {}",
+        args.join(" ")
+    ))?;
+
+    let mut server = IncrementalServer {
+        stream,
+        apk,
+        size,
+        tree: signature.tree,
+        pending: Vec::new(),
+        prefetch: None,
+    };
+    let (requested, status) = server.serve_installation()?;
+    if let Err(e) = status {
+        // Device did not start loading blocks, it most likely does not support incremental file system
+        if !requested {
+            log::info!(
+                "incremental installation failed ({e}), falling back to regular installation"
+            );
+            device.install_with_options(&apk_path, options)?;
+            return Ok(IncrementalInstall { server: None });
+        }
+        return Err(e);
+    }
+
+    log::info!("APK {name} installed, streaming its remaining blocks");
+    Ok(IncrementalInstall {
+        server: Some(std::thread::spawn(move || server.serve_remaining())),
+    })
+}
+
+#[test]
+fn test_v4_signature_parse() {
+    let idsig = [
+        &2i32.to_le_bytes()[..],
+        &3i32.to_le_bytes(),
+        b"abc",
+        &2i32.to_le_bytes(),
+        b"de",
+        &4i32.to_le_bytes(),
+        b"tree",
+    ]
+    .concat();
+
+    let signature = V4Signature::parse(&idsig).expect("cannot parse signature");
+    assert_eq!(signature.headers, idsig[..17]);
+    assert_eq!(signature.tree, b"tree");
+
+    assert!(V4Signature::parse(&idsig[..idsig.len() - 1]).is_err());
+}
+
+#[test]
+fn test_take_request() {
+    let mut pending = [
+        &b"Performing"[..],
+        b"INCR",
+        &[0, 1, 0, 0, 0, 0, 0, 42],
+        b"Succ",
+        b"IN",
+    ]
+    .concat();
+    let mut output = Vec::new();
+
+    assert_eq!(
+        take_request(&mut pending, &mut output),
+        Some(Request {
+            kind: REQUEST_BLOCK_MISSING,
+            file_id: 0,
+            block_index: 42,
+        })
+    );
+    assert_eq!(output, b"Performing");
+
+    // Possible start of a magic is kept until more bytes are received
+    assert_eq!(take_request(&mut pending, &mut output), None);
+    assert_eq!(output, b"PerformingSucc");
+    assert_eq!(pending, b"IN");
+}
@@ -0,0 +1,172 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use crate::utils::{check_copied_size, check_extension_is_apk, shell_quote};
+use crate::{ADBDeviceExt, InstallOptions, Result, RustADBError};
+
+/// Package manager session installing several APKs at once (base APK and its splits).
+///
+/// Session is driven using `install-create`, `install-write` and `install-commit` commands of package manager,
+/// and must be committed or abandoned once all APKs have been written. Sessions left open are dropped by package
+/// manager after a while, along with APKs written into them.
+///
+/// Sessions created by [`InstallSession::create_multi_package`] hold other sessions instead of APKs, which are all
+/// installed or all rejected when it is committed (e.g. an app and its dependencies).
+#[derive(Debug)]
+pub struct InstallSession {
+    id: u32,
+}
+
+/// Run package manager `command`, failing unless it reports a success, and return its output.
+fn package_manager<D: ADBDeviceExt + ?Sized>(device: &mut D, command: &str) -> Result<String> {
+    let mut output = Vec::new();
+    device.shell_command(&[&format!("cmd package {command}")], &mut output)?;
+
+    let output = String::from_utf8(output)?;
+    if !output.trim_start().starts_with("Success") {
+        return Err(RustADBError::ADBRequestFailed(output.trim().to_string()));
+    }
+    Ok(output)
+}
+
+/// Parse identifier of session created by `install-create`, printed as `Success: created install session [1234]`
+fn parse_session_id(output: &str) -> Result<u32> {
+    output
+        .split(['[', ']'])
+        .nth(1)
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| {
+            RustADBError::ADBRequestFailed(format!("cannot find session id in {:?}", output.trim()))
+        })
+}
+
+impl InstallSession {
+    /// Create a session for APKs totaling `total_size` bytes, installed according to `options`
+    pub fn create<D: ADBDeviceExt + ?Sized>(
+        device: &mut D,
+        total_size: u64,
+        options: &InstallOptions,
+    ) -> Result<Self> {
+        let output = package_manager(
+            device,
+            &format!("install-create {}", options.args(total_size)?),
+        )?;
+        Ok(Self {
+            id: parse_session_id(&output)?,
+        })
+    }
+
+    /// Create a session holding other sessions, added using [`InstallSession::add_session`]
+    pub fn create_multi_package<D: ADBDeviceExt + ?Sized>(
+        device: &mut D,
+        options: &InstallOptions,
+    ) -> Result<Self> {
+        let mut args = vec!["--multi-package".to_string()];
+        args.extend(options.flags()?);
+        let output = package_manager(device, &format!("install-create {}", args.join(" ")))?;
+        Ok(Self {
+            id: parse_session_id(&output)?,
+        })
+    }
+
+    /// Identifier of session, as listed by package manager
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Stream APK `name` of `size` bytes from `reader` into session
+    pub fn write<D: ADBDeviceExt + ?Sized>(
+        &self,
+        device: &mut D,
+        name: &str,
+        size: u64,
+        reader: &mut dyn Read,
+    ) -> Result<()> {
+        let mut stream = device.open_service(&format!(
+            "exec:cmd package install-write -S {size} {} {} -",
+            self.id,
+            shell_quote(name)
+        ))?;
+        // Package manager waits for exactly `size` bytes, never send more
+        check_copied_size(std::io::copy(&mut reader.take(size), &mut stream)?, size)?;
+        stream.flush()?;
+
+        let mut output = String::new();
+        stream.read_to_string(&mut output)?;
+        if !output.trim_start().starts_with("Success") {
+            return Err(RustADBError::ADBRequestFailed(output.trim().to_string()));
+        }
+        Ok(())
+    }
+
+    /// Add `child` session to this multi-package session, `child` being committed along with it
+    pub fn add_session<D: ADBDeviceExt + ?Sized>(
+        &self,
+        device: &mut D,
+        child: InstallSession,
+    ) -> Result<()> {
+        package_manager(
+            device,
+            &format!("install-add-session {} {}", self.id, child.id),
+        )?;
+        Ok(())
+    }
+
+    /// Install all APKs written into session
+    pub fn commit<D: ADBDeviceExt + ?Sized>(self, device: &mut D) -> Result<()> {
+        package_manager(device, &format!("install-commit {}", self.id))?;
+        Ok(())
+    }
+
+    /// Drop session and APKs written into it
+    pub fn abandon<D: ADBDeviceExt + ?Sized>(self, device: &mut D) -> Result<()> {
+        package_manager(device, &format!("install-abandon {}", self.id))?;
+        Ok(())
+    }
+}
+
+/// Install `apk_paths` together on `device`, see [`ADBDeviceExt::install_multiple`].
+pub(crate) fn install_multiple<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    apk_paths: &[PathBuf],
+    options: &InstallOptions,
+) -> Result<()> {
+    if apk_paths.is_empty() {
+        return Err(RustADBError::ADBRequestFailed(
+            "no APK to install".to_string(),
+        ));
+    }
+
+    let mut apks = Vec::with_capacity(apk_paths.len());
+    for path in apk_paths {
+        check_extension_is_apk(path)?;
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        apks.push((path, file, size));
+    }
+
+    let session =
+        InstallSession::create(device, apks.iter().map(|(_, _, size)| size).sum(), options)?;
+    for (path, file, size) in &mut apks {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if let Err(e) = session.write(device, &name, *size, file) {
+            // Best effort, failure to write is what matters to caller
+            let _ = session.abandon(device);
+            return Err(e);
+        }
+    }
+    session.commit(device)?;
+    log::info!("{} APKs successfully installed", apk_paths.len());
+    Ok(())
+}
+
+#[test]
+fn test_parse_session_id() {
+    assert_eq!(
+        parse_session_id("Success: created install session [1234]\n")
+            .expect("cannot parse session id"),
+        1234
+    );
+    assert!(parse_session_id("Error: failed to create session\n").is_err());
+}
@@ -0,0 +1,65 @@
+use std::io::{ErrorKind, Read};
+
+use crate::event_stream::EventSender;
+use crate::{ReadWriteStream, Result, RustADBError};
+
+/// Service reporting processes available for debugging, sending an updated list each time one starts or stops
+pub(crate) const TRACK_JDWP_SERVICE: &str = "track-jdwp";
+
+/// Parse process identifiers listed in `message`, one per line
+fn parse_pids(message: &[u8]) -> Result<Vec<u32>> {
+    String::from_utf8_lossy(message)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse().map_err(|_| {
+                RustADBError::ADBRequestFailed(format!("invalid JDWP process identifier {line:?}"))
+            })
+        })
+        .collect()
+}
+
+/// Read lists of debuggable processes sent by `track-jdwp` service running in `stream`, sending them to `sender`.
+///
+/// Each list is prefixed by its length, as 4 hexadecimal digits.
+pub(crate) fn track_jdwp(
+    mut stream: Box<dyn ReadWriteStream>,
+    sender: &EventSender<Vec<u32>>,
+) -> Result<()> {
+    let mut message = Vec::new();
+
+    loop {
+        let mut length = [0; 4];
+        match stream.read_exact(&mut length) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            read => read?,
+        }
+        let length = std::str::from_utf8(&length)
+            .ok()
+            .and_then(|length| usize::from_str_radix(length, 16).ok())
+            .ok_or_else(|| {
+                RustADBError::ADBRequestFailed(format!(
+                    "invalid JDWP list length {}",
+                    String::from_utf8_lossy(&length)
+                ))
+            })?;
+
+        message.resize(length, 0);
+        stream.read_exact(&mut message)?;
+        sender.send(parse_pids(&message)?)?;
+    }
+}
+
+#[test]
+fn test_parse_pids() {
+    assert_eq!(
+        parse_pids(b"1234\n5678\n").expect("cannot parse pids"),
+        [1234, 5678]
+    );
+    assert_eq!(
+        parse_pids(b"").expect("cannot parse pids"),
+        Vec::<u32>::new()
+    );
+    assert!(parse_pids(b"12ab\n").is_err());
+}
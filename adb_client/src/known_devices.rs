@@ -0,0 +1,228 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ADBDeviceExt, ADBServer, ADBTcpDevice, Result, RustADBError};
+
+/// How a known device is preferably connected to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+pub enum ConnectionMethod {
+    /// Through local ADB server, using device serial
+    Server,
+    /// Directly over USB
+    Usb {
+        /// USB vendor id of device
+        vendor_id: u16,
+        /// USB product id of device
+        product_id: u16,
+    },
+    /// Directly over TCP/IP
+    Tcp {
+        /// Address device is listening on
+        address: SocketAddr,
+    },
+}
+
+/// Entry of a [`KnownDevices`] registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownDevice {
+    /// Serial of device, as reported by `adb devices`
+    pub serial: String,
+    /// Human readable name of device (e.g. `pixel-lab-3`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// Preferred way to connect to device, [`ConnectionMethod::Server`] if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection: Option<ConnectionMethod>,
+    /// Private key used to authenticate to device over USB, default key if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key_path: Option<PathBuf>,
+}
+
+impl KnownDevice {
+    /// Return `true` if `name` is either serial or alias of this device
+    pub fn matches(&self, name: &str) -> bool {
+        self.serial == name || self.alias.as_deref() == Some(name)
+    }
+
+    /// Connect to this device using its preferred connection method.
+    pub fn connect(&self) -> Result<Box<dyn ADBDeviceExt>> {
+        match &self.connection {
+            None | Some(ConnectionMethod::Server) => Ok(ADBServer::default()
+                .get_device_by_name(&self.serial)?
+                .boxed()),
+            #[cfg(feature = "usb")]
+            Some(ConnectionMethod::Usb {
+                vendor_id,
+                product_id,
+            }) => {
+                let device = match &self.private_key_path {
+                    Some(private_key_path) => crate::ADBUSBDevice::new_with_custom_private_key(
+                        *vendor_id,
+                        *product_id,
+                        private_key_path.clone(),
+                    )?,
+                    None => crate::ADBUSBDevice::new(*vendor_id, *product_id)?,
+                };
+                Ok(device.boxed())
+            }
+            #[cfg(not(feature = "usb"))]
+            Some(ConnectionMethod::Usb { .. }) => {
+                Err(RustADBError::UnknownTransport("usb".to_string()))
+            }
+            Some(ConnectionMethod::Tcp { address }) => Ok(ADBTcpDevice::new(*address)?.boxed()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    #[serde(default, rename = "device")]
+    devices: Vec<KnownDevice>,
+}
+
+/// Persistent registry of known devices, mapping their serials to aliases and connection preferences.
+///
+/// Registry is stored as TOML, or as JSON if its path ends with `.json`. In TOML, each device is a `[[device]]` table:
+///
+/// ```toml
+/// [[device]]
+/// serial = "0123456789ABCDEF"
+/// alias = "pixel-lab-3"
+/// connection = { method = "tcp", address = "192.168.1.23:5555" }
+/// ```
+#[derive(Debug)]
+pub struct KnownDevices {
+    path: PathBuf,
+    devices: Vec<KnownDevice>,
+}
+
+impl KnownDevices {
+    /// Default location of registry, `~/.android/known_devices.toml`
+    pub fn default_path() -> Result<PathBuf> {
+        homedir::my_home()?
+            .map(|home| home.join(".android").join("known_devices.toml"))
+            .ok_or(RustADBError::NoHomeDirectory)
+    }
+
+    /// Load registry stored at `path`, which is empty if file does not exist yet
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let registry = match std::fs::read_to_string(&path) {
+            Ok(content) if is_json(&path) => serde_json::from_str(&content)
+                .map_err(|e| RustADBError::InvalidRegistry(e.to_string()))?,
+            Ok(content) => toml::from_str(&content)
+                .map_err(|e| RustADBError::InvalidRegistry(e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => RegistryFile::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path,
+            devices: registry.devices,
+        })
+    }
+
+    /// Load registry stored at [`KnownDevices::default_path`]
+    pub fn load_default() -> Result<Self> {
+        Self::load(Self::default_path()?)
+    }
+
+    /// Write registry back to the path it has been loaded from
+    pub fn save(&self) -> Result<()> {
+        let registry = RegistryFile {
+            devices: self.devices.clone(),
+        };
+        let content = if is_json(&self.path) {
+            serde_json::to_string_pretty(&registry)
+                .map_err(|e| RustADBError::InvalidRegistry(e.to_string()))?
+        } else {
+            toml::to_string_pretty(&registry)
+                .map_err(|e| RustADBError::InvalidRegistry(e.to_string()))?
+        };
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, content)?;
+        Ok(())
+    }
+
+    /// All devices of registry
+    pub fn devices(&self) -> &[KnownDevice] {
+        &self.devices
+    }
+
+    /// Find device whose serial or alias is `name`
+    pub fn get(&self, name: &str) -> Option<&KnownDevice> {
+        self.devices.iter().find(|device| device.matches(name))
+    }
+
+    /// Add `device` to registry, replacing any previous entry with the same serial
+    pub fn insert(&mut self, device: KnownDevice) {
+        match self
+            .devices
+            .iter_mut()
+            .find(|known| known.serial == device.serial)
+        {
+            Some(known) => *known = device,
+            None => self.devices.push(device),
+        }
+    }
+
+    /// Remove device whose serial or alias is `name`, and return it
+    pub fn remove(&mut self, name: &str) -> Option<KnownDevice> {
+        let index = self
+            .devices
+            .iter()
+            .position(|device| device.matches(name))?;
+        Some(self.devices.remove(index))
+    }
+
+    /// Connect to device whose serial or alias is `name`, using its preferred connection method.
+    ///
+    /// Devices missing from registry are looked up by serial on local ADB server.
+    pub fn connect(&self, name: &str) -> Result<Box<dyn ADBDeviceExt>> {
+        match self.get(name) {
+            Some(device) => device.connect(),
+            None => Ok(ADBServer::default().get_device_by_name(name)?.boxed()),
+        }
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+}
+
+#[test]
+fn test_known_devices_round_trip() {
+    let directory =
+        std::env::temp_dir().join(format!("adb_client_registry_{}", std::process::id()));
+    let pixel = KnownDevice {
+        serial: "0123456789ABCDEF".to_string(),
+        alias: Some("pixel-lab-3".to_string()),
+        connection: Some(ConnectionMethod::Tcp {
+            address: "192.168.1.23:5555".parse().expect("cannot parse address"),
+        }),
+        private_key_path: None,
+    };
+
+    for file_name in ["known_devices.toml", "known_devices.json"] {
+        let path = directory.join(file_name);
+        let mut registry = KnownDevices::load(&path).expect("cannot load missing registry");
+        assert!(registry.devices().is_empty());
+
+        registry.insert(pixel.clone());
+        registry.save().expect("cannot save registry");
+
+        let registry = KnownDevices::load(&path).expect("cannot load registry");
+        assert_eq!(registry.get("pixel-lab-3"), Some(&pixel));
+        assert_eq!(registry.get("0123456789ABCDEF"), Some(&pixel));
+        assert_eq!(registry.get("pixel-lab-4"), None);
+    }
+
+    std::fs::remove_dir_all(&directory).expect("cannot remove registry directory");
+}
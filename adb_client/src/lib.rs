@@ -4,37 +4,143 @@
 #![forbid(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+mod activity_monitor;
+#[cfg(feature = "async")]
+mod adb_device_async_ext;
 mod adb_device_ext;
+mod adbd_restart;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+mod app_export;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+mod bundle;
+mod cancel_token;
 mod constants;
+mod copy;
 #[cfg(any(feature = "tcp", feature = "usb"))]
 mod device;
+mod device_client;
+mod device_group;
+#[cfg(feature = "tcp")]
+mod device_watcher;
 #[cfg(feature = "tcp")]
 mod emulator_device;
 mod error;
+mod event_stream;
+mod exit_code;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+mod fastboot;
+mod framebuffer_stream;
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+mod getevent;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+mod incremental_install;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+mod install_session;
+mod jdwp;
+#[cfg(feature = "registry")]
+mod known_devices;
+mod list_dir;
+mod logcat;
 mod mdns;
 mod models;
+mod obb;
+mod observer;
+#[cfg(feature = "tcp")]
+mod pairing;
+mod path_watcher;
+mod process_kill;
+mod progress;
+mod pull_dir;
+mod push_dir;
+mod push_resume;
+mod screen_record;
+mod screenshot;
 #[cfg(feature = "tcp")]
 mod server;
 #[cfg(feature = "tcp")]
 mod server_device;
+mod shell_protocol;
 #[cfg(any(feature = "tcp", feature = "usb"))]
-mod transports;
+mod sideload;
+mod sparse_file;
+mod stat_v2;
+mod sync_dir;
+mod sync_v2;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+mod throttle;
 #[cfg(any(feature = "tcp", feature = "usb"))]
+mod transports;
+mod ui_automation;
 mod utils;
+mod verity;
+#[cfg(feature = "tcp")]
+mod wireless;
+mod zip_archive;
 
-pub use adb_device_ext::ADBDeviceExt;
+pub use activity_monitor::ActivityEvent;
+#[cfg(feature = "async")]
+pub use adb_device_async_ext::ADBDeviceAsyncExt;
+pub use adb_device_ext::{ADBDeviceExt, ReadSeek, ReadWriteStream};
+pub use cancel_token::CancelToken;
+pub use copy::copy_between;
+#[cfg(all(feature = "async", any(feature = "tcp", feature = "usb")))]
+pub use device::ADBAsyncMessageDevice;
+#[cfg(all(feature = "vsock", any(target_os = "linux", target_os = "android")))]
+pub use device::ADBVsockDevice;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub use device::{
+    ADBMessageDevice, ADBTransportMessage, ADBTransportMessageHeader, DeviceMode, MessageCommand,
+    PortForward,
+};
 #[cfg(feature = "tcp")]
-pub use device::ADBTcpDevice;
+pub use device::{ADBTcpDevice, ReconnectPolicy};
 #[cfg(feature = "usb")]
-pub use device::ADBUSBDevice;
+pub use device::{ADBUSBDevice, USBDeviceSelector};
+pub use device_client::DeviceClient;
+pub use device_group::DeviceGroup;
+#[cfg(feature = "tcp")]
+pub use device_watcher::{DeviceEvent, DeviceWatcher, WatchedDevice};
 #[cfg(feature = "tcp")]
 pub use emulator_device::ADBEmulatorDevice;
 pub use error::{Result, RustADBError};
+pub use event_stream::EventStream;
+#[cfg(feature = "tcp")]
+pub use fastboot::TcpFastbootTransport;
+#[cfg(feature = "usb")]
+pub use fastboot::USBFastbootTransport;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub use fastboot::{FastbootDevice, FastbootTransport};
+pub use framebuffer_stream::{Frame, FramebufferStream};
+pub use getevent::InputEvent;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub use incremental_install::IncrementalInstall;
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub use install_session::InstallSession;
+#[cfg(feature = "registry")]
+pub use known_devices::{ConnectionMethod, KnownDevice, KnownDevices};
 pub use mdns::*;
-pub use models::{AdbStatResponse, RebootType};
+#[cfg(feature = "tcp")]
+pub use models::ForwardRule;
+pub use models::{
+    ActivityLaunch, AdbStatResponse, AdbStatV2Response, BackupOptions, BatteryStats, DirEntry,
+    FrameBufferInfo, FrameBufferInfoV1, FrameBufferInfoV2, InstallOptions, InstallPhase, Intent,
+    JobStats, LogPriority, LogcatEntry, NetworkUsage, PathFilter, ProcessInfo, PullOptions,
+    RawFramebuffer, RebootType, ScreenRecordOptions, ShellMode, ShellOptions, ShellOutput, Signal,
+    SymlinkPolicy, SyncCompression, SyncDirection, ThermalInfo, ThermalStatus, ThermalZone,
+    TransferStats, UidTraffic, VolumeUsage, Wakelock,
+};
+pub use observer::AdbObserver;
+#[cfg(feature = "tcp")]
+pub use pairing::ADBPairing;
+pub use path_watcher::{FileEvent, PathWatcher};
 #[cfg(feature = "tcp")]
 pub use server::*;
 #[cfg(feature = "tcp")]
 pub use server_device::ADBServerDevice;
 #[cfg(any(feature = "tcp", feature = "usb"))]
 pub use transports::*;
+pub use ui_automation::{UiNode, UiSelector};
+#[cfg(feature = "tcp")]
+pub use wireless::{QrPairing, connect_wireless, switch_to_tcp};
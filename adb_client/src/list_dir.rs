@@ -0,0 +1,146 @@
+use std::io::{Read, Write};
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::models::DirEntry;
+use crate::{Result, RustADBError};
+
+/// List entries of `remote_path` over `stream`, opened on `sync:` service.
+///
+/// Device answers a `LIST` request with one `DENT` message per entry (mode, size, modification time and name),
+/// followed by a `DONE` message of the same layout. `.` and `..` entries are skipped.
+pub(crate) fn list_dir<S: Read + Write + ?Sized>(
+    stream: &mut S,
+    remote_path: &str,
+) -> Result<Vec<DirEntry>> {
+    let mut request = b"LIST".to_vec();
+    request.extend_from_slice(&(remote_path.len() as u32).to_le_bytes());
+    request.extend_from_slice(remote_path.as_bytes());
+    stream.write_all(&request)?;
+
+    let mut entries = Vec::new();
+    loop {
+        // Identifier, then mode for `DENT` and `DONE`, or message length for `FAIL`
+        let mut id = [0; 8];
+        stream.read_exact(&mut id)?;
+        let first_field = LittleEndian::read_u32(&id[4..]);
+        match &id[..4] {
+            b"DENT" | b"DONE" => {
+                // Size, modification time and name length
+                let mut fields = [0; 12];
+                stream.read_exact(&mut fields)?;
+                let mut name = vec![0; LittleEndian::read_u32(&fields[8..]) as usize];
+                stream.read_exact(&mut name)?;
+                if &id[..4] == b"DONE" {
+                    break;
+                }
+
+                let name = String::from_utf8_lossy(&name).into_owned();
+                if name == "." || name == ".." {
+                    continue;
+                }
+                entries.push(DirEntry {
+                    name,
+                    mode: first_field,
+                    size: LittleEndian::read_u32(&fields[..4]),
+                    mod_time: LittleEndian::read_u32(&fields[4..8]),
+                });
+            }
+            b"FAIL" => {
+                let mut message = vec![0; first_field as usize];
+                stream.read_exact(&mut message)?;
+                return Err(RustADBError::ADBRequestFailed(
+                    String::from_utf8_lossy(&message).into_owned(),
+                ));
+            }
+            id => {
+                return Err(RustADBError::InvalidMessage(format!(
+                    "unexpected sync response {}",
+                    String::from_utf8_lossy(id)
+                )));
+            }
+        }
+    }
+
+    stream.write_all(b"QUIT\0\0\0\0")?;
+    stream.flush()?;
+    Ok(entries)
+}
+
+#[test]
+fn test_list_dir() {
+    use std::io::Cursor;
+
+    /// Stream answering a `LIST` request with canned sync messages
+    struct SyncStream {
+        responses: Cursor<Vec<u8>>,
+        requests: Vec<u8>,
+    }
+
+    impl Read for SyncStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.responses.read(buf)
+        }
+    }
+
+    impl Write for SyncStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.requests.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let dent = |mode: u32, size: u32, mod_time: u32, name: &str| {
+        [
+            &b"DENT"[..],
+            &mode.to_le_bytes(),
+            &size.to_le_bytes(),
+            &mod_time.to_le_bytes(),
+            &(name.len() as u32).to_le_bytes(),
+            name.as_bytes(),
+        ]
+        .concat()
+    };
+    let mut stream = SyncStream {
+        responses: Cursor::new(
+            [
+                dent(0o040755, 4096, 1_700_000_000, "."),
+                dent(0o100644, 42, 1_700_000_001, "file.txt"),
+                dent(0o040771, 4096, 1_700_000_002, "Download"),
+                [&b"DONE"[..], &[0; 16]].concat(),
+            ]
+            .concat(),
+        ),
+        requests: Vec::new(),
+    };
+
+    let entries = list_dir(&mut stream, "/sdcard").expect("cannot list directory");
+    assert_eq!(
+        entries,
+        [
+            DirEntry {
+                name: "file.txt".to_string(),
+                mode: 0o100644,
+                size: 42,
+                mod_time: 1_700_000_001,
+            },
+            DirEntry {
+                name: "Download".to_string(),
+                mode: 0o040771,
+                size: 4096,
+                mod_time: 1_700_000_002,
+            },
+        ]
+    );
+    assert!(entries[0].is_file() && entries[1].is_dir());
+    assert_eq!(stream.requests, b"LIST\x07\0\0\0/sdcardQUIT\0\0\0\0");
+
+    let mut stream = SyncStream {
+        responses: Cursor::new([&b"FAIL"[..], &6u32.to_le_bytes(), b"denied"].concat()),
+        requests: Vec::new(),
+    };
+    assert!(list_dir(&mut stream, "/data").is_err());
+}
@@ -0,0 +1,106 @@
+use std::io::{ErrorKind, Read};
+
+use crate::event_stream::EventSender;
+use crate::models::{LogPriority, LogcatEntry};
+use crate::{ReadWriteStream, Result, RustADBError};
+
+/// Service running logcat, printing messages as binary logger entries.
+///
+/// Binary entries do not depend on device locale or logcat version, but are not filtered by logcat.
+pub(crate) const LOGCAT_SERVICE: &str = "exec:logcat -B";
+
+/// Filter keeping log messages matching logcat filter specs, e.g. `ActivityManager:I` or `*:S`.
+#[derive(Debug)]
+pub(crate) struct LogcatFilter {
+    /// Lowest priority of messages kept for each tag, `None` if tag is silenced
+    tags: Vec<(String, Option<LogPriority>)>,
+    /// Lowest priority of messages kept for tags without their own spec, as set by `*` spec
+    default: Option<LogPriority>,
+}
+
+impl LogcatFilter {
+    /// Parse `filter_specs`, `<tag>[:<priority>]` pairs as understood by logcat
+    pub(crate) fn new(filter_specs: &[&str]) -> Result<Self> {
+        let mut filter = Self {
+            tags: Vec::new(),
+            default: Some(LogPriority::Verbose),
+        };
+
+        for spec in filter_specs {
+            let (tag, priority) = spec.split_once(':').unwrap_or((spec, "V"));
+            let priority = match priority {
+                "S" => None,
+                "*" => Some(LogPriority::Verbose),
+                letter => Some(LogPriority::from_letter(letter).ok_or_else(|| {
+                    RustADBError::ADBRequestFailed(format!("invalid logcat filter spec {spec:?}"))
+                })?),
+            };
+            match tag {
+                "*" => filter.default = priority,
+                tag => filter.tags.push((tag.to_string(), priority)),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Return `true` if `entry` is kept, last spec of its tag taking precedence
+    pub(crate) fn matches(&self, entry: &LogcatEntry) -> bool {
+        let lowest_priority = self
+            .tags
+            .iter()
+            .rev()
+            .find(|(tag, _)| *tag == entry.tag)
+            .map_or(self.default, |(_, priority)| *priority);
+        lowest_priority.is_some_and(|priority| entry.priority >= priority)
+    }
+}
+
+/// Read binary entries printed by logcat running in `stream`, sending messages kept by `filter` to `sender`.
+pub(crate) fn read_logcat(
+    mut stream: Box<dyn ReadWriteStream>,
+    filter: &LogcatFilter,
+    sender: &EventSender<LogcatEntry>,
+) -> Result<()> {
+    let mut entry = Vec::new();
+
+    loop {
+        let mut prefix = [0; 4];
+        match stream.read_exact(&mut prefix) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            read => read?,
+        }
+
+        entry.clear();
+        entry.extend_from_slice(&prefix);
+        entry.resize(LogcatEntry::entry_size(prefix), 0);
+        stream.read_exact(&mut entry[prefix.len()..])?;
+
+        if let Some(entry) = LogcatEntry::parse(&entry).filter(|entry| filter.matches(entry)) {
+            sender.send(entry)?;
+        }
+    }
+}
+
+#[test]
+fn test_logcat_filter() {
+    let entry = |tag: &str, priority| LogcatEntry {
+        timestamp: std::time::SystemTime::UNIX_EPOCH,
+        pid: 1,
+        tid: 1,
+        uid: None,
+        priority,
+        tag: tag.to_string(),
+        message: String::new(),
+    };
+
+    let filter = LogcatFilter::new(&["ActivityManager:I", "*:S"]).expect("cannot parse specs");
+    assert!(filter.matches(&entry("ActivityManager", LogPriority::Warn)));
+    assert!(!filter.matches(&entry("ActivityManager", LogPriority::Debug)));
+    assert!(!filter.matches(&entry("Other", LogPriority::Fatal)));
+
+    let filter = LogcatFilter::new(&["Noisy:S"]).expect("cannot parse specs");
+    assert!(!filter.matches(&entry("Noisy", LogPriority::Error)));
+    assert!(filter.matches(&entry("Other", LogPriority::Verbose)));
+
+    assert!(LogcatFilter::new(&["Tag:X"]).is_err());
+}
@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use mdns_sd::{IfKind, ServiceDaemon, ServiceEvent};
+
+use crate::event_stream::EventSender;
+#[cfg(feature = "tcp")]
+use crate::{ADBServer, constants::MDNS_SERVER_POLL_INTERVAL};
+use crate::{CancelToken, EventStream, MDNSDevice, Result};
+#[cfg(feature = "tcp")]
+use std::{collections::HashSet, net::SocketAddrV4};
+
+/// ADB service advertised by devices over mDNS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MDNSServiceType {
+    /// `_adb._tcp`, unencrypted connection of devices switched to TCP mode (e.g. using `adb tcpip`)
+    Adb,
+    /// `_adb-tls-connect._tcp`, TLS connection of devices in wireless debugging mode
+    TlsConnect,
+    /// `_adb-tls-pairing._tcp`, pairing service of devices showing a pairing dialog
+    TlsPairing,
+}
+
+impl MDNSServiceType {
+    /// Full mDNS service type, e.g. `_adb-tls-connect._tcp.local.`
+    pub fn service_type(self) -> &'static str {
+        match self {
+            MDNSServiceType::Adb => "_adb._tcp.local.",
+            MDNSServiceType::TlsConnect => "_adb-tls-connect._tcp.local.",
+            MDNSServiceType::TlsPairing => "_adb-tls-pairing._tcp.local.",
+        }
+    }
+
+    /// Parse a service type as reported by ADB server, e.g. `_adb-tls-connect._tcp`
+    #[cfg(feature = "tcp")]
+    fn from_reg_type(reg_type: &str) -> Option<Self> {
+        let service_type = format!("{}.local.", reg_type.trim_end_matches('.'));
+        [Self::Adb, Self::TlsConnect, Self::TlsPairing]
+            .into_iter()
+            .find(|candidate| candidate.service_type() == service_type)
+    }
+}
+
+/// Where a [`MDNSBrowser`] gets advertised services from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MDNSBrowserBackend {
+    /// Browse from this process, without any ADB server
+    #[default]
+    Native,
+    /// Poll services discovered by ADB server at given address, or at default one if `None`.
+    ///
+    /// Server backend (Bonjour or Openscreen) can be chosen using [`ADBServer::mdns_force_backend`]. Only IPv4
+    /// addresses are reported by server, without any TXT attribute, and interface selection does not apply.
+    #[cfg(feature = "tcp")]
+    Server(Option<SocketAddrV4>),
+}
+
+/// IP versions a [`MDNSBrowser`] browses and reports addresses of
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MDNSAddressFamily {
+    /// Both IPv4 and IPv6
+    #[default]
+    Any,
+    /// IPv4 only
+    IPv4,
+    /// IPv6 only
+    IPv6,
+}
+
+impl MDNSAddressFamily {
+    fn contains(self, address: &IpAddr) -> bool {
+        match self {
+            MDNSAddressFamily::Any => true,
+            MDNSAddressFamily::IPv4 => address.is_ipv4(),
+            MDNSAddressFamily::IPv6 => address.is_ipv6(),
+        }
+    }
+}
+
+/// Change in ADB services advertised over mDNS, as reported by a [`MDNSBrowser`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MDNSEvent {
+    /// Service appeared on network
+    Added(MDNSDevice),
+    /// Addresses, port or TXT attributes of an already advertised service changed
+    Updated(MDNSDevice),
+    /// Service is not advertised anymore, identified by its full name
+    Removed(MDNSServiceType, String),
+}
+
+/// Browses continuously for ADB services advertised over mDNS, reporting devices appearing and leaving network.
+#[derive(Debug, Clone)]
+pub struct MDNSBrowser {
+    service_types: Vec<MDNSServiceType>,
+    backend: MDNSBrowserBackend,
+    interfaces: Vec<String>,
+    address_family: MDNSAddressFamily,
+    timeout: Option<Duration>,
+}
+
+impl Default for MDNSBrowser {
+    fn default() -> Self {
+        Self {
+            service_types: vec![
+                MDNSServiceType::Adb,
+                MDNSServiceType::TlsConnect,
+                MDNSServiceType::TlsPairing,
+            ],
+            backend: MDNSBrowserBackend::default(),
+            interfaces: Vec::new(),
+            address_family: MDNSAddressFamily::default(),
+            timeout: None,
+        }
+    }
+}
+
+impl MDNSBrowser {
+    /// Instantiate a new [`MDNSBrowser`], browsing all ADB service types on all interfaces using native backend
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only browse given service types
+    pub fn with_service_types(mut self, service_types: &[MDNSServiceType]) -> Self {
+        self.service_types = service_types.to_vec();
+        self
+    }
+
+    /// Get advertised services from given backend
+    pub fn with_backend(mut self, backend: MDNSBrowserBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Only browse on network interfaces with given names (e.g. `wlan0`), all interfaces being used if empty
+    pub fn with_interfaces(mut self, interfaces: &[&str]) -> Self {
+        self.interfaces = interfaces.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Only browse using given IP versions, addresses of other versions being left out of reported devices
+    pub fn with_address_family(mut self, address_family: MDNSAddressFamily) -> Self {
+        self.address_family = address_family;
+        self
+    }
+
+    /// Stop browsing after `timeout`, ending stream. Browsing goes on until stream is dropped otherwise.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Start browsing, and return an [`EventStream`] of changes.
+    ///
+    /// Services are reported once resolved, an update being reported if they are resolved again with other
+    /// addresses, port or TXT attributes. Browsing stops once stream has been dropped.
+    pub fn browse(&self) -> Result<EventStream<MDNSEvent>> {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        match self.backend {
+            MDNSBrowserBackend::Native => self.browse_native(deadline),
+            #[cfg(feature = "tcp")]
+            MDNSBrowserBackend::Server(address) => Ok(self.browse_server(address, deadline)),
+        }
+    }
+
+    fn browse_native(&self, deadline: Option<Instant>) -> Result<EventStream<MDNSEvent>> {
+        let daemon = ServiceDaemon::new()?;
+        // Later selections take precedence over earlier ones
+        if !self.interfaces.is_empty() {
+            daemon.disable_interface(IfKind::All)?;
+            daemon.enable_interface(
+                self.interfaces
+                    .iter()
+                    .map(|name| IfKind::Name(name.clone()))
+                    .collect::<Vec<_>>(),
+            )?;
+        }
+        match self.address_family {
+            MDNSAddressFamily::Any => {}
+            MDNSAddressFamily::IPv4 => daemon.disable_interface(IfKind::IPv6)?,
+            MDNSAddressFamily::IPv6 => daemon.disable_interface(IfKind::IPv4)?,
+        }
+
+        let (events, receiver) = mpsc::channel();
+        for &service_type in &self.service_types {
+            let service_events = daemon.browse(service_type.service_type())?;
+            let events = events.clone();
+            std::thread::spawn(move || {
+                // Daemon closes its channels once shut down
+                while let Ok(event) = service_events.recv() {
+                    if events.send((service_type, event)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+
+        // Daemon closes its channels once shut down, which ends forwarding
+        let stream_token = CancelToken::new();
+        let stopped_daemon = daemon.clone();
+        let shutdown_guard = stream_token.on_cancel(move || {
+            let _ = stopped_daemon.shutdown();
+        });
+
+        let address_family = self.address_family;
+        Ok(EventStream::spawn(move |sender| {
+            let _shutdown_guard = shutdown_guard;
+            let mut tracker = ServiceTracker::new(sender, address_family);
+            let result = forward_events(&receiver, &mut tracker, deadline);
+            // Best effort, browsing is over anyway
+            let _ = daemon.shutdown();
+            result
+        })
+        .cancel_on_drop(stream_token))
+    }
+
+    #[cfg(feature = "tcp")]
+    fn browse_server(
+        &self,
+        address: Option<SocketAddrV4>,
+        deadline: Option<Instant>,
+    ) -> EventStream<MDNSEvent> {
+        let mut server = address.map_or_else(ADBServer::default, ADBServer::new);
+        let service_types = self.service_types.clone();
+        let address_family = self.address_family;
+        let stream_token = CancelToken::new();
+        let polling_token = stream_token.clone();
+        EventStream::spawn(move |sender| {
+            let mut tracker = ServiceTracker::new(sender, address_family);
+            while !polling_token.is_cancelled() {
+                let mut advertised = HashSet::new();
+                for service in server.mdns_services()? {
+                    let Some(service_type) = MDNSServiceType::from_reg_type(&service.reg_type)
+                        .filter(|service_type| service_types.contains(service_type))
+                    else {
+                        continue;
+                    };
+                    let device = MDNSDevice::from_server(service_type, &service);
+                    advertised.insert(device.fullname.clone());
+                    tracker.resolved(device)?;
+                }
+                tracker.retain(|fullname| advertised.contains(fullname))?;
+
+                let remaining =
+                    deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+                if remaining == Some(Duration::ZERO) {
+                    return Ok(());
+                }
+                std::thread::sleep(remaining.map_or(MDNS_SERVER_POLL_INTERVAL, |remaining| {
+                    remaining.min(MDNS_SERVER_POLL_INTERVAL)
+                }));
+            }
+            Ok(())
+        })
+        .cancel_on_drop(stream_token)
+    }
+}
+
+/// Services currently advertised, turning resolutions and removals into [`MDNSEvent`]s
+struct ServiceTracker<'a> {
+    sender: &'a EventSender<MDNSEvent>,
+    address_family: MDNSAddressFamily,
+    services: HashMap<String, MDNSDevice>,
+}
+
+impl<'a> ServiceTracker<'a> {
+    fn new(sender: &'a EventSender<MDNSEvent>, address_family: MDNSAddressFamily) -> Self {
+        Self {
+            sender,
+            address_family,
+            services: HashMap::new(),
+        }
+    }
+
+    fn resolved(&mut self, mut device: MDNSDevice) -> Result<()> {
+        device
+            .addresses
+            .retain(|address| self.address_family.contains(address));
+        if device.addresses.is_empty() {
+            return Ok(());
+        }
+
+        match self
+            .services
+            .insert(device.fullname.clone(), device.clone())
+        {
+            None => self.sender.send(MDNSEvent::Added(device)),
+            Some(previous) if previous != device => self.sender.send(MDNSEvent::Updated(device)),
+            // Services are resolved again when their records are refreshed
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn removed(&mut self, fullname: &str) -> Result<()> {
+        match self.services.remove(fullname) {
+            Some(device) => self
+                .sender
+                .send(MDNSEvent::Removed(device.service_type, device.fullname)),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove services whose full name does not match `advertised`
+    #[cfg(feature = "tcp")]
+    fn retain(&mut self, advertised: impl Fn(&str) -> bool) -> Result<()> {
+        let gone: Vec<String> = self
+            .services
+            .keys()
+            .filter(|fullname| !advertised(fullname))
+            .cloned()
+            .collect();
+        for fullname in gone {
+            self.removed(&fullname)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn forward_events(
+    receiver: &mpsc::Receiver<(MDNSServiceType, ServiceEvent)>,
+    tracker: &mut ServiceTracker,
+    deadline: Option<Instant>,
+) -> Result<()> {
+    loop {
+        let event = match deadline {
+            Some(deadline) => receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .ok(),
+            None => receiver.recv().ok(),
+        };
+        match event {
+            Some((service_type, ServiceEvent::ServiceResolved(service_info))) => {
+                tracker.resolved(MDNSDevice::new(service_type, &service_info))?
+            }
+            Some((_, ServiceEvent::ServiceRemoved(_, fullname))) => tracker.removed(&fullname)?,
+            Some(_) => {}
+            None => return Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_forward_events() {
+    let service_info = |port| {
+        mdns_sd::ServiceInfo::new(
+            MDNSServiceType::TlsConnect.service_type(),
+            "adb-1A2B3C4D",
+            "android.local.",
+            "192.168.1.23",
+            port,
+            [("name", "Pixel 8")].as_slice(),
+        )
+        .expect("cannot create service info")
+    };
+    let fullname = service_info(5555).get_fullname().to_string();
+
+    let (events, receiver) = mpsc::channel();
+    for event in [
+        ServiceEvent::ServiceResolved(service_info(5555)),
+        ServiceEvent::ServiceResolved(service_info(5555)),
+        ServiceEvent::ServiceResolved(service_info(37851)),
+        ServiceEvent::ServiceRemoved(String::new(), fullname.clone()),
+        ServiceEvent::ServiceRemoved(String::new(), fullname.clone()),
+    ] {
+        events
+            .send((MDNSServiceType::TlsConnect, event))
+            .expect("cannot send event");
+    }
+    drop(events);
+
+    let stream = EventStream::spawn(move |sender| {
+        let mut tracker = ServiceTracker::new(sender, MDNSAddressFamily::IPv4);
+        forward_events(&receiver, &mut tracker, None)
+    });
+    let events = stream
+        .collect::<Result<Vec<_>>>()
+        .expect("cannot forward events");
+    assert!(matches!(
+        &events[..],
+        [
+            MDNSEvent::Added(added @ MDNSDevice { port: 5555, .. }),
+            MDNSEvent::Updated(MDNSDevice { port: 37851, .. }),
+            MDNSEvent::Removed(MDNSServiceType::TlsConnect, name),
+        ] if *name == fullname && added.instance_name == "adb-1A2B3C4D" && added.name() == "Pixel 8"
+    ));
+}
+
+#[cfg(feature = "tcp")]
+#[test]
+fn test_from_reg_type() {
+    assert_eq!(
+        MDNSServiceType::from_reg_type("_adb-tls-connect._tcp"),
+        Some(MDNSServiceType::TlsConnect)
+    );
+    assert_eq!(
+        MDNSServiceType::from_reg_type("_adb._tcp."),
+        Some(MDNSServiceType::Adb)
+    );
+}
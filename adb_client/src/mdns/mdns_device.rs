@@ -1,19 +1,71 @@
-use std::{collections::HashSet, net::IpAddr};
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+};
+
+use crate::MDNSServiceType;
+#[cfg(feature = "tcp")]
+use crate::MDNSServices;
 
 /// Represent a device found from mdns search
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MDNSDevice {
+    /// Type of advertised service, telling whether device can be connected to or paired with
+    pub service_type: MDNSServiceType,
+    /// Service instance name (e.g. `adb-1A2B3C4D-XyZ123`)
+    pub instance_name: String,
     /// Full device address when resolved
     pub fullname: String,
+    /// Host name of device (e.g. `Android.local.`)
+    pub hostname: String,
     /// Device IP addresses
     pub addresses: HashSet<IpAddr>,
+    /// Port of advertised service
+    pub port: u16,
+    /// TXT attributes of advertised service
+    pub txt: HashMap<String, String>,
 }
 
-impl From<mdns_sd::ServiceInfo> for MDNSDevice {
-    fn from(value: mdns_sd::ServiceInfo) -> Self {
+impl MDNSDevice {
+    pub(crate) fn new(service_type: MDNSServiceType, value: &mdns_sd::ServiceInfo) -> Self {
+        let fullname = value.get_fullname();
+        let instance_name = fullname
+            .strip_suffix(value.get_type())
+            .map_or(fullname, |name| name.trim_end_matches('.'));
+
         Self {
-            fullname: value.get_fullname().to_string(),
+            service_type,
+            instance_name: instance_name.to_string(),
+            fullname: fullname.to_string(),
+            hostname: value.get_hostname().to_string(),
             addresses: value.get_addresses().to_owned(),
+            port: value.get_port(),
+            txt: value
+                .get_properties()
+                .iter()
+                .map(|property| (property.key().to_string(), property.val_str().to_string()))
+                .collect(),
+        }
+    }
+
+    /// Build a device from a service discovered by ADB server, which only knows its IPv4 address and port
+    #[cfg(feature = "tcp")]
+    pub(crate) fn from_server(service_type: MDNSServiceType, service: &MDNSServices) -> Self {
+        Self {
+            service_type,
+            instance_name: service.service_name.clone(),
+            fullname: format!("{}.{}", service.service_name, service_type.service_type()),
+            hostname: String::new(),
+            addresses: HashSet::from([IpAddr::V4(*service.socket_v4.ip())]),
+            port: service.socket_v4.port(),
+            txt: HashMap::new(),
         }
     }
+
+    /// Name to display for this device: its `name` TXT attribute when advertised, its instance name otherwise
+    pub fn name(&self) -> &str {
+        self.txt
+            .get("name")
+            .map_or(&self.instance_name, |name| name)
+    }
 }
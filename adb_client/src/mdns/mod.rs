@@ -1,5 +1,7 @@
+mod mdns_browser;
 mod mdns_device;
-mod mdns_discovery;
 
+pub use mdns_browser::{
+    MDNSAddressFamily, MDNSBrowser, MDNSBrowserBackend, MDNSEvent, MDNSServiceType,
+};
 pub use mdns_device::MDNSDevice;
-pub use mdns_discovery::MDNSDiscoveryService;
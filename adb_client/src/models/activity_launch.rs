@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use crate::{Result, RustADBError};
+
+/// Result of an activity launch waited for, as reported by `am start -W`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityLaunch {
+    /// Launch status, `ok` on success (`timeout` if activity has not been drawn in time)
+    pub status: String,
+    /// Launch state, if reported (`COLD`, `WARM` or `HOT`)
+    pub launch_state: Option<String>,
+    /// Activity which has been launched (e.g. `com.example/.MainActivity`)
+    pub activity: Option<String>,
+    /// Time taken to launch activity, from start of its process to its first frame
+    pub total_time: Option<Duration>,
+    /// Total time taken by activity manager to handle launch
+    pub wait_time: Option<Duration>,
+}
+
+impl ActivityLaunch {
+    /// Parse output of `am start -W`
+    pub(crate) fn parse(output: &str) -> Result<Self> {
+        let mut status = None;
+        let mut launch = Self {
+            status: String::new(),
+            launch_state: None,
+            activity: None,
+            total_time: None,
+            wait_time: None,
+        };
+
+        for line in output.lines().map(str::trim) {
+            if line.starts_with("Error") {
+                if line.contains("unable to resolve Intent") {
+                    return Err(RustADBError::UnresolvedIntent(line.to_string()));
+                }
+                return Err(RustADBError::ADBRequestFailed(line.to_string()));
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            let millis = || value.parse().ok().map(Duration::from_millis);
+            match key {
+                "Status" => status = Some(value.to_string()),
+                "LaunchState" => launch.launch_state = Some(value.to_string()),
+                "Activity" => launch.activity = Some(value.to_string()),
+                "TotalTime" => launch.total_time = millis(),
+                "WaitTime" => launch.wait_time = millis(),
+                _ => {}
+            }
+        }
+
+        launch.status = status.ok_or_else(|| {
+            RustADBError::ADBRequestFailed(format!("no launch status in {:?}", output.trim()))
+        })?;
+        Ok(launch)
+    }
+}
+
+#[test]
+fn test_activity_launch_parse() {
+    let output = "Starting: Intent { cmp=com.example/.MainActivity }\n\
+        Status: ok\n\
+        LaunchState: COLD\n\
+        Activity: com.example/.MainActivity\n\
+        TotalTime: 345\n\
+        WaitTime: 350\n\
+        Complete\n";
+    assert_eq!(
+        ActivityLaunch::parse(output).expect("cannot parse launch"),
+        ActivityLaunch {
+            status: "ok".to_string(),
+            launch_state: Some("COLD".to_string()),
+            activity: Some("com.example/.MainActivity".to_string()),
+            total_time: Some(Duration::from_millis(345)),
+            wait_time: Some(Duration::from_millis(350)),
+        }
+    );
+
+    let output = "Starting: Intent { cmp=com.example/.Missing }\n\
+        Error type 3\n\
+        Error: Activity class {com.example/com.example.Missing} does not exist.\n";
+    assert!(matches!(
+        ActivityLaunch::parse(output),
+        Err(RustADBError::ADBRequestFailed(_))
+    ));
+
+    let output = "Starting: Intent { act=android.intent.action.VIEW dat=myapp://home }\n\
+        Error: Activity not started, unable to resolve Intent { act=android.intent.action.VIEW dat=myapp://home }\n";
+    assert!(matches!(
+        ActivityLaunch::parse(output),
+        Err(RustADBError::UnresolvedIntent(_))
+    ));
+}
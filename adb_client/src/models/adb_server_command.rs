@@ -23,21 +23,31 @@ pub(crate) enum AdbServerCommand {
     ServerStatus,
     ReconnectOffline,
     Uninstall(String),
-    Install(u64),
-    WaitForDevice(WaitForDeviceState, WaitForDeviceTransport),
+    Install(String),
+    WaitForDevice(Option<String>, WaitForDeviceState, WaitForDeviceTransport),
     // Local commands
     ShellCommand(String),
     Shell,
     FrameBuffer,
     Sync,
     Reboot(RebootType),
-    Forward(String, String),
-    ForwardRemoveAll,
+    // Forward commands, applying to device of given serial or to the only one connected
+    Forward(Option<String>, String, String),
+    ForwardRemove(Option<String>, String),
+    ForwardRemoveAll(Option<String>),
+    ForwardList,
     Reverse(String, String),
     ReverseRemoveAll,
     Reconnect,
-    TcpIp(u16),
-    Usb,
+    Service(String),
+}
+
+/// Prefix of host services applying to device of `serial`, or to the only one connected
+fn host_prefix(serial: &Option<String>) -> String {
+    match serial {
+        Some(serial) => format!("host-serial:{serial}:"),
+        None => "host:".to_string(),
+    }
 }
 
 impl Display for AdbServerCommand {
@@ -69,10 +79,16 @@ impl Display for AdbServerCommand {
                 write!(f, "host:pair:{code}:{addr}")
             }
             AdbServerCommand::FrameBuffer => write!(f, "framebuffer:"),
-            AdbServerCommand::Forward(remote, local) => {
-                write!(f, "host:forward:{local};{remote}")
+            AdbServerCommand::Forward(serial, local, remote) => {
+                write!(f, "{}forward:{local};{remote}", host_prefix(serial))
             }
-            AdbServerCommand::ForwardRemoveAll => write!(f, "host:killforward-all"),
+            AdbServerCommand::ForwardRemove(serial, local) => {
+                write!(f, "{}killforward:{local}", host_prefix(serial))
+            }
+            AdbServerCommand::ForwardRemoveAll(serial) => {
+                write!(f, "{}killforward-all", host_prefix(serial))
+            }
+            AdbServerCommand::ForwardList => write!(f, "host:list-forward"),
             AdbServerCommand::Reverse(remote, local) => {
                 write!(f, "reverse:forward:{remote};{local}")
             }
@@ -82,18 +98,20 @@ impl Display for AdbServerCommand {
             AdbServerCommand::ServerStatus => write!(f, "host:server-status"),
             AdbServerCommand::Reconnect => write!(f, "reconnect"),
             AdbServerCommand::ReconnectOffline => write!(f, "host:reconnect-offline"),
-            AdbServerCommand::TcpIp(port) => {
-                write!(f, "tcpip:{port}")
-            }
-            AdbServerCommand::Usb => write!(f, "usb:"),
-            AdbServerCommand::Install(size) => write!(f, "exec:cmd package 'install' -S {size}"),
+            AdbServerCommand::Service(service) => write!(f, "{service}"),
+            AdbServerCommand::Install(args) => write!(f, "exec:cmd package 'install' {args}"),
             AdbServerCommand::Uninstall(package) => {
                 write!(f, "exec:cmd package 'uninstall' {package}")
             }
-            AdbServerCommand::WaitForDevice(wait_for_device_state, wait_for_device_transport) => {
+            AdbServerCommand::WaitForDevice(
+                serial,
+                wait_for_device_state,
+                wait_for_device_transport,
+            ) => {
                 write!(
                     f,
-                    "host:wait-for-{wait_for_device_transport}-{wait_for_device_state}"
+                    "{}wait-for-{wait_for_device_transport}-{wait_for_device_state}",
+                    host_prefix(serial)
                 )
             }
         }
@@ -110,3 +128,42 @@ fn test_pair_command() {
     assert_eq!(pair.to_string(), format!("host:pair:{code}:{host}"));
     assert_ne!(pair.to_string(), format!("host:pair:{code_u32}:{host}"))
 }
+
+#[test]
+fn test_forward_commands() {
+    let serial = Some("emulator-5554".to_string());
+    assert_eq!(
+        AdbServerCommand::Forward(serial.clone(), "tcp:8080".into(), "tcp:80".into()).to_string(),
+        "host-serial:emulator-5554:forward:tcp:8080;tcp:80"
+    );
+    assert_eq!(
+        AdbServerCommand::ForwardRemove(None, "tcp:8080".into()).to_string(),
+        "host:killforward:tcp:8080"
+    );
+    assert_eq!(
+        AdbServerCommand::ForwardRemoveAll(serial).to_string(),
+        "host-serial:emulator-5554:killforward-all"
+    );
+}
+
+#[test]
+fn test_wait_for_device_command() {
+    assert_eq!(
+        AdbServerCommand::WaitForDevice(
+            None,
+            WaitForDeviceState::Device,
+            WaitForDeviceTransport::Usb
+        )
+        .to_string(),
+        "host:wait-for-usb-device"
+    );
+    assert_eq!(
+        AdbServerCommand::WaitForDevice(
+            Some("emulator-5554".to_string()),
+            WaitForDeviceState::Disconnect,
+            WaitForDeviceTransport::Any
+        )
+        .to_string(),
+        "host-serial:emulator-5554:wait-for-any-disconnect"
+    );
+}
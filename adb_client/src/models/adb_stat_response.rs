@@ -8,6 +8,8 @@ use std::{
 use byteorder::LittleEndian;
 use serde::{Deserialize, Serialize};
 
+use crate::RustADBError;
+
 /// Represents a `stat` response
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AdbStatResponse {
@@ -29,6 +31,25 @@ impl From<[u8; 12]> for AdbStatResponse {
     }
 }
 
+impl TryFrom<&[u8]> for AdbStatResponse {
+    type Error = RustADBError;
+
+    /// Parse a sync `STAT` response, including its leading `STAT` identifier
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            [b'S', b'T', b'A', b'T', data @ ..] => {
+                let data: [u8; 12] = data.try_into().map_err(|_| {
+                    RustADBError::InvalidMessage(format!("stat response of {} bytes", value.len()))
+                })?;
+                Ok(data.into())
+            }
+            _ => Err(RustADBError::InvalidMessage(
+                "stat response does not start with STAT".to_string(),
+            )),
+        }
+    }
+}
+
 impl Display for AdbStatResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let d = UNIX_EPOCH + Duration::from_secs(self.mod_time.into());
@@ -0,0 +1,156 @@
+use std::fmt::Display;
+
+use byteorder::{ByteOrder, LittleEndian};
+use chrono::DateTime;
+
+use super::AdbStatResponse;
+use super::dir_entry::{S_IFDIR, S_IFLNK, S_IFMT, S_IFREG};
+use crate::RustADBError;
+
+/// `errno` value of a missing file
+const ENOENT: u32 = 2;
+
+/// Represents a `stat_v2` response, as returned by [`crate::ADBDeviceExt::stat_v2`].
+///
+/// Devices not supporting `stat_v2` only report mode, size and modification time, other fields being zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdbStatV2Response {
+    /// `errno` value of failed `stat(2)` call, `0` on success. Other fields are zero on failure.
+    pub error: u32,
+    /// Identifier of device containing file
+    pub dev: u64,
+    /// Inode number
+    pub ino: u64,
+    /// File type and permissions, as `st_mode` of `stat(2)`
+    pub mode: u32,
+    /// Number of hard links
+    pub nlink: u32,
+    /// User identifier of owner
+    pub uid: u32,
+    /// Group identifier of owner
+    pub gid: u32,
+    /// File size, in bytes
+    pub size: u64,
+    /// Last access time, in seconds since Unix epoch
+    pub atime: i64,
+    /// Last modification time, in seconds since Unix epoch
+    pub mtime: i64,
+    /// Last status change time, in seconds since Unix epoch
+    pub ctime: i64,
+}
+
+impl AdbStatV2Response {
+    /// Return `true` if file is a directory
+    pub fn is_dir(&self) -> bool {
+        self.mode & S_IFMT == S_IFDIR
+    }
+
+    /// Return `true` if file is a regular file
+    pub fn is_file(&self) -> bool {
+        self.mode & S_IFMT == S_IFREG
+    }
+
+    /// Return `true` if file is a symbolic link
+    pub fn is_symlink(&self) -> bool {
+        self.mode & S_IFMT == S_IFLNK
+    }
+}
+
+impl From<AdbStatResponse> for AdbStatV2Response {
+    /// Convert a legacy `STAT` response, which reports missing files as a zero mode
+    fn from(value: AdbStatResponse) -> Self {
+        Self {
+            error: if value.file_perm == 0 { ENOENT } else { 0 },
+            dev: 0,
+            ino: 0,
+            mode: value.file_perm,
+            nlink: 0,
+            uid: 0,
+            gid: 0,
+            size: value.file_size.into(),
+            atime: 0,
+            mtime: value.mod_time.into(),
+            ctime: 0,
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for AdbStatV2Response {
+    type Error = RustADBError;
+
+    /// Parse a sync `STA2` or `LST2` response, including its leading identifier
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let data = match value {
+            [b'S', b'T', b'A', b'2', data @ ..] | [b'L', b'S', b'T', b'2', data @ ..] => data,
+            _ => {
+                return Err(RustADBError::InvalidMessage(
+                    "stat response does not start with STA2 or LST2".to_string(),
+                ));
+            }
+        };
+        if data.len() != 68 {
+            return Err(RustADBError::InvalidMessage(format!(
+                "stat response of {} bytes",
+                value.len()
+            )));
+        }
+
+        Ok(Self {
+            error: LittleEndian::read_u32(&data[0..4]),
+            dev: LittleEndian::read_u64(&data[4..12]),
+            ino: LittleEndian::read_u64(&data[12..20]),
+            mode: LittleEndian::read_u32(&data[20..24]),
+            nlink: LittleEndian::read_u32(&data[24..28]),
+            uid: LittleEndian::read_u32(&data[28..32]),
+            gid: LittleEndian::read_u32(&data[32..36]),
+            size: LittleEndian::read_u64(&data[36..44]),
+            atime: LittleEndian::read_i64(&data[44..52]),
+            mtime: LittleEndian::read_i64(&data[52..60]),
+            ctime: LittleEndian::read_i64(&data[60..68]),
+        })
+    }
+}
+
+impl Display for AdbStatV2Response {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format_time = |time: i64| {
+            DateTime::from_timestamp(time, 0)
+                .unwrap_or_default()
+                .format("%Y-%m-%d %H:%M:%S %Z")
+        };
+
+        writeln!(f, "Mode: {:06o}", self.mode)?;
+        writeln!(f, "Size: {} bytes", self.size)?;
+        writeln!(f, "Links: {}", self.nlink)?;
+        writeln!(f, "Owner: {}:{}", self.uid, self.gid)?;
+        writeln!(f, "Device: {} Inode: {}", self.dev, self.ino)?;
+        writeln!(f, "Access time: {}", format_time(self.atime))?;
+        writeln!(f, "Modification time: {}", format_time(self.mtime))?;
+        write!(f, "Change time: {}", format_time(self.ctime))
+    }
+}
+
+#[test]
+fn test_parse_stat_v2_response() {
+    let mut response = b"STA2".to_vec();
+    response.extend_from_slice(&0_u32.to_le_bytes());
+    response.extend_from_slice(&0xfd00_u64.to_le_bytes());
+    response.extend_from_slice(&1234_u64.to_le_bytes());
+    response.extend_from_slice(&0o100644_u32.to_le_bytes());
+    response.extend_from_slice(&1_u32.to_le_bytes());
+    response.extend_from_slice(&2000_u32.to_le_bytes());
+    response.extend_from_slice(&1015_u32.to_le_bytes());
+    response.extend_from_slice(&(5_u64 << 32).to_le_bytes());
+    for time in [1_700_000_000_i64, 1_700_000_001, 1_700_000_002] {
+        response.extend_from_slice(&time.to_le_bytes());
+    }
+
+    let stat = AdbStatV2Response::try_from(response.as_slice()).expect("cannot parse response");
+    assert!(stat.is_file());
+    assert_eq!((stat.uid, stat.gid), (2000, 1015));
+    assert_eq!(stat.size, 5 << 32);
+    assert_eq!(stat.mtime, 1_700_000_001);
+
+    assert!(AdbStatV2Response::try_from(&response[..40]).is_err());
+    assert!(AdbStatV2Response::try_from(b"STAT".as_slice()).is_err());
+}
@@ -0,0 +1,76 @@
+use crate::obb::check_package_name;
+use crate::{Result, RustADBError};
+
+/// Content of an `adb backup` archive, see [`crate::ADBDeviceExt::backup`].
+#[derive(Debug, Clone, Default)]
+pub struct BackupOptions {
+    /// Packages to back up, in addition to all packages if `all` is set
+    pub packages: Vec<String>,
+    /// Back up all installed packages
+    pub all: bool,
+    /// Include APKs of packages, not only their data
+    pub apk: bool,
+    /// Include content of shared storage (`/sdcard`)
+    pub shared: bool,
+}
+
+impl BackupOptions {
+    /// Back up data of `packages`, without their APKs
+    pub fn packages<S: ToString>(packages: &[S]) -> Self {
+        Self {
+            packages: packages.iter().map(ToString::to_string).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Service backing up what these options select, as understood by `bu backup`
+    pub(crate) fn service(&self) -> Result<String> {
+        if !self.all && self.packages.is_empty() {
+            return Err(RustADBError::ADBRequestFailed(
+                "no package to back up".to_string(),
+            ));
+        }
+
+        let mut args = vec![
+            if self.apk { "-apk" } else { "-noapk" },
+            if self.shared { "-shared" } else { "-noshared" },
+        ];
+        if self.all {
+            args.push("-all");
+        }
+        for package in &self.packages {
+            check_package_name(package)?;
+            args.push(package);
+        }
+
+        Ok(format!("backup:{}", args.join(" ")))
+    }
+}
+
+#[test]
+fn test_backup_options_service() {
+    assert_eq!(
+        BackupOptions::packages(&["com.example.app"])
+            .service()
+            .expect("cannot build service"),
+        "backup:-noapk -noshared com.example.app"
+    );
+
+    let options = BackupOptions {
+        all: true,
+        apk: true,
+        shared: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        options.service().expect("cannot build service"),
+        "backup:-apk -shared -all"
+    );
+
+    assert!(BackupOptions::default().service().is_err());
+    assert!(
+        BackupOptions::packages(&["com.example; reboot"])
+            .service()
+            .is_err()
+    );
+}
@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+/// Wakelock held by an app, as reported by [`crate::ADBDeviceExt::batterystats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wakelock {
+    /// User identifier of app holding wakelock
+    pub uid: u32,
+    /// Name (tag) of wakelock
+    pub name: String,
+    /// Time spent holding wakelock as a full wakelock
+    pub full_time: Duration,
+    /// Number of times wakelock has been acquired as a full wakelock
+    pub full_count: u64,
+    /// Time spent holding wakelock as a partial wakelock (keeping CPU awake)
+    pub partial_time: Duration,
+    /// Number of times wakelock has been acquired as a partial wakelock
+    pub partial_count: u64,
+}
+
+/// Network usage of an app, as reported by [`crate::ADBDeviceExt::batterystats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkUsage {
+    /// User identifier of app
+    pub uid: u32,
+    /// Bytes received over mobile network
+    pub mobile_rx_bytes: u64,
+    /// Bytes sent over mobile network
+    pub mobile_tx_bytes: u64,
+    /// Bytes received over Wi-Fi
+    pub wifi_rx_bytes: u64,
+    /// Bytes sent over Wi-Fi
+    pub wifi_tx_bytes: u64,
+}
+
+/// Scheduled job run by an app, as reported by [`crate::ADBDeviceExt::batterystats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobStats {
+    /// User identifier of app
+    pub uid: u32,
+    /// Name of job, usually its service component
+    pub name: String,
+    /// Total time spent running job
+    pub total_time: Duration,
+    /// Number of times job has been run
+    pub count: u64,
+}
+
+/// Battery statistics of a package since last charge, parsed from `dumpsys batterystats --checkin`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatteryStats {
+    /// Wakelocks held
+    pub wakelocks: Vec<Wakelock>,
+    /// Network usage, one entry per user identifier
+    pub network: Vec<NetworkUsage>,
+    /// Jobs run
+    pub jobs: Vec<JobStats>,
+}
+
+impl BatteryStats {
+    /// Parse checkin output, made of `<version>,<uid>,<aggregation>,<section>,<values...>` lines.
+    ///
+    /// Only statistics aggregated since last charge (`l`) are kept, missing wakelock counters being reported as zero.
+    pub(crate) fn parse(output: &str) -> Self {
+        let mut stats = Self::default();
+
+        for line in output.lines() {
+            let fields: Vec<&str> = line.trim().split(',').collect();
+            let [_, uid, "l", section, values @ ..] = fields.as_slice() else {
+                continue;
+            };
+            let Ok(uid) = uid.parse() else {
+                continue;
+            };
+            let number = |index: usize| values.get(index).and_then(|value| value.parse().ok());
+
+            match *section {
+                // `wl,<name>,<full time>,f,<full count>,...,<partial time>,p,<partial count>,...`,
+                // time and count of each kind surrounding its marker
+                "wl" => {
+                    let Some(name) = values.first() else {
+                        continue;
+                    };
+                    let around = |marker: &str| {
+                        let index = values.iter().position(|value| *value == marker)?;
+                        Some((
+                            Duration::from_millis(number(index.checked_sub(1)?)?),
+                            number(index + 1)?,
+                        ))
+                    };
+                    let (full_time, full_count) = around("f").unwrap_or_default();
+                    let (partial_time, partial_count) = around("p").unwrap_or_default();
+                    stats.wakelocks.push(Wakelock {
+                        uid,
+                        name: name.to_string(),
+                        full_time,
+                        full_count,
+                        partial_time,
+                        partial_count,
+                    });
+                }
+                // `nt,<mobile rx>,<mobile tx>,<wifi rx>,<wifi tx>,...`, in bytes
+                "nt" => {
+                    if let (
+                        Some(mobile_rx_bytes),
+                        Some(mobile_tx_bytes),
+                        Some(wifi_rx_bytes),
+                        Some(wifi_tx_bytes),
+                    ) = (number(0), number(1), number(2), number(3))
+                    {
+                        stats.network.push(NetworkUsage {
+                            uid,
+                            mobile_rx_bytes,
+                            mobile_tx_bytes,
+                            wifi_rx_bytes,
+                            wifi_tx_bytes,
+                        });
+                    }
+                }
+                // `jb,<name>,<total time>,<count>`, job names possibly holding commas
+                "jb" if values.len() >= 3 => {
+                    let (name, counters) = values.split_at(values.len() - 2);
+                    if let (Ok(total_time), Ok(count)) = (counters[0].parse(), counters[1].parse())
+                    {
+                        stats.jobs.push(JobStats {
+                            uid,
+                            name: name.join(","),
+                            total_time: Duration::from_millis(total_time),
+                            count,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        stats
+    }
+}
+
+#[test]
+fn test_battery_stats_parse() {
+    let output = "9,0,i,vers,36,214,TP1A,TP1A\n\
+        9,10123,i,uid,10123,com.example\n\
+        9,10123,l,wl,*alarm*,0,f,0,0,0,0,1520,p,12,0,30,1520,0,w,0\n\
+        9,10123,l,nt,2048,1024,4096,512,20,10,40,5,0,0\n\
+        9,10123,l,jb,com.example/.SyncJob,3000,2\n\
+        9,10123,u,jb,com.example/.SyncJob,1000,1\n\
+        9,10123,l,wl,truncated\n";
+
+    assert_eq!(
+        BatteryStats::parse(output),
+        BatteryStats {
+            wakelocks: vec![
+                Wakelock {
+                    uid: 10123,
+                    name: "*alarm*".to_string(),
+                    full_time: Duration::ZERO,
+                    full_count: 0,
+                    partial_time: Duration::from_millis(1520),
+                    partial_count: 12,
+                },
+                Wakelock {
+                    uid: 10123,
+                    name: "truncated".to_string(),
+                    full_time: Duration::ZERO,
+                    full_count: 0,
+                    partial_time: Duration::ZERO,
+                    partial_count: 0,
+                },
+            ],
+            network: vec![NetworkUsage {
+                uid: 10123,
+                mobile_rx_bytes: 2048,
+                mobile_tx_bytes: 1024,
+                wifi_rx_bytes: 4096,
+                wifi_tx_bytes: 512,
+            }],
+            jobs: vec![JobStats {
+                uid: 10123,
+                name: "com.example/.SyncJob".to_string(),
+                total_time: Duration::from_millis(3000),
+                count: 2,
+            }],
+        }
+    );
+}
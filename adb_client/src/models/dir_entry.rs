@@ -0,0 +1,53 @@
+use std::fmt::Display;
+
+use chrono::DateTime;
+
+/// File type bits of `mode`, as defined by `stat(2)`
+pub(crate) const S_IFMT: u32 = 0o170000;
+pub(crate) const S_IFDIR: u32 = 0o040000;
+pub(crate) const S_IFREG: u32 = 0o100000;
+pub(crate) const S_IFLNK: u32 = 0o120000;
+
+/// Entry of a remote directory, as listed by [`crate::ADBDeviceExt::list_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// Name of entry, relative to listed directory
+    pub name: String,
+    /// File type and permissions, as `st_mode` of `stat(2)`
+    pub mode: u32,
+    /// File size, in bytes
+    pub size: u32,
+    /// Modification time, in seconds since Unix epoch
+    pub mod_time: u32,
+}
+
+impl DirEntry {
+    /// Return `true` if entry is a directory
+    pub fn is_dir(&self) -> bool {
+        self.mode & S_IFMT == S_IFDIR
+    }
+
+    /// Return `true` if entry is a regular file
+    pub fn is_file(&self) -> bool {
+        self.mode & S_IFMT == S_IFREG
+    }
+
+    /// Return `true` if entry is a symbolic link, which is not followed when listing
+    pub fn is_symlink(&self) -> bool {
+        self.mode & S_IFMT == S_IFLNK
+    }
+}
+
+impl Display for DirEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mod_time = DateTime::from_timestamp(self.mod_time.into(), 0).unwrap_or_default();
+        write!(
+            f,
+            "{:06o} {:>10} {} {}",
+            self.mode,
+            self.size,
+            mod_time.format("%Y-%m-%d %H:%M"),
+            self.name
+        )
+    }
+}
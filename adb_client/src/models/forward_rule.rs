@@ -0,0 +1,48 @@
+/// Forward rule of ADB server, as reported by [`crate::ADBServerDevice::forward_list`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForwardRule {
+    /// Serial of device the rule applies to
+    pub serial: String,
+    /// Socket listened on host (e.g. `tcp:8080`)
+    pub local: String,
+    /// Socket connections are forwarded to on device (e.g. `tcp:80` or `localabstract:name`)
+    pub remote: String,
+}
+
+impl ForwardRule {
+    /// Parse `host:list-forward` output, one `<serial> <local> <remote>` rule per line
+    pub(crate) fn parse(output: &str) -> Vec<Self> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                Some(Self {
+                    serial: fields.next()?.to_string(),
+                    local: fields.next()?.to_string(),
+                    remote: fields.next()?.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_forward_rule_parse() {
+    assert_eq!(
+        ForwardRule::parse(
+            "emulator-5554 tcp:8080 tcp:80\nR58M123ABC tcp:9222 localabstract:chrome_devtools_remote\n"
+        ),
+        [
+            ForwardRule {
+                serial: "emulator-5554".to_string(),
+                local: "tcp:8080".to_string(),
+                remote: "tcp:80".to_string(),
+            },
+            ForwardRule {
+                serial: "R58M123ABC".to_string(),
+                local: "tcp:9222".to_string(),
+                remote: "localabstract:chrome_devtools_remote".to_string(),
+            },
+        ]
+    );
+}
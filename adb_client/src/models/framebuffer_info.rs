@@ -1,6 +1,8 @@
+use std::io::Read;
 use std::{iter::Map, slice::ChunksExact};
 
-use byteorder::{ByteOrder, LittleEndian};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
+use image::{ImageBuffer, Rgba};
 
 use crate::{Result, RustADBError};
 
@@ -12,20 +14,33 @@ fn read_next(chunks: &mut U32ChunkIter) -> Result<u32> {
         .ok_or(RustADBError::FramebufferConversionError)?
 }
 
-#[derive(Debug)]
-pub(crate) struct FrameBufferInfoV1 {
-    pub _bpp: u32,
+/// Header of a version 1 framebuffer, which pixels are sent as `RGBA_8888`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameBufferInfoV1 {
+    /// Bits per pixel
+    pub bpp: u32,
+    /// Size of pixel data, in bytes
     pub size: u32,
+    /// Width of framebuffer, in pixels
     pub width: u32,
+    /// Height of framebuffer, in pixels
     pub height: u32,
-    pub _red_offset: u32,
-    pub _red_length: u32,
-    pub _blue_offset: u32,
-    pub _blue_length: u32,
-    pub _green_offset: u32,
-    pub _green_length: u32,
-    pub _alpha_offset: u32,
-    pub _alpha_length: u32,
+    /// Offset of red component in a pixel, in bits
+    pub red_offset: u32,
+    /// Length of red component, in bits
+    pub red_length: u32,
+    /// Offset of blue component in a pixel, in bits
+    pub blue_offset: u32,
+    /// Length of blue component, in bits
+    pub blue_length: u32,
+    /// Offset of green component in a pixel, in bits
+    pub green_offset: u32,
+    /// Length of green component, in bits
+    pub green_length: u32,
+    /// Offset of alpha component in a pixel, in bits
+    pub alpha_offset: u32,
+    /// Length of alpha component, in bits, `0` if pixels have no alpha
+    pub alpha_length: u32,
 }
 
 impl TryFrom<[u8; std::mem::size_of::<Self>()]> for FrameBufferInfoV1 {
@@ -37,37 +52,51 @@ impl TryFrom<[u8; std::mem::size_of::<Self>()]> for FrameBufferInfoV1 {
         let mut chunks: U32ChunkIter = value.chunks_exact(4).map(|v| Ok(LittleEndian::read_u32(v)));
 
         Ok(Self {
-            _bpp: read_next(&mut chunks)?,
+            bpp: read_next(&mut chunks)?,
             size: read_next(&mut chunks)?,
             width: read_next(&mut chunks)?,
             height: read_next(&mut chunks)?,
-            _red_offset: read_next(&mut chunks)?,
-            _red_length: read_next(&mut chunks)?,
-            _blue_offset: read_next(&mut chunks)?,
-            _blue_length: read_next(&mut chunks)?,
-            _green_offset: read_next(&mut chunks)?,
-            _green_length: read_next(&mut chunks)?,
-            _alpha_offset: read_next(&mut chunks)?,
-            _alpha_length: read_next(&mut chunks)?,
+            red_offset: read_next(&mut chunks)?,
+            red_length: read_next(&mut chunks)?,
+            blue_offset: read_next(&mut chunks)?,
+            blue_length: read_next(&mut chunks)?,
+            green_offset: read_next(&mut chunks)?,
+            green_length: read_next(&mut chunks)?,
+            alpha_offset: read_next(&mut chunks)?,
+            alpha_length: read_next(&mut chunks)?,
         })
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct FrameBufferInfoV2 {
-    pub _bpp: u32,
-    pub _color_space: u32,
+/// Header of a version 2 framebuffer, which pixels are sent as `RGBX_8888` along with their color space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameBufferInfoV2 {
+    /// Bits per pixel
+    pub bpp: u32,
+    /// Color space of pixels, `0` if unknown, `1` for sRGB and `2` for Display P3
+    pub color_space: u32,
+    /// Size of pixel data, in bytes
     pub size: u32,
+    /// Width of framebuffer, in pixels
     pub width: u32,
+    /// Height of framebuffer, in pixels
     pub height: u32,
-    pub _red_offset: u32,
-    pub _red_length: u32,
-    pub _blue_offset: u32,
-    pub _blue_length: u32,
-    pub _green_offset: u32,
-    pub _green_length: u32,
-    pub _alpha_offset: u32,
-    pub _alpha_length: u32,
+    /// Offset of red component in a pixel, in bits
+    pub red_offset: u32,
+    /// Length of red component, in bits
+    pub red_length: u32,
+    /// Offset of blue component in a pixel, in bits
+    pub blue_offset: u32,
+    /// Length of blue component, in bits
+    pub blue_length: u32,
+    /// Offset of green component in a pixel, in bits
+    pub green_offset: u32,
+    /// Length of green component, in bits
+    pub green_length: u32,
+    /// Offset of alpha component in a pixel, in bits
+    pub alpha_offset: u32,
+    /// Length of alpha component, in bits, `0` if pixels have no alpha
+    pub alpha_length: u32,
 }
 
 impl TryFrom<[u8; std::mem::size_of::<Self>()]> for FrameBufferInfoV2 {
@@ -79,19 +108,113 @@ impl TryFrom<[u8; std::mem::size_of::<Self>()]> for FrameBufferInfoV2 {
         let mut chunks: U32ChunkIter = value.chunks_exact(4).map(|v| Ok(LittleEndian::read_u32(v)));
 
         Ok(Self {
-            _bpp: read_next(&mut chunks)?,
-            _color_space: read_next(&mut chunks)?,
+            bpp: read_next(&mut chunks)?,
+            color_space: read_next(&mut chunks)?,
             size: read_next(&mut chunks)?,
             width: read_next(&mut chunks)?,
             height: read_next(&mut chunks)?,
-            _red_offset: read_next(&mut chunks)?,
-            _red_length: read_next(&mut chunks)?,
-            _blue_offset: read_next(&mut chunks)?,
-            _blue_length: read_next(&mut chunks)?,
-            _green_offset: read_next(&mut chunks)?,
-            _green_length: read_next(&mut chunks)?,
-            _alpha_offset: read_next(&mut chunks)?,
-            _alpha_length: read_next(&mut chunks)?,
+            red_offset: read_next(&mut chunks)?,
+            red_length: read_next(&mut chunks)?,
+            blue_offset: read_next(&mut chunks)?,
+            blue_length: read_next(&mut chunks)?,
+            green_offset: read_next(&mut chunks)?,
+            green_length: read_next(&mut chunks)?,
+            alpha_offset: read_next(&mut chunks)?,
+            alpha_length: read_next(&mut chunks)?,
         })
     }
 }
+
+/// Header sent by `framebuffer:` service ahead of pixel data, which layout depends on its version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameBufferInfo {
+    /// Version 1 header
+    V1(FrameBufferInfoV1),
+    /// Version 2 header
+    V2(FrameBufferInfoV2),
+}
+
+impl FrameBufferInfo {
+    /// Read version of header from `reader`, followed by header itself
+    pub(crate) fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        match reader.read_u32::<LittleEndian>()? {
+            // RGBA_8888
+            1 => {
+                let mut buf = [0u8; std::mem::size_of::<FrameBufferInfoV1>()];
+                reader.read_exact(&mut buf)?;
+                Ok(Self::V1(buf.try_into()?))
+            }
+            // RGBX_8888
+            2 => {
+                let mut buf = [0u8; std::mem::size_of::<FrameBufferInfoV2>()];
+                reader.read_exact(&mut buf)?;
+                Ok(Self::V2(buf.try_into()?))
+            }
+            v => Err(RustADBError::UnimplementedFramebufferImageVersion(v)),
+        }
+    }
+
+    /// Size of pixel data, in bytes
+    pub fn size(&self) -> u32 {
+        match self {
+            Self::V1(info) => info.size,
+            Self::V2(info) => info.size,
+        }
+    }
+
+    /// Width of framebuffer, in pixels
+    pub fn width(&self) -> u32 {
+        match self {
+            Self::V1(info) => info.width,
+            Self::V2(info) => info.width,
+        }
+    }
+
+    /// Height of framebuffer, in pixels
+    pub fn height(&self) -> u32 {
+        match self {
+            Self::V1(info) => info.height,
+            Self::V2(info) => info.height,
+        }
+    }
+}
+
+/// Framebuffer as sent by device, see [`crate::ADBDeviceExt::framebuffer_raw`].
+#[derive(Debug, Clone)]
+pub struct RawFramebuffer {
+    /// Header describing pixel data
+    pub info: FrameBufferInfo,
+    /// Pixel data, `info.size()` bytes laid out as described by `info`
+    pub data: Vec<u8>,
+}
+
+impl RawFramebuffer {
+    /// Convert into an RGBA image, reusing pixel data as is
+    pub fn into_image(self) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        ImageBuffer::from_vec(self.info.width(), self.info.height(), self.data)
+            .ok_or(RustADBError::FramebufferConversionError)
+    }
+}
+
+#[test]
+fn test_framebuffer_info_read() {
+    let header: Vec<u8> = [2, 32, 1, 8 * 4, 2, 4, 0, 8, 16, 8, 8, 8, 24, 8]
+        .iter()
+        .flat_map(|value: &u32| value.to_le_bytes())
+        .collect();
+
+    let info = FrameBufferInfo::read(&mut header.as_slice()).expect("cannot read header");
+    assert_eq!((info.width(), info.height(), info.size()), (2, 4, 32));
+    assert!(matches!(info, FrameBufferInfo::V2(info) if info.color_space == 1));
+
+    let raw = RawFramebuffer {
+        info,
+        data: vec![0; 32],
+    };
+    assert_eq!(
+        raw.into_image().expect("cannot convert").dimensions(),
+        (2, 4)
+    );
+
+    assert!(FrameBufferInfo::read(&mut [3, 0, 0, 0].as_slice()).is_err());
+}
@@ -0,0 +1,86 @@
+use crate::Result;
+use crate::obb::check_package_name;
+
+/// Flags of package manager `install` command, see [`crate::ADBDeviceExt::install_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Replace app if already installed, keeping its data (`-r`)
+    pub replace: bool,
+    /// Allow installing a lower version code than the installed one, only for debuggable apps (`-d`)
+    pub allow_downgrade: bool,
+    /// Grant all runtime permissions requested by app manifest (`-g`)
+    pub grant_permissions: bool,
+    /// Allow installing apps flagged as test only (`-t`)
+    pub allow_test: bool,
+    /// Install app as an instant app (`--instant`)
+    pub instant: bool,
+    /// Stage installation, applied on next reboot, as needed for APEX packages (`--staged`)
+    pub staged: bool,
+    /// Install app for this user only, for all users if `None` (`--user`)
+    pub user: Option<u32>,
+    /// Package name recorded as installer of app, e.g. `com.android.vending` (`-i`)
+    pub installer_package_name: Option<String>,
+}
+
+impl InstallOptions {
+    /// Arguments of `cmd package install` matching these options, installing `size` bytes read from standard input
+    pub(crate) fn args(&self, size: u64) -> Result<String> {
+        let mut args = self.flags()?;
+        args.push(format!("-S {size}"));
+        Ok(args.join(" "))
+    }
+
+    /// Flags of package manager commands creating an install session matching these options
+    pub(crate) fn flags(&self) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        for (flag, set) in [
+            ("-r", self.replace),
+            ("-d", self.allow_downgrade),
+            ("-g", self.grant_permissions),
+            ("-t", self.allow_test),
+            ("--instant", self.instant),
+            ("--staged", self.staged),
+        ] {
+            if set {
+                args.push(flag.to_string());
+            }
+        }
+        if let Some(user) = self.user {
+            args.push(format!("--user {user}"));
+        }
+        if let Some(installer) = &self.installer_package_name {
+            check_package_name(installer)?;
+            args.push(format!("-i {installer}"));
+        }
+        Ok(args)
+    }
+}
+
+#[test]
+fn test_install_options_args() {
+    assert_eq!(
+        InstallOptions::default()
+            .args(42)
+            .expect("cannot build args"),
+        "-S 42"
+    );
+
+    let options = InstallOptions {
+        replace: true,
+        grant_permissions: true,
+        staged: true,
+        user: Some(10),
+        installer_package_name: Some("com.android.vending".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        options.args(42).expect("cannot build args"),
+        "-r -g --staged --user 10 -i com.android.vending -S 42"
+    );
+
+    let options = InstallOptions {
+        installer_package_name: Some("com.example; reboot".to_string()),
+        ..Default::default()
+    };
+    assert!(options.args(42).is_err());
+}
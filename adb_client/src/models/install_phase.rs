@@ -0,0 +1,10 @@
+/// Phases of an APK installation, as reported to [`crate::AdbObserver::on_install_phase`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallPhase {
+    /// APK content is being sent to device
+    Uploading,
+    /// APK has been sent, device is installing it
+    Installing,
+    /// Installation succeeded
+    Completed,
+}
@@ -0,0 +1,88 @@
+use crate::utils::shell_quote;
+
+/// Intent started by activity manager, see [`crate::ADBDeviceExt::start_activity_wait`].
+#[derive(Debug, Clone, Default)]
+pub struct Intent {
+    /// Action (e.g. `android.intent.action.VIEW`)
+    pub action: Option<String>,
+    /// Data URI
+    pub data: Option<String>,
+    /// MIME type of data
+    pub mime_type: Option<String>,
+    /// Categories (e.g. `android.intent.category.LAUNCHER`)
+    pub categories: Vec<String>,
+    /// Explicit component, as `package/activity` (e.g. `com.example/.MainActivity`)
+    pub component: Option<String>,
+    /// String extras, as name and value
+    pub extras: Vec<(String, String)>,
+    /// Flags (e.g. `0x10000000` for `FLAG_ACTIVITY_NEW_TASK`)
+    pub flags: Option<u32>,
+}
+
+impl Intent {
+    /// Intent explicitly starting `activity` of `package`
+    pub fn component(package: &str, activity: &str) -> Self {
+        Self {
+            component: Some(format!("{package}/{activity}")),
+            ..Default::default()
+        }
+    }
+
+    /// Intent viewing `url`, as done when opening a link (e.g. `https://example.com` or a deep link like `myapp://home`)
+    pub fn view(url: &str) -> Self {
+        Self {
+            action: Some("android.intent.action.VIEW".to_string()),
+            data: Some(url.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Arguments of `am start` matching this intent, quoted for device shell
+    pub(crate) fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(action) = &self.action {
+            args.push(format!("-a {}", shell_quote(action)));
+        }
+        if let Some(data) = &self.data {
+            args.push(format!("-d {}", shell_quote(data)));
+        }
+        if let Some(mime_type) = &self.mime_type {
+            args.push(format!("-t {}", shell_quote(mime_type)));
+        }
+        for category in &self.categories {
+            args.push(format!("-c {}", shell_quote(category)));
+        }
+        for (name, value) in &self.extras {
+            args.push(format!("--es {} {}", shell_quote(name), shell_quote(value)));
+        }
+        if let Some(flags) = self.flags {
+            args.push(format!("-f {flags:#x}"));
+        }
+        // Component comes last, as am would otherwise read it as an URI
+        if let Some(component) = &self.component {
+            args.push(shell_quote(component));
+        }
+        args
+    }
+}
+
+#[test]
+fn test_intent_args() {
+    let intent = Intent {
+        action: Some("android.intent.action.VIEW".to_string()),
+        data: Some("https://example.com/?q=it's".to_string()),
+        extras: vec![("name".to_string(), "value".to_string())],
+        flags: Some(0x1000_0000),
+        ..Intent::component("com.example", ".MainActivity")
+    };
+    assert_eq!(
+        intent.args(),
+        [
+            "-a 'android.intent.action.VIEW'",
+            r"-d 'https://example.com/?q=it'\''s'",
+            "--es 'name' 'value'",
+            "-f 0x10000000",
+            "'com.example/.MainActivity'",
+        ]
+    );
+}
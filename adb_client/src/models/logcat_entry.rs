@@ -0,0 +1,186 @@
+use std::fmt::Display;
+use std::time::{Duration, SystemTime};
+
+/// Priority of a log message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogPriority {
+    /// Verbose (`V`)
+    Verbose,
+    /// Debug (`D`)
+    Debug,
+    /// Info (`I`)
+    Info,
+    /// Warning (`W`)
+    Warn,
+    /// Error (`E`)
+    Error,
+    /// Fatal, e.g. for `Log.wtf` (`F`)
+    Fatal,
+}
+
+impl LogPriority {
+    /// Priority printed by logcat as `letter`
+    pub(crate) fn from_letter(letter: &str) -> Option<Self> {
+        match letter {
+            "V" => Some(Self::Verbose),
+            "D" => Some(Self::Debug),
+            "I" => Some(Self::Info),
+            "W" => Some(Self::Warn),
+            "E" => Some(Self::Error),
+            "F" => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+
+    /// Priority stored as `value` by logger, as defined by `android_LogPriority`
+    fn from_value(value: u8) -> Option<Self> {
+        match value {
+            2 => Some(Self::Verbose),
+            3 => Some(Self::Debug),
+            4 => Some(Self::Info),
+            5 => Some(Self::Warn),
+            6 => Some(Self::Error),
+            7 => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+}
+
+impl Display for LogPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let letter = match self {
+            Self::Verbose => "V",
+            Self::Debug => "D",
+            Self::Info => "I",
+            Self::Warn => "W",
+            Self::Error => "E",
+            Self::Fatal => "F",
+        };
+        write!(f, "{letter}")
+    }
+}
+
+/// Log message, as reported by [`crate::ADBDeviceExt::logcat`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogcatEntry {
+    /// Time at which message has been logged, with nanosecond precision
+    pub timestamp: SystemTime,
+    /// Identifier of logging process
+    pub pid: u32,
+    /// Identifier of logging thread
+    pub tid: u32,
+    /// Identifier of logging user, `None` on devices not reporting it
+    pub uid: Option<u32>,
+    /// Priority of message
+    pub priority: LogPriority,
+    /// Tag of message, usually naming its component
+    pub tag: String,
+    /// Message content, spanning several lines for multi-line messages
+    pub message: String,
+}
+
+/// Size of the header of version 1 logger entries, which have a padding instead of a header size field
+const LOGGER_ENTRY_V1_HEADER_SIZE: usize = 20;
+/// Smallest size of the header of logger entries holding a user identifier (version 4)
+const LOGGER_ENTRY_V4_HEADER_SIZE: usize = 28;
+
+impl LogcatEntry {
+    /// Return header size of logger entry starting with `prefix`
+    fn header_size(prefix: [u8; 4]) -> usize {
+        match u16::from_le_bytes([prefix[2], prefix[3]]) as usize {
+            0 => LOGGER_ENTRY_V1_HEADER_SIZE,
+            header_size => header_size,
+        }
+    }
+
+    /// Return full size of logger entry starting with `prefix`, the 4 bytes holding its payload and header sizes
+    pub(crate) fn entry_size(prefix: [u8; 4]) -> usize {
+        Self::header_size(prefix) + u16::from_le_bytes([prefix[0], prefix[1]]) as usize
+    }
+
+    /// Parse `entry` printed by `logcat -B`, a `logger_entry` header followed by its payload.
+    ///
+    /// Payload is made of priority, tag and message, the last two being NUL terminated. Return `None` for entries
+    /// not holding a text message, such as the ones of `events` buffer.
+    pub(crate) fn parse(entry: &[u8]) -> Option<Self> {
+        let field = |offset: usize| -> Option<u32> {
+            Some(u32::from_le_bytes(
+                entry.get(offset..offset + 4)?.try_into().ok()?,
+            ))
+        };
+
+        let header_size = Self::header_size(entry.get(..4)?.try_into().ok()?);
+        let timestamp =
+            SystemTime::UNIX_EPOCH.checked_add(Duration::new(field(12)?.into(), field(16)?))?;
+        let uid = if header_size >= LOGGER_ENTRY_V4_HEADER_SIZE {
+            field(24)
+        } else {
+            None
+        };
+
+        let (priority, payload) = entry.get(header_size..)?.split_first()?;
+        let mut strings = payload.splitn(2, |byte| *byte == 0);
+        let tag = String::from_utf8_lossy(strings.next()?);
+        let message = String::from_utf8_lossy(strings.next()?);
+
+        Some(Self {
+            timestamp,
+            pid: field(4)?,
+            tid: field(8)?,
+            uid,
+            priority: LogPriority::from_value(*priority)?,
+            tag: tag.to_string(),
+            message: message.trim_end_matches(['\0', '\n']).to_string(),
+        })
+    }
+}
+
+#[test]
+fn test_logcat_entry_parse() {
+    let payload = b"\x04ActivityManager\0Start proc 4321:com.example/u0a123\n\0";
+    let header = [
+        &(payload.len() as u16).to_le_bytes()[..],
+        &28u16.to_le_bytes(),
+        &1234u32.to_le_bytes(),
+        &5678u32.to_le_bytes(),
+        &1_700_000_000u32.to_le_bytes(),
+        &123_456_789u32.to_le_bytes(),
+        // Log buffer identifier
+        &0u32.to_le_bytes(),
+        &1000u32.to_le_bytes(),
+    ]
+    .concat();
+    let entry = [&header[..], payload].concat();
+
+    assert_eq!(
+        LogcatEntry::entry_size(entry[..4].try_into().expect("cannot read prefix")),
+        entry.len()
+    );
+    assert_eq!(
+        LogcatEntry::parse(&entry),
+        Some(LogcatEntry {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789),
+            pid: 1234,
+            tid: 5678,
+            uid: Some(1000),
+            priority: LogPriority::Info,
+            tag: "ActivityManager".to_string(),
+            message: "Start proc 4321:com.example/u0a123".to_string(),
+        })
+    );
+
+    // Version 1 entries have no user identifier
+    let mut v1_entry = [&header[..2], &[0, 0], &header[4..20], payload].concat();
+    assert_eq!(
+        LogcatEntry::entry_size(v1_entry[..4].try_into().expect("cannot read prefix")),
+        v1_entry.len()
+    );
+    assert_eq!(
+        LogcatEntry::parse(&v1_entry).map(|entry| entry.uid),
+        Some(None)
+    );
+
+    // Binary payload of `events` buffer
+    v1_entry[20] = 0;
+    assert_eq!(LogcatEntry::parse(&v1_entry), None);
+}
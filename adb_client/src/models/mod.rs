@@ -1,23 +1,71 @@
+mod activity_launch;
 mod adb_request_status;
 #[cfg(feature = "tcp")]
 mod adb_server_command;
 mod adb_stat_response;
-#[cfg(any(feature = "tcp", feature = "usb"))]
+mod adb_stat_v2_response;
+mod backup_options;
+mod battery_stats;
+mod dir_entry;
+#[cfg(feature = "tcp")]
+mod forward_rule;
 mod framebuffer_info;
 mod host_features;
+mod install_options;
+mod install_phase;
+mod intent;
+mod logcat_entry;
+mod path_filter;
+mod process_info;
+mod pull_options;
 mod reboot_type;
+mod screen_record_options;
+mod shell_options;
+mod shell_output;
+mod signal;
+mod symlink_policy;
 #[cfg(feature = "tcp")]
 mod sync_command;
+mod sync_compression;
+mod sync_direction;
+mod thermal_info;
+mod transfer_stats;
+mod uid_traffic;
+mod volume_usage;
 
+pub use activity_launch::ActivityLaunch;
 #[cfg(feature = "tcp")]
 pub use adb_request_status::AdbRequestStatus;
 #[cfg(feature = "tcp")]
 pub(crate) use adb_server_command::AdbServerCommand;
 pub use adb_stat_response::AdbStatResponse;
-#[cfg(any(feature = "tcp", feature = "usb"))]
-pub(crate) use framebuffer_info::{FrameBufferInfoV1, FrameBufferInfoV2};
+pub use adb_stat_v2_response::AdbStatV2Response;
+pub use backup_options::BackupOptions;
+pub use battery_stats::{BatteryStats, JobStats, NetworkUsage, Wakelock};
+pub use dir_entry::DirEntry;
+#[cfg(feature = "tcp")]
+pub use forward_rule::ForwardRule;
+pub use framebuffer_info::{FrameBufferInfo, FrameBufferInfoV1, FrameBufferInfoV2, RawFramebuffer};
 #[cfg(feature = "tcp")]
 pub use host_features::HostFeatures;
+pub use install_options::InstallOptions;
+pub use install_phase::InstallPhase;
+pub use intent::Intent;
+pub use logcat_entry::{LogPriority, LogcatEntry};
+pub use path_filter::PathFilter;
+pub use process_info::ProcessInfo;
+pub use pull_options::PullOptions;
 pub use reboot_type::RebootType;
+pub use screen_record_options::ScreenRecordOptions;
+pub use shell_options::{ShellMode, ShellOptions};
+pub use shell_output::ShellOutput;
+pub use signal::Signal;
+pub use symlink_policy::SymlinkPolicy;
 #[cfg(feature = "tcp")]
 pub use sync_command::SyncCommand;
+pub use sync_compression::SyncCompression;
+pub use sync_direction::SyncDirection;
+pub use thermal_info::{ThermalInfo, ThermalStatus, ThermalZone};
+pub use transfer_stats::TransferStats;
+pub use uid_traffic::UidTraffic;
+pub use volume_usage::VolumeUsage;
@@ -0,0 +1,64 @@
+/// Include and exclude patterns selecting files of a remote directory, see [`crate::ADBDeviceExt::pull_dir`].
+///
+/// Patterns support `*` (any sequence of characters) and `?` (any single character) wildcards. Patterns without a `/`
+/// are matched against file names, others against paths relative to pulled directory (e.g. `DCIM/*.jpg`).
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    /// Patterns of files to pull, all files if empty
+    pub include: Vec<String>,
+    /// Patterns of files and directories to skip, taking precedence over `include`
+    pub exclude: Vec<String>,
+}
+
+/// Return `true` if `text` matches glob `pattern`
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|start| glob_matches(rest, &text[start..])),
+        Some((b'?', rest)) => !text.is_empty() && glob_matches(rest, &text[1..]),
+        Some((byte, rest)) => text.first() == Some(byte) && glob_matches(rest, &text[1..]),
+    }
+}
+
+/// Return `true` if `relative_path` matches one of `patterns`
+fn any_matches(patterns: &[String], relative_path: &str) -> bool {
+    let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+    patterns.iter().any(|pattern| {
+        let text = if pattern.contains('/') {
+            relative_path
+        } else {
+            name
+        };
+        glob_matches(pattern.as_bytes(), text.as_bytes())
+    })
+}
+
+impl PathFilter {
+    /// Return `true` if file at `relative_path` is selected
+    pub(crate) fn includes_file(&self, relative_path: &str) -> bool {
+        (self.include.is_empty() || any_matches(&self.include, relative_path))
+            && !self.excludes(relative_path)
+    }
+
+    /// Return `true` if file or directory at `relative_path` is excluded, with all its content for directories
+    pub(crate) fn excludes(&self, relative_path: &str) -> bool {
+        any_matches(&self.exclude, relative_path)
+    }
+}
+
+#[test]
+fn test_path_filter() {
+    let filter = PathFilter {
+        include: vec!["*.jpg".to_string(), "Notes/?.txt".to_string()],
+        exclude: vec![".thumbnails".to_string(), "DCIM/skip*".to_string()],
+    };
+
+    assert!(filter.includes_file("DCIM/Camera/IMG_0001.jpg"));
+    assert!(filter.includes_file("Notes/a.txt"));
+    assert!(!filter.includes_file("Notes/ab.txt"));
+    assert!(!filter.includes_file("DCIM/skipped.jpg"));
+    assert!(!filter.includes_file("video.mp4"));
+    assert!(filter.excludes("DCIM/.thumbnails"));
+
+    assert!(PathFilter::default().includes_file("any/file"));
+}
@@ -0,0 +1,122 @@
+/// Process running on device, as reported by [`crate::ADBDeviceExt::processes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    /// Process identifier
+    pub pid: u32,
+    /// Identifier of parent process
+    pub ppid: u32,
+    /// User identifier, `None` if it could not be resolved from user name on older devices
+    pub uid: Option<u32>,
+    /// Resident set size, in kilobytes
+    pub rss_kb: u64,
+    /// Process name, package name for app processes (e.g. `com.example` or `com.example:remote`)
+    pub name: String,
+}
+
+/// Resolve user name printed by toolbox `ps` (e.g. `u0_a123`) into a user identifier
+fn uid_from_user_name(user: &str) -> Option<u32> {
+    match user {
+        "root" => return Some(0),
+        "system" => return Some(1000),
+        "radio" => return Some(1001),
+        "shell" => return Some(2000),
+        _ => {}
+    }
+
+    // `u<user>_a<app>` for apps, `u<user>_i<id>` for isolated processes
+    let (user_id, app) = user.strip_prefix('u')?.split_once('_')?;
+    let user_id: u32 = user_id.parse().ok()?;
+    let base = match app.as_bytes().first()? {
+        b'a' => 10000,
+        b'i' => 99000,
+        _ => return None,
+    };
+    let id: u32 = app[1..].parse().ok()?;
+    Some(user_id * 100_000 + base + id)
+}
+
+impl ProcessInfo {
+    /// Return `true` if `ps` output has been produced by toybox with requested columns, and not by toolbox
+    /// (before Android 8) which ignores `-o` and prints its own columns.
+    pub(crate) fn is_toybox_output(output: &str) -> bool {
+        output
+            .lines()
+            .next()
+            .is_some_and(|header| header.split_whitespace().any(|column| column == "UID"))
+    }
+
+    /// Parse `ps` output, locating columns using its header line
+    pub(crate) fn parse(output: &str) -> Vec<Self> {
+        let mut lines = output.lines();
+        let Some(header) = lines.next() else {
+            return Vec::new();
+        };
+        let columns: Vec<&str> = header.split_whitespace().collect();
+        let column = |name: &str| columns.iter().position(|column| *column == name);
+        let (Some(pid), Some(ppid), Some(rss)) = (column("PID"), column("PPID"), column("RSS"))
+        else {
+            return Vec::new();
+        };
+        let uid = column("UID");
+        let user = column("USER");
+
+        lines
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                Some(Self {
+                    pid: fields.get(pid)?.parse().ok()?,
+                    ppid: fields.get(ppid)?.parse().ok()?,
+                    uid: match (uid, user) {
+                        (Some(uid), _) => fields.get(uid)?.parse().ok(),
+                        (None, Some(user)) => uid_from_user_name(fields.get(user)?),
+                        (None, None) => None,
+                    },
+                    rss_kb: fields.get(rss)?.parse().ok()?,
+                    // Name is last column, toolbox printing process state before it without a header
+                    name: fields.last()?.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_process_info_parse() {
+    let toybox = "  PID  PPID   UID   RSS NAME\n\
+        \x20    1     0     0 10240 init\n\
+        \x20 4321   600 10123 81920 com.example:remote\n";
+    assert!(ProcessInfo::is_toybox_output(toybox));
+    assert_eq!(
+        ProcessInfo::parse(toybox),
+        [
+            ProcessInfo {
+                pid: 1,
+                ppid: 0,
+                uid: Some(0),
+                rss_kb: 10240,
+                name: "init".to_string(),
+            },
+            ProcessInfo {
+                pid: 4321,
+                ppid: 600,
+                uid: Some(10123),
+                rss_kb: 81920,
+                name: "com.example:remote".to_string(),
+            },
+        ]
+    );
+
+    let toolbox = "USER     PID   PPID  VSIZE  RSS     WCHAN    PC        NAME\n\
+        u10_a5    4321  600   1024   81920 ffffffff 00000000 S com.example\n";
+    assert!(!ProcessInfo::is_toybox_output(toolbox));
+    assert_eq!(
+        ProcessInfo::parse(toolbox),
+        [ProcessInfo {
+            pid: 4321,
+            ppid: 600,
+            uid: Some(1_010_005),
+            rss_kb: 81920,
+            name: "com.example".to_string(),
+        }]
+    );
+}
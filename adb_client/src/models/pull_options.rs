@@ -0,0 +1,11 @@
+use crate::models::SymlinkPolicy;
+
+/// Options of pulls to local files, see [`crate::ADBDeviceExt::pull_path`] and
+/// [`crate::ADBDeviceExt::pull_dir_with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PullOptions {
+    /// Handling of remote symbolic links
+    pub symlinks: SymlinkPolicy,
+    /// Give pulled files the modification time of their remote copy, like `adb pull -a`
+    pub preserve_times: bool,
+}
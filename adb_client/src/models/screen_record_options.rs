@@ -0,0 +1,27 @@
+/// Options of on-device `screenrecord`, see [`crate::ADBDeviceExt::record_screen`].
+#[derive(Debug, Clone, Default)]
+pub struct ScreenRecordOptions {
+    /// Video size as `(width, height)`, device screen resolution if `None`
+    pub size: Option<(u32, u32)>,
+    /// Video bit rate in bits per second, `screenrecord` default (20Mbps) if `None`
+    pub bit_rate: Option<u32>,
+    /// Overlay timestamp and frame number on video, as done for bug reports
+    pub bugreport: bool,
+}
+
+impl ScreenRecordOptions {
+    /// Arguments of `screenrecord` matching these options
+    pub(crate) fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some((width, height)) = self.size {
+            args.push(format!("--size {width}x{height}"));
+        }
+        if let Some(bit_rate) = self.bit_rate {
+            args.push(format!("--bit-rate {bit_rate}"));
+        }
+        if self.bugreport {
+            args.push("--bugreport".to_string());
+        }
+        args
+    }
+}
@@ -0,0 +1,91 @@
+use crate::utils::shell_quote;
+use crate::{Result, RustADBError};
+
+/// How device connects the standard streams of a shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellMode {
+    /// Plain pipes, output is left untouched (e.g. no `\n` to `\r\n` conversion)
+    Raw,
+    /// Pseudo-terminal, needed by programs expecting a terminal (line editing, colors, full-screen programs...)
+    Pty,
+}
+
+/// Options used to open a shell, see [`crate::ADBDeviceExt::shell_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct ShellOptions {
+    /// Value of `TERM` on device, local `TERM` environment variable being used if `None`
+    pub term: Option<String>,
+    /// Mode of the shell, device choosing it if `None` (a pseudo-terminal for interactive shells)
+    pub mode: Option<ShellMode>,
+    /// Environment variables exported in shell before anything else runs
+    pub env: Vec<(String, String)>,
+}
+
+impl ShellOptions {
+    /// Build name of the shell service running `command` with these options, or an interactive shell if `command` is empty.
+    pub(crate) fn service(&self, command: &str) -> Result<String> {
+        let mut service = String::from("shell");
+        if let Some(term) = self.term.clone().or_else(|| std::env::var("TERM").ok()) {
+            // Arguments are separated by ',' and end at ':'
+            if term.contains([',', ':']) {
+                return Err(RustADBError::ADBRequestFailed(format!(
+                    "invalid TERM value {term:?}"
+                )));
+            }
+            service.push_str(&format!(",TERM={term}"));
+        }
+        match self.mode {
+            Some(ShellMode::Raw) => service.push_str(",raw"),
+            Some(ShellMode::Pty) => service.push_str(",pty"),
+            None => {}
+        }
+        service.push(':');
+
+        if self.env.is_empty() {
+            service.push_str(command);
+            return Ok(service);
+        }
+
+        for (name, value) in &self.env {
+            let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_name {
+                return Err(RustADBError::ADBRequestFailed(format!(
+                    "invalid environment variable name {name:?}"
+                )));
+            }
+            service.push_str(&format!("export {name}={}; ", shell_quote(value)));
+        }
+        // Commands given to shell service are run by a non-interactive shell, start an interactive one
+        service.push_str(if command.is_empty() {
+            "exec sh"
+        } else {
+            command
+        });
+
+        Ok(service)
+    }
+}
+
+#[test]
+fn test_shell_options_service() {
+    let options = ShellOptions {
+        term: Some("xterm-256color".to_string()),
+        mode: Some(ShellMode::Pty),
+        env: vec![("LANG".to_string(), "it's".to_string())],
+    };
+    assert_eq!(
+        options.service("").expect("cannot build service"),
+        r"shell,TERM=xterm-256color,pty:export LANG='it'\''s'; exec sh"
+    );
+
+    let options = ShellOptions {
+        term: Some("dumb".to_string()),
+        mode: Some(ShellMode::Raw),
+        env: Vec::new(),
+    };
+    assert_eq!(
+        options.service("ls").expect("cannot build service"),
+        "shell,TERM=dumb,raw:ls"
+    );
+}
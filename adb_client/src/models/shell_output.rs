@@ -0,0 +1,38 @@
+use std::fmt::Debug;
+use std::io::Write;
+
+/// Destination of standard output and error streams of a shell command, see [`crate::ADBDeviceExt::shell_command_output`].
+pub enum ShellOutput<'a> {
+    /// Both streams written into the same writer, in the order device sent them
+    Merged(&'a mut dyn Write),
+    /// Each stream written into its own writer
+    Separate {
+        /// Writer receiving standard output
+        stdout: &'a mut dyn Write,
+        /// Writer receiving standard error
+        stderr: &'a mut dyn Write,
+    },
+}
+
+impl ShellOutput<'_> {
+    /// Borrow writers again, for a shorter lifetime
+    #[cfg(feature = "tcp")]
+    pub(crate) fn reborrow(&mut self) -> ShellOutput<'_> {
+        match self {
+            ShellOutput::Merged(output) => ShellOutput::Merged(&mut **output),
+            ShellOutput::Separate { stdout, stderr } => ShellOutput::Separate {
+                stdout: &mut **stdout,
+                stderr: &mut **stderr,
+            },
+        }
+    }
+}
+
+impl Debug for ShellOutput<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShellOutput::Merged(_) => write!(f, "Merged"),
+            ShellOutput::Separate { .. } => write!(f, "Separate"),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Signal sent to a process, see [`crate::ADBDeviceExt::kill_pid`].
+pub enum Signal {
+    /// Ask process to terminate (`SIGTERM`)
+    Term,
+    /// Kill process immediately (`SIGKILL`)
+    Kill,
+    /// Interrupt process, as Ctrl-C would (`SIGINT`)
+    Int,
+    /// Hang up (`SIGHUP`)
+    Hup,
+    /// Stop process until it is continued (`SIGSTOP`)
+    Stop,
+    /// Continue a stopped process (`SIGCONT`)
+    Cont,
+    /// Any other signal, by number
+    Other(u8),
+}
+
+impl Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Numbers are the same on all architectures supported by Android
+        match self {
+            Signal::Term => write!(f, "15"),
+            Signal::Kill => write!(f, "9"),
+            Signal::Int => write!(f, "2"),
+            Signal::Hup => write!(f, "1"),
+            Signal::Stop => write!(f, "19"),
+            Signal::Cont => write!(f, "18"),
+            Signal::Other(number) => write!(f, "{number}"),
+        }
+    }
+}
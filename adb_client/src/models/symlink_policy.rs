@@ -0,0 +1,28 @@
+use crate::RustADBError;
+
+/// Handling of remote symbolic links when pulling, see [`crate::ADBDeviceExt::pull_path`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Pull content of link target, walking it if it is a directory
+    Follow,
+    /// Create a local symbolic link to the same target, only supported on Unix platforms
+    Recreate,
+    /// Do not pull symbolic links
+    #[default]
+    Skip,
+}
+
+impl TryFrom<&str> for SymlinkPolicy {
+    type Error = RustADBError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "follow" => Ok(Self::Follow),
+            "recreate" => Ok(Self::Recreate),
+            "skip" => Ok(Self::Skip),
+            value => Err(RustADBError::ADBRequestFailed(format!(
+                "unknown symbolic link policy {value}"
+            ))),
+        }
+    }
+}
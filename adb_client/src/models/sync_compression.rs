@@ -0,0 +1,136 @@
+use crate::{Result, RustADBError};
+
+/// Compression of file data transferred using sync protocol v2, see [`crate::ADBDeviceExt::push_compressed`].
+///
+/// Each algorithm is only available if its cargo feature is enabled (`sync-brotli`, `sync-lz4` and `sync-zstd`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncCompression {
+    /// Best algorithm supported by both device and this build (Zstandard, LZ4 then Brotli), or no compression
+    #[default]
+    Any,
+    /// Data is sent as is
+    None,
+    /// Brotli, best compression ratio but slowest
+    Brotli,
+    /// LZ4, fastest but lowest compression ratio
+    Lz4,
+    /// Zstandard, good compression ratio at a high speed
+    Zstd,
+}
+
+impl SyncCompression {
+    /// Algorithms picked by [`SyncCompression::Any`], in order of preference
+    const PREFERRED: [SyncCompression; 3] = [Self::Zstd, Self::Lz4, Self::Brotli];
+
+    /// Flag of `SND2` and `RCV2` requests selecting this algorithm
+    pub(crate) fn flag(self) -> u32 {
+        match self {
+            Self::Any | Self::None => 0,
+            Self::Brotli => 1,
+            Self::Lz4 => 2,
+            Self::Zstd => 4,
+        }
+    }
+
+    /// Device feature advertising support of this algorithm
+    fn device_feature(self) -> Option<&'static str> {
+        match self {
+            Self::Any | Self::None => None,
+            Self::Brotli => Some("sendrecv_v2_brotli"),
+            Self::Lz4 => Some("sendrecv_v2_lz4"),
+            Self::Zstd => Some("sendrecv_v2_zstd"),
+        }
+    }
+
+    /// Whether this algorithm has been built in
+    fn is_available(self) -> bool {
+        match self {
+            Self::Any | Self::None => true,
+            Self::Brotli => cfg!(feature = "sync-brotli"),
+            Self::Lz4 => cfg!(feature = "sync-lz4"),
+            Self::Zstd => cfg!(feature = "sync-zstd"),
+        }
+    }
+
+    /// Algorithm to use with a device advertising `features`, `None` if it does not support sync protocol v2.
+    ///
+    /// [`SyncCompression::Any`] is resolved to an actual algorithm, while an explicitly requested algorithm is an error
+    /// if it cannot be used.
+    pub(crate) fn resolve(self, features: &[String]) -> Result<Option<Self>> {
+        let supports = |feature: &str| features.iter().any(|f| f == feature);
+        if !supports("sendrecv_v2") {
+            return match self {
+                Self::Any | Self::None => Ok(None),
+                _ => Err(RustADBError::ADBRequestFailed(
+                    "device does not support sync protocol v2".to_string(),
+                )),
+            };
+        }
+
+        match (self, self.device_feature()) {
+            (Self::Any, _) => Ok(Some(
+                Self::PREFERRED
+                    .into_iter()
+                    .find(|compression| {
+                        compression.is_available()
+                            && compression.device_feature().is_some_and(supports)
+                    })
+                    .unwrap_or(Self::None),
+            )),
+            (compression, _) if !compression.is_available() => Err(RustADBError::ADBRequestFailed(
+                format!("{compression:?} compression has not been built in"),
+            )),
+            (compression, Some(feature)) if !supports(feature) => {
+                Err(RustADBError::ADBRequestFailed(format!(
+                    "{compression:?} compression is not supported by device"
+                )))
+            }
+            (compression, _) => Ok(Some(compression)),
+        }
+    }
+}
+
+impl TryFrom<&str> for SyncCompression {
+    type Error = RustADBError;
+
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+        match value {
+            "any" => Ok(Self::Any),
+            "none" => Ok(Self::None),
+            "brotli" => Ok(Self::Brotli),
+            "lz4" => Ok(Self::Lz4),
+            "zstd" => Ok(Self::Zstd),
+            value => Err(RustADBError::ADBRequestFailed(format!(
+                "unknown compression {value}"
+            ))),
+        }
+    }
+}
+
+#[test]
+fn test_sync_compression_resolve() {
+    let features = |features: &[&str]| -> Vec<String> {
+        features.iter().map(|feature| feature.to_string()).collect()
+    };
+
+    let v1 = features(&["shell_v2", "cmd"]);
+    assert_eq!(SyncCompression::Any.resolve(&v1).ok(), Some(None));
+    assert!(SyncCompression::Zstd.resolve(&v1).is_err());
+
+    let v2 = features(&["sendrecv_v2"]);
+    assert_eq!(
+        SyncCompression::Any.resolve(&v2).ok(),
+        Some(Some(SyncCompression::None))
+    );
+    assert!(SyncCompression::Zstd.resolve(&v2).is_err());
+
+    let v2_zstd = features(&["sendrecv_v2", "sendrecv_v2_zstd"]);
+    let expected = match cfg!(feature = "sync-zstd") {
+        true => SyncCompression::Zstd,
+        false => SyncCompression::None,
+    };
+    assert_eq!(
+        SyncCompression::Any.resolve(&v2_zstd).ok(),
+        Some(Some(expected))
+    );
+}
@@ -0,0 +1,8 @@
+/// Direction of a directory synchronization, see [`crate::ADBDeviceExt::sync_dir`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    /// Update remote directory from local one
+    Push,
+    /// Update local directory from remote one
+    Pull,
+}
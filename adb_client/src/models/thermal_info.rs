@@ -0,0 +1,175 @@
+/// Thermal throttling status of a device or zone, as defined by Android `PowerManager`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ThermalStatus {
+    /// Not under throttling
+    None,
+    /// Light throttling, user experience not impacted
+    Light,
+    /// Moderate throttling, user experience not largely impacted
+    Moderate,
+    /// Severe throttling, user experience largely impacted
+    Severe,
+    /// Platform has done everything to reduce power
+    Critical,
+    /// Key components are shutting down due to thermal conditions
+    Emergency,
+    /// Device is shutting down immediately
+    Shutdown,
+}
+
+impl ThermalStatus {
+    fn from_code(code: &str) -> Option<Self> {
+        Some(match code.trim().parse::<u8>().ok()? {
+            0 => Self::None,
+            1 => Self::Light,
+            2 => Self::Moderate,
+            3 => Self::Severe,
+            4 => Self::Critical,
+            5 => Self::Emergency,
+            6 => Self::Shutdown,
+            _ => return None,
+        })
+    }
+}
+
+/// Temperature of a thermal zone (CPU, battery, skin...).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalZone {
+    /// Name of zone (e.g. `cpu0` or `battery`)
+    pub name: String,
+    /// Temperature, in degrees Celsius
+    pub temperature: f32,
+    /// Throttling status of zone, only reported by thermal service
+    pub status: Option<ThermalStatus>,
+}
+
+/// Thermal state of a device, as reported by [`crate::ADBDeviceExt::thermal_info`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalInfo {
+    /// Overall throttling status, `None` if thermal service is not available (before Android 10)
+    pub status: Option<ThermalStatus>,
+    /// Temperatures of thermal zones
+    pub zones: Vec<ThermalZone>,
+}
+
+impl ThermalInfo {
+    /// Parse `dumpsys thermalservice` output, returning `None` if it does not report a thermal status.
+    ///
+    /// Current temperatures read from thermal HAL are preferred over cached ones.
+    pub(crate) fn parse_thermalservice(output: &str) -> Option<Self> {
+        let mut status = None;
+        let mut cached = Vec::new();
+        let mut current = Vec::new();
+        let mut section = "";
+
+        for line in output.lines() {
+            let line = line.trim();
+            if let Some(code) = line.strip_prefix("Thermal Status:") {
+                status = ThermalStatus::from_code(code);
+            } else if line.ends_with(':') {
+                section = line;
+            } else if let Some(fields) = line
+                .strip_prefix("Temperature{")
+                .and_then(|fields| fields.strip_suffix('}'))
+            {
+                // Temperature{mValue=41.3, mType=0, mName=cpu0, mStatus=0}
+                let field = |name: &str| {
+                    fields
+                        .split(", ")
+                        .find_map(|field| field.strip_prefix(name)?.strip_prefix('='))
+                };
+                let (Some(name), Some(Ok(temperature))) =
+                    (field("mName"), field("mValue").map(str::parse))
+                else {
+                    continue;
+                };
+                let zone = ThermalZone {
+                    name: name.to_string(),
+                    temperature,
+                    status: field("mStatus").and_then(ThermalStatus::from_code),
+                };
+                match section {
+                    "Current temperatures from HAL:" => current.push(zone),
+                    "Cached temperatures:" => cached.push(zone),
+                    _ => {}
+                }
+            }
+        }
+
+        Some(Self {
+            status: Some(status?),
+            zones: if current.is_empty() { cached } else { current },
+        })
+    }
+
+    /// Parse `<type> <temp>` lines read from `/sys/class/thermal/thermal_zone*`.
+    ///
+    /// Kernel reports temperatures in millidegrees, though some zones use degrees: values below 1000 are taken as such.
+    pub(crate) fn parse_sysfs(output: &str) -> Self {
+        let zones = output
+            .lines()
+            .filter_map(|line| {
+                let (name, temperature) = line.trim().rsplit_once(' ')?;
+                let temperature: f32 = temperature.parse().ok()?;
+                Some(ThermalZone {
+                    name: name.to_string(),
+                    temperature: if temperature.abs() >= 1000.0 {
+                        temperature / 1000.0
+                    } else {
+                        temperature
+                    },
+                    status: None,
+                })
+            })
+            .collect();
+
+        Self {
+            status: None,
+            zones,
+        }
+    }
+}
+
+#[test]
+fn test_thermal_info_parse() {
+    let output = "IsStatusOverride: false\n\
+        Thermal Status: 2\n\
+        Cached temperatures:\n\
+        \tTemperature{mValue=36.8, mType=2, mName=battery, mStatus=0}\n\
+        HAL Ready: true\n\
+        Current temperatures from HAL:\n\
+        \tTemperature{mValue=41.5, mType=0, mName=cpu0, mStatus=2}\n\
+        Current cooling devices from HAL:\n\
+        \tCoolingDevice{mValue=0, mType=0, mName=cpu0}\n";
+    assert_eq!(
+        ThermalInfo::parse_thermalservice(output),
+        Some(ThermalInfo {
+            status: Some(ThermalStatus::Moderate),
+            zones: vec![ThermalZone {
+                name: "cpu0".to_string(),
+                temperature: 41.5,
+                status: Some(ThermalStatus::Moderate),
+            }],
+        })
+    );
+    assert_eq!(
+        ThermalInfo::parse_thermalservice("Can't find service: thermalservice"),
+        None
+    );
+
+    assert_eq!(
+        ThermalInfo::parse_sysfs("cpu-0-0-usr 41300\nbattery 36\n").zones,
+        [
+            ThermalZone {
+                name: "cpu-0-0-usr".to_string(),
+                temperature: 41.3,
+                status: None,
+            },
+            ThermalZone {
+                name: "battery".to_string(),
+                temperature: 36.0,
+                status: None,
+            },
+        ]
+    );
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+/// Statistics of a file transfer, as returned by [`crate::ADBDeviceExt::push`] and [`crate::ADBDeviceExt::pull`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    /// Bytes transferred
+    pub bytes: u64,
+    /// Time taken by transfer, including reconnections
+    pub elapsed: Duration,
+    /// Number of times transfer has been started over after a connection loss
+    pub retries: u32,
+}
+
+impl TransferStats {
+    /// Average throughput of transfer, in bytes per second
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.bytes as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
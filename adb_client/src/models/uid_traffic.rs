@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+/// Network traffic of a user identifier, as reported by [`crate::ADBDeviceExt::network_stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UidTraffic {
+    /// User identifier (each app having its own, e.g. `10123`)
+    pub uid: u32,
+    /// Bytes received
+    pub rx_bytes: u64,
+    /// Packets received
+    pub rx_packets: u64,
+    /// Bytes sent
+    pub tx_bytes: u64,
+    /// Packets sent
+    pub tx_packets: u64,
+}
+
+impl UidTraffic {
+    fn add(&mut self, rx_bytes: u64, rx_packets: u64, tx_bytes: u64, tx_packets: u64) {
+        self.rx_bytes += rx_bytes;
+        self.rx_packets += rx_packets;
+        self.tx_bytes += tx_bytes;
+        self.tx_packets += tx_packets;
+    }
+
+    /// Parse `/proc/net/xt_qtaguid/stats`, summing untagged traffic of each uid over interfaces and counter sets.
+    ///
+    /// Return `None` if `output` is not such a file, e.g. when kernel does not provide it (Android 9 and later).
+    pub(crate) fn parse_qtaguid(output: &str) -> Option<Vec<Self>> {
+        let mut lines = output.lines();
+        // idx iface acct_tag_hex uid_tag_int cnt_set rx_bytes rx_packets tx_bytes tx_packets ...
+        if !lines.next()?.starts_with("idx ") {
+            return None;
+        }
+
+        let mut traffic = BTreeMap::new();
+        for line in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |index: usize| {
+                fields
+                    .get(index)
+                    .and_then(|field| field.parse::<u64>().ok())
+            };
+            // Tagged traffic is also accounted for in untagged rows
+            if fields.get(2) != Some(&"0x0") {
+                continue;
+            }
+            if let (Some(uid), Some(rx_bytes), Some(rx_packets), Some(tx_bytes), Some(tx_packets)) = (
+                fields.get(3).and_then(|uid| uid.parse().ok()),
+                number(5),
+                number(6),
+                number(7),
+                number(8),
+            ) {
+                Self::entry(&mut traffic, uid).add(rx_bytes, rx_packets, tx_bytes, tx_packets);
+            }
+        }
+        Some(traffic.into_values().collect())
+    }
+
+    /// Parse `dumpsys netstats detail`, summing history buckets of untagged traffic of each uid.
+    pub(crate) fn parse_netstats(output: &str) -> Vec<Self> {
+        let mut traffic = BTreeMap::new();
+        // Identity line of current history: `ident=[...] uid=10123 set=DEFAULT tag=0x0`
+        let mut uid = None;
+
+        for line in output.lines().map(str::trim) {
+            let fields = || {
+                line.split_whitespace()
+                    .filter_map(|field| field.split_once('='))
+            };
+            if line.starts_with("ident=") {
+                let tag = fields().find(|(key, _)| *key == "tag").map(|(_, tag)| tag);
+                uid = fields()
+                    .find(|(key, _)| *key == "uid")
+                    .and_then(|(_, uid)| uid.parse().ok())
+                    .filter(|_| tag == Some("0x0"));
+            } else if line.starts_with("st=")
+                && let Some(uid) = uid
+            {
+                // Bucket line: `st=1700000000 rb=1234 rp=10 tb=567 tp=5 op=0`
+                let counter = |name: &str| {
+                    fields()
+                        .find(|(key, _)| *key == name)
+                        .and_then(|(_, value)| value.parse().ok())
+                        .unwrap_or(0)
+                };
+                Self::entry(&mut traffic, uid).add(
+                    counter("rb"),
+                    counter("rp"),
+                    counter("tb"),
+                    counter("tp"),
+                );
+            } else if line.ends_with(':') {
+                // New section (e.g. `UID tag stats:`)
+                uid = None;
+            }
+        }
+        traffic.into_values().collect()
+    }
+
+    fn entry(traffic: &mut BTreeMap<u32, Self>, uid: u32) -> &mut Self {
+        traffic.entry(uid).or_insert_with(|| Self {
+            uid,
+            ..Default::default()
+        })
+    }
+}
+
+#[test]
+fn test_uid_traffic_parse() {
+    let qtaguid = "idx iface acct_tag_hex uid_tag_int cnt_set rx_bytes rx_packets tx_bytes tx_packets\n\
+        2 wlan0 0x0 0 0 100 1 200 2\n\
+        3 wlan0 0x0 10123 0 1000 10 500 5\n\
+        4 wlan0 0x0 10123 1 24 1 0 0\n\
+        5 wlan0 0x3e8 10123 0 1000 10 500 5\n";
+    assert_eq!(
+        UidTraffic::parse_qtaguid(qtaguid),
+        Some(vec![
+            UidTraffic {
+                uid: 0,
+                rx_bytes: 100,
+                rx_packets: 1,
+                tx_bytes: 200,
+                tx_packets: 2,
+            },
+            UidTraffic {
+                uid: 10123,
+                rx_bytes: 1024,
+                rx_packets: 11,
+                tx_bytes: 500,
+                tx_packets: 5,
+            },
+        ])
+    );
+    assert_eq!(UidTraffic::parse_qtaguid("cat: No such file"), None);
+
+    let netstats = "Xt stats:\n\
+        \x20 ident=[{type=WIFI, subType=COMBINED}] uid=-1 set=ALL tag=0x0\n\
+        \x20   st=1700000000 rb=9999 rp=99 tb=9999 tp=99 op=0\n\
+        UID stats:\n\
+        \x20 ident=[{type=WIFI, subType=COMBINED}] uid=10123 set=DEFAULT tag=0x0\n\
+        \x20   NetworkStatsHistory: bucketDuration=7200\n\
+        \x20     st=1700000000 rb=1000 rp=10 tb=500 tp=5 op=0\n\
+        \x20     st=1700007200 rb=24 rp=1 tb=0 tp=0 op=0\n\
+        UID tag stats:\n\
+        \x20 ident=[{type=WIFI, subType=COMBINED}] uid=10123 set=DEFAULT tag=0x3e8\n\
+        \x20     st=1700000000 rb=1000 rp=10 tb=500 tp=5 op=0\n";
+    assert_eq!(
+        UidTraffic::parse_netstats(netstats),
+        [UidTraffic {
+            uid: 10123,
+            rx_bytes: 1024,
+            rx_packets: 11,
+            tx_bytes: 500,
+            tx_packets: 5,
+        }]
+    );
+}
@@ -0,0 +1,137 @@
+/// Space usage of a mounted volume, as reported by [`crate::ADBDeviceExt::storage_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeUsage {
+    /// Mounted filesystem (e.g. `/dev/block/dm-5`), same as mount point if unknown
+    pub filesystem: String,
+    /// Directory where volume is mounted (e.g. `/data`)
+    pub mount_point: String,
+    /// Total size, in bytes
+    pub total_bytes: u64,
+    /// Used space, in bytes
+    pub used_bytes: u64,
+    /// Space available to apps and shell user, in bytes
+    pub available_bytes: u64,
+}
+
+/// Parse a size printed by toolbox `df` or `dumpsys diskstats` (e.g. `1.4G` or `60.0K`)
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, unit) = match size.char_indices().last()? {
+        (index, 'K') => (&size[..index], 1u64 << 10),
+        (index, 'M') => (&size[..index], 1 << 20),
+        (index, 'G') => (&size[..index], 1 << 30),
+        (index, 'T') => (&size[..index], 1 << 40),
+        _ => (size, 1),
+    };
+    let number: f64 = number.parse().ok()?;
+    Some((number * unit as f64) as u64)
+}
+
+impl VolumeUsage {
+    /// Parse `df -k` output of toybox, or `df` output of toolbox (before Android 6) which ignores `-k`.
+    pub(crate) fn parse_df(output: &str) -> Vec<Self> {
+        let mut lines = output.lines();
+        let Some(header) = lines.next() else {
+            return Vec::new();
+        };
+        let toolbox = header.contains("Blksize");
+
+        lines
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if toolbox {
+                    // Filesystem Size Used Free Blksize, filesystem being mount point
+                    let [mount_point, total, used, free, _] = fields.as_slice() else {
+                        return None;
+                    };
+                    Some(Self {
+                        filesystem: mount_point.to_string(),
+                        mount_point: mount_point.to_string(),
+                        total_bytes: parse_size(total)?,
+                        used_bytes: parse_size(used)?,
+                        available_bytes: parse_size(free)?,
+                    })
+                } else {
+                    // Filesystem 1K-blocks Used Available Use% Mounted on
+                    let [filesystem, total, used, available, _, mount_point] = fields.as_slice()
+                    else {
+                        return None;
+                    };
+                    let kilobytes =
+                        |value: &str| value.parse::<u64>().ok().map(|value| value * 1024);
+                    Some(Self {
+                        filesystem: filesystem.to_string(),
+                        mount_point: mount_point.to_string(),
+                        total_bytes: kilobytes(total)?,
+                        used_bytes: kilobytes(used)?,
+                        available_bytes: kilobytes(available)?,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Parse `dumpsys diskstats` output, made of `Data-Free: 1234K / 5678K total = 21% free` lines.
+    pub(crate) fn parse_diskstats(output: &str) -> Vec<Self> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let (volume, sizes) = line.trim().split_once("-Free: ")?;
+                let (free, total) = sizes.split_once(" / ")?;
+                let total = total.split_whitespace().next()?;
+
+                let mount_point = format!("/{}", volume.to_lowercase());
+                let total_bytes = parse_size(total)?;
+                let available_bytes = parse_size(free)?;
+                Some(Self {
+                    filesystem: mount_point.clone(),
+                    mount_point,
+                    total_bytes,
+                    used_bytes: total_bytes.saturating_sub(available_bytes),
+                    available_bytes,
+                })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_volume_usage_parse() {
+    let toybox = "Filesystem     1K-blocks    Used Available Use% Mounted on\n\
+        /dev/block/dm-5  5000000 4000000    900000  82% /data\n";
+    assert_eq!(
+        VolumeUsage::parse_df(toybox),
+        [VolumeUsage {
+            filesystem: "/dev/block/dm-5".to_string(),
+            mount_point: "/data".to_string(),
+            total_bytes: 5_120_000_000,
+            used_bytes: 4_096_000_000,
+            available_bytes: 921_600_000,
+        }]
+    );
+
+    let toolbox = "Filesystem               Size     Used     Free   Blksize\n\
+        /data                    1.5G   512.0M     1.0G   4096\n";
+    assert_eq!(
+        VolumeUsage::parse_df(toolbox),
+        [VolumeUsage {
+            filesystem: "/data".to_string(),
+            mount_point: "/data".to_string(),
+            total_bytes: 1_610_612_736,
+            used_bytes: 536_870_912,
+            available_bytes: 1_073_741_824,
+        }]
+    );
+
+    let diskstats = "Latency: 2ms [512B Data Write]\n\
+        Data-Free: 1024K / 4096K total = 25% free\n";
+    assert_eq!(
+        VolumeUsage::parse_diskstats(diskstats),
+        [VolumeUsage {
+            filesystem: "/data".to_string(),
+            mount_point: "/data".to_string(),
+            total_bytes: 4_194_304,
+            used_bytes: 3_145_728,
+            available_bytes: 1_048_576,
+        }]
+    );
+}
@@ -0,0 +1,80 @@
+use std::sync::LazyLock;
+
+use regex::bytes::Regex;
+
+use crate::{Result, RustADBError};
+
+/// Directory holding expansion files of all packages on device.
+pub(crate) const OBB_DIRECTORY: &str = "/sdcard/Android/obb";
+
+static VERSION_CODE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"versionCode=(?P<version_code>\d+)").expect("cannot build version code regex")
+});
+
+/// Ensure `package` is a valid package name, as it is used in shell commands and remote paths.
+pub(crate) fn check_package_name(package: &str) -> Result<()> {
+    let valid = !package.is_empty()
+        && package.split('.').all(|part| {
+            !part.is_empty() && part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        });
+
+    if !valid {
+        return Err(RustADBError::InvalidPackageName(package.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Return `true` if `file_name` is named as expected for an expansion file of `package`,
+/// i.e. `main.<version-code>.<package>.obb` or `patch.<version-code>.<package>.obb`
+pub(crate) fn is_obb_file_name(file_name: &str, package: &str) -> bool {
+    let Some(rest) = file_name
+        .strip_prefix("main.")
+        .or_else(|| file_name.strip_prefix("patch."))
+    else {
+        return false;
+    };
+
+    rest.strip_suffix(".obb")
+        .and_then(|rest| rest.strip_suffix(package))
+        .and_then(|rest| rest.strip_suffix('.'))
+        .is_some_and(|version_code| {
+            !version_code.is_empty() && version_code.bytes().all(|b| b.is_ascii_digit())
+        })
+}
+
+/// Extract version code of package from `dumpsys package` output
+pub(crate) fn parse_version_code(dumpsys_output: &[u8]) -> Result<u64> {
+    let captures = VERSION_CODE_REGEX
+        .captures(dumpsys_output)
+        .ok_or(RustADBError::RegexParsingError)?;
+
+    Ok(std::str::from_utf8(&captures["version_code"])?.parse()?)
+}
+
+#[test]
+fn test_obb_file_name() {
+    assert!(is_obb_file_name(
+        "main.42.com.example.game.obb",
+        "com.example.game"
+    ));
+    assert!(is_obb_file_name(
+        "patch.7.com.example.game.obb",
+        "com.example.game"
+    ));
+    assert!(!is_obb_file_name(
+        "main.42.com.other.obb",
+        "com.example.game"
+    ));
+    assert!(!is_obb_file_name(
+        "main..com.example.game.obb",
+        "com.example.game"
+    ));
+    assert!(!is_obb_file_name("game.obb", "com.example.game"));
+
+    assert_eq!(
+        parse_version_code(b"    versionCode=1234 minSdk=24 targetSdk=34\n")
+            .expect("cannot parse version code"),
+        1234
+    );
+}
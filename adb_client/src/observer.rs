@@ -0,0 +1,205 @@
+use std::fmt::Debug;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use crate::InstallPhase;
+
+/// Observer receiving events from long operations performed on a device.
+///
+/// An observer is attached once to a device handle (e.g. using [`crate::ADBServerDevice::set_observer`]),
+/// and is then notified by all operations run on it. All methods have an empty default implementation.
+pub trait AdbObserver: Send + Sync {
+    /// `transferred` bytes out of `total` (if known) of file `path` have been transferred.
+    fn on_transfer_progress(&self, _path: &str, _transferred: u64, _total: Option<u64>) {}
+
+    /// An APK installation entered given `phase`.
+    fn on_install_phase(&self, _phase: InstallPhase) {}
+
+    /// An APK installation reached `percent` percents of its current `phase`.
+    ///
+    /// Upload progress is computed from bytes streamed, while installation progress is parsed from package manager output
+    /// when it reports any.
+    fn on_install_progress(&self, _phase: InstallPhase, _percent: u8) {}
+
+    /// A sideloaded package reached `percent` percents.
+    fn on_sideload_progress(&self, _percent: u8) {}
+
+    /// Device is waiting for the user to accept our public key.
+    fn on_auth_pending(&self) {}
+}
+
+/// Optional [`AdbObserver`] attached to a device, notifying it only if set.
+#[derive(Clone, Default)]
+pub(crate) struct ObserverSlot(Option<Arc<dyn AdbObserver>>);
+
+impl Debug for ObserverSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ObserverSlot")
+            .field(&self.0.as_ref().map(|_| "AdbObserver"))
+            .finish()
+    }
+}
+
+impl ObserverSlot {
+    pub(crate) fn set(&mut self, observer: Arc<dyn AdbObserver>) {
+        self.0 = Some(observer);
+    }
+
+    pub(crate) fn transfer_progress(&self, path: &str, transferred: u64, total: Option<u64>) {
+        if let Some(observer) = &self.0 {
+            observer.on_transfer_progress(path, transferred, total);
+        }
+    }
+
+    pub(crate) fn install_phase(&self, phase: InstallPhase) {
+        if let Some(observer) = &self.0 {
+            observer.on_install_phase(phase);
+        }
+    }
+
+    pub(crate) fn install_progress(&self, phase: InstallPhase, percent: u8) {
+        if let Some(observer) = &self.0 {
+            observer.on_install_progress(phase, percent);
+        }
+    }
+
+    pub(crate) fn sideload_progress(&self, percent: u8) {
+        if let Some(observer) = &self.0 {
+            observer.on_sideload_progress(percent);
+        }
+    }
+
+    #[cfg_attr(not(feature = "usb"), allow(dead_code))]
+    pub(crate) fn auth_pending(&self) {
+        if let Some(observer) = &self.0 {
+            observer.on_auth_pending();
+        }
+    }
+
+    /// Wrap `inner` to report data read from it as transfer progress of `path`
+    pub(crate) fn progress_reader<R: Read>(
+        &self,
+        inner: R,
+        path: &str,
+        total: Option<u64>,
+    ) -> ProgressReader<R> {
+        ProgressReader {
+            inner,
+            progress: TransferProgress::new(self.clone(), path, total),
+        }
+    }
+
+    /// Wrap `inner` like [`ObserverSlot::progress_reader`], also reporting upload progress of an APK installation
+    pub(crate) fn install_reader<R: Read>(
+        &self,
+        inner: R,
+        path: &str,
+        total: u64,
+    ) -> ProgressReader<R> {
+        let mut progress = TransferProgress::new(self.clone(), path, Some(total));
+        progress.install_percent = Some(0);
+        ProgressReader { inner, progress }
+    }
+
+    /// Wrap `inner` to report data written to it as transfer progress of `path`
+    pub(crate) fn progress_writer<W: Write>(
+        &self,
+        inner: W,
+        path: &str,
+        total: Option<u64>,
+    ) -> ProgressWriter<W> {
+        ProgressWriter {
+            inner,
+            progress: TransferProgress::new(self.clone(), path, total),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TransferProgress {
+    observer: ObserverSlot,
+    path: String,
+    transferred: u64,
+    total: Option<u64>,
+    /// Last percentage reported as install upload progress, `None` if this transfer is not an installation
+    install_percent: Option<u8>,
+}
+
+impl TransferProgress {
+    fn new(observer: ObserverSlot, path: &str, total: Option<u64>) -> Self {
+        Self {
+            observer,
+            path: path.to_string(),
+            transferred: 0,
+            total,
+            install_percent: None,
+        }
+    }
+
+    fn advance(&mut self, amount: usize) {
+        if amount > 0 {
+            self.transferred += amount as u64;
+            self.observer
+                .transfer_progress(&self.path, self.transferred, self.total);
+
+            if let (Some(last_percent), Some(total)) = (self.install_percent, self.total) {
+                let percent = (self.transferred * 100)
+                    .checked_div(total)
+                    .map_or(100, |percent| percent.min(100) as u8);
+                if percent != last_percent {
+                    self.install_percent = Some(percent);
+                    self.observer
+                        .install_progress(InstallPhase::Uploading, percent);
+                }
+            }
+        }
+    }
+}
+
+/// [`Read`] implementation reporting progress to an [`AdbObserver`].
+#[derive(Debug)]
+pub(crate) struct ProgressReader<R: Read> {
+    inner: R,
+    progress: TransferProgress,
+}
+
+impl<R: Read> ProgressReader<R> {
+    /// Bytes read so far
+    pub(crate) fn transferred(&self) -> u64 {
+        self.progress.transferred
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let amount = self.inner.read(buf)?;
+        self.progress.advance(amount);
+        Ok(amount)
+    }
+}
+
+/// [`Write`] implementation reporting progress to an [`AdbObserver`].
+#[derive(Debug)]
+pub(crate) struct ProgressWriter<W: Write> {
+    inner: W,
+    progress: TransferProgress,
+}
+
+impl<W: Write> ProgressWriter<W> {
+    /// Bytes written so far
+    pub(crate) fn transferred(&self) -> u64 {
+        self.progress.transferred
+    }
+}
+
+impl<W: Write> Write for ProgressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let amount = self.inner.write(buf)?;
+        self.progress.advance(amount);
+        Ok(amount)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
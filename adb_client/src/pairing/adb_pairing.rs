@@ -0,0 +1,191 @@
+use std::fs::{create_dir_all, read_to_string};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::{ClientConnection, StreamOwned};
+
+use super::pairing_cipher::PairingCipher;
+use super::spake2::{Spake2, Spake2Role};
+use crate::device::{ADBRsaKey, get_default_adb_key_path};
+use crate::transports::tls_client_config;
+use crate::{Result, RustADBError};
+
+/// Version of pairing packets header
+const PACKET_VERSION: u8 = 1;
+/// Type of packets carrying a SPAKE2 message
+const PACKET_SPAKE2_MESSAGE: u8 = 0;
+/// Type of packets carrying encrypted peer information
+const PACKET_PEER_INFO: u8 = 1;
+/// Size of peer information, made of a type byte followed by zero padded data
+const PEER_INFO_SIZE: usize = 8192;
+/// Type of peer information sent by host, its public key
+const PEER_INFO_PUBLIC_KEY: u8 = 0;
+/// Type of peer information sent by device, its GUID
+const PEER_INFO_DEVICE_GUID: u8 = 1;
+/// Maximum payload size of pairing packets
+const MAX_PAYLOAD_SIZE: usize = 2 * PEER_INFO_SIZE;
+/// Label of TLS keying material appended to pairing code, trailing NUL included
+const EXPORTED_KEY_LABEL: &[u8] = b"adb-label\0";
+const EXPORTED_KEY_SIZE: usize = 64;
+/// SPAKE2 names of host and device, trailing NUL included
+const CLIENT_NAME: &[u8] = b"adb pair client\0";
+const SERVER_NAME: &[u8] = b"adb pair server\0";
+/// Time given to device to answer each pairing packet
+const PAIRING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pairing with a device in wireless debugging mode, as done by `adb pair`, without any ADB server.
+///
+/// Pairing service address and code are displayed by device in "Pair device with pairing code" dialog. Once paired,
+/// device accepts TLS connections of [`crate::ADBTcpDevice`] instances using the same private key.
+#[derive(Debug)]
+pub struct ADBPairing {
+    address: SocketAddr,
+    private_key_path: PathBuf,
+}
+
+impl ADBPairing {
+    /// Instantiate a new [`ADBPairing`] with pairing service at `address`, using default key (`~/.android/adbkey`)
+    pub fn new(address: SocketAddr) -> Result<Self> {
+        Ok(Self::new_with_custom_private_key(
+            address,
+            get_default_adb_key_path()?,
+        ))
+    }
+
+    /// Instantiate a new [`ADBPairing`] with pairing service at `address`, using a given private key
+    pub fn new_with_custom_private_key(address: SocketAddr, private_key_path: PathBuf) -> Self {
+        Self {
+            address,
+            private_key_path,
+        }
+    }
+
+    /// Pair with device using `pairing_code`, returning device GUID (e.g. `adb-1A2B3C4D-XyZ123`).
+    ///
+    /// Private key is generated and stored if missing, for following TLS connections to use it.
+    pub fn pair(&self, pairing_code: &str) -> Result<String> {
+        let private_key = read_or_create_private_key(&self.private_key_path)?;
+
+        let stream = TcpStream::connect_timeout(&self.address, PAIRING_TIMEOUT)?;
+        stream.set_read_timeout(Some(PAIRING_TIMEOUT))?;
+        stream.set_write_timeout(Some(PAIRING_TIMEOUT))?;
+        let connection = ClientConnection::new(
+            Arc::new(tls_client_config(&self.private_key_path)?),
+            self.address.ip().into(),
+        )?;
+        let mut stream = StreamOwned::new(connection, stream);
+        while stream.conn.is_handshaking() {
+            stream.conn.complete_io(&mut stream.sock)?;
+        }
+
+        // Both peers authenticate TLS session by appending its keying material to pairing code
+        let mut password = pairing_code.trim().as_bytes().to_vec();
+        password.extend(stream.conn.export_keying_material(
+            [0; EXPORTED_KEY_SIZE],
+            EXPORTED_KEY_LABEL,
+            None,
+        )?);
+
+        let spake2 = Spake2::new(Spake2Role::Alice, CLIENT_NAME, SERVER_NAME, &password);
+        write_packet(&mut stream, PACKET_SPAKE2_MESSAGE, spake2.message())?;
+        let their_message = read_packet(&mut stream, PACKET_SPAKE2_MESSAGE)?;
+        let mut cipher = PairingCipher::new(&spake2.finish(&their_message)?)?;
+
+        let public_key = private_key.android_pubkey_encode()?;
+        let mut peer_info = vec![0; PEER_INFO_SIZE];
+        peer_info[0] = PEER_INFO_PUBLIC_KEY;
+        // Keep a trailing NUL
+        if public_key.len() >= PEER_INFO_SIZE - 1 {
+            return Err(RustADBError::PairingError("public key is too long".into()));
+        }
+        peer_info[1..=public_key.len()].copy_from_slice(public_key.as_bytes());
+        write_packet(&mut stream, PACKET_PEER_INFO, &cipher.encrypt(&peer_info)?)?;
+
+        // Device closes connection if it cannot decrypt our information, having derived another key
+        let device_info = match read_packet(&mut stream, PACKET_PEER_INFO) {
+            Err(RustADBError::IOError(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Err(RustADBError::PairingError(
+                    "device rejected pairing, check pairing code".into(),
+                ));
+            }
+            device_info => cipher.decrypt(&device_info?)?,
+        };
+        parse_device_guid(&device_info)
+    }
+}
+
+/// Read private key at `path`, generating and storing a new one if missing
+fn read_or_create_private_key(path: &Path) -> Result<ADBRsaKey> {
+    match read_to_string(path) {
+        Ok(content) => ADBRsaKey::new_from_pkcs8(&content),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            log::info!("generating new private key at {}", path.display());
+            let private_key = ADBRsaKey::new_random()?;
+            if let Some(parent) = path.parent() {
+                create_dir_all(parent)?;
+            }
+            std::fs::write(path, private_key.to_pkcs8_pem()?)?;
+            Ok(private_key)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_packet<W: Write>(writer: &mut W, packet_type: u8, payload: &[u8]) -> Result<()> {
+    let mut packet = vec![PACKET_VERSION, packet_type];
+    packet.extend(u32::try_from(payload.len())?.to_be_bytes());
+    packet.extend(payload);
+    writer.write_all(&packet)?;
+    Ok(writer.flush()?)
+}
+
+fn read_packet<R: Read>(reader: &mut R, expected_type: u8) -> Result<Vec<u8>> {
+    let mut header = [0; 6];
+    reader.read_exact(&mut header)?;
+    let [version, packet_type, size @ ..] = header;
+    let size = usize::try_from(u32::from_be_bytes(size))?;
+    if version != PACKET_VERSION || packet_type != expected_type || size > MAX_PAYLOAD_SIZE {
+        return Err(RustADBError::PairingError(format!(
+            "unexpected packet of version {version}, type {packet_type} and size {size}"
+        )));
+    }
+
+    let mut payload = vec![0; size];
+    reader.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+/// Parse device GUID from decrypted peer information sent by device
+fn parse_device_guid(peer_info: &[u8]) -> Result<String> {
+    match peer_info.split_first() {
+        Some((&PEER_INFO_DEVICE_GUID, guid)) => {
+            let guid = guid.split(|&byte| byte == 0).next().unwrap_or_default();
+            Ok(String::from_utf8(guid.to_vec())?)
+        }
+        _ => Err(RustADBError::PairingError(
+            "device did not send its GUID".into(),
+        )),
+    }
+}
+
+#[test]
+fn test_pairing_packets() {
+    let mut packet = Vec::new();
+    write_packet(&mut packet, PACKET_SPAKE2_MESSAGE, &[0xAB; 32]).expect("cannot write packet");
+    assert_eq!(packet[..6], [1, 0, 0, 0, 0, 32]);
+
+    let payload = read_packet(&mut packet.as_slice(), PACKET_SPAKE2_MESSAGE).expect("cannot read");
+    assert_eq!(payload, [0xAB; 32]);
+    assert!(read_packet(&mut packet.as_slice(), PACKET_PEER_INFO).is_err());
+
+    let mut peer_info = vec![0; PEER_INFO_SIZE];
+    peer_info[0] = PEER_INFO_DEVICE_GUID;
+    peer_info[1..13].copy_from_slice(b"adb-1A2B3C4D");
+    assert_eq!(
+        parse_device_guid(&peer_info).expect("cannot parse GUID"),
+        "adb-1A2B3C4D"
+    );
+}
@@ -0,0 +1,5 @@
+mod adb_pairing;
+mod pairing_cipher;
+mod spake2;
+
+pub use adb_pairing::ADBPairing;
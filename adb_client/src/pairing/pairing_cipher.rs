@@ -0,0 +1,71 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes128Gcm, Key, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+use crate::{Result, RustADBError};
+
+/// Information of HKDF deriving encryption key from key material shared using SPAKE2
+const KEY_INFO: &[u8] = b"adb pairing_auth aes-128-gcm key";
+
+/// AES-128-GCM cipher of messages exchanged once pairing peers share a key.
+///
+/// Nonce of each message is its sequence number in its direction, in little endian.
+pub(crate) struct PairingCipher {
+    cipher: Aes128Gcm,
+    encrypt_sequence: u64,
+    decrypt_sequence: u64,
+}
+
+impl PairingCipher {
+    pub(crate) fn new(key_material: &[u8]) -> Result<Self> {
+        let mut key = [0; 16];
+        Hkdf::<Sha256>::new(None, key_material)
+            .expand(KEY_INFO, &mut key)
+            .map_err(|e| RustADBError::PairingError(format!("cannot derive key: {e}")))?;
+
+        Ok(Self {
+            cipher: Aes128Gcm::new(Key::<Aes128Gcm>::from_slice(&key)),
+            encrypt_sequence: 0,
+            decrypt_sequence: 0,
+        })
+    }
+
+    pub(crate) fn encrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let nonce = nonce(self.encrypt_sequence);
+        self.encrypt_sequence += 1;
+        self.cipher
+            .encrypt(Nonce::from_slice(&nonce), data)
+            .map_err(|_| RustADBError::PairingError("cannot encrypt message".into()))
+    }
+
+    pub(crate) fn decrypt(&mut self, data: &[u8]) -> Result<Vec<u8>> {
+        let nonce = nonce(self.decrypt_sequence);
+        self.decrypt_sequence += 1;
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), data)
+            .map_err(|_| RustADBError::PairingError("cannot decrypt message".into()))
+    }
+}
+
+fn nonce(sequence: u64) -> [u8; 12] {
+    let mut nonce = [0; 12];
+    nonce[..8].copy_from_slice(&sequence.to_le_bytes());
+    nonce
+}
+
+#[test]
+fn test_pairing_cipher() {
+    let mut client = PairingCipher::new(&[1; 64]).expect("cannot create cipher");
+    let mut server = PairingCipher::new(&[1; 64]).expect("cannot create cipher");
+
+    for message in [b"first".as_slice(), b"second"] {
+        let encrypted = client.encrypt(message).expect("cannot encrypt");
+        assert_eq!(server.decrypt(&encrypted).expect("cannot decrypt"), message);
+    }
+
+    // Replayed messages do not match expected sequence number
+    let encrypted = client.encrypt(b"third").expect("cannot encrypt");
+    assert!(server.decrypt(&encrypted).is_ok());
+    assert!(server.decrypt(&encrypted).is_err());
+}
@@ -0,0 +1,207 @@
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::Rng;
+use sha2::{Digest, Sha512};
+
+use crate::{Result, RustADBError};
+
+/// Point masking messages of alice, found by hashing `edwards25519 point generation seed (M)` until a valid point is found
+const POINT_M: [u8; 32] = [
+    0x5a, 0xda, 0x7e, 0x4b, 0xf6, 0xdd, 0xd9, 0xad, 0xb6, 0x62, 0x6d, 0x32, 0x13, 0x1c, 0x6b, 0x5c,
+    0x51, 0xa1, 0xe3, 0x47, 0xa3, 0x47, 0x8f, 0x53, 0xcf, 0xcf, 0x44, 0x1b, 0x88, 0xee, 0xd1, 0x2e,
+];
+/// Point masking messages of bob, found by hashing `edwards25519 point generation seed (N)` until a valid point is found
+const POINT_N: [u8; 32] = [
+    0x10, 0xe3, 0xdf, 0x0a, 0xe3, 0x7d, 0x8e, 0x7a, 0x99, 0xb5, 0xfe, 0x74, 0xb4, 0x46, 0x72, 0x10,
+    0x3d, 0xbd, 0xdc, 0xbd, 0x06, 0xaf, 0x68, 0x0d, 0x71, 0x32, 0x9a, 0x11, 0x69, 0x3b, 0xc7, 0x78,
+];
+
+/// Side taken in a SPAKE2 exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Spake2Role {
+    /// Side of host
+    Alice,
+    /// Side of device, only taken by tests
+    #[cfg(test)]
+    Bob,
+}
+
+/// SPAKE2 key exchange over Ed25519, compatible with BoringSSL implementation used by adbd.
+pub(crate) struct Spake2 {
+    role: Spake2Role,
+    my_name: Vec<u8>,
+    their_name: Vec<u8>,
+    /// Private key divided by cofactor, actual private key being a multiple of eight
+    private_key: Scalar,
+    password_hash: [u8; 64],
+    password_scalar: Scalar,
+    my_message: [u8; 32],
+}
+
+impl Spake2 {
+    /// Start an exchange as `role`, authenticated by `password`, and generate the message to send to peer
+    pub(crate) fn new(
+        role: Spake2Role,
+        my_name: &[u8],
+        their_name: &[u8],
+        password: &[u8],
+    ) -> Self {
+        let mut random = [0; 64];
+        rand::rng().fill(&mut random[..]);
+        Self::new_with_random(role, my_name, their_name, password, &random)
+    }
+
+    fn new_with_random(
+        role: Spake2Role,
+        my_name: &[u8],
+        their_name: &[u8],
+        password: &[u8],
+        random: &[u8; 64],
+    ) -> Self {
+        let private_key = Scalar::from_bytes_mod_order_wide(random);
+        let password_hash: [u8; 64] = Sha512::digest(password).into();
+        let password_scalar = Scalar::from_bytes_mod_order_wide(&password_hash);
+
+        let (my_point, _) = role.points();
+        let message = EdwardsPoint::mul_base(&(private_key * Scalar::from(8_u8)))
+            + mask(password_scalar, &my_point);
+
+        Self {
+            role,
+            my_name: my_name.to_vec(),
+            their_name: their_name.to_vec(),
+            private_key,
+            password_hash,
+            password_scalar,
+            my_message: message.compress().to_bytes(),
+        }
+    }
+
+    /// Message to send to peer
+    pub(crate) fn message(&self) -> &[u8; 32] {
+        &self.my_message
+    }
+
+    /// Process `their_message` received from peer, returning key material shared with it.
+    ///
+    /// Key material only matches the one of peer if both used the same password.
+    pub(crate) fn finish(self, their_message: &[u8]) -> Result<[u8; 64]> {
+        let their_message: [u8; 32] = their_message.try_into().map_err(|_| {
+            RustADBError::PairingError(format!(
+                "SPAKE2 message of {} bytes, expected 32",
+                their_message.len()
+            ))
+        })?;
+        let their_point = CompressedEdwardsY(their_message)
+            .decompress()
+            .ok_or_else(|| {
+                RustADBError::PairingError("SPAKE2 message is not a curve point".into())
+            })?;
+
+        let (_, peer_point) = self.role.points();
+        let unmasked = their_point - mask(self.password_scalar, &peer_point);
+        // Multiplying by cofactor first clears any small order component
+        let shared = (self.private_key * unmasked.mul_by_cofactor()).compress();
+
+        let mut hash = Sha512::new();
+        let transcript: [&[u8]; 4] = match self.role {
+            Spake2Role::Alice => [
+                &self.my_name,
+                &self.their_name,
+                &self.my_message,
+                &their_message,
+            ],
+            #[cfg(test)]
+            Spake2Role::Bob => [
+                &self.their_name,
+                &self.my_name,
+                &their_message,
+                &self.my_message,
+            ],
+        };
+        for data in transcript
+            .into_iter()
+            .chain([shared.as_bytes().as_slice(), &self.password_hash])
+        {
+            hash.update((data.len() as u64).to_le_bytes());
+            hash.update(data);
+        }
+
+        Ok(hash.finalize().into())
+    }
+}
+
+impl Spake2Role {
+    /// Points masking messages of this side and of its peer
+    fn points(self) -> (EdwardsPoint, EdwardsPoint) {
+        let point = |bytes| {
+            CompressedEdwardsY(bytes)
+                .decompress()
+                .expect("SPAKE2 masking points are valid")
+        };
+        match self {
+            Spake2Role::Alice => (point(POINT_M), point(POINT_N)),
+            #[cfg(test)]
+            Spake2Role::Bob => (point(POINT_N), point(POINT_M)),
+        }
+    }
+}
+
+/// Compute `password_scalar * point`, as done by BoringSSL.
+///
+/// BoringSSL adds multiples of the group order to `password_scalar` until it is a multiple of eight, which only
+/// changes the small order component of the result but has to be reproduced for messages to match.
+fn mask(password_scalar: Scalar, point: &EdwardsPoint) -> EdwardsPoint {
+    // Adding the group order to a scalar adds 5 to its lowest three bits
+    let mut low_bits = password_scalar.as_bytes()[0] & 7;
+    let mut order_multiple = 0_u8;
+    for bit in [1, 2, 4] {
+        if low_bits & bit != 0 {
+            order_multiple += bit;
+            low_bits = low_bits.wrapping_add(bit * 5) & 7;
+        }
+    }
+
+    // `order * point`, the group order being one more than the largest scalar
+    let order_point = -Scalar::ONE * point + point;
+    password_scalar * point + Scalar::from(order_multiple) * order_point
+}
+
+#[test]
+fn test_spake2() {
+    let password = b"123456";
+    let alice = Spake2::new_with_random(Spake2Role::Alice, b"alice", b"bob", password, &[1; 64]);
+    let bob = Spake2::new_with_random(Spake2Role::Bob, b"bob", b"alice", password, &[2; 64]);
+
+    let alice_message = *alice.message();
+    let bob_message = *bob.message();
+    let alice_key = alice.finish(&bob_message).expect("cannot finish exchange");
+    let bob_key = bob.finish(&alice_message).expect("cannot finish exchange");
+    assert_eq!(alice_key, bob_key);
+
+    // Reference values computed following BoringSSL algorithm on plain integers
+    assert_eq!(
+        alice_message,
+        [
+            0x6a, 0xe9, 0xf0, 0x8f, 0x3c, 0x16, 0x3d, 0x19, 0x7c, 0x9f, 0x64, 0x19, 0x63, 0x70,
+            0xf7, 0x19, 0x6d, 0x00, 0x4e, 0x6e, 0xde, 0x36, 0xfa, 0x7b, 0x29, 0x21, 0x24, 0x44,
+            0xa1, 0xcb, 0x47, 0xbd
+        ]
+    );
+    assert_eq!(
+        alice_key,
+        [
+            0x0c, 0xc5, 0xf4, 0xb4, 0x00, 0x62, 0x06, 0x0c, 0xe9, 0x39, 0xc2, 0xcf, 0x6d, 0x63,
+            0xc9, 0xf8, 0x2f, 0xb7, 0x4b, 0x04, 0xd3, 0x99, 0x9e, 0x9c, 0xd9, 0xbe, 0x61, 0xb8,
+            0xaf, 0xbf, 0x62, 0x79, 0x83, 0x31, 0x91, 0x82, 0x4a, 0xd3, 0x8b, 0x5c, 0xf0, 0x38,
+            0x5a, 0x5f, 0xca, 0x99, 0xf1, 0x8b, 0xb2, 0xbb, 0x74, 0x9c, 0x7c, 0x9d, 0x9f, 0x0b,
+            0x2f, 0x0e, 0xe2, 0x81, 0x0c, 0xad, 0xf8, 0xa6
+        ]
+    );
+
+    let mallory = Spake2::new_with_random(Spake2Role::Bob, b"bob", b"alice", b"654321", &[2; 64]);
+    let mallory_key = mallory
+        .finish(&alice_message)
+        .expect("cannot finish exchange");
+    assert_ne!(alice_key, mallory_key);
+}
@@ -0,0 +1,143 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::utils::shell_quote;
+use crate::{ADBDeviceExt, Result};
+
+/// Change of a file on device, as reported by a [`PathWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileEvent {
+    /// File appeared, identified by its path
+    Created(String),
+    /// File size or modification time changed
+    Modified(String),
+    /// File has been removed
+    Deleted(String),
+}
+
+/// Size and modification time of files, by path
+type Snapshot = BTreeMap<String, (u64, u64)>;
+
+/// Iterator polling files under a directory of a device, and yielding their changes.
+///
+/// Files are listed using `find` and `stat`, which does not need root nor inotify support. First poll only records
+/// existing files, and changes happening within a poll interval are merged (e.g. a file created and removed in between
+/// is not reported). Modification times have a one second resolution.
+#[derive(Debug)]
+pub struct PathWatcher<'a, D: ADBDeviceExt + ?Sized> {
+    device: &'a mut D,
+    path: String,
+    interval: Duration,
+    next_poll: Option<Instant>,
+    snapshot: Option<Snapshot>,
+    pending: VecDeque<FileEvent>,
+}
+
+impl<'a, D: ADBDeviceExt + ?Sized> PathWatcher<'a, D> {
+    /// Watch files under `path` on `device`, polling them every `interval`.
+    pub fn new(device: &'a mut D, path: &str, interval: Duration) -> Self {
+        Self {
+            device,
+            path: path.to_string(),
+            interval,
+            next_poll: None,
+            snapshot: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// List files under watched path
+    fn poll(&mut self) -> Result<Snapshot> {
+        let quoted = shell_quote(&self.path);
+        let mut output = Vec::new();
+        self.device.shell_command(
+            &[
+                // Follow `path` itself if it is a symbolic link, as `/sdcard` is
+                "find",
+                "-H",
+                &quoted,
+                "-type f -exec stat -c '%Y %s %n' {} + 2>/dev/null",
+            ],
+            &mut output,
+        )?;
+        Ok(parse_snapshot(&String::from_utf8_lossy(&output)))
+    }
+}
+
+/// Parse `stat -c '%Y %s %n'` output
+fn parse_snapshot(output: &str) -> Snapshot {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ' ');
+            let modified = fields.next()?.parse().ok()?;
+            let size = fields.next()?.parse().ok()?;
+            Some((fields.next()?.to_string(), (size, modified)))
+        })
+        .collect()
+}
+
+/// Events turning `previous` snapshot into `current` one
+fn diff_snapshots(previous: &Snapshot, current: &Snapshot) -> Vec<FileEvent> {
+    let mut events = Vec::new();
+    for (path, metadata) in current {
+        match previous.get(path) {
+            None => events.push(FileEvent::Created(path.clone())),
+            Some(previous_metadata) if previous_metadata != metadata => {
+                events.push(FileEvent::Modified(path.clone()));
+            }
+            Some(_) => {}
+        }
+    }
+    for path in previous.keys() {
+        if !current.contains_key(path) {
+            events.push(FileEvent::Deleted(path.clone()));
+        }
+    }
+    events
+}
+
+impl<D: ADBDeviceExt + ?Sized> Iterator for PathWatcher<'_, D> {
+    type Item = Result<FileEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            if let Some(next_poll) = self.next_poll {
+                std::thread::sleep(next_poll.saturating_duration_since(Instant::now()));
+            }
+            self.next_poll = Some(Instant::now() + self.interval);
+
+            let current = match self.poll() {
+                Ok(current) => current,
+                Err(e) => return Some(Err(e)),
+            };
+            if let Some(previous) = &self.snapshot {
+                self.pending.extend(diff_snapshots(previous, &current));
+            }
+            self.snapshot = Some(current);
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+#[test]
+fn test_diff_snapshots() {
+    let previous = parse_snapshot(
+        "1700000000 10 /sdcard/Download/kept\n\
+        1700000000 20 /sdcard/Download/changed\n\
+        1700000000 30 /sdcard/Download/removed\n",
+    );
+    let current = parse_snapshot(
+        "1700000000 10 /sdcard/Download/kept\n\
+        1700000005 25 /sdcard/Download/changed\n\
+        1700000005 5 /sdcard/Download/with space\n",
+    );
+    assert_eq!(
+        diff_snapshots(&previous, &current),
+        [
+            FileEvent::Modified("/sdcard/Download/changed".to_string()),
+            FileEvent::Created("/sdcard/Download/with space".to_string()),
+            FileEvent::Deleted("/sdcard/Download/removed".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,43 @@
+use crate::models::Signal;
+use crate::utils::shell_quote;
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Run kill `command`, mapping its failure to an error.
+fn run_kill<D: ADBDeviceExt + ?Sized>(device: &mut D, command: &[&str]) -> Result<u8> {
+    let mut output = Vec::new();
+    let exit_code = device.run_command(command, &mut output, true)?;
+
+    let output = String::from_utf8_lossy(&output);
+    if output.contains("Operation not permitted") {
+        return Err(RustADBError::PermissionDenied(output.trim().to_string()));
+    }
+    Ok(exit_code)
+}
+
+/// Send `signal` to process `pid`, see [`ADBDeviceExt::kill_pid`].
+pub(crate) fn kill_pid<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    pid: u32,
+    signal: Signal,
+) -> Result<()> {
+    let exit_code = run_kill(device, &["kill", &format!("-{signal}"), &pid.to_string()])?;
+    if exit_code != 0 {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "cannot send signal {signal} to process {pid}"
+        )));
+    }
+    Ok(())
+}
+
+/// Kill processes matching `pattern`, see [`ADBDeviceExt::pkill`].
+pub(crate) fn pkill<D: ADBDeviceExt + ?Sized>(device: &mut D, pattern: &str) -> Result<bool> {
+    let quoted = shell_quote(pattern);
+    // Exit code is 1 when no process matched, and greater on errors
+    match run_kill(device, &["pkill", &quoted])? {
+        0 => Ok(true),
+        1 => Ok(false),
+        exit_code => Err(RustADBError::ADBRequestFailed(format!(
+            "pkill {pattern:?} failed with exit code {exit_code}"
+        ))),
+    }
+}
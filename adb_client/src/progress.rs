@@ -0,0 +1,117 @@
+use std::io::{Read, Write};
+
+/// Callback given bytes transferred so far out of total (if known) of a file, see [`crate::ADBDeviceExt::push_with_progress`]
+pub(crate) type ProgressCallback<'a> = dyn FnMut(&str, u64, Option<u64>) + 'a;
+
+/// Transfer of a single file, reporting its progress to a callback
+struct CallbackProgress<'a> {
+    callback: &'a mut ProgressCallback<'a>,
+    path: String,
+    transferred: u64,
+    total: Option<u64>,
+}
+
+impl CallbackProgress<'_> {
+    fn advance(&mut self, amount: usize) {
+        if amount > 0 {
+            self.transferred += amount as u64;
+            (self.callback)(&self.path, self.transferred, self.total);
+        }
+    }
+}
+
+/// [`Read`] implementation reporting data read from `inner` to a callback.
+pub(crate) struct CallbackReader<'a, R: Read + ?Sized> {
+    progress: CallbackProgress<'a>,
+    inner: &'a mut R,
+}
+
+impl<'a, R: Read + ?Sized> CallbackReader<'a, R> {
+    /// Report data read from `inner` to `callback` as transfer progress of `path`
+    pub(crate) fn new(
+        inner: &'a mut R,
+        path: &str,
+        total: Option<u64>,
+        callback: &'a mut ProgressCallback<'a>,
+    ) -> Self {
+        Self {
+            progress: CallbackProgress {
+                callback,
+                path: path.to_string(),
+                transferred: 0,
+                total,
+            },
+            inner,
+        }
+    }
+}
+
+impl<R: Read + ?Sized> Read for CallbackReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let amount = self.inner.read(buf)?;
+        self.progress.advance(amount);
+        Ok(amount)
+    }
+}
+
+/// [`Write`] implementation reporting data written to `inner` to a callback.
+pub(crate) struct CallbackWriter<'a, W: Write + ?Sized> {
+    progress: CallbackProgress<'a>,
+    inner: &'a mut W,
+}
+
+impl<'a, W: Write + ?Sized> CallbackWriter<'a, W> {
+    /// Report data written to `inner` to `callback` as transfer progress of `path`
+    pub(crate) fn new(
+        inner: &'a mut W,
+        path: &str,
+        total: Option<u64>,
+        callback: &'a mut ProgressCallback<'a>,
+    ) -> Self {
+        Self {
+            progress: CallbackProgress {
+                callback,
+                path: path.to_string(),
+                transferred: 0,
+                total,
+            },
+            inner,
+        }
+    }
+}
+
+impl<W: Write + ?Sized> Write for CallbackWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let amount = self.inner.write(buf)?;
+        self.progress.advance(amount);
+        Ok(amount)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+fn test_callback_reader() {
+    let mut reports = Vec::new();
+    let mut callback = |path: &str, transferred, total| {
+        reports.push((path.to_string(), transferred, total));
+    };
+    let mut input: &[u8] = &[0; 10];
+    let mut reader = CallbackReader::new(&mut input, "/sdcard/file", Some(10), &mut callback);
+
+    let mut buffer = [0; 6];
+    reader.read_exact(&mut buffer).expect("cannot read");
+    assert_eq!(reader.read(&mut buffer).expect("cannot read"), 4);
+    assert_eq!(reader.read(&mut buffer).expect("cannot read"), 0);
+    drop(reader);
+
+    assert_eq!(
+        reports,
+        [
+            ("/sdcard/file".to_string(), 6, Some(10)),
+            ("/sdcard/file".to_string(), 10, Some(10))
+        ]
+    );
+}
@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::Path;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::models::{PathFilter, PullOptions, SymlinkPolicy, TransferStats};
+use crate::sync_dir::is_unchanged;
+use crate::utils::shell_quote;
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Pull remote path `source` into local path `destination`, see [`ADBDeviceExt::pull_path`].
+pub(crate) fn pull_path<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    source: &str,
+    destination: &Path,
+    options: &PullOptions,
+) -> Result<TransferStats> {
+    let started = Instant::now();
+    let mut stat = device.lstat_v2(source)?;
+    if stat.is_symlink() {
+        match options.symlinks {
+            SymlinkPolicy::Follow if options.preserve_times => stat = device.stat_v2(source)?,
+            SymlinkPolicy::Follow => {}
+            SymlinkPolicy::Recreate => {
+                recreate_symlink(device, source, destination)?;
+                return Ok(TransferStats {
+                    elapsed: started.elapsed(),
+                    ..Default::default()
+                });
+            }
+            SymlinkPolicy::Skip => {
+                log::debug!("skipping {source}, which is a symbolic link");
+                return Ok(TransferStats::default());
+            }
+        }
+    }
+
+    let mut output = File::create(destination)?;
+    let stats = device.pull(&source, &mut output)?;
+    if options.preserve_times {
+        set_mod_time(&output, stat.mtime)?;
+    }
+    Ok(stats)
+}
+
+/// Pull remote directory `source` into local directory `destination`, see [`ADBDeviceExt::pull_dir_with_options`].
+///
+/// If `only_changed` is set, files whose local copy has the same size and modification time are not pulled, and
+/// pulled files get the modification time of their remote copy as if `options.preserve_times` was set.
+pub(crate) fn pull_dir<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    source: &str,
+    destination: &Path,
+    filter: &PathFilter,
+    options: &PullOptions,
+    only_changed: bool,
+) -> Result<TransferStats> {
+    let started = Instant::now();
+    let mut stats = TransferStats::default();
+    // Device and inode of directories walked through followed links, to stop at link cycles
+    let mut walked_links = HashSet::new();
+
+    // Directories left to pull, as paths relative to `source`
+    let mut directories = vec![String::new()];
+    while let Some(directory) = directories.pop() {
+        let remote_directory = match directory.as_str() {
+            "" => source.trim_end_matches('/').to_string(),
+            directory => format!("{}/{directory}", source.trim_end_matches('/')),
+        };
+        std::fs::create_dir_all(destination.join(&directory))?;
+
+        for entry in device.list_dir(&remote_directory)? {
+            let relative_path = match directory.as_str() {
+                "" => entry.name.clone(),
+                directory => format!("{directory}/{}", entry.name),
+            };
+            let remote_path = format!("{remote_directory}/{}", entry.name);
+
+            let mut mod_time = i64::from(entry.mod_time);
+            let (is_dir, is_file) = match (entry.is_symlink(), options.symlinks) {
+                (false, _) => (entry.is_dir(), entry.is_file()),
+                (true, SymlinkPolicy::Follow) => {
+                    let target = device.stat_v2(&remote_path)?;
+                    if target.error != 0 {
+                        log::debug!("skipping {relative_path}, which is a dangling symbolic link");
+                        continue;
+                    }
+                    // Legacy devices do not follow links, which are then pulled as files
+                    if target.is_dir()
+                        && target.ino != 0
+                        && !walked_links.insert((target.dev, target.ino))
+                    {
+                        log::debug!(
+                            "skipping {relative_path}, which links to an already pulled directory"
+                        );
+                        continue;
+                    }
+                    mod_time = target.mtime;
+                    (target.is_dir(), target.is_file() || target.is_symlink())
+                }
+                (true, SymlinkPolicy::Recreate) => {
+                    if !filter.excludes(&relative_path) {
+                        recreate_symlink(device, &remote_path, &destination.join(&relative_path))?;
+                    }
+                    continue;
+                }
+                (true, SymlinkPolicy::Skip) => (false, false),
+            };
+
+            if is_dir {
+                if !filter.excludes(&relative_path) {
+                    directories.push(relative_path);
+                }
+            } else if is_file {
+                let local_path = destination.join(&relative_path);
+                if !filter.includes_file(&relative_path)
+                    || (only_changed && is_unchanged(&local_path, &entry))
+                {
+                    continue;
+                }
+                let mut output = File::create(&local_path)?;
+                let file_stats = device.pull(&remote_path, &mut output)?;
+                if options.preserve_times || only_changed {
+                    set_mod_time(&output, mod_time)?;
+                }
+                stats.bytes += file_stats.bytes;
+                stats.retries += file_stats.retries;
+            } else {
+                log::debug!("skipping {relative_path}, which is neither a file nor a directory");
+            }
+        }
+    }
+
+    stats.elapsed = started.elapsed();
+    Ok(stats)
+}
+
+/// Set modification time of local file `file` to `mod_time`, in seconds since Unix epoch
+fn set_mod_time(file: &File, mod_time: i64) -> Result<()> {
+    let mod_time = UNIX_EPOCH + Duration::from_secs(mod_time.max(0).unsigned_abs());
+    Ok(file.set_modified(mod_time)?)
+}
+
+/// Create local symbolic link `destination`, pointing to the target of remote symbolic link `source`
+fn recreate_symlink<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    source: &str,
+    destination: &Path,
+) -> Result<()> {
+    let mut output = Vec::new();
+    device.shell_command(&[&format!("readlink {}", shell_quote(source))], &mut output)?;
+    let target = String::from_utf8(output)?;
+    let target = target.trim_end_matches(['\r', '\n']);
+    if target.is_empty() {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "cannot read target of symbolic link {source}"
+        )));
+    }
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, destination)?;
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        Err(RustADBError::ADBRequestFailed(format!(
+            "cannot recreate symbolic link {} to {target} on this platform",
+            destination.display()
+        )))
+    }
+}
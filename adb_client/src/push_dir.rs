@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::models::TransferStats;
+use crate::sync_dir::is_unchanged;
+use crate::utils::shell_quote;
+use crate::{ADBDeviceExt, Result};
+
+/// Permissions of pushed files when local ones cannot be read
+#[cfg(not(unix))]
+const DEFAULT_FILE_MODE: u32 = 0o644;
+
+/// Permissions of local file described by `metadata`, as given to device
+#[cfg(unix)]
+fn file_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+#[cfg(not(unix))]
+fn file_mode(_metadata: &std::fs::Metadata) -> u32 {
+    DEFAULT_FILE_MODE
+}
+
+/// Push local file `local` to `remote`, keeping its metadata, see [`ADBDeviceExt::push_file`].
+pub(crate) fn push_file<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    local: &Path,
+    remote: &str,
+) -> Result<TransferStats> {
+    let mut file = File::open(local)?;
+    let metadata = file.metadata()?;
+    device.push_with_metadata(
+        &mut file,
+        &remote,
+        file_mode(&metadata),
+        metadata.modified()?,
+    )
+}
+
+/// Push local directory `source` into remote directory `destination`, see [`ADBDeviceExt::push_dir`].
+///
+/// If `only_changed` is set, files whose remote copy has the same size and modification time are not pushed.
+pub(crate) fn push_dir<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    source: &Path,
+    destination: &str,
+    only_changed: bool,
+) -> Result<TransferStats> {
+    let started = Instant::now();
+    let destination = destination.trim_end_matches('/');
+
+    // Walk whole tree first, so that remote directories are created at once
+    let mut remote_directories = vec![destination.to_string()];
+    let mut files = Vec::new();
+    let mut directories = vec![(source.to_path_buf(), destination.to_string())];
+    while let Some((local_directory, remote_directory)) = directories.pop() {
+        for entry in std::fs::read_dir(&local_directory)? {
+            let entry = entry?;
+            let remote_path = format!("{remote_directory}/{}", entry.file_name().to_string_lossy());
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                remote_directories.push(remote_path.clone());
+                directories.push((entry.path(), remote_path));
+            } else if file_type.is_file() {
+                files.push((entry.path(), remote_path));
+            } else {
+                log::debug!(
+                    "skipping {}, which is neither a file nor a directory",
+                    entry.path().display()
+                );
+            }
+        }
+    }
+
+    // Pushing a file creates its parent directories, but empty ones must be created explicitly
+    let quoted: Vec<_> = remote_directories
+        .iter()
+        .map(|path| shell_quote(path))
+        .collect();
+    let mut output = Vec::new();
+    device.shell_command(&[&format!("mkdir -p {}", quoted.join(" "))], &mut output)?;
+
+    let mut remote_files = HashMap::new();
+    if only_changed {
+        for directory in &remote_directories {
+            for entry in device.list_dir(directory)? {
+                remote_files.insert(format!("{directory}/{}", entry.name), entry);
+            }
+        }
+    }
+
+    let mut stats = TransferStats::default();
+    for (local_path, remote_path) in files {
+        if remote_files
+            .get(&remote_path)
+            .is_some_and(|entry| is_unchanged(&local_path, entry))
+        {
+            continue;
+        }
+        let file_stats = push_file(device, &local_path, &remote_path)?;
+        stats.bytes += file_stats.bytes;
+        stats.retries += file_stats.retries;
+    }
+
+    stats.elapsed = started.elapsed();
+    Ok(stats)
+}
@@ -0,0 +1,162 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::utils::shell_quote;
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Size of parts uploaded by [`push_resume`], a failure only losing the part being uploaded.
+const PUSH_RESUME_PART_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Lookup table of CRC used by POSIX `cksum` (polynomial `0x04C11DB7`, most significant bit first).
+const CKSUM_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// [`Write`] implementation computing the checksum printed by POSIX `cksum` of all data written.
+#[derive(Debug, Default)]
+struct Cksum {
+    crc: u32,
+    length: u64,
+}
+
+impl Cksum {
+    fn update(&mut self, byte: u8) {
+        self.crc = (self.crc << 8) ^ CKSUM_TABLE[((self.crc >> 24) as u8 ^ byte) as usize];
+    }
+
+    fn finish(mut self) -> u32 {
+        // Length is appended to data, least significant byte first and without trailing zeros
+        let mut length = self.length;
+        while length != 0 {
+            self.update(length as u8);
+            length >>= 8;
+        }
+        !self.crc
+    }
+}
+
+impl Write for Cksum {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for byte in buf {
+            self.update(*byte);
+        }
+        self.length += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn shell_output<D: ADBDeviceExt + ?Sized>(device: &mut D, command: &str) -> Result<String> {
+    let mut output = Vec::new();
+    device.shell_command(&[command], &mut output)?;
+    Ok(String::from_utf8(output)?.trim().to_string())
+}
+
+/// Size of remote file at `quoted_path`, `0` if it does not exist.
+///
+/// Sync `STAT` only reports sizes on 32 bits, which is not enough for large files.
+fn remote_file_size<D: ADBDeviceExt + ?Sized>(device: &mut D, quoted_path: &str) -> Result<u64> {
+    let output = shell_output(
+        device,
+        &format!("stat -c %s {quoted_path} 2>/dev/null || echo 0"),
+    )?;
+    Ok(output.parse()?)
+}
+
+/// Checksum of the first `length` bytes of remote file at `quoted_path`, as printed by `cksum`.
+fn remote_cksum<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    quoted_path: &str,
+    length: u64,
+) -> Result<u32> {
+    let output = shell_output(device, &format!("head -c {length} {quoted_path} | cksum"))?;
+    let crc = output.split_whitespace().next().ok_or_else(|| {
+        RustADBError::ADBRequestFailed(format!("unexpected cksum output {output:?}"))
+    })?;
+    Ok(crc.parse()?)
+}
+
+/// Push `local` to `remote`, resuming from what an interrupted previous push already uploaded.
+///
+/// Remote file is kept if its content matches the beginning of `local`, checked using `cksum`, and the rest of `local`
+/// is uploaded part by part, each part being pushed next to `remote` before being appended to it.
+pub(crate) fn push_resume<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    local: &Path,
+    remote: &str,
+) -> Result<()> {
+    let mut file = File::open(local)?;
+    let file_size = file.metadata()?.len();
+    let quoted_remote = shell_quote(remote);
+
+    let mut offset = remote_file_size(device, &quoted_remote)?;
+    if offset > file_size {
+        offset = 0;
+    } else if offset > 0 {
+        let mut local_cksum = Cksum::default();
+        std::io::copy(&mut (&mut file).take(offset), &mut local_cksum)?;
+        if remote_cksum(device, &quoted_remote, offset)? != local_cksum.finish() {
+            log::debug!(
+                "{remote} differs from {}, pushing it again",
+                local.display()
+            );
+            offset = 0;
+        }
+    }
+
+    if offset == 0 {
+        file.seek(SeekFrom::Start(0))?;
+        device.push(&mut (&mut file).take(PUSH_RESUME_PART_SIZE), &remote)?;
+        offset = file_size.min(PUSH_RESUME_PART_SIZE);
+    }
+
+    let part = format!("{remote}.part");
+    let quoted_part = shell_quote(&part);
+    while offset < file_size {
+        log::debug!("resuming push of {} at offset {offset}", local.display());
+        file.seek(SeekFrom::Start(offset))?;
+        device.push(&mut (&mut file).take(PUSH_RESUME_PART_SIZE), &part)?;
+        shell_output(
+            device,
+            &format!("cat {quoted_part} >> {quoted_remote} && rm -f {quoted_part}"),
+        )?;
+        offset += (file_size - offset).min(PUSH_RESUME_PART_SIZE);
+    }
+
+    let remote_size = remote_file_size(device, &quoted_remote)?;
+    if remote_size != file_size {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "{remote} has a size of {remote_size} bytes instead of {file_size}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_cksum() {
+    let mut cksum = Cksum::default();
+    cksum.write_all(b"123456789").expect("cannot compute cksum");
+    assert_eq!(cksum.finish(), 930766865);
+    assert_eq!(Cksum::default().finish(), 4294967295);
+}
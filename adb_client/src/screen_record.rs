@@ -0,0 +1,77 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::models::ScreenRecordOptions;
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Longest recording supported by `screenrecord`
+const SCREEN_RECORD_MAX_DURATION: Duration = Duration::from_secs(180);
+/// Time given to `screenrecord` on top of recording duration to start and finalize its file
+const SCREEN_RECORD_STOP_MARGIN: Duration = Duration::from_secs(10);
+
+/// Record screen of `device` into `local_path`, see [`ADBDeviceExt::record_screen`].
+pub(crate) fn record_screen<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    duration: Duration,
+    options: &ScreenRecordOptions,
+    local_path: &Path,
+) -> Result<()> {
+    if duration > SCREEN_RECORD_MAX_DURATION {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "screenrecord cannot record more than {} seconds",
+            SCREEN_RECORD_MAX_DURATION.as_secs()
+        )));
+    }
+    // Time limit is in whole seconds, and 0 means no limit
+    let seconds = duration.as_secs_f64().ceil().max(1.0) as u64;
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis());
+    let remote_path = format!("/data/local/tmp/adb_client_screenrecord_{timestamp}.mp4");
+
+    let recorded = record_to(device, seconds, options, &remote_path).and_then(|()| {
+        let mut output = BufWriter::new(File::create(local_path)?);
+        device.pull(&remote_path, &mut output)
+    });
+
+    // Remove remote file whatever happened, failure to record being what matters to caller
+    let removed = device.shell_command(&["rm", "-f", &remote_path], &mut std::io::sink());
+    recorded.and(removed)
+}
+
+/// Run `screenrecord` on `device` for `seconds` into `remote_path`
+fn record_to<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    seconds: u64,
+    options: &ScreenRecordOptions,
+    remote_path: &str,
+) -> Result<()> {
+    let mut command = vec![
+        "screenrecord".to_string(),
+        format!("--time-limit {seconds}"),
+    ];
+    command.extend(options.args());
+    command.push(remote_path.to_string());
+    let command: Vec<&str> = command.iter().map(String::as_str).collect();
+
+    // `screenrecord` stops by itself once time limit is reached, finalizing its file
+    let mut output = Vec::new();
+    device.shell_command_with_timeout(
+        &command,
+        &mut output,
+        Duration::from_secs(seconds) + SCREEN_RECORD_STOP_MARGIN,
+    )?;
+
+    // Errors are reported on output, file being left empty or missing
+    let size = device.stat(remote_path)?.file_size;
+    if size == 0 {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "screenrecord failed: {}",
+            String::from_utf8_lossy(&output).trim()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::io::{Cursor, Read};
+
+use image::codecs::png::PngDecoder;
+use image::{ColorType, DynamicImage, ImageBuffer, ImageDecoder, Rgba};
+
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Signature starting every PNG file
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Capture screen of `device` using `screencap`, returning PNG it encoded.
+///
+/// `screencap` reports failures on its output, which is then not a PNG.
+pub(crate) fn screencap<D: ADBDeviceExt + ?Sized>(device: &mut D) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    device.exec_out(&["screencap", "-p"], &mut png)?;
+
+    if !png.starts_with(PNG_SIGNATURE) {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "screencap failed: {}",
+            String::from_utf8_lossy(&png).trim()
+        )));
+    }
+
+    Ok(png)
+}
+
+/// Read next PNG written by `screencap` on `stream` into `png`, which is left empty if stream has been closed before.
+///
+/// PNG files are made of chunks following signature, the last one being `IEND`, which allows to read several of them
+/// from a single stream.
+pub(crate) fn read_png(stream: &mut dyn Read, png: &mut Vec<u8>) -> Result<()> {
+    png.clear();
+    png.resize(PNG_SIGNATURE.len(), 0);
+    let size = stream.read(png)?;
+    if size == 0 {
+        png.clear();
+        return Ok(());
+    }
+    stream.read_exact(&mut png[size..])?;
+    if png != PNG_SIGNATURE {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "screencap failed: {}",
+            String::from_utf8_lossy(png).trim()
+        )));
+    }
+
+    loop {
+        // Chunk is made of its length, type, data and CRC
+        let start = png.len();
+        png.resize(start + 8, 0);
+        stream.read_exact(&mut png[start..])?;
+        let length =
+            u32::from_be_bytes([png[start], png[start + 1], png[start + 2], png[start + 3]]);
+        let is_end = png[start + 4..start + 8] == *b"IEND";
+
+        let start = png.len();
+        png.resize(start + length as usize + 4, 0);
+        stream.read_exact(&mut png[start..])?;
+        if is_end {
+            return Ok(());
+        }
+    }
+}
+
+/// Decode `png` into `buffer`, reusing its allocation when PNG is already made of RGBA pixels (as encoded by `screencap`)
+pub(crate) fn decode_png_into(
+    png: &[u8],
+    mut buffer: Vec<u8>,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let decoder = PngDecoder::new(Cursor::new(png))?;
+    if decoder.color_type() != ColorType::Rgba8 {
+        return Ok(DynamicImage::from_decoder(decoder)?.into_rgba8());
+    }
+
+    let (width, height) = decoder.dimensions();
+    buffer.clear();
+    buffer.resize(
+        decoder
+            .total_bytes()
+            .try_into()
+            .map_err(|_| RustADBError::ConversionError)?,
+        0,
+    );
+    decoder.read_image(&mut buffer)?;
+    ImageBuffer::from_raw(width, height, buffer).ok_or(RustADBError::FramebufferConversionError)
+}
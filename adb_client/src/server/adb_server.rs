@@ -1,9 +1,18 @@
 use crate::ADBTransport;
+use crate::CancelToken;
 use crate::Result;
 use crate::RustADBError;
+use crate::ServerAddress;
+#[cfg(feature = "ssh")]
+use crate::SshTunnelTransport;
 use crate::TCPServerTransport;
+use crate::TcpProxy;
+#[cfg(unix)]
+use crate::transports::ADB_SERVER_SOCKET_ENV;
 use std::collections::HashMap;
 use std::net::SocketAddrV4;
+#[cfg(unix)]
+use std::path::PathBuf;
 use std::process::Command;
 
 /// Represents an ADB Server
@@ -11,13 +20,20 @@ use std::process::Command;
 pub struct ADBServer {
     /// Internal [TcpStream], lazily initialized
     pub(crate) transport: Option<TCPServerTransport>,
-    /// Address to connect to
-    pub(crate) socket_addr: Option<SocketAddrV4>,
+    /// Address to connect to, read from environment (`ADB_SERVER_SOCKET`, `ANDROID_ADB_SERVER_PORT`) if not set
+    pub(crate) address: Option<ServerAddress>,
     /// adb-server start envs
     pub(crate) envs: HashMap<String, String>,
     /// Path to adb binary
     /// If not set, will use adb from PATH
     pub(crate) adb_path: Option<String>,
+    /// Token cancelling in-flight operations
+    pub(crate) cancel_token: Option<CancelToken>,
+    /// Proxy traversed to reach server
+    pub(crate) proxy: Option<TcpProxy>,
+    /// SSH tunnel server is reached through, kept open as long as server is used
+    #[cfg(feature = "ssh")]
+    pub(crate) tunnel: Option<SshTunnelTransport>,
 }
 
 impl ADBServer {
@@ -25,9 +41,13 @@ impl ADBServer {
     pub fn new(address: SocketAddrV4) -> Self {
         Self {
             transport: None,
-            socket_addr: Some(address),
+            address: Some(address.into()),
             envs: HashMap::new(),
             adb_path: None,
+            cancel_token: None,
+            proxy: None,
+            #[cfg(feature = "ssh")]
+            tunnel: None,
         }
     }
 
@@ -35,12 +55,56 @@ impl ADBServer {
     pub fn new_from_path(address: SocketAddrV4, adb_path: Option<String>) -> Self {
         Self {
             transport: None,
-            socket_addr: Some(address),
+            address: Some(address.into()),
             envs: HashMap::new(),
             adb_path,
+            cancel_token: None,
+            proxy: None,
+            #[cfg(feature = "ssh")]
+            tunnel: None,
         }
     }
 
+    /// Instantiates a new [ADBServer] listening on unix socket at `path`.
+    ///
+    /// If not running yet, server is started with `ADB_SERVER_SOCKET` pointing to this socket.
+    #[cfg(unix)]
+    pub fn new_unix(path: PathBuf) -> Self {
+        Self {
+            transport: None,
+            address: Some(ServerAddress::Unix(path)),
+            envs: HashMap::new(),
+            adb_path: None,
+            cancel_token: None,
+            proxy: None,
+            #[cfg(feature = "ssh")]
+            tunnel: None,
+        }
+    }
+
+    /// Instantiates a new [ADBServer] reached through `tunnel`, forwarding a remote adb server (e.g. `localhost:5037`
+    /// of SSH server). Tunnel is connected if needed, and closed once server is dropped.
+    #[cfg(feature = "ssh")]
+    pub fn new_over_ssh(mut tunnel: SshTunnelTransport) -> Result<Self> {
+        if tunnel.local_address().is_err() {
+            tunnel.connect()?;
+        }
+
+        let mut server = Self::new(tunnel.local_address()?);
+        server.tunnel = Some(tunnel);
+        Ok(server)
+    }
+
+    /// Attach a [`CancelToken`] honored by device tracking
+    pub fn set_cancel_token(&mut self, cancel_token: CancelToken) {
+        self.cancel_token = Some(cancel_token);
+    }
+
+    /// Reach server, and devices it returns, through `proxy`. A server behind a proxy is never started.
+    pub fn set_proxy(&mut self, proxy: Option<TcpProxy>) {
+        self.proxy = proxy;
+    }
+
     /// Start an instance of `adb-server`
     pub fn start(envs: &HashMap<String, String>, adb_path: &Option<String>) {
         // ADB Server is local, we start it if not already running
@@ -68,6 +132,18 @@ impl ADBServer {
         }
     }
 
+    /// Whether server is reached through an SSH tunnel, listening locally but never to be started
+    fn is_tunneled(&self) -> bool {
+        #[cfg(feature = "ssh")]
+        {
+            self.tunnel.is_some()
+        }
+        #[cfg(not(feature = "ssh"))]
+        {
+            false
+        }
+    }
+
     /// Returns the current selected transport
     pub(crate) fn get_transport(&mut self) -> Result<&mut TCPServerTransport> {
         self.transport
@@ -80,22 +156,25 @@ impl ADBServer {
 
     /// Connect to underlying transport
     pub(crate) fn connect(&mut self) -> Result<&mut TCPServerTransport> {
-        let mut is_local_ip = false;
-        let mut transport = if let Some(addr) = &self.socket_addr {
-            let ip = addr.ip();
-            if ip.is_loopback() || ip.is_unspecified() {
-                is_local_ip = true;
+        let address = self
+            .address
+            .clone()
+            .unwrap_or_else(ServerAddress::from_env_or_default);
+
+        if address.is_local() && self.proxy.is_none() && !self.is_tunneled() {
+            let mut envs = self.envs.clone();
+            #[cfg(unix)]
+            if matches!(address, ServerAddress::Unix(_)) {
+                envs.insert(
+                    ADB_SERVER_SOCKET_ENV.to_string(),
+                    address.server_socket_spec(),
+                );
             }
-            TCPServerTransport::new(*addr)
-        } else {
-            is_local_ip = true;
-            TCPServerTransport::default()
-        };
-
-        if is_local_ip {
-            Self::start(&self.envs, &self.adb_path);
+            Self::start(&envs, &self.adb_path);
         }
 
+        let mut transport = TCPServerTransport::new_with_address(address);
+        transport.set_proxy(self.proxy.clone());
         transport.connect()?;
         self.transport = Some(transport);
 
@@ -1,7 +1,8 @@
 use std::io::Read;
 
 use crate::{
-    ADBEmulatorDevice, ADBServer, ADBServerDevice, DeviceLong, DeviceShort, Result, RustADBError,
+    ADBEmulatorDevice, ADBServer, ADBServerDevice, CancelToken, DeviceLong, DeviceShort,
+    EventStream, Result, RustADBError, TCPServerTransport, event_stream::EventSender,
     models::AdbServerCommand,
 };
 
@@ -42,6 +43,13 @@ impl ADBServer {
         Ok(vec_devices)
     }
 
+    /// Device reached through this server, sharing its address and proxy
+    fn server_device(&self, identifier: String) -> ADBServerDevice {
+        let mut device = ADBServerDevice::new_with_address(Some(identifier), self.address.clone());
+        device.transport.set_proxy(self.proxy.clone());
+        device
+    }
+
     /// Get a device, assuming that only this device is connected.
     pub fn get_device(&mut self) -> Result<ADBServerDevice> {
         let mut devices = self.devices()?.into_iter();
@@ -50,7 +58,7 @@ impl ADBServer {
                 Some(_) => Err(RustADBError::DeviceNotFound(
                     "too many devices connected".to_string(),
                 )),
-                None => Ok(ADBServerDevice::new(device.identifier, self.socket_addr)),
+                None => Ok(self.server_device(device.identifier)),
             },
             None => Err(RustADBError::DeviceNotFound(
                 "no device connected".to_string(),
@@ -74,35 +82,95 @@ impl ADBServer {
                 "could not find device {name}"
             )))
         } else {
-            Ok(ADBServerDevice::new(name.to_string(), self.socket_addr))
+            Ok(self.server_device(name.to_string()))
         }
     }
 
     /// Tracks new devices showing up.
     pub fn track_devices(&mut self, callback: impl Fn(DeviceShort) -> Result<()>) -> Result<()> {
+        for device in self.track_devices_stream()? {
+            callback(device?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Tracks new devices showing up, returning them as an [`EventStream`].
+    pub fn track_devices_stream(&mut self) -> Result<EventStream<DeviceShort>> {
+        let mut transport = self.track_devices_connection()?;
+
+        // Shutting tracking connection down unblocks the producer waiting for next update
+        let stream_token = CancelToken::new();
+        let stream = transport.get_raw_connection()?.try_clone()?;
+        let shutdown_guard = stream_token.on_cancel(move || {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        });
+        // Cancelling server still stops tracking
+        let cancel_token = self.cancel_token.clone();
+        let cancel_guard = cancel_token.as_ref().map(|cancel_token| {
+            let stream_token = stream_token.clone();
+            cancel_token.on_cancel(move || stream_token.cancel())
+        });
+
+        Ok(EventStream::spawn(move |sender| {
+            let _guards = (shutdown_guard, cancel_guard);
+            let result = Self::track_devices_loop(&mut transport, sender);
+            match cancel_token {
+                Some(cancel_token) if cancel_token.is_cancelled() => Err(RustADBError::Cancelled),
+                _ => result,
+            }
+        })
+        .cancel_on_drop(stream_token))
+    }
+
+    /// Start tracking devices, and return the connection dedicated to it.
+    pub(crate) fn track_devices_connection(&mut self) -> Result<TCPServerTransport> {
         self.connect()?
             .send_adb_request(AdbServerCommand::TrackDevices)?;
 
-        loop {
-            let length = self.get_transport()?.get_hex_body_length()?;
-
-            if length > 0 {
-                let mut body = vec![
-                    0;
-                    length
-                        .try_into()
-                        .map_err(|_| RustADBError::ConversionError)?
-                ];
-                self.get_transport()?
-                    .get_raw_connection()?
-                    .read_exact(&mut body)?;
-
-                for device in body.split(|x| x.eq(&b'\n')) {
-                    if device.is_empty() {
-                        break;
-                    }
-                    callback(DeviceShort::try_from(device.to_vec())?)?;
+        // Tracking connection is owned by caller, next requests will use a new one
+        self.transport
+            .take()
+            .ok_or(RustADBError::IOError(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "server connection not initialized",
+            )))
+    }
+
+    /// Wait for next update on a tracking connection, and return the complete list of devices it contains.
+    pub(crate) fn read_devices_update(
+        transport: &mut TCPServerTransport,
+    ) -> Result<Vec<DeviceShort>> {
+        let length = transport.get_hex_body_length()?;
+
+        let mut devices = Vec::new();
+        if length > 0 {
+            let mut body = vec![
+                0;
+                length
+                    .try_into()
+                    .map_err(|_| RustADBError::ConversionError)?
+            ];
+            transport.get_raw_connection()?.read_exact(&mut body)?;
+
+            for device in body.split(|x| x.eq(&b'\n')) {
+                if device.is_empty() {
+                    break;
                 }
+                devices.push(DeviceShort::try_from(device.to_vec())?);
+            }
+        }
+
+        Ok(devices)
+    }
+
+    fn track_devices_loop(
+        transport: &mut TCPServerTransport,
+        sender: &EventSender<DeviceShort>,
+    ) -> Result<()> {
+        loop {
+            for device in Self::read_devices_update(transport)? {
+                sender.send(device)?;
             }
         }
     }
@@ -12,7 +12,7 @@ impl ADBServer {
         let transport = transport.unwrap_or_default();
 
         self.connect()?
-            .send_adb_request(AdbServerCommand::WaitForDevice(state, transport))?;
+            .send_adb_request(AdbServerCommand::WaitForDevice(None, state, transport))?;
 
         // Server should respond with an "OKAY" response
         self.get_transport()?.read_adb_response()
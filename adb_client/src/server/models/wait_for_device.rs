@@ -53,6 +53,8 @@ pub enum WaitForDeviceState {
     Sideload,
     /// Device in "bootloader" state
     Bootloader,
+    /// Device disconnected
+    Disconnect,
 }
 
 impl Display for WaitForDeviceState {
@@ -62,6 +64,7 @@ impl Display for WaitForDeviceState {
             WaitForDeviceState::Recovery => write!(f, "recovery"),
             WaitForDeviceState::Sideload => write!(f, "sideload"),
             WaitForDeviceState::Bootloader => write!(f, "bootloader"),
+            WaitForDeviceState::Disconnect => write!(f, "disconnect"),
         }
     }
 }
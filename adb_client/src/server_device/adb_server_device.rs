@@ -1,5 +1,9 @@
-use crate::{ADBTransport, Result, TCPServerTransport, models::AdbServerCommand};
-use std::net::SocketAddrV4;
+use crate::{
+    ADBTransport, AdbObserver, CancelToken, Result, RustADBError, ServerAddress,
+    TCPServerTransport, cancel_token::CancelGuard, models::AdbServerCommand,
+    observer::ObserverSlot,
+};
+use std::{net::SocketAddrV4, sync::Arc};
 
 /// Represents a device connected to the ADB server.
 #[derive(Debug)]
@@ -8,33 +12,77 @@ pub struct ADBServerDevice {
     pub identifier: Option<String>,
     /// Internal [TCPServerTransport]
     pub(crate) transport: TCPServerTransport,
+    /// Observer notified by long operations
+    pub(crate) observer: ObserverSlot,
+    /// Token cancelling in-flight operations
+    pub(crate) cancel_token: Option<CancelToken>,
+    /// Shuts current connection down on cancellation
+    pub(crate) cancel_guard: Option<CancelGuard>,
+    /// Maximum rate of file transfers, in bytes per second
+    pub(crate) transfer_rate_limit: Option<u64>,
 }
 
 impl ADBServerDevice {
     /// Instantiates a new [ADBServerDevice], knowing its ADB identifier (as returned by `adb devices` command).
     pub fn new(identifier: String, server_addr: Option<SocketAddrV4>) -> Self {
-        let transport = TCPServerTransport::new_or_default(server_addr);
+        Self::new_with_address(Some(identifier), server_addr.map(ServerAddress::from))
+    }
+
+    /// Instantiates a new [ADBServerDevice] reaching server at given [ServerAddress], or the one set in environment if not specified.
+    pub(crate) fn new_with_address(
+        identifier: Option<String>,
+        server_address: Option<ServerAddress>,
+    ) -> Self {
+        let transport = TCPServerTransport::new_with_address(
+            server_address.unwrap_or_else(ServerAddress::from_env_or_default),
+        );
 
         Self {
-            identifier: Some(identifier),
+            identifier,
             transport,
+            observer: ObserverSlot::default(),
+            cancel_token: None,
+            cancel_guard: None,
+            transfer_rate_limit: None,
         }
     }
 
     /// Instantiates a new [ADBServerDevice], assuming only one is currently connected.
     pub fn autodetect(server_addr: Option<SocketAddrV4>) -> Self {
-        let transport = TCPServerTransport::new_or_default(server_addr);
+        Self::new_with_address(None, server_addr.map(ServerAddress::from))
+    }
 
-        Self {
-            identifier: None,
-            transport,
-        }
+    /// Attach an [`AdbObserver`] notified by all following operations
+    pub fn set_observer(&mut self, observer: Arc<dyn AdbObserver>) {
+        self.observer.set(observer);
+    }
+
+    /// Attach a [`CancelToken`] honored by all following operations
+    pub fn set_cancel_token(&mut self, cancel_token: CancelToken) {
+        self.cancel_token = Some(cancel_token);
+    }
+
+    /// Limit rate of following file transfers (push and pull) to `bytes_per_second`, `None` or `0` meaning no limit
+    pub fn set_transfer_rate_limit(&mut self, bytes_per_second: Option<u64>) {
+        self.transfer_rate_limit = bytes_per_second;
     }
 
     /// Connect to underlying transport
     pub(crate) fn connect(&mut self) -> Result<&mut TCPServerTransport> {
+        if let Some(cancel_token) = &self.cancel_token {
+            cancel_token.check()?;
+        }
+
         self.transport.connect()?;
 
+        if let Some(cancel_token) = &self.cancel_token {
+            // Shutting the socket down unblocks any pending read or write on it
+            let stream = self.transport.get_raw_connection()?.try_clone()?;
+            self.cancel_guard = Some(cancel_token.on_cancel(move || {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }));
+        }
+
         Ok(&mut self.transport)
     }
 
@@ -50,6 +98,16 @@ impl ADBServerDevice {
 
         Ok(())
     }
+
+    /// Replace `result` by [`RustADBError::Cancelled`] if operation has been cancelled meanwhile.
+    ///
+    /// A cancelled connection may either look closed or fail, depending on the pending call.
+    pub(crate) fn cancellable<T>(&self, result: Result<T>) -> Result<T> {
+        match &self.cancel_token {
+            Some(cancel_token) if cancel_token.is_cancelled() => Err(RustADBError::Cancelled),
+            _ => result,
+        }
+    }
 }
 
 impl Drop for ADBServerDevice {
@@ -1,12 +1,15 @@
 use std::{
-    io::{ErrorKind, Read, Write},
+    io::{Read, Write},
     path::Path,
+    time::{Duration, SystemTime},
 };
 
 use crate::{
-    ADBDeviceExt, Result, RustADBError,
+    ADBDeviceExt, CancelToken, InstallOptions, ReadSeek, ReadWriteStream, Result, RustADBError,
+    ShellOptions, ShellOutput, TransferStats,
     constants::BUFFER_SIZE,
     models::{AdbServerCommand, AdbStatResponse, HostFeatures},
+    shell_protocol::{legacy_run_command, legacy_shell_command_output},
 };
 
 use super::ADBServerDevice;
@@ -25,98 +28,181 @@ impl ADBDeviceExt for ADBServerDevice {
         self.transport
             .send_adb_request(AdbServerCommand::ShellCommand(command.join(" ")))?;
 
-        loop {
+        let result = loop {
             let mut buffer = [0; BUFFER_SIZE];
             match self.transport.get_raw_connection()?.read(&mut buffer) {
                 Ok(size) => {
                     if size == 0 {
-                        return Ok(());
+                        break Ok(());
                     } else {
                         output.write_all(&buffer[..size])?;
                     }
                 }
                 Err(e) => {
-                    return Err(RustADBError::IOError(e));
+                    break Err(RustADBError::IOError(e));
                 }
             }
+        };
+
+        self.cancellable(result)
+    }
+
+    fn shell_command_output(&mut self, command: &[&str], output: ShellOutput<'_>) -> Result<()> {
+        if !self.host_features()?.contains(&HostFeatures::ShellV2) {
+            return legacy_shell_command_output(self, command, output);
+        }
+        self.shell_command_v2(command, output)?;
+        Ok(())
+    }
+
+    fn run_command(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        legacy_exit_code: bool,
+    ) -> Result<u8> {
+        if !self.host_features()?.contains(&HostFeatures::ShellV2) {
+            return legacy_run_command(self, command, output, legacy_exit_code);
         }
+        self.shell_command_v2(command, ShellOutput::Merged(output))
+    }
+
+    fn shell_command_with_timeout(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<()> {
+        self.shell_command_with_timeout(command, output, timeout)
+    }
+
+    fn features(&mut self) -> Result<Vec<String>> {
+        self.features()
     }
 
     fn stat(&mut self, remote_path: &str) -> Result<AdbStatResponse> {
         self.stat(remote_path)
     }
 
-    fn shell(
+    fn shell(&mut self, reader: &mut dyn Read, writer: Box<(dyn Write + Send)>) -> Result<()> {
+        self.interactive_shell(AdbServerCommand::Shell, reader, writer)
+    }
+
+    fn shell_with_options(
         &mut self,
-        mut reader: &mut dyn Read,
-        mut writer: Box<(dyn Write + Send)>,
+        options: &ShellOptions,
+        reader: &mut dyn Read,
+        writer: Box<dyn Write + Send>,
     ) -> Result<()> {
-        let supported_features = self.host_features()?;
-        if !supported_features.contains(&HostFeatures::ShellV2)
-            && !supported_features.contains(&HostFeatures::Cmd)
-        {
-            return Err(RustADBError::ADBShellNotSupported);
-        }
+        let service = options.service("")?;
+        self.interactive_shell(AdbServerCommand::Service(service), reader, writer)
+    }
 
-        self.set_serial_transport()?;
-        self.transport.send_adb_request(AdbServerCommand::Shell)?;
+    fn pull(
+        &mut self,
+        source: &dyn AsRef<str>,
+        mut output: &mut dyn Write,
+    ) -> Result<TransferStats> {
+        let result = self.pull(source, &mut output);
+        self.cancellable(result)
+    }
+
+    fn reboot(&mut self, reboot_type: crate::RebootType) -> Result<()> {
+        self.reboot(reboot_type)
+    }
 
-        let mut read_stream = self.transport.get_raw_connection()?.try_clone()?;
+    fn push_with_metadata(
+        &mut self,
+        stream: &mut dyn Read,
+        path: &dyn AsRef<str>,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<TransferStats> {
+        let result = self.push_with_metadata(stream, path, mode, mod_time);
+        self.cancellable(result)
+    }
 
-        let mut write_stream = read_stream.try_clone()?;
+    fn root(&mut self) -> Result<()> {
+        let result = self.root();
+        self.cancellable(result)
+    }
 
-        // Reading thread, reads response from adb-server
-        std::thread::spawn(move || -> Result<()> {
-            loop {
-                let mut buffer = [0; BUFFER_SIZE];
-                match read_stream.read(&mut buffer) {
-                    Ok(0) => {
-                        read_stream.shutdown(std::net::Shutdown::Both)?;
-                        return Ok(());
-                    }
-                    Ok(size) => {
-                        writer.write_all(&buffer[..size])?;
-                        writer.flush()?;
-                    }
-                    Err(e) => {
-                        return Err(RustADBError::IOError(e));
-                    }
-                }
-            }
-        });
+    fn unroot(&mut self) -> Result<()> {
+        let result = self.unroot();
+        self.cancellable(result)
+    }
 
-        // Read from given reader (that could be stdin e.g), and write content to server socket
-        if let Err(e) = std::io::copy(&mut reader, &mut write_stream) {
-            match e.kind() {
-                ErrorKind::BrokenPipe => return Ok(()),
-                _ => return Err(RustADBError::IOError(e)),
-            }
-        }
+    fn remount(&mut self, reboot: bool) -> Result<bool> {
+        let result = self.remount(reboot);
+        self.cancellable(result)
+    }
 
-        Ok(())
+    fn disable_verity(&mut self, reboot: bool) -> Result<bool> {
+        let result = self.disable_verity(reboot);
+        self.cancellable(result)
     }
 
-    fn pull(&mut self, source: &dyn AsRef<str>, mut output: &mut dyn Write) -> Result<()> {
-        self.pull(source, &mut output)
+    fn enable_verity(&mut self, reboot: bool) -> Result<bool> {
+        let result = self.enable_verity(reboot);
+        self.cancellable(result)
     }
 
-    fn reboot(&mut self, reboot_type: crate::RebootType) -> Result<()> {
-        self.reboot(reboot_type)
+    fn install_with_options(
+        &mut self,
+        apk_path: &dyn AsRef<Path>,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        let result = self.install_with_options(apk_path, options);
+        self.cancellable(result)
     }
 
-    fn push(&mut self, stream: &mut dyn Read, path: &dyn AsRef<str>) -> Result<()> {
-        self.push(stream, path)
+    fn install_from_reader_with_options(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        let result = self.install_from_reader_with_options(reader, size, options);
+        self.cancellable(result)
     }
 
-    fn install(&mut self, apk_path: &dyn AsRef<Path>) -> Result<()> {
-        self.install(apk_path)
+    fn sideload_from_reader(
+        &mut self,
+        reader: &mut dyn ReadSeek,
+        size: u64,
+    ) -> Result<TransferStats> {
+        let result = self.sideload_from_reader(reader, size);
+        self.cancellable(result)
     }
 
     fn uninstall(&mut self, package: &str) -> Result<()> {
         self.uninstall(package)
     }
 
+    fn open_service(&mut self, service: &str) -> Result<Box<dyn ReadWriteStream>> {
+        self.open_service(service)
+    }
+
+    fn open_service_cancellable(
+        &mut self,
+        service: &str,
+        cancel_token: &CancelToken,
+    ) -> Result<Box<dyn ReadWriteStream>> {
+        self.open_service_cancellable(service, cancel_token)
+    }
+
     fn framebuffer_inner(&mut self) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
         self.framebuffer_inner()
     }
+
+    fn framebuffer_into(
+        &mut self,
+        buffer: Vec<u8>,
+    ) -> Result<image::ImageBuffer<image::Rgba<u8>, Vec<u8>>> {
+        self.framebuffer_into(buffer)
+    }
+
+    fn framebuffer_raw(&mut self) -> Result<crate::RawFramebuffer> {
+        self.framebuffer_raw()
+    }
 }
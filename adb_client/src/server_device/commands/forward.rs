@@ -1,21 +1,65 @@
-use crate::{ADBServerDevice, Result, models::AdbServerCommand};
+use std::io::Read;
+
+use crate::{
+    ADBServerDevice, Result, RustADBError,
+    models::{AdbServerCommand, ForwardRule},
+};
 
 impl ADBServerDevice {
-    /// Forward socket connection
-    pub fn forward(&mut self, remote: String, local: String) -> Result<()> {
-        self.set_serial_transport()?;
+    /// Forward connections to `local` socket on host to `remote` socket on device, e.g. `tcp:8080` to `tcp:80`.
+    ///
+    /// If `local` is `tcp:0`, server picks a free port which is returned.
+    pub fn forward(&mut self, local: &str, remote: &str) -> Result<Option<u16>> {
+        let serial = self.identifier.clone();
+        let transport = self.connect()?;
+        transport.send_adb_request(AdbServerCommand::Forward(
+            serial,
+            local.to_string(),
+            remote.to_string(),
+        ))?;
+        // Server replies a second time once rule has been installed
+        transport.read_adb_response()?;
+
+        // Picked port is sent as a length-prefixed string, before connection gets closed
+        let mut port = String::new();
+        transport.get_raw_connection()?.read_to_string(&mut port)?;
+        match port.get(4..) {
+            Some(port) if !port.is_empty() => Ok(Some(port.parse().map_err(|_| {
+                RustADBError::ADBRequestFailed(format!("invalid forwarded port {port:?}"))
+            })?)),
+            _ => Ok(None),
+        }
+    }
 
-        self.transport
-            .proxy_connection(AdbServerCommand::Forward(remote, local), false)
-            .map(|_| ())
+    /// Remove forward rule of `local` socket on host
+    pub fn forward_remove(&mut self, local: &str) -> Result<()> {
+        let serial = self.identifier.clone();
+        let transport = self.connect()?;
+        transport.send_adb_request(AdbServerCommand::ForwardRemove(serial, local.to_string()))?;
+        transport.read_adb_response()
     }
 
     /// Remove all previously applied forward rules
     pub fn forward_remove_all(&mut self) -> Result<()> {
-        self.set_serial_transport()?;
+        let serial = self.identifier.clone();
+        let transport = self.connect()?;
+        transport.send_adb_request(AdbServerCommand::ForwardRemoveAll(serial))?;
+        transport.read_adb_response()
+    }
+
+    /// List forward rules applied to this device, or to all devices if it has no identifier
+    pub fn forward_list(&mut self) -> Result<Vec<ForwardRule>> {
+        let output = self
+            .connect()?
+            .proxy_connection(AdbServerCommand::ForwardList, true)?;
 
-        self.transport
-            .proxy_connection(AdbServerCommand::ForwardRemoveAll, false)
-            .map(|_| ())
+        Ok(ForwardRule::parse(&String::from_utf8(output)?)
+            .into_iter()
+            .filter(|rule| {
+                self.identifier
+                    .as_ref()
+                    .is_none_or(|serial| *serial == rule.serial)
+            })
+            .collect())
     }
 }
@@ -1,76 +1,51 @@
 use std::io::Read;
 
-use byteorder::{LittleEndian, ReadBytesExt};
 use image::{ImageBuffer, Rgba};
 
 use crate::{
     ADBServerDevice, Result, RustADBError,
-    models::{AdbServerCommand, FrameBufferInfoV1, FrameBufferInfoV2},
+    models::{AdbServerCommand, FrameBufferInfo, RawFramebuffer},
 };
 
 impl ADBServerDevice {
     /// Inner method requesting framebuffer from Android device
     pub(crate) fn framebuffer_inner(&mut self) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
-        self.set_serial_transport()?;
-
-        self.transport
-            .send_adb_request(AdbServerCommand::FrameBuffer)?;
-
-        let version = self
-            .transport
-            .get_raw_connection()?
-            .read_u32::<LittleEndian>()?;
-
-        match version {
-            // RGBA_8888
-            1 => {
-                let mut buf = [0u8; std::mem::size_of::<FrameBufferInfoV1>()];
+        self.framebuffer_into(Vec::new())
+    }
 
-                self.transport.get_raw_connection()?.read_exact(&mut buf)?;
+    /// Request framebuffer from Android device into `buffer`, reusing its allocation
+    pub(crate) fn framebuffer_into(
+        &mut self,
+        buffer: Vec<u8>,
+    ) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        self.framebuffer_raw_into(buffer)?.into_image()
+    }
 
-                let framebuffer_info: FrameBufferInfoV1 = buf.try_into()?;
+    /// Request framebuffer from Android device, along with its header
+    pub fn framebuffer_raw(&mut self) -> Result<RawFramebuffer> {
+        self.framebuffer_raw_into(Vec::new())
+    }
 
-                let mut data = vec![
-                    0_u8;
-                    framebuffer_info
-                        .size
-                        .try_into()
-                        .map_err(|_| RustADBError::ConversionError)?
-                ];
-                self.transport.get_raw_connection()?.read_exact(&mut data)?;
+    /// Request framebuffer as sent by Android device into `buffer`, reusing its allocation
+    fn framebuffer_raw_into(&mut self, mut buffer: Vec<u8>) -> Result<RawFramebuffer> {
+        self.set_serial_transport()?;
 
-                Ok(ImageBuffer::<Rgba<u8>, Vec<u8>>::from_vec(
-                    framebuffer_info.width,
-                    framebuffer_info.height,
-                    data,
-                )
-                .ok_or_else(|| RustADBError::FramebufferConversionError)?)
-            }
-            // RGBX_8888
-            2 => {
-                let mut buf = [0u8; std::mem::size_of::<FrameBufferInfoV2>()];
+        self.transport
+            .send_adb_request(AdbServerCommand::FrameBuffer)?;
 
-                self.transport.get_raw_connection()?.read_exact(&mut buf)?;
+        let mut connection = self.transport.get_raw_connection()?;
 
-                let framebuffer_info: FrameBufferInfoV2 = buf.try_into()?;
+        let info = FrameBufferInfo::read(&mut connection)?;
 
-                let mut data = vec![
-                    0_u8;
-                    framebuffer_info
-                        .size
-                        .try_into()
-                        .map_err(|_| RustADBError::ConversionError)?
-                ];
-                self.transport.get_raw_connection()?.read_exact(&mut data)?;
+        buffer.clear();
+        buffer.resize(
+            info.size()
+                .try_into()
+                .map_err(|_| RustADBError::ConversionError)?,
+            0,
+        );
+        connection.read_exact(&mut buffer)?;
 
-                Ok(ImageBuffer::<Rgba<u8>, Vec<u8>>::from_vec(
-                    framebuffer_info.width,
-                    framebuffer_info.height,
-                    data,
-                )
-                .ok_or_else(|| RustADBError::FramebufferConversionError)?)
-            }
-            v => Err(RustADBError::UnimplementedFramebufferImageVersion(v)),
-        }
+        Ok(RawFramebuffer { info, data: buffer })
     }
 }
@@ -6,15 +6,25 @@ use crate::{
 impl ADBServerDevice {
     /// Lists available ADB server features.
     pub fn host_features(&mut self) -> Result<Vec<HostFeatures>> {
+        Ok(self
+            .features()?
+            .iter()
+            .filter_map(|v| HostFeatures::try_from(v.as_bytes()).ok())
+            .collect())
+    }
+
+    /// Lists available ADB server features, including those not known by [`HostFeatures`].
+    pub fn features(&mut self) -> Result<Vec<String>> {
         self.set_serial_transport()?;
 
         let features = self
             .transport
             .proxy_connection(AdbServerCommand::HostFeatures, true)?;
 
-        Ok(features
-            .split(|x| x.eq(&b','))
-            .filter_map(|v| HostFeatures::try_from(v).ok())
+        Ok(String::from_utf8_lossy(&features)
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .map(str::to_string)
             .collect())
     }
 }
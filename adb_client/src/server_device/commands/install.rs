@@ -1,41 +1,90 @@
 use std::{fs::File, io::Read, path::Path};
 
 use crate::{
-    Result, models::AdbServerCommand, server_device::ADBServerDevice, utils::check_extension_is_apk,
+    InstallOptions, InstallPhase, Result,
+    models::AdbServerCommand,
+    server_device::ADBServerDevice,
+    utils::{InstallOutput, check_copied_size, check_extension_is_apk},
 };
 
 impl ADBServerDevice {
     /// Install an APK on device
     pub fn install<P: AsRef<Path>>(&mut self, apk_path: P) -> Result<()> {
+        self.install_with_options(apk_path, &InstallOptions::default())
+    }
+
+    /// Install an APK on device, passing `options` to package manager
+    pub fn install_with_options<P: AsRef<Path>>(
+        &mut self,
+        apk_path: P,
+        options: &InstallOptions,
+    ) -> Result<()> {
         let mut apk_file = File::open(&apk_path)?;
 
         check_extension_is_apk(&apk_path)?;
 
         let file_size = apk_file.metadata()?.len();
 
+        self.install_stream(
+            &mut apk_file,
+            file_size,
+            &apk_path.as_ref().to_string_lossy(),
+            options,
+        )
+    }
+
+    /// Install an APK of `size` bytes read from `reader` on device
+    pub fn install_from_reader(&mut self, reader: &mut dyn Read, size: u64) -> Result<()> {
+        self.install_from_reader_with_options(reader, size, &InstallOptions::default())
+    }
+
+    /// Install an APK of `size` bytes read from `reader` on device, passing `options` to package manager
+    pub fn install_from_reader_with_options(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        self.install_stream(reader, size, "APK stream", options)
+    }
+
+    /// Stream `size` bytes of APK from `reader` to package manager, `name` identifying it in progress and logs.
+    fn install_stream(
+        &mut self,
+        reader: &mut dyn Read,
+        size: u64,
+        name: &str,
+        options: &InstallOptions,
+    ) -> Result<()> {
+        let args = options.args(size)?;
         self.set_serial_transport()?;
 
         self.transport
-            .send_adb_request(AdbServerCommand::Install(file_size))?;
+            .send_adb_request(AdbServerCommand::Install(args))?;
 
         let mut raw_connection = self.transport.get_raw_connection()?;
 
-        std::io::copy(&mut apk_file, &mut raw_connection)?;
+        self.observer.install_phase(InstallPhase::Uploading);
+        // Package manager waits for exactly `size` bytes, never send more
+        let mut apk_reader = self.observer.install_reader(reader.take(size), name, size);
+        check_copied_size(std::io::copy(&mut apk_reader, &mut raw_connection)?, size)?;
+        self.observer.install_phase(InstallPhase::Installing);
 
+        let mut output = InstallOutput::default();
         let mut data = [0; 1024];
-        let read_amount = self.transport.get_raw_connection()?.read(&mut data)?;
-
-        match &data[0..read_amount] {
-            b"Success\n" => {
-                log::info!(
-                    "APK file {} successfully installed",
-                    apk_path.as_ref().display()
-                );
-                Ok(())
+        let status = loop {
+            let read_amount = raw_connection.read(&mut data)?;
+            if read_amount == 0 {
+                break output.finish(&self.observer);
             }
-            d => Err(crate::RustADBError::ADBRequestFailed(String::from_utf8(
-                d.to_vec(),
-            )?)),
-        }
+            if let Some(status) = output.feed(&data[..read_amount], &self.observer) {
+                break status;
+            }
+        };
+
+        status?;
+        log::info!("APK {name} successfully installed");
+        self.observer.install_phase(InstallPhase::Completed);
+        Ok(())
     }
 }
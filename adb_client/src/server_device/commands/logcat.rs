@@ -1,6 +1,9 @@
 use std::io::{self, Write};
 
-use crate::{ADBDeviceExt, ADBServerDevice, Result};
+use crate::{
+    ADBDeviceExt, ADBServerDevice, CancelToken, EventStream, Result, TCPServerTransport,
+    event_stream::EventSender,
+};
 
 struct LogFilter<W: Write> {
     writer: W,
@@ -54,3 +57,53 @@ impl ADBServerDevice {
         self.shell_command(&["exec logcat"], &mut LogFilter::new(output))
     }
 }
+
+impl ADBServerDevice {
+    /// Get logs from device as an [`EventStream`] of lines, without their trailing newline.
+    pub fn logs_stream(&mut self) -> Result<EventStream<String>> {
+        // Logs are read over a dedicated connection owned by the stream
+        let mut transport =
+            TCPServerTransport::new_with_address(self.transport.get_address().clone());
+        transport.set_proxy(self.transport.get_proxy().cloned());
+        // Dropping the stream shuts its connection down, as cancelling device still does
+        let stream_token = CancelToken::new();
+        let cancel_guard = self.cancel_token.as_ref().map(|cancel_token| {
+            let stream_token = stream_token.clone();
+            cancel_token.on_cancel(move || stream_token.cancel())
+        });
+        let mut device = ADBServerDevice {
+            identifier: self.identifier.clone(),
+            transport,
+            observer: self.observer.clone(),
+            cancel_token: Some(stream_token.clone()),
+            cancel_guard: None,
+            transfer_rate_limit: self.transfer_rate_limit,
+        };
+
+        Ok(EventStream::spawn(move |sender| {
+            let _cancel_guard = cancel_guard;
+            let mut lines = LogFilter::new(LineSender { sender });
+            device.shell_command(&["exec logcat"], &mut lines)
+        })
+        .cancel_on_drop(stream_token))
+    }
+}
+
+/// [`Write`] implementation forwarding each received line to an [`EventStream`].
+struct LineSender<'a> {
+    sender: &'a EventSender<String>,
+}
+
+impl Write for LineSender<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let line = String::from_utf8_lossy(buf);
+        self.sender
+            .send(line.trim_end_matches(['\r', '\n']).to_string())
+            .map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -4,13 +4,21 @@ mod host_features;
 mod install;
 mod list;
 mod logcat;
+mod open_service;
 mod reboot;
 mod reconnect;
 mod recv;
 mod reverse;
+mod root;
 mod send;
+mod shell;
+mod sideload;
 mod stat;
 mod tcpip;
 mod transport;
 mod uninstall;
 mod usb;
+mod verity;
+
+#[cfg(feature = "fuzzing")]
+pub(crate) use recv::ADBRecvCommandReader;
@@ -0,0 +1,66 @@
+use std::io::{Read, Write};
+
+use crate::cancel_token::CancelGuard;
+use crate::transports::ServerStream;
+use crate::{
+    CancelToken, ReadWriteStream, Result, models::AdbServerCommand, server_device::ADBServerDevice,
+};
+
+/// Service connection shut down once the token it has been opened with gets cancelled
+struct CancellableStream {
+    stream: ServerStream,
+    _cancel_guard: CancelGuard,
+}
+
+impl Read for CancellableStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl Write for CancellableStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl ADBServerDevice {
+    /// Open a raw stream to `service` on the device
+    pub fn open_service(&mut self, service: &str) -> Result<Box<dyn ReadWriteStream>> {
+        self.set_serial_transport()?;
+
+        self.transport
+            .send_adb_request(AdbServerCommand::Service(service.to_string()))?;
+
+        // Connection is now dedicated to this service, next commands will use a new one
+        Ok(Box::new(self.transport.take_raw_connection()?))
+    }
+
+    /// Same as [`ADBServerDevice::open_service`], stream being shut down once `cancel_token` gets cancelled
+    pub fn open_service_cancellable(
+        &mut self,
+        service: &str,
+        cancel_token: &CancelToken,
+    ) -> Result<Box<dyn ReadWriteStream>> {
+        self.set_serial_transport()?;
+
+        self.transport
+            .send_adb_request(AdbServerCommand::Service(service.to_string()))?;
+
+        let stream = self.transport.take_raw_connection()?;
+        // Shutting the socket down unblocks any pending read or write on it
+        let connection = stream.try_clone()?;
+        let cancel_guard = cancel_token.on_cancel(move || {
+            let _ = connection.shutdown(std::net::Shutdown::Both);
+        });
+
+        Ok(Box::new(CancellableStream {
+            stream,
+            _cancel_guard: cancel_guard,
+        }))
+    }
+}
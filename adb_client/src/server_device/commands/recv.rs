@@ -1,12 +1,14 @@
 use crate::{
-    ADBServerDevice, Result, constants,
+    ADBServerDevice, Result, TransferStats, constants,
     models::{AdbServerCommand, SyncCommand},
+    throttle::ThrottledWriter,
 };
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::{BufReader, BufWriter, Read, Write};
+use std::time::Instant;
 
 /// Internal structure wrapping a [std::io::Read] and hiding underlying protocol logic.
-struct ADBRecvCommandReader<R: Read> {
+pub(crate) struct ADBRecvCommandReader<R: Read> {
     inner: R,
     remaining_data_bytes_to_read: usize,
 }
@@ -31,16 +33,21 @@ impl<R: Read> Read for ADBRecvCommandReader<R> {
             match &header[..] {
                 b"DATA" => {
                     let length = self.inner.read_u32::<LittleEndian>()? as usize;
-                    let effective_read = self.inner.read(&mut buf[0..length])?;
+                    // Chunk may not fit in `buf`, what is left is read by next calls
+                    let chunk_size = length.min(buf.len());
+                    let effective_read = self.inner.read(&mut buf[..chunk_size])?;
                     self.remaining_data_bytes_to_read = length - effective_read;
 
                     Ok(effective_read)
                 }
                 b"DONE" => Ok(0),
                 b"FAIL" => {
-                    let length = self.inner.read_u32::<LittleEndian>()? as usize;
-                    let mut error_msg = vec![0; length];
-                    self.inner.read_exact(&mut error_msg)?;
+                    let length = self.inner.read_u32::<LittleEndian>()?;
+                    // Length is not trusted to allocate memory upfront
+                    let mut error_msg = Vec::new();
+                    (&mut self.inner)
+                        .take(length.into())
+                        .read_to_end(&mut error_msg)?;
 
                     Err(std::io::Error::new(
                         std::io::ErrorKind::Other,
@@ -60,7 +67,7 @@ impl<R: Read> Read for ADBRecvCommandReader<R> {
             let data_to_read = std::cmp::min(self.remaining_data_bytes_to_read, buf.len());
             self.inner.read_exact(&mut buf[..data_to_read])?;
 
-            self.remaining_data_bytes_to_read -= self.remaining_data_bytes_to_read;
+            self.remaining_data_bytes_to_read -= data_to_read;
 
             Ok(data_to_read)
         }
@@ -69,7 +76,8 @@ impl<R: Read> Read for ADBRecvCommandReader<R> {
 
 impl ADBServerDevice {
     /// Receives path to stream from the device.
-    pub fn pull(&mut self, path: &dyn AsRef<str>, stream: &mut dyn Write) -> Result<()> {
+    pub fn pull(&mut self, path: &dyn AsRef<str>, stream: &mut dyn Write) -> Result<TransferStats> {
+        let started = Instant::now();
         self.set_serial_transport()?;
 
         // Set device in SYNC mode
@@ -78,14 +86,21 @@ impl ADBServerDevice {
         // Send a recv command
         self.transport.send_sync_request(SyncCommand::Recv)?;
 
-        self.handle_recv_command(path, stream)
+        let bytes = self.handle_recv_command(path, stream)?;
+
+        Ok(TransferStats {
+            bytes,
+            elapsed: started.elapsed(),
+            retries: 0,
+        })
     }
 
+    /// Receive `from` into `output`, returning number of bytes received
     fn handle_recv_command<S: AsRef<str>>(
         &mut self,
         from: S,
         output: &mut dyn Write,
-    ) -> Result<()> {
+    ) -> Result<u64> {
         let mut raw_connection = self.transport.get_raw_connection()?;
 
         let from_as_bytes = from.as_ref().as_bytes();
@@ -95,12 +110,34 @@ impl ADBServerDevice {
         raw_connection.write_all(&buffer)?;
 
         let reader = ADBRecvCommandReader::new(raw_connection);
-        std::io::copy(
+        let output = self.observer.progress_writer(output, from.as_ref(), None);
+        let output = ThrottledWriter::new(output, self.transfer_rate_limit);
+        let bytes = std::io::copy(
             &mut BufReader::with_capacity(constants::BUFFER_SIZE, reader),
             &mut BufWriter::with_capacity(constants::BUFFER_SIZE, output),
         )?;
 
         // Connection should've been left in SYNC mode by now
-        Ok(())
+        Ok(bytes)
     }
 }
+
+#[test]
+fn test_recv_reader_chunk_larger_than_buffer() {
+    let mut data = b"DATA".to_vec();
+    data.extend_from_slice(&8_u32.to_le_bytes());
+    data.extend_from_slice(b"abcdefgh");
+    data.extend_from_slice(b"DONE\0\0\0\0");
+
+    let mut reader = ADBRecvCommandReader::new(data.as_slice());
+    let mut output = Vec::new();
+    let mut buffer = [0; 3];
+    loop {
+        match reader.read(&mut buffer).expect("cannot read chunk") {
+            0 => break,
+            size => output.extend_from_slice(&buffer[..size]),
+        }
+    }
+
+    assert_eq!(output, b"abcdefgh");
+}
@@ -0,0 +1,45 @@
+use crate::{
+    ADBServerDevice, Result, WaitForDeviceState, WaitForDeviceTransport,
+    adbd_restart::run_root_service, models::AdbServerCommand,
+};
+
+impl ADBServerDevice {
+    /// Restart adbd with root permissions, waiting for device to be connected again
+    pub fn root(&mut self) -> Result<()> {
+        self.restart_adbd("root:")
+    }
+
+    /// Restart adbd without root permissions, waiting for device to be connected again
+    pub fn unroot(&mut self) -> Result<()> {
+        self.restart_adbd("unroot:")
+    }
+
+    fn restart_adbd(&mut self, service: &str) -> Result<()> {
+        if !run_root_service(self, service)? {
+            return Ok(());
+        }
+
+        self.wait_for_restart()
+    }
+
+    /// Wait for device to disconnect, e.g. as adbd exits, and then to be connected again
+    pub(crate) fn wait_for_restart(&mut self) -> Result<()> {
+        // Server still sees device until exiting adbd closed its connection
+        self.wait_for_state(WaitForDeviceState::Disconnect)?;
+        self.wait_for_state(WaitForDeviceState::Device)
+    }
+
+    /// Wait for this device to reach `state`
+    fn wait_for_state(&mut self, state: WaitForDeviceState) -> Result<()> {
+        let identifier = self.identifier.clone();
+        self.connect()?
+            .send_adb_request(AdbServerCommand::WaitForDevice(
+                identifier,
+                state,
+                WaitForDeviceTransport::Any,
+            ))?;
+
+        // Server answers a second time once state has been reached
+        self.transport.read_adb_response()
+    }
+}
@@ -1,12 +1,14 @@
 use crate::{
-    ADBServerDevice, Result, RustADBError, constants,
+    ADBServerDevice, Result, RustADBError, TransferStats, constants,
     models::{AdbRequestStatus, AdbServerCommand, SyncCommand},
+    throttle::ThrottledReader,
+    utils::sync_mod_time,
 };
 use std::{
     convert::TryInto,
     io::{BufReader, BufWriter, Read, Write},
     str::{self, FromStr},
-    time::SystemTime,
+    time::{Instant, SystemTime},
 };
 
 /// Internal structure wrapping a [std::io::Write] and hiding underlying protocol logic.
@@ -42,7 +44,30 @@ impl<W: Write> Write for ADBSendCommandWriter<W> {
 
 impl ADBServerDevice {
     /// Send stream to path on the device.
-    pub fn push<R: Read, A: AsRef<str>>(&mut self, stream: R, path: A) -> Result<()> {
+    pub fn push<R: Read, A: AsRef<str>>(&mut self, stream: R, path: A) -> Result<TransferStats> {
+        self.push_with_mode(stream, path, 0o777)
+    }
+
+    /// Send stream to path on the device, setting permissions of remote file to `mode`.
+    pub fn push_with_mode<R: Read, A: AsRef<str>>(
+        &mut self,
+        stream: R,
+        path: A,
+        mode: u32,
+    ) -> Result<TransferStats> {
+        self.push_with_metadata(stream, path, mode, SystemTime::now())
+    }
+
+    /// Send stream to path on the device, setting permissions of remote file to `mode` and its modification time to
+    /// `mod_time`.
+    pub fn push_with_metadata<R: Read, A: AsRef<str>>(
+        &mut self,
+        stream: R,
+        path: A,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<TransferStats> {
+        let started = Instant::now();
         log::info!("Sending data to {}", path.as_ref());
         self.set_serial_transport()?;
 
@@ -52,12 +77,27 @@ impl ADBServerDevice {
         // Send a send command
         self.transport.send_sync_request(SyncCommand::Send)?;
 
-        self.handle_send_command(stream, path)
+        let stream = self.observer.progress_reader(stream, path.as_ref(), None);
+        let stream = ThrottledReader::new(stream, self.transfer_rate_limit);
+        let bytes = self.handle_send_command(stream, path, mode, mod_time)?;
+
+        Ok(TransferStats {
+            bytes,
+            elapsed: started.elapsed(),
+            retries: 0,
+        })
     }
 
-    fn handle_send_command<R: Read, S: AsRef<str>>(&mut self, input: R, to: S) -> Result<()> {
-        // Append the permission flags to the filename
-        let to = to.as_ref().to_string() + ",0777";
+    /// Send `input` to `to`, returning number of bytes sent
+    fn handle_send_command<R: Read, S: AsRef<str>>(
+        &mut self,
+        input: R,
+        to: S,
+        mode: u32,
+        mod_time: SystemTime,
+    ) -> Result<u64> {
+        // Append the permission flags to the filename, parsed by device as an octal C literal
+        let to = format!("{},0{mode:o}", to.as_ref());
 
         let mut raw_connection = self.transport.get_raw_connection()?;
 
@@ -70,21 +110,16 @@ impl ADBServerDevice {
 
         let writer = ADBSendCommandWriter::new(raw_connection);
 
-        std::io::copy(
+        let bytes = std::io::copy(
             &mut BufReader::with_capacity(constants::BUFFER_SIZE, input),
             &mut BufWriter::with_capacity(constants::BUFFER_SIZE, writer),
         )?;
 
         // Copy is finished, we can now notify as finished
         // Have to send DONE + file mtime
-        let last_modified = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => n,
-            Err(_) => panic!("SystemTime before UNIX EPOCH!"),
-        };
-
         let mut done_buffer = Vec::with_capacity(8);
         done_buffer.extend_from_slice(b"DONE");
-        done_buffer.extend_from_slice(&last_modified.as_secs().to_le_bytes());
+        done_buffer.extend_from_slice(&sync_mod_time(mod_time).to_le_bytes());
         raw_connection.write_all(&done_buffer)?;
 
         // We expect 'OKAY' response from this
@@ -108,7 +143,7 @@ impl ADBServerDevice {
 
                 Err(RustADBError::ADBRequestFailed(String::from_utf8(body)?))
             }
-            AdbRequestStatus::Okay => Ok(()),
+            AdbRequestStatus::Okay => Ok(bytes),
         }
     }
 }
@@ -0,0 +1,155 @@
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+
+use crate::{
+    ADBTransport, Result, RustADBError, ShellOutput,
+    constants::{BUFFER_SIZE, SHELL_ABORT_TIMEOUT},
+    models::{AdbServerCommand, HostFeatures},
+    server_device::ADBServerDevice,
+    shell_protocol::{ShellV2Demuxer, shell_v2_service},
+    utils::{RemotePidFilter, kill_remote_pid, with_remote_pid},
+};
+
+impl ADBServerDevice {
+    /// Runs 'command' like [`crate::ADBDeviceExt::shell_command`], killing it if still running after `timeout`.
+    pub fn shell_command_with_timeout(
+        &mut self,
+        command: &[&str],
+        output: &mut dyn Write,
+        timeout: Duration,
+    ) -> Result<()> {
+        let supported_features = self.host_features()?;
+        if !supported_features.contains(&HostFeatures::ShellV2)
+            && !supported_features.contains(&HostFeatures::Cmd)
+        {
+            return Err(RustADBError::ADBShellNotSupported);
+        }
+
+        let mut output = RemotePidFilter::new(output);
+        let result = self.shell_command_until(
+            &with_remote_pid(command),
+            &mut output,
+            Instant::now() + timeout,
+        );
+
+        if let Err(e) = &result
+            && e.is_timeout()
+            && let Some(pid) = output.pid()
+        {
+            // Best effort, device may be unresponsive
+            let _ = self.shell_command_until(
+                &kill_remote_pid(pid),
+                &mut std::io::sink(),
+                Instant::now() + SHELL_ABORT_TIMEOUT,
+            );
+        }
+
+        self.cancellable(result)
+    }
+
+    /// Runs `command` using `shell,v2` service, writing its streams as requested by `output` and returning its exit code.
+    ///
+    /// Device must support `shell,v2`, as advertised by [`HostFeatures::ShellV2`].
+    pub(crate) fn shell_command_v2(
+        &mut self,
+        command: &[&str],
+        output: ShellOutput<'_>,
+    ) -> Result<u8> {
+        self.set_serial_transport()?;
+        self.transport
+            .send_adb_request(AdbServerCommand::Service(shell_v2_service(command)))?;
+
+        let mut demuxer = ShellV2Demuxer::new(output);
+        let mut connection = self.transport.get_raw_connection()?;
+        let result = std::io::copy(&mut connection, &mut demuxer)
+            .map_err(RustADBError::from)
+            .and_then(|_| {
+                demuxer.flush()?;
+                demuxer.finish()
+            });
+
+        self.cancellable(result)
+    }
+
+    /// Runs 'command_line' in a shell, closing connection if it does not end before `deadline`.
+    fn shell_command_until(
+        &mut self,
+        command_line: &str,
+        output: &mut dyn Write,
+        deadline: Instant,
+    ) -> Result<()> {
+        self.set_serial_transport()?;
+
+        self.transport
+            .send_adb_request(AdbServerCommand::ShellCommand(command_line.to_string()))?;
+
+        let mut buffer = [0; BUFFER_SIZE];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                // Closing connection makes server close the shell session on device
+                let _ = self.transport.disconnect();
+                return Err(std::io::Error::from(ErrorKind::TimedOut).into());
+            }
+
+            let mut connection = self.transport.get_raw_connection()?;
+            connection.set_read_timeout(Some(remaining))?;
+            match connection.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(size) => output.write_all(&buffer[..size])?,
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Starts an interactive shell session on the device, using `command` to open it.
+    pub(crate) fn interactive_shell(
+        &mut self,
+        command: AdbServerCommand,
+        mut reader: &mut dyn Read,
+        mut writer: Box<dyn Write + Send>,
+    ) -> Result<()> {
+        let supported_features = self.host_features()?;
+        if !supported_features.contains(&HostFeatures::ShellV2)
+            && !supported_features.contains(&HostFeatures::Cmd)
+        {
+            return Err(RustADBError::ADBShellNotSupported);
+        }
+
+        self.set_serial_transport()?;
+        self.transport.send_adb_request(command)?;
+
+        let mut read_stream = self.transport.get_raw_connection()?.try_clone()?;
+
+        let mut write_stream = read_stream.try_clone()?;
+
+        // Reading thread, reads response from adb-server
+        std::thread::spawn(move || -> Result<()> {
+            loop {
+                let mut buffer = [0; BUFFER_SIZE];
+                match read_stream.read(&mut buffer) {
+                    Ok(0) => {
+                        read_stream.shutdown(std::net::Shutdown::Both)?;
+                        return Ok(());
+                    }
+                    Ok(size) => {
+                        writer.write_all(&buffer[..size])?;
+                        writer.flush()?;
+                    }
+                    Err(e) => {
+                        return Err(RustADBError::IOError(e));
+                    }
+                }
+            }
+        });
+
+        // Read from given reader (that could be stdin e.g), and write content to server socket
+        let result = match std::io::copy(&mut reader, &mut write_stream) {
+            Err(e) if e.kind() != ErrorKind::BrokenPipe => Err(RustADBError::IOError(e)),
+            _ => Ok(()),
+        };
+
+        self.cancellable(result)
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{
+    ReadSeek, Result, TransferStats,
+    server_device::ADBServerDevice,
+    sideload::{sideload, sideload_service},
+};
+
+impl ADBServerDevice {
+    /// Sideload OTA package of `size` bytes read from `reader` on device, which must be in sideload mode
+    pub fn sideload_from_reader(
+        &mut self,
+        reader: &mut dyn ReadSeek,
+        size: u64,
+    ) -> Result<TransferStats> {
+        let mut stream = self.open_service(&sideload_service(size))?;
+        sideload(
+            &mut stream,
+            reader,
+            size,
+            &self.observer,
+            self.transfer_rate_limit,
+        )
+    }
+}
@@ -1,12 +1,8 @@
-use crate::{ADBServerDevice, Result, models::AdbServerCommand};
+use crate::{ADBServerDevice, Result, adbd_restart::tcpip};
 
 impl ADBServerDevice {
-    /// Set adb daemon to tcp/ip mode
-    pub fn tcpip(&mut self, port: u16) -> Result<()> {
-        self.set_serial_transport()?;
-
-        self.transport
-            .proxy_connection(AdbServerCommand::TcpIp(port), false)
-            .map(|_| ())
+    /// Set adb daemon to tcp/ip mode, returning port it listens on
+    pub fn tcpip(&mut self, port: u16) -> Result<u16> {
+        tcpip(self, port)
     }
 }
@@ -1,11 +1,8 @@
-use crate::{ADBServerDevice, Result, models::AdbServerCommand};
+use crate::{ADBServerDevice, Result, adbd_restart::usb};
 
 impl ADBServerDevice {
     /// Set adb daemon to usb mode
     pub fn usb(&mut self) -> Result<()> {
-        self.set_serial_transport()?;
-        self.transport
-            .proxy_connection(AdbServerCommand::Usb, false)
-            .map(|_| ())
+        usb(self)
     }
 }
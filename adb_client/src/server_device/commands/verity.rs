@@ -0,0 +1,29 @@
+use crate::{ADBServerDevice, RebootType, Result, verity::run_verity_service};
+
+impl ADBServerDevice {
+    /// Remount system partitions read-write, returning `true` if a reboot is required, see [`crate::ADBDeviceExt::remount`]
+    pub fn remount(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("remount:", reboot)
+    }
+
+    /// Disable dm-verity, returning `true` if a reboot is required, see [`crate::ADBDeviceExt::disable_verity`]
+    pub fn disable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("disable-verity:", reboot)
+    }
+
+    /// Enable dm-verity, returning `true` if a reboot is required, see [`crate::ADBDeviceExt::enable_verity`]
+    pub fn enable_verity(&mut self, reboot: bool) -> Result<bool> {
+        self.apply_verity_service("enable-verity:", reboot)
+    }
+
+    fn apply_verity_service(&mut self, service: &str, reboot: bool) -> Result<bool> {
+        let reboot_required = run_verity_service(self, service)?;
+
+        if reboot && reboot_required {
+            self.reboot(RebootType::System)?;
+            self.wait_for_restart()?;
+        }
+
+        Ok(reboot_required)
+    }
+}
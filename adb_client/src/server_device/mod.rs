@@ -3,3 +3,5 @@ mod adb_server_device_commands;
 mod commands;
 
 pub use adb_server_device::ADBServerDevice;
+#[cfg(feature = "fuzzing")]
+pub(crate) use commands::ADBRecvCommandReader;
@@ -0,0 +1,153 @@
+use std::io::Write;
+
+use crate::exit_code::{ExitCodeFilter, with_exit_code};
+use crate::{ADBDeviceExt, Result, RustADBError, ShellOutput};
+
+/// Size of `shell,v2` packet headers, an id byte followed by a little endian 32 bits length
+const PACKET_HEADER_SIZE: usize = 5;
+/// Packet carrying standard output data
+const ID_STDOUT: u8 = 1;
+/// Packet carrying standard error data
+const ID_STDERR: u8 = 2;
+/// Packet carrying exit code of command, as a single byte
+const ID_EXIT: u8 = 3;
+
+/// Name of the `shell,v2` service running `command` without a pseudo-terminal
+pub(crate) fn shell_v2_service(command: &[&str]) -> String {
+    format!("shell,v2,raw:{}", command.join(" "))
+}
+
+/// [`Write`] implementation splitting a `shell,v2` stream into standard output and error, as requested by `output`.
+pub(crate) struct ShellV2Demuxer<'a> {
+    output: ShellOutput<'a>,
+    /// Bytes received but not forming a full packet yet
+    buffer: Vec<u8>,
+    exit_code: Option<u8>,
+}
+
+impl<'a> ShellV2Demuxer<'a> {
+    pub(crate) fn new(output: ShellOutput<'a>) -> Self {
+        Self {
+            output,
+            buffer: Vec::new(),
+            exit_code: None,
+        }
+    }
+
+    /// Return exit code reported by device, once stream ended
+    pub(crate) fn finish(self) -> Result<u8> {
+        self.exit_code.ok_or(RustADBError::ExitCodeUnavailable)
+    }
+}
+
+impl Write for ShellV2Demuxer<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        while let Some(header) = self.buffer.get(..PACKET_HEADER_SIZE) {
+            let length = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+            let Some(data) = self
+                .buffer
+                .get(PACKET_HEADER_SIZE..PACKET_HEADER_SIZE + length)
+            else {
+                break;
+            };
+
+            match (header[0], &mut self.output) {
+                (ID_STDOUT | ID_STDERR, ShellOutput::Merged(output)) => output.write_all(data)?,
+                (ID_STDOUT, ShellOutput::Separate { stdout, .. }) => stdout.write_all(data)?,
+                (ID_STDERR, ShellOutput::Separate { stderr, .. }) => stderr.write_all(data)?,
+                (ID_EXIT, _) => self.exit_code = data.first().copied(),
+                (id, _) => log::debug!("ignoring shell packet with id {id}"),
+            }
+            self.buffer.drain(..PACKET_HEADER_SIZE + length);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.output {
+            ShellOutput::Merged(output) => output.flush(),
+            ShellOutput::Separate { stdout, stderr } => {
+                stdout.flush()?;
+                stderr.flush()
+            }
+        }
+    }
+}
+
+/// Run `command` writing its streams as requested by `output`, for devices not supporting `shell,v2`.
+///
+/// Legacy `shell:` service merges both streams: when they are to be kept separate, error stream is redirected to
+/// a temporary file on device, which is written into `stderr` once command ended.
+pub(crate) fn legacy_shell_command_output<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    command: &[&str],
+    output: ShellOutput<'_>,
+) -> Result<()> {
+    let (stdout, stderr) = match output {
+        ShellOutput::Merged(output) => return device.shell_command(command, output),
+        ShellOutput::Separate { stdout, stderr } => (stdout, stderr),
+    };
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let stderr_path = format!("/data/local/tmp/adb_client_stderr_{timestamp}");
+
+    let ran = device.shell_command(
+        &[&format!("{{ {}; }} 2>{stderr_path}", command.join(" "))],
+        stdout,
+    );
+    let read = device.shell_command(
+        &["cat", &stderr_path, ";", "rm", "-f", &stderr_path],
+        stderr,
+    );
+    ran.and(read)
+}
+
+/// Run `command` and return its exit code, for devices not supporting `shell,v2`.
+///
+/// Exit code is printed after command output and parsed back if `legacy_exit_code` is set.
+pub(crate) fn legacy_run_command<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    command: &[&str],
+    output: &mut dyn Write,
+    legacy_exit_code: bool,
+) -> Result<u8> {
+    if !legacy_exit_code {
+        return Err(RustADBError::ExitCodeUnavailable);
+    }
+
+    let mut filter = ExitCodeFilter::new(output);
+    device.shell_command(&[&with_exit_code(command)], &mut filter)?;
+    filter.finish()
+}
+
+#[test]
+fn test_shell_v2_demuxer() {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut demuxer = ShellV2Demuxer::new(ShellOutput::Separate {
+        stdout: &mut stdout,
+        stderr: &mut stderr,
+    });
+
+    let stream = [
+        &[ID_STDOUT, 3, 0, 0, 0][..],
+        b"out",
+        &[ID_STDERR, 3, 0, 0, 0],
+        b"err",
+        &[ID_EXIT, 1, 0, 0, 0, 42],
+    ]
+    .concat();
+    // Packets may be split across messages
+    for chunk in stream.chunks(4) {
+        demuxer.write_all(chunk).expect("cannot write to demuxer");
+    }
+
+    assert_eq!(demuxer.finish().expect("no exit code"), 42);
+    assert_eq!(stdout, b"out");
+    assert_eq!(stderr, b"err");
+}
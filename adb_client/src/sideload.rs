@@ -0,0 +1,158 @@
+use std::io::{Read, SeekFrom, Write};
+use std::time::Instant;
+
+use crate::adb_device_ext::ReadSeek;
+use crate::observer::ObserverSlot;
+use crate::throttle::ThrottledReader;
+use crate::{Result, RustADBError, TransferStats};
+
+/// Size of blocks of package requested by device, as used by `adb sideload`
+pub(crate) const SIDELOAD_BLOCK_SIZE: u64 = 65536;
+
+/// Service serving a package of `size` bytes to recovery, block by block
+pub(crate) fn sideload_service(size: u64) -> String {
+    format!("sideload-host:{size}:{SIDELOAD_BLOCK_SIZE}")
+}
+
+/// Serve `size` bytes of package read from `reader` to recovery over `stream`, opened on [`sideload_service`].
+///
+/// Device requests blocks by sending their index as 8 ASCII digits, until it sends `DONEDONE`. Package is usually
+/// read more than once (verified, then installed), progress is thus only an estimate. Blocks are read at
+/// `rate_limit` bytes per second at most.
+pub(crate) fn sideload<S: Read + Write + ?Sized>(
+    stream: &mut S,
+    reader: &mut dyn ReadSeek,
+    size: u64,
+    observer: &ObserverSlot,
+    rate_limit: Option<u64>,
+) -> Result<TransferStats> {
+    let started = Instant::now();
+    let mut reader = ThrottledReader::new(reader, rate_limit);
+    let mut request = [0; 8];
+    let mut block = Vec::with_capacity(SIDELOAD_BLOCK_SIZE as usize);
+    let mut served = 0u64;
+    let mut last_percent = None;
+
+    loop {
+        stream.read_exact(&mut request)?;
+        match &request {
+            b"DONEDONE" => break,
+            b"FAILFAIL" => {
+                return Err(RustADBError::ADBRequestFailed(
+                    "device failed to apply sideloaded package".to_string(),
+                ));
+            }
+            _ => {}
+        }
+
+        let index: u64 = std::str::from_utf8(&request)
+            .ok()
+            .and_then(|request| request.parse().ok())
+            .ok_or_else(|| {
+                RustADBError::ADBRequestFailed(format!(
+                    "invalid block request {}",
+                    String::from_utf8_lossy(&request)
+                ))
+            })?;
+        let offset = index * SIDELOAD_BLOCK_SIZE;
+        if offset >= size {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "device requested block {index} past end of package"
+            )));
+        }
+
+        // Last block is only partially filled
+        let length = SIDELOAD_BLOCK_SIZE.min(size - offset);
+        block.clear();
+        reader.get_mut().seek(SeekFrom::Start(offset))?;
+        (&mut reader).take(length).read_to_end(&mut block)?;
+        if block.len() as u64 != length {
+            return Err(RustADBError::ADBRequestFailed(format!(
+                "package is shorter than {size} bytes"
+            )));
+        }
+        stream.write_all(&block)?;
+
+        served += length;
+        let percent = (served * 100 / size).min(99) as u8;
+        if last_percent != Some(percent) {
+            observer.sideload_progress(percent);
+            last_percent = Some(percent);
+        }
+    }
+
+    observer.sideload_progress(100);
+    Ok(TransferStats {
+        bytes: served,
+        elapsed: started.elapsed(),
+        retries: 0,
+    })
+}
+
+#[test]
+fn test_sideload() {
+    use std::io::Cursor;
+
+    /// Stream answering block requests of device with blocks it received
+    struct Recovery {
+        requests: Cursor<Vec<u8>>,
+        received: Vec<u8>,
+    }
+
+    impl Read for Recovery {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.requests.read(buf)
+        }
+    }
+
+    impl Write for Recovery {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.received.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let package: Vec<u8> = (0..SIDELOAD_BLOCK_SIZE + 10).map(|i| i as u8).collect();
+    let mut recovery = Recovery {
+        requests: Cursor::new(b"000000010000000000000001DONEDONE".to_vec()),
+        received: Vec::new(),
+    };
+    let stats = sideload(
+        &mut recovery,
+        &mut Cursor::new(&package),
+        package.len() as u64,
+        &ObserverSlot::default(),
+        None,
+    )
+    .expect("cannot sideload package");
+    assert_eq!(stats.bytes, recovery.received.len() as u64);
+
+    let last_block = &package[SIDELOAD_BLOCK_SIZE as usize..];
+    assert_eq!(
+        recovery.received,
+        [
+            last_block,
+            &package[..SIDELOAD_BLOCK_SIZE as usize],
+            last_block
+        ]
+        .concat()
+    );
+
+    let mut recovery = Recovery {
+        requests: Cursor::new(b"00000002".to_vec()),
+        received: Vec::new(),
+    };
+    assert!(
+        sideload(
+            &mut recovery,
+            &mut Cursor::new(&package),
+            package.len() as u64,
+            &ObserverSlot::default(),
+            None,
+        )
+        .is_err()
+    );
+}
@@ -0,0 +1,81 @@
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+
+/// Size of blocks checked for zeros, matching usual file system block size.
+const SPARSE_BLOCK_SIZE: usize = 4096;
+
+/// [`Write`] implementation creating a sparse file, blocks only made of zeros being skipped instead of written.
+///
+/// File systems not supporting sparse files fill holes with zeros, so content is always preserved.
+pub(crate) struct SparseFileWriter {
+    file: File,
+    /// Data of current block, not written yet
+    block: Vec<u8>,
+    /// Position in file once current block will be written
+    position: u64,
+}
+
+impl SparseFileWriter {
+    pub(crate) fn new(file: File) -> Self {
+        Self {
+            file,
+            block: Vec::with_capacity(SPARSE_BLOCK_SIZE),
+            position: 0,
+        }
+    }
+
+    fn write_block(&mut self) -> std::io::Result<()> {
+        if self.block.iter().all(|byte| *byte == 0) {
+            self.file.seek(SeekFrom::Current(self.block.len() as i64))?;
+        } else {
+            self.file.write_all(&self.block)?;
+        }
+        self.position += self.block.len() as u64;
+        self.block.clear();
+        Ok(())
+    }
+
+    /// Write last block and set file length, which is needed when file ends with a hole. Return size of file.
+    pub(crate) fn finish(mut self) -> std::io::Result<u64> {
+        self.write_block()?;
+        self.file.set_len(self.position)?;
+        self.file.flush()?;
+        Ok(self.position)
+    }
+}
+
+impl Write for SparseFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let size = buf.len().min(SPARSE_BLOCK_SIZE - self.block.len());
+        self.block.extend_from_slice(&buf[..size]);
+        if self.block.len() == SPARSE_BLOCK_SIZE {
+            self.write_block()?;
+        }
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[test]
+fn test_sparse_file_writer() {
+    let path = std::env::temp_dir().join(format!("adb_client_sparse_{}", std::process::id()));
+    let mut content = vec![0; 3 * SPARSE_BLOCK_SIZE + 100];
+    content[SPARSE_BLOCK_SIZE + 1] = 42;
+
+    let mut writer = SparseFileWriter::new(File::create(&path).expect("cannot create file"));
+    // Chunks not aligned on blocks, as received from device
+    for chunk in content.chunks(1000) {
+        writer.write_all(chunk).expect("cannot write to file");
+    }
+    assert_eq!(
+        writer.finish().expect("cannot finish file"),
+        content.len() as u64
+    );
+
+    let written = std::fs::read(&path).expect("cannot read file");
+    std::fs::remove_file(&path).expect("cannot remove file");
+    assert_eq!(written, content);
+}
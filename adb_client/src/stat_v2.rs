@@ -0,0 +1,45 @@
+use std::io::{Read, Write};
+
+use crate::models::AdbStatV2Response;
+use crate::{ADBDeviceExt, Result};
+
+/// Stat `remote_path` on `device`, see [`ADBDeviceExt::stat_v2`] and [`ADBDeviceExt::lstat_v2`].
+///
+/// Devices not advertising `stat_v2` feature are sent a legacy `STAT` request instead.
+pub(crate) fn stat_v2<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    remote_path: &str,
+    follow_links: bool,
+) -> Result<AdbStatV2Response> {
+    let features = device.features()?;
+    if !features.iter().any(|feature| feature == "stat_v2") {
+        return Ok(device.stat(remote_path)?.into());
+    }
+
+    let mut stream = device.open_service("sync:")?;
+    let id = match follow_links {
+        true => b"STA2",
+        false => b"LST2",
+    };
+    request_stat_v2(&mut stream, id, remote_path)
+}
+
+/// Send a `STA2` or `LST2` request of `remote_path` over `stream`, opened on `sync:` service.
+///
+/// Device answers with a fixed size message, holding `errno` of the failed call, if any, and all fields of `stat(2)`.
+fn request_stat_v2<S: Read + Write + ?Sized>(
+    stream: &mut S,
+    id: &[u8; 4],
+    remote_path: &str,
+) -> Result<AdbStatV2Response> {
+    let mut request = id.to_vec();
+    request.extend_from_slice(&(remote_path.len() as u32).to_le_bytes());
+    request.extend_from_slice(remote_path.as_bytes());
+    stream.write_all(&request)?;
+
+    let mut response = [0; 72];
+    stream.read_exact(&mut response)?;
+    stream.write_all(b"QUIT\0\0\0\0")?;
+    stream.flush()?;
+    AdbStatV2Response::try_from(response.as_slice())
+}
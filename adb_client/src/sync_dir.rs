@@ -0,0 +1,68 @@
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::models::{DirEntry, PathFilter, PullOptions, SyncDirection, TransferStats};
+use crate::pull_dir::pull_dir;
+use crate::push_dir::push_dir;
+use crate::{ADBDeviceExt, Result};
+
+/// Synchronize local directory `local` and remote directory `remote`, see [`ADBDeviceExt::sync_dir`].
+pub(crate) fn sync_dir<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    local: &Path,
+    remote: &str,
+    direction: SyncDirection,
+) -> Result<TransferStats> {
+    match direction {
+        SyncDirection::Push => push_dir(device, local, remote, true),
+        SyncDirection::Pull => pull_dir(
+            device,
+            remote,
+            local,
+            &PathFilter::default(),
+            &PullOptions::default(),
+            true,
+        ),
+    }
+}
+
+/// Return `true` if local file at `path` has the same size and modification time as remote file `entry`.
+///
+/// Sizes are compared on their lowest 32 bits only, as listed by sync protocol.
+pub(crate) fn is_unchanged(path: &Path, entry: &DirEntry) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    let mod_time = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_secs() as u32);
+    metadata.is_file() && metadata.len() as u32 == entry.size && mod_time == Some(entry.mod_time)
+}
+
+#[test]
+fn test_is_unchanged() {
+    use std::time::Duration;
+
+    let path = std::env::temp_dir().join(format!("adb_client_sync_{}", std::process::id()));
+    let file = std::fs::File::create(&path).expect("cannot create file");
+    file.set_len(42).expect("cannot write file");
+    file.set_modified(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        .expect("cannot set modification time");
+
+    let mut entry = DirEntry {
+        name: "file".to_string(),
+        mode: 0o100644,
+        size: 42,
+        mod_time: 1_700_000_000,
+    };
+    let unchanged = is_unchanged(&path, &entry);
+    entry.mod_time += 1;
+    let modified = is_unchanged(&path, &entry);
+    std::fs::remove_file(&path).expect("cannot remove file");
+
+    assert!(unchanged);
+    assert!(!modified);
+    assert!(!is_unchanged(&path, &entry));
+}
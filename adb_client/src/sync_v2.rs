@@ -0,0 +1,412 @@
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Instant, SystemTime};
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::models::{SyncCompression, TransferStats};
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Maximum size of a `DATA` chunk accepted by device
+const SYNC_DATA_MAX: usize = 64 * 1024;
+
+/// Push `stream` to `path` using sync protocol v2, see [`ADBDeviceExt::push_compressed`].
+pub(crate) fn push_compressed<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    stream: &mut dyn Read,
+    path: &str,
+    mode: u32,
+    compression: SyncCompression,
+) -> Result<TransferStats> {
+    let Some(compression) = compression.resolve(&device.features()?)? else {
+        return device.push_with_mode(stream, &path, mode);
+    };
+
+    let started = Instant::now();
+    let mut sync = device.open_service("sync:")?;
+    let bytes = send_v2(&mut sync, stream, path, mode, compression)?;
+    sync.write_all(b"QUIT\0\0\0\0")?;
+    sync.flush()?;
+
+    Ok(TransferStats {
+        bytes,
+        elapsed: started.elapsed(),
+        retries: 0,
+    })
+}
+
+/// Pull `source` into `output` using sync protocol v2, see [`ADBDeviceExt::pull_compressed`].
+pub(crate) fn pull_compressed<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    source: &str,
+    output: &mut dyn Write,
+    compression: SyncCompression,
+) -> Result<TransferStats> {
+    let Some(compression) = compression.resolve(&device.features()?)? else {
+        return device.pull(&source, output);
+    };
+
+    let started = Instant::now();
+    let mut sync = device.open_service("sync:")?;
+    let bytes = recv_v2(&mut sync, source, output, compression)?;
+    sync.write_all(b"QUIT\0\0\0\0")?;
+    sync.flush()?;
+
+    Ok(TransferStats {
+        bytes,
+        elapsed: started.elapsed(),
+        retries: 0,
+    })
+}
+
+/// First message of a v2 request: identifier and path, followed by a second message with the same identifier
+fn request_v2(id: &[u8; 4], path: &str) -> Vec<u8> {
+    let mut request = id.to_vec();
+    request.extend_from_slice(&(path.len() as u32).to_le_bytes());
+    request.extend_from_slice(path.as_bytes());
+    request.extend_from_slice(id);
+    request
+}
+
+/// Send `input` to `path` over `stream`, opened on `sync:` service, returning number of uncompressed bytes sent.
+///
+/// `SND2` request is made of path, then mode and compression flags. Compressed data follows as `DATA` chunks, ended by
+/// a `DONE` message holding modification time of file. Device answers with `OKAY` once file is written.
+fn send_v2<S: Read + Write + ?Sized>(
+    stream: &mut S,
+    input: &mut dyn Read,
+    path: &str,
+    mode: u32,
+    compression: SyncCompression,
+) -> Result<u64> {
+    let mut request = request_v2(b"SND2", path);
+    request.extend_from_slice(&mode.to_le_bytes());
+    request.extend_from_slice(&compression.flag().to_le_bytes());
+    stream.write_all(&request)?;
+
+    let mut encoder = Encoder::new(
+        DataWriter {
+            inner: &mut *stream,
+            chunk: Vec::with_capacity(SYNC_DATA_MAX),
+        },
+        compression,
+    )?;
+    let bytes = std::io::copy(input, &mut encoder)?;
+    encoder.finish()?.flush()?;
+
+    let mod_time = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as u32);
+    let mut done = b"DONE".to_vec();
+    done.extend_from_slice(&mod_time.to_le_bytes());
+    stream.write_all(&done)?;
+    stream.flush()?;
+
+    let mut status = [0; 8];
+    stream.read_exact(&mut status)?;
+    match &status[..4] {
+        b"OKAY" => Ok(bytes),
+        b"FAIL" => {
+            let mut message = vec![0; LittleEndian::read_u32(&status[4..]) as usize];
+            stream.read_exact(&mut message)?;
+            Err(RustADBError::ADBRequestFailed(
+                String::from_utf8_lossy(&message).into_owned(),
+            ))
+        }
+        id => Err(RustADBError::InvalidMessage(format!(
+            "unexpected sync response {}",
+            String::from_utf8_lossy(id)
+        ))),
+    }
+}
+
+/// Receive `path` over `stream`, opened on `sync:` service, into `output`, returning number of uncompressed bytes
+/// received.
+///
+/// `RCV2` request is made of path, then compression flags. Device answers with compressed data as `DATA` chunks, ended
+/// by a `DONE` message, or with a `FAIL` message.
+fn recv_v2<S: Read + Write + ?Sized>(
+    stream: &mut S,
+    path: &str,
+    output: &mut dyn Write,
+    compression: SyncCompression,
+) -> Result<u64> {
+    let mut request = request_v2(b"RCV2", path);
+    request.extend_from_slice(&compression.flag().to_le_bytes());
+    stream.write_all(&request)?;
+    stream.flush()?;
+
+    let mut data = DataReader {
+        inner: &mut *stream,
+        remaining: 0,
+        done: false,
+    };
+    let bytes = std::io::copy(&mut decoder(&mut data, compression)?, output)?;
+    // Decoder may stop reading at the end of compressed data, before `DONE` message
+    std::io::copy(&mut data, &mut std::io::sink())?;
+    Ok(bytes)
+}
+
+/// [`Write`] implementation sending data as `DATA` chunks of at most [`SYNC_DATA_MAX`] bytes.
+///
+/// Data is buffered until a chunk is full, [`Write::flush`] sending the last partial one.
+struct DataWriter<W: Write> {
+    inner: W,
+    chunk: Vec<u8>,
+}
+
+impl<W: Write> DataWriter<W> {
+    fn send_chunk(&mut self) -> std::io::Result<()> {
+        if !self.chunk.is_empty() {
+            let mut message = Vec::with_capacity(8 + self.chunk.len());
+            message.extend_from_slice(b"DATA");
+            message.extend_from_slice(&(self.chunk.len() as u32).to_le_bytes());
+            message.append(&mut self.chunk);
+            self.inner.write_all(&message)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for DataWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let amount = buf.len().min(SYNC_DATA_MAX - self.chunk.len());
+        self.chunk.extend_from_slice(&buf[..amount]);
+        if self.chunk.len() == SYNC_DATA_MAX {
+            self.send_chunk()?;
+        }
+        Ok(amount)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.send_chunk()?;
+        self.inner.flush()
+    }
+}
+
+/// [`Read`] implementation returning data of `DATA` chunks, until a `DONE` message.
+struct DataReader<R: Read> {
+    inner: R,
+    /// Bytes of current chunk not read yet
+    remaining: usize,
+    done: bool,
+}
+
+impl<R: Read> Read for DataReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while self.remaining == 0 {
+            if self.done {
+                return Ok(0);
+            }
+
+            // Identifier, then chunk or message length (unused for `DONE`)
+            let mut header = [0; 8];
+            self.inner.read_exact(&mut header)?;
+            let length = LittleEndian::read_u32(&header[4..]);
+            match &header[..4] {
+                b"DATA" => self.remaining = length as usize,
+                b"DONE" => self.done = true,
+                b"FAIL" => {
+                    let mut message = Vec::new();
+                    (&mut self.inner)
+                        .take(length.into())
+                        .read_to_end(&mut message)?;
+                    return Err(std::io::Error::other(format!(
+                        "ADB request failed: {}",
+                        String::from_utf8_lossy(&message)
+                    )));
+                }
+                id => {
+                    return Err(std::io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!("unexpected sync response {}", String::from_utf8_lossy(id)),
+                    ));
+                }
+            }
+        }
+
+        let amount = self.remaining.min(buf.len());
+        let amount = self.inner.read(&mut buf[..amount])?;
+        if amount == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= amount;
+        Ok(amount)
+    }
+}
+
+/// Error of an algorithm which cannot be used, [`SyncCompression::resolve`] only returning available ones
+fn unavailable(compression: SyncCompression) -> RustADBError {
+    RustADBError::ADBRequestFailed(format!("{compression:?} compression is not available"))
+}
+
+/// Compressor of data sent to device.
+enum Encoder<W: Write> {
+    None(W),
+    #[cfg(feature = "sync-brotli")]
+    Brotli(Box<brotli::CompressorWriter<W>>),
+    #[cfg(feature = "sync-lz4")]
+    Lz4(Box<lz4_flex::frame::FrameEncoder<W>>),
+    #[cfg(feature = "sync-zstd")]
+    Zstd(Box<zstd::stream::write::Encoder<'static, W>>),
+}
+
+impl<W: Write> Encoder<W> {
+    fn new(inner: W, compression: SyncCompression) -> Result<Self> {
+        match compression {
+            SyncCompression::None => Ok(Self::None(inner)),
+            // Lowest quality and a 1 MiB window like `adb`, as transfers are mostly limited by compression speed
+            #[cfg(feature = "sync-brotli")]
+            SyncCompression::Brotli => Ok(Self::Brotli(Box::new(brotli::CompressorWriter::new(
+                inner,
+                SYNC_DATA_MAX,
+                1,
+                20,
+            )))),
+            #[cfg(feature = "sync-lz4")]
+            SyncCompression::Lz4 => Ok(Self::Lz4(Box::new(lz4_flex::frame::FrameEncoder::new(
+                inner,
+            )))),
+            #[cfg(feature = "sync-zstd")]
+            SyncCompression::Zstd => Ok(Self::Zstd(Box::new(zstd::stream::write::Encoder::new(
+                inner, 0,
+            )?))),
+            compression => Err(unavailable(compression)),
+        }
+    }
+
+    /// End compressed stream, returning inner writer
+    fn finish(self) -> std::io::Result<W> {
+        match self {
+            Self::None(inner) => Ok(inner),
+            #[cfg(feature = "sync-brotli")]
+            Self::Brotli(encoder) => Ok(encoder.into_inner()),
+            #[cfg(feature = "sync-lz4")]
+            Self::Lz4(encoder) => encoder.finish().map_err(std::io::Error::other),
+            #[cfg(feature = "sync-zstd")]
+            Self::Zstd(encoder) => encoder.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::None(inner) => inner.write(buf),
+            #[cfg(feature = "sync-brotli")]
+            Self::Brotli(encoder) => encoder.write(buf),
+            #[cfg(feature = "sync-lz4")]
+            Self::Lz4(encoder) => encoder.write(buf),
+            #[cfg(feature = "sync-zstd")]
+            Self::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::None(inner) => inner.flush(),
+            #[cfg(feature = "sync-brotli")]
+            Self::Brotli(encoder) => encoder.flush(),
+            #[cfg(feature = "sync-lz4")]
+            Self::Lz4(encoder) => encoder.flush(),
+            #[cfg(feature = "sync-zstd")]
+            Self::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Decompressor of data received from device
+fn decoder<'a, R: Read + 'a>(inner: R, compression: SyncCompression) -> Result<Box<dyn Read + 'a>> {
+    match compression {
+        SyncCompression::None => Ok(Box::new(inner)),
+        #[cfg(feature = "sync-brotli")]
+        SyncCompression::Brotli => Ok(Box::new(brotli::Decompressor::new(inner, SYNC_DATA_MAX))),
+        #[cfg(feature = "sync-lz4")]
+        SyncCompression::Lz4 => Ok(Box::new(lz4_flex::frame::FrameDecoder::new(inner))),
+        #[cfg(feature = "sync-zstd")]
+        SyncCompression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::new(inner)?)),
+        compression => Err(unavailable(compression)),
+    }
+}
+
+#[test]
+fn test_sync_v2() {
+    use std::io::Cursor;
+
+    /// Stream answering sync requests with canned messages
+    struct SyncStream {
+        responses: Cursor<Vec<u8>>,
+        requests: Vec<u8>,
+    }
+
+    impl Read for SyncStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.responses.read(buf)
+        }
+    }
+
+    impl Write for SyncStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.requests.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let file: Vec<u8> = (0..SYNC_DATA_MAX + 10).map(|i| i as u8).collect();
+    let mut stream = SyncStream {
+        responses: Cursor::new(b"OKAY\0\0\0\0".to_vec()),
+        requests: Vec::new(),
+    };
+    let bytes = send_v2(
+        &mut stream,
+        &mut file.as_slice(),
+        "/data/local/tmp/file",
+        0o644,
+        SyncCompression::None,
+    )
+    .expect("cannot send file");
+    assert_eq!(bytes, file.len() as u64);
+
+    let mut expected = request_v2(b"SND2", "/data/local/tmp/file");
+    expected.extend_from_slice(&0o644_u32.to_le_bytes());
+    expected.extend_from_slice(&0_u32.to_le_bytes());
+    for chunk in file.chunks(SYNC_DATA_MAX) {
+        expected.extend_from_slice(b"DATA");
+        expected.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        expected.extend_from_slice(chunk);
+    }
+    expected.extend_from_slice(b"DONE");
+    assert_eq!(stream.requests[..expected.len()], expected);
+
+    let mut responses = Vec::new();
+    for chunk in file.chunks(SYNC_DATA_MAX) {
+        responses.extend_from_slice(b"DATA");
+        responses.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        responses.extend_from_slice(chunk);
+    }
+    responses.extend_from_slice(b"DONE\0\0\0\0");
+    let mut stream = SyncStream {
+        responses: Cursor::new(responses),
+        requests: Vec::new(),
+    };
+    let mut output = Vec::new();
+    recv_v2(
+        &mut stream,
+        "/data/local/tmp/file",
+        &mut output,
+        SyncCompression::None,
+    )
+    .expect("cannot receive file");
+    assert_eq!(output, file);
+
+    let mut stream = SyncStream {
+        responses: Cursor::new(b"FAIL\x0c\0\0\0No such file".to_vec()),
+        requests: Vec::new(),
+    };
+    assert!(recv_v2(&mut stream, "/missing", &mut output, SyncCompression::None).is_err());
+}
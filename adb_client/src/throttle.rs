@@ -0,0 +1,103 @@
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+/// Limit of the average rate of a transfer, sleeping once it gets ahead of schedule.
+#[derive(Debug)]
+struct Throttle {
+    bytes_per_second: u64,
+    started: Instant,
+    transferred: u64,
+}
+
+impl Throttle {
+    /// Throttle at `bytes_per_second`, `None` and `0` meaning no limit
+    fn new(bytes_per_second: Option<u64>) -> Option<Self> {
+        let bytes_per_second = bytes_per_second.filter(|rate| *rate > 0)?;
+        Some(Self {
+            bytes_per_second,
+            started: Instant::now(),
+            transferred: 0,
+        })
+    }
+
+    fn advance(&mut self, amount: usize) {
+        self.transferred += amount as u64;
+        let scheduled =
+            Duration::from_secs_f64(self.transferred as f64 / self.bytes_per_second as f64);
+        let elapsed = self.started.elapsed();
+        if scheduled > elapsed {
+            std::thread::sleep(scheduled - elapsed);
+        }
+    }
+}
+
+/// [`Read`] implementation limiting rate at which data is read from `inner`.
+#[derive(Debug)]
+pub(crate) struct ThrottledReader<R: Read> {
+    inner: R,
+    throttle: Option<Throttle>,
+}
+
+impl<R: Read> ThrottledReader<R> {
+    /// Read from `inner` at `bytes_per_second` at most, `None` meaning no limit
+    pub(crate) fn new(inner: R, bytes_per_second: Option<u64>) -> Self {
+        Self {
+            inner,
+            throttle: Throttle::new(bytes_per_second),
+        }
+    }
+
+    /// Underlying reader, e.g. to seek it
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let amount = self.inner.read(buf)?;
+        if let Some(throttle) = &mut self.throttle {
+            throttle.advance(amount);
+        }
+        Ok(amount)
+    }
+}
+
+/// [`Write`] implementation limiting rate at which data is written to `inner`.
+#[derive(Debug)]
+pub(crate) struct ThrottledWriter<W: Write> {
+    inner: W,
+    throttle: Option<Throttle>,
+}
+
+impl<W: Write> ThrottledWriter<W> {
+    /// Write to `inner` at `bytes_per_second` at most, `None` meaning no limit
+    pub(crate) fn new(inner: W, bytes_per_second: Option<u64>) -> Self {
+        Self {
+            inner,
+            throttle: Throttle::new(bytes_per_second),
+        }
+    }
+}
+
+impl<W: Write> Write for ThrottledWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let amount = self.inner.write(buf)?;
+        if let Some(throttle) = &mut self.throttle {
+            throttle.advance(amount);
+        }
+        Ok(amount)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+fn test_throttled_reader() {
+    let started = Instant::now();
+    let mut reader = ThrottledReader::new(&[0u8; 2000][..], Some(20_000));
+    std::io::copy(&mut reader, &mut std::io::sink()).expect("cannot read");
+    assert!(started.elapsed() >= Duration::from_millis(100));
+}
@@ -0,0 +1,176 @@
+use std::io::{ErrorKind, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use async_io::Async;
+use futures_lite::AsyncReadExt;
+
+use super::{ADBAsyncMessageTransport, ADBAsyncTransport};
+use crate::{
+    Result, RustADBError,
+    device::{ADBTransportMessage, ADBTransportMessageHeader},
+};
+
+/// Bytes waiting to be written to a connection shared by all clones of a transport.
+///
+/// Messages are queued as a whole, so that messages written concurrently by clones never interleave on the wire.
+#[derive(Debug, Default)]
+struct WriteQueue {
+    pending: Vec<u8>,
+    /// Number of bytes queued since connection
+    queued: u64,
+    /// Number of bytes written since connection
+    written: u64,
+}
+
+impl WriteQueue {
+    /// Write as many pending bytes as possible to `stream` without blocking
+    fn flush_into(&mut self, mut stream: &TcpStream) -> std::io::Result<()> {
+        while !self.pending.is_empty() {
+            match stream.write(&self.pending) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.pending.drain(..written);
+                    self.written += written as u64;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Asynchronous transport running on TCP, for devices not requiring TLS (e.g. emulators).
+///
+/// Socket readiness is driven by the `async-io` reactor, so futures can be awaited from any runtime (e.g. `tokio`) without blocking its threads.
+#[derive(Debug, Clone)]
+pub struct AsyncTcpTransport {
+    address: SocketAddr,
+    connection: Arc<Mutex<Option<Arc<Async<TcpStream>>>>>,
+    write_queue: Arc<Mutex<WriteQueue>>,
+}
+
+impl AsyncTcpTransport {
+    /// Instantiate a new [`AsyncTcpTransport`]
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            connection: Arc::new(Mutex::new(None)),
+            write_queue: Arc::new(Mutex::new(WriteQueue::default())),
+        }
+    }
+
+    fn get_connection(&self) -> Result<Arc<Async<TcpStream>>> {
+        self.connection
+            .lock()?
+            .clone()
+            .ok_or(RustADBError::IOError(std::io::Error::new(
+                ErrorKind::NotConnected,
+                "not connected",
+            )))
+    }
+}
+
+impl ADBAsyncTransport for AsyncTcpTransport {
+    async fn connect(&mut self) -> Result<()> {
+        let stream = Async::<TcpStream>::connect(self.address).await?;
+        *self.write_queue.lock()? = WriteQueue::default();
+        *self.connection.lock()? = Some(Arc::new(stream));
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        let connection = self.connection.lock()?.take();
+        if let Some(stream) = connection {
+            stream.get_ref().shutdown(Shutdown::Both)?;
+        }
+        Ok(())
+    }
+}
+
+impl ADBAsyncMessageTransport for AsyncTcpTransport {
+    async fn read_message(&mut self) -> Result<ADBTransportMessage> {
+        let stream = self.get_connection()?;
+        let mut reader = &*stream;
+
+        let mut data = [0; 24];
+        reader.read_exact(&mut data).await?;
+        let header = ADBTransportMessageHeader::try_from(data)?;
+
+        let mut payload = vec![0_u8; header.data_length() as usize];
+        reader.read_exact(&mut payload).await?;
+        let message = ADBTransportMessage::from_header_and_payload(header, payload);
+
+        // Check message integrity
+        if !message.check_message_integrity() {
+            return Err(RustADBError::InvalidIntegrity(
+                ADBTransportMessageHeader::compute_crc32(message.payload()),
+                message.header().data_crc32(),
+            ));
+        }
+
+        Ok(message)
+    }
+
+    async fn write_message(&mut self, message: ADBTransportMessage) -> Result<()> {
+        let stream = self.get_connection()?;
+
+        let mut bytes = message.header().as_bytes()?;
+        bytes.extend_from_slice(&message.into_payload());
+        let end = {
+            let mut write_queue = self.write_queue.lock()?;
+            write_queue.pending.extend_from_slice(&bytes);
+            write_queue.queued += bytes.len() as u64;
+            write_queue.queued
+        };
+
+        // Message may be written by a concurrent clone flushing the queue, wait until it has been
+        let write_queue = self.write_queue.clone();
+        stream
+            .write_with(|stream| {
+                let mut write_queue = write_queue
+                    .lock()
+                    .map_err(|_| std::io::Error::other("poisoned write queue"))?;
+                write_queue.flush_into(stream)?;
+                if write_queue.written >= end {
+                    Ok(())
+                } else {
+                    Err(ErrorKind::WouldBlock.into())
+                }
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_async_tcp_transport_round_trip() {
+    use crate::device::MessageCommand;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("cannot bind listener");
+    let mut transport = AsyncTcpTransport::new(listener.local_addr().expect("no local address"));
+
+    futures_lite::future::block_on(async {
+        transport.connect().await.expect("cannot connect");
+        let (mut device, _) = listener.accept().expect("cannot accept connection");
+
+        let message = ADBTransportMessage::new(MessageCommand::Write, 1, 2, b"hello");
+        transport
+            .write_message(message)
+            .await
+            .expect("cannot write message");
+        let mut received = [0; 29];
+        device
+            .read_exact(&mut received)
+            .expect("cannot read message");
+        assert_eq!(&received[24..], b"hello");
+
+        device.write_all(&received).expect("cannot write message");
+        let message = transport.read_message().await.expect("cannot read message");
+        assert_eq!(message.header().command(), MessageCommand::Write);
+        assert_eq!(message.payload().as_slice(), b"hello");
+    });
+}
@@ -0,0 +1,53 @@
+use std::future::poll_fn;
+
+use super::{ADBAsyncMessageTransport, ADBAsyncTransport};
+use crate::{
+    ADBTransport, Result, USBTransport,
+    device::{ADBTransportMessage, MessageCommand},
+};
+
+/// Asynchronous transport running on USB, awaiting the same `nusb` transfer queues [`USBTransport`] blocks on.
+#[derive(Debug, Clone)]
+pub struct AsyncUSBTransport {
+    inner: USBTransport,
+}
+
+impl AsyncUSBTransport {
+    /// Instantiate a new [`AsyncUSBTransport`].
+    /// Only the first device with given vendor_id and product_id is returned.
+    pub fn new(vendor_id: u16, product_id: u16) -> Result<Self> {
+        Ok(Self::new_from_transport(USBTransport::new(
+            vendor_id, product_id,
+        )?))
+    }
+
+    /// Instantiate a new [`AsyncUSBTransport`] from a [`USBTransport`] not connected yet.
+    pub fn new_from_transport(transport: USBTransport) -> Self {
+        Self { inner: transport }
+    }
+}
+
+impl ADBAsyncTransport for AsyncUSBTransport {
+    async fn connect(&mut self) -> Result<()> {
+        // Opening device and claiming its interface do not wait for device
+        ADBTransport::connect(&mut self.inner)
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.inner
+            .submit_message(ADBTransportMessage::new(MessageCommand::Clse, 0, 0, &[]))?;
+        poll_fn(|cx| self.inner.poll_flush(cx)).await
+    }
+}
+
+impl ADBAsyncMessageTransport for AsyncUSBTransport {
+    async fn read_message(&mut self) -> Result<ADBTransportMessage> {
+        // Received data is only consumed once a whole message is available, making this future safe to drop
+        poll_fn(|cx| self.inner.poll_read_message(cx)).await
+    }
+
+    async fn write_message(&mut self, message: ADBTransportMessage) -> Result<()> {
+        self.inner.submit_message(message)?;
+        poll_fn(|cx| self.inner.poll_write_pipeline(cx)).await
+    }
+}
@@ -0,0 +1,76 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex},
+    time::Duration,
+};
+
+use super::{ADBMessageTransport, ADBTransport};
+use crate::{Result, device::ADBTransportMessage};
+
+#[derive(Debug, Default)]
+struct MessageQueue {
+    messages: Mutex<VecDeque<ADBTransportMessage>>,
+    available: Condvar,
+}
+
+/// In-memory transport connected to a peer created alongside it by [`LoopbackTransport::pair`].
+///
+/// Messages written on one end are read on the other one, which allows to emulate a device from another thread.
+#[derive(Debug, Clone)]
+pub struct LoopbackTransport {
+    inbound: Arc<MessageQueue>,
+    outbound: Arc<MessageQueue>,
+}
+
+impl LoopbackTransport {
+    /// Instantiates two connected [`LoopbackTransport`]s
+    pub fn pair() -> (Self, Self) {
+        let first = Arc::new(MessageQueue::default());
+        let second = Arc::new(MessageQueue::default());
+
+        (
+            Self {
+                inbound: first.clone(),
+                outbound: second.clone(),
+            },
+            Self {
+                inbound: second,
+                outbound: first,
+            },
+        )
+    }
+}
+
+impl ADBTransport for LoopbackTransport {
+    fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ADBMessageTransport for LoopbackTransport {
+    fn read_message_with_timeout(&mut self, read_timeout: Duration) -> Result<ADBTransportMessage> {
+        let messages = self.inbound.messages.lock()?;
+        let (mut messages, _) =
+            self.inbound
+                .available
+                .wait_timeout_while(messages, read_timeout, |messages| messages.is_empty())?;
+
+        messages
+            .pop_front()
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::TimedOut).into())
+    }
+
+    fn write_message_with_timeout(
+        &mut self,
+        message: ADBTransportMessage,
+        _write_timeout: Duration,
+    ) -> Result<()> {
+        self.outbound.messages.lock()?.push_back(message);
+        self.outbound.available.notify_one();
+        Ok(())
+    }
+}
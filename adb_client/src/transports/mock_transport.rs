@@ -0,0 +1,265 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use super::{ADBMessageTransport, ADBTransport};
+use crate::{
+    Result, RustADBError,
+    device::{ADBTransportMessage, MessageCommand},
+};
+
+/// Identifier used by [`MockTransport`] as the device side `local-id` of every session.
+const MOCK_REMOTE_ID: u32 = 0x4D4F434B;
+
+#[derive(Debug)]
+struct MockResponse {
+    command: MessageCommand,
+    args: Option<(u32, u32)>,
+    payload: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct MockExchange {
+    command: MessageCommand,
+    payload: Option<Vec<u8>>,
+    responses: Vec<MockResponse>,
+}
+
+#[derive(Debug, Default)]
+struct MockTransportState {
+    exchanges: Vec<MockExchange>,
+    next_exchange: usize,
+    pending: VecDeque<ADBTransportMessage>,
+    written: Vec<ADBTransportMessage>,
+}
+
+/// Scriptable in-memory transport, allowing to test code built on top of this crate without any device.
+///
+/// Each written message is checked against the next expectation registered with [`MockTransport::expect`],
+/// whose responses then become readable. Unless explicitly set, response arguments mirror the session ids of the written message.
+///
+/// Clones share the same script.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockTransportState>>,
+}
+
+/// Builder returned by [`MockTransport::expect`] to describe an expected message and its responses.
+#[derive(Debug)]
+pub struct MockExpectation<'a> {
+    transport: &'a MockTransport,
+    index: usize,
+}
+
+impl MockTransport {
+    /// Instantiates a new [`MockTransport`] without any expectation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect next written message to have `command` as command
+    pub fn expect(&self, command: MessageCommand) -> MockExpectation<'_> {
+        let mut state = self.lock_state();
+        state.exchanges.push(MockExchange {
+            command,
+            payload: None,
+            responses: Vec::new(),
+        });
+
+        MockExpectation {
+            transport: self,
+            index: state.exchanges.len() - 1,
+        }
+    }
+
+    /// Return a copy of all messages written so far
+    pub fn written(&self) -> Vec<ADBTransportMessage> {
+        self.lock_state().written.clone()
+    }
+
+    /// Return `true` if all expectations have been met and all responses have been read
+    pub fn is_exhausted(&self) -> bool {
+        let state = self.lock_state();
+        state.next_exchange == state.exchanges.len() && state.pending.is_empty()
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, MockTransportState> {
+        // A panic while holding this lock can only come from a failing test, keep going to report it
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl MockExpectation<'_> {
+    /// Expect written message to carry exactly `payload`
+    pub fn with_payload(self, payload: &[u8]) -> Self {
+        self.transport.lock_state().exchanges[self.index].payload = Some(payload.to_vec());
+        self
+    }
+
+    /// Make a `command` message with `payload` readable once expected message has been written
+    pub fn respond(self, command: MessageCommand, payload: &[u8]) -> Self {
+        self.push_response(command, None, payload)
+    }
+
+    /// Same as [`MockExpectation::respond`], but using explicit `arg0` and `arg1` instead of mirrored session ids
+    pub fn respond_with_args(
+        self,
+        command: MessageCommand,
+        arg0: u32,
+        arg1: u32,
+        payload: &[u8],
+    ) -> Self {
+        self.push_response(command, Some((arg0, arg1)), payload)
+    }
+
+    fn push_response(
+        self,
+        command: MessageCommand,
+        args: Option<(u32, u32)>,
+        payload: &[u8],
+    ) -> Self {
+        self.transport.lock_state().exchanges[self.index]
+            .responses
+            .push(MockResponse {
+                command,
+                args,
+                payload: payload.to_vec(),
+            });
+        self
+    }
+}
+
+impl ADBTransport for MockTransport {
+    fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ADBMessageTransport for MockTransport {
+    fn read_message_with_timeout(
+        &mut self,
+        _read_timeout: Duration,
+    ) -> Result<ADBTransportMessage> {
+        // Nothing will ever be written concurrently, waiting would only delay the error
+        self.lock_state()
+            .pending
+            .pop_front()
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::TimedOut).into())
+    }
+
+    fn write_message_with_timeout(
+        &mut self,
+        message: ADBTransportMessage,
+        _write_timeout: Duration,
+    ) -> Result<()> {
+        let mut state = self.lock_state();
+        let state = &mut *state;
+
+        let exchange = state.exchanges.get(state.next_exchange).ok_or_else(|| {
+            RustADBError::UnexpectedMockMessage(format!(
+                "{} written while no more message was expected",
+                message.header().command()
+            ))
+        })?;
+
+        if exchange.command != message.header().command() {
+            return Err(RustADBError::UnexpectedMockMessage(format!(
+                "{} written while {} was expected",
+                message.header().command(),
+                exchange.command
+            )));
+        }
+
+        if let Some(payload) = &exchange.payload
+            && payload != message.payload()
+        {
+            return Err(RustADBError::UnexpectedMockMessage(format!(
+                "{} written with payload {:?} while {:?} was expected",
+                exchange.command,
+                String::from_utf8_lossy(message.payload()),
+                String::from_utf8_lossy(payload)
+            )));
+        }
+
+        // Device replies with its own id first, and ours second
+        let remote_id = match message.header().arg1() {
+            0 => MOCK_REMOTE_ID,
+            id => id,
+        };
+        let local_id = message.header().arg0();
+
+        for response in &exchange.responses {
+            let (arg0, arg1) = response.args.unwrap_or((remote_id, local_id));
+            state.pending.push_back(ADBTransportMessage::new(
+                response.command,
+                arg0,
+                arg1,
+                &response.payload,
+            ));
+        }
+
+        state.next_exchange += 1;
+        state.written.push(message);
+
+        Ok(())
+    }
+
+    fn upgrade_tls(&mut self) -> Result<()> {
+        // Messages are exchanged in memory, there is nothing to encrypt
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl super::ADBAsyncTransport for MockTransport {
+    async fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl super::ADBAsyncMessageTransport for MockTransport {
+    async fn read_message(&mut self) -> Result<ADBTransportMessage> {
+        ADBMessageTransport::read_message(self)
+    }
+
+    async fn write_message(&mut self, message: ADBTransportMessage) -> Result<()> {
+        ADBMessageTransport::write_message(self, message)
+    }
+}
+
+#[test]
+fn test_mock_transport_shell_command() {
+    use crate::ADBMessageDevice;
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"shell:echo hello\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Write, b"hello\n");
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[]);
+
+    let mut device = ADBMessageDevice::new(transport.clone());
+    let mut output = Vec::new();
+    device
+        .shell_command(&["echo", "hello"], &mut output)
+        .expect("cannot run shell command");
+
+    assert_eq!(output, b"hello\n");
+    assert!(transport.is_exhausted());
+}
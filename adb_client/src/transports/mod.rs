@@ -1,21 +1,63 @@
+#[cfg(all(feature = "async", feature = "tcp"))]
+mod async_tcp_transport;
+#[cfg(all(feature = "async", feature = "trans-nusb"))]
+mod async_usb_transport;
+mod loopback_transport;
+mod mock_transport;
+mod record_replay_transport;
+#[cfg(feature = "tcp")]
+mod server_address;
+#[cfg(feature = "ssh")]
+mod ssh_tunnel_transport;
 #[cfg(feature = "tcp")]
 mod tcp_emulator_transport;
 #[cfg(feature = "tcp")]
+mod tcp_proxy;
+#[cfg(feature = "tcp")]
 mod tcp_server_transport;
 #[cfg(feature = "tcp")]
 mod tcp_transport;
 mod traits;
 #[cfg(feature = "usb")]
 mod usb_transport;
+#[cfg(all(feature = "vsock", any(target_os = "linux", target_os = "android")))]
+mod vsock_transport;
 
+#[cfg(all(feature = "async", feature = "tcp"))]
+pub use async_tcp_transport::AsyncTcpTransport;
+#[cfg(all(feature = "async", feature = "trans-nusb"))]
+pub use async_usb_transport::AsyncUSBTransport;
+pub use loopback_transport::LoopbackTransport;
+pub use mock_transport::{MockExpectation, MockTransport};
+pub use record_replay_transport::{RecordingTransport, ReplayTransport};
+#[cfg(all(feature = "tcp", unix))]
+pub(crate) use server_address::ADB_SERVER_SOCKET_ENV;
+#[cfg(feature = "tcp")]
+pub use server_address::ServerAddress;
+#[cfg(feature = "tcp")]
+pub(crate) use server_address::ServerStream;
+#[cfg(feature = "ssh")]
+pub use ssh_tunnel_transport::{SshAuth, SshTunnelTransport};
 #[cfg(feature = "tcp")]
 pub use tcp_emulator_transport::TCPEmulatorTransport;
 #[cfg(feature = "tcp")]
+pub use tcp_proxy::{TcpProxy, TcpProxyKind};
+#[cfg(feature = "tcp")]
 pub use tcp_server_transport::TCPServerTransport;
+#[cfg(feature = "fuzzing")]
+pub(crate) use tcp_server_transport::read_adb_response;
+#[cfg(feature = "tcp")]
+pub(crate) use tcp_transport::tls_client_config;
 #[cfg(feature = "tcp")]
-pub use tcp_transport::TcpTransport;
+pub use tcp_transport::{TcpConnectOptions, TcpTransport};
+#[cfg(feature = "async")]
+pub use traits::{ADBAsyncMessageTransport, ADBAsyncTransport};
 pub use traits::{ADBMessageTransport, ADBTransport};
+#[cfg(all(feature = "usb", feature = "tcp"))]
+pub(crate) use usb_transport::list_adb_device_serials;
 #[cfg(feature = "usb")]
-pub use usb_transport::USBTransport;
+pub use usb_transport::{USBDeviceStrings, USBLinkInfo, USBSpeed, USBTransport};
 #[cfg(feature = "usb")]
-pub use usb_transport::search_adb_devices;
+pub use usb_transport::{search_adb_devices, search_fastboot_devices};
+#[cfg(all(feature = "vsock", any(target_os = "linux", target_os = "android")))]
+pub use vsock_transport::VsockTransport;
@@ -0,0 +1,297 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use super::{ADBMessageTransport, ADBTransport};
+use crate::{
+    Result, RustADBError,
+    device::{ADBTransportMessage, ADBTransportMessageHeader, MessageCommand},
+};
+
+/// Magic bytes starting every session record, followed by records of exchanged messages.
+const RECORD_MAGIC: &[u8; 8] = b"ADBREC01";
+/// Record of a message written by host.
+const DIRECTION_WRITTEN: u8 = b'>';
+/// Record of a message read from device.
+const DIRECTION_READ: u8 = b'<';
+
+/// Transport wrapper recording all messages exchanged by `T` into a file, to be served back later by a [`ReplayTransport`].
+///
+/// Every record is flushed immediately, so that sessions ending abruptly are still usable.
+#[derive(Debug, Clone)]
+pub struct RecordingTransport<T: ADBMessageTransport> {
+    inner: T,
+    output: Arc<Mutex<BufWriter<File>>>,
+}
+
+impl<T: ADBMessageTransport> RecordingTransport<T> {
+    /// Wrap `inner`, recording its session into a file created at `path`
+    pub fn new<P: AsRef<Path>>(inner: T, path: P) -> Result<Self> {
+        let mut output = BufWriter::new(File::create(path)?);
+        output.write_all(RECORD_MAGIC)?;
+
+        Ok(Self {
+            inner,
+            output: Arc::new(Mutex::new(output)),
+        })
+    }
+
+    /// Get a reference to the wrapped transport
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn record(&self, direction: u8, message: &ADBTransportMessage) -> Result<()> {
+        let mut output = self.output.lock()?;
+        output.write_all(&[direction])?;
+        output.write_all(&message.header().as_bytes()?)?;
+        output.write_all(message.payload())?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+impl<T: ADBMessageTransport> ADBTransport for RecordingTransport<T> {
+    fn connect(&mut self) -> Result<()> {
+        self.inner.connect()
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        self.inner.disconnect()
+    }
+}
+
+impl<T: ADBMessageTransport> ADBMessageTransport for RecordingTransport<T> {
+    fn read_message_with_timeout(&mut self, read_timeout: Duration) -> Result<ADBTransportMessage> {
+        let message = self.inner.read_message_with_timeout(read_timeout)?;
+        self.record(DIRECTION_READ, &message)?;
+        Ok(message)
+    }
+
+    fn try_read_message(&mut self) -> Result<Option<ADBTransportMessage>> {
+        let message = self.inner.try_read_message()?;
+        if let Some(message) = &message {
+            self.record(DIRECTION_READ, message)?;
+        }
+        Ok(message)
+    }
+
+    fn write_message_with_timeout(
+        &mut self,
+        message: ADBTransportMessage,
+        write_timeout: Duration,
+    ) -> Result<()> {
+        self.record(DIRECTION_WRITTEN, &message)?;
+        self.inner
+            .write_message_with_timeout(message, write_timeout)
+    }
+
+    fn upgrade_tls(&mut self) -> Result<()> {
+        self.inner.upgrade_tls()
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReplayState {
+    records: VecDeque<(u8, ADBTransportMessage)>,
+    /// Session ids used in record, mapped to ones chosen by current session
+    local_ids: HashMap<u32, u32>,
+}
+
+/// Transport serving back a session recorded by a [`RecordingTransport`], without any device.
+///
+/// Written messages are checked against recorded ones, and recorded responses become readable once all previous
+/// messages have been written. As session ids are randomly chosen, ids used in record are transparently mapped to current ones.
+///
+/// Clones share the same record.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayTransport {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl ReplayTransport {
+    /// Load session recorded in file at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Load session recorded in `reader`
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let mut magic = [0; RECORD_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != RECORD_MAGIC {
+            return Err(RustADBError::ReplayMismatch(
+                "not a session record".to_string(),
+            ));
+        }
+
+        let mut records = VecDeque::new();
+        loop {
+            let mut direction = [0; 1];
+            match reader.read_exact(&mut direction) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+
+            let mut header = [0; 24];
+            reader.read_exact(&mut header)?;
+            let header = ADBTransportMessageHeader::try_from(header)?;
+
+            let mut payload = vec![0; header.data_length() as usize];
+            reader.read_exact(&mut payload)?;
+
+            records.push_back((
+                direction[0],
+                ADBTransportMessage::from_header_and_payload(header, payload),
+            ));
+        }
+
+        Ok(Self {
+            state: Arc::new(Mutex::new(ReplayState {
+                records,
+                local_ids: HashMap::new(),
+            })),
+        })
+    }
+
+    /// Return `true` if all recorded messages have been written and read
+    pub fn is_exhausted(&self) -> bool {
+        self.lock_state().records.is_empty()
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, ReplayState> {
+        // A panic while holding this lock can only come from a failing test, keep going to report it
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ADBTransport for ReplayTransport {
+    fn connect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ADBMessageTransport for ReplayTransport {
+    fn read_message_with_timeout(
+        &mut self,
+        _read_timeout: Duration,
+    ) -> Result<ADBTransportMessage> {
+        let mut state = self.lock_state();
+
+        // Nothing can be read while next recorded message is still to be written
+        let message = match state.records.front() {
+            Some((DIRECTION_READ, _)) => state.records.pop_front().map(|(_, message)| message),
+            _ => None,
+        }
+        .ok_or_else(|| std::io::Error::from(ErrorKind::TimedOut))?;
+
+        // Device refers to our session using our `local-id` as second argument
+        let header = message.header();
+        let arg1 = state
+            .local_ids
+            .get(&header.arg1())
+            .copied()
+            .unwrap_or(header.arg1());
+
+        Ok(ADBTransportMessage::new(
+            header.command(),
+            header.arg0(),
+            arg1,
+            message.payload(),
+        ))
+    }
+
+    fn write_message_with_timeout(
+        &mut self,
+        message: ADBTransportMessage,
+        _write_timeout: Duration,
+    ) -> Result<()> {
+        let mut state = self.lock_state();
+
+        let expected = match state.records.pop_front() {
+            Some((DIRECTION_WRITTEN, expected)) => expected,
+            Some((_, expected)) => {
+                return Err(RustADBError::ReplayMismatch(format!(
+                    "{} written while {} was still to be read",
+                    message.header().command(),
+                    expected.header().command()
+                )));
+            }
+            None => {
+                return Err(RustADBError::ReplayMismatch(format!(
+                    "{} written after end of record",
+                    message.header().command()
+                )));
+            }
+        };
+
+        let command = message.header().command();
+        if command != expected.header().command() {
+            return Err(RustADBError::ReplayMismatch(format!(
+                "{command} written while {} was recorded",
+                expected.header().command()
+            )));
+        }
+
+        // Signatures depend on the key used, which may differ from the recorded one
+        if command != MessageCommand::Auth && message.payload() != expected.payload() {
+            return Err(RustADBError::ReplayMismatch(format!(
+                "{command} written with payload {:?} while {:?} was recorded",
+                String::from_utf8_lossy(message.payload()),
+                String::from_utf8_lossy(expected.payload())
+            )));
+        }
+
+        if command == MessageCommand::Open {
+            state
+                .local_ids
+                .insert(expected.header().arg0(), message.header().arg0());
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_record_replay_shell_command() {
+    use crate::{ADBMessageDevice, MockTransport};
+
+    let transport = MockTransport::new();
+    transport
+        .expect(MessageCommand::Open)
+        .with_payload(b"shell:echo hello\0")
+        .respond(MessageCommand::Okay, &[])
+        .respond(MessageCommand::Write, b"hello\n");
+    transport
+        .expect(MessageCommand::Okay)
+        .respond(MessageCommand::Clse, &[]);
+
+    let path = std::env::temp_dir().join(format!("adb_client_record_{}", std::process::id()));
+    let mut recorded = Vec::new();
+    ADBMessageDevice::new(RecordingTransport::new(transport, &path).expect("cannot record"))
+        .shell_command(&["echo", "hello"], &mut recorded)
+        .expect("cannot run recorded shell command");
+
+    let replay = ReplayTransport::open(&path).expect("cannot load record");
+    std::fs::remove_file(&path).expect("cannot remove record");
+
+    let mut replayed = Vec::new();
+    ADBMessageDevice::new(replay.clone())
+        .shell_command(&["echo", "hello"], &mut replayed)
+        .expect("cannot run replayed shell command");
+
+    assert_eq!(replayed, recorded);
+    assert!(replay.is_exhausted());
+}
@@ -0,0 +1,294 @@
+use std::fmt::Display;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, SocketAddrV4, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::PathBuf};
+
+use super::TcpProxy;
+use crate::{Result, RustADBError};
+
+const DEFAULT_SERVER_IP: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
+const DEFAULT_SERVER_PORT: u16 = 5037;
+/// Environment variable holding server socket specification, e.g. `tcp:127.0.0.1:5037`
+pub(crate) const ADB_SERVER_SOCKET_ENV: &str = "ADB_SERVER_SOCKET";
+/// Environment variable holding port of a local server
+const ANDROID_ADB_SERVER_PORT_ENV: &str = "ANDROID_ADB_SERVER_PORT";
+
+/// Address an ADB server listens on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerAddress {
+    /// TCP socket, `127.0.0.1:5037` by default
+    Tcp(SocketAddrV4),
+    /// Unix domain socket, as used by servers started with `ADB_SERVER_SOCKET=localfilesystem:<path>`
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl Default for ServerAddress {
+    fn default() -> Self {
+        Self::Tcp(SocketAddrV4::new(DEFAULT_SERVER_IP, DEFAULT_SERVER_PORT))
+    }
+}
+
+impl From<SocketAddrV4> for ServerAddress {
+    fn from(value: SocketAddrV4) -> Self {
+        Self::Tcp(value)
+    }
+}
+
+impl FromStr for ServerAddress {
+    type Err = RustADBError;
+
+    /// Parse a server socket specification, as found in `ADB_SERVER_SOCKET`: `tcp:port`, `tcp:host:port`,
+    /// `unix:path` or `localfilesystem:path`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || RustADBError::InvalidServerAddress(s.to_string());
+        let (kind, value) = s.split_once(':').ok_or_else(invalid)?;
+        match kind {
+            "tcp" => {
+                let (host, port) = match value.rsplit_once(':') {
+                    Some((host, port)) => (host, port),
+                    None => ("127.0.0.1", value),
+                };
+                let port = port.parse::<u16>()?;
+                // Only IPv4 servers are supported
+                (host, port)
+                    .to_socket_addrs()?
+                    .find_map(|addr| match addr {
+                        SocketAddr::V4(addr) => Some(Self::Tcp(addr)),
+                        SocketAddr::V6(_) => None,
+                    })
+                    .ok_or_else(invalid)
+            }
+            #[cfg(unix)]
+            "unix" | "localfilesystem" if !value.is_empty() => Ok(Self::Unix(PathBuf::from(value))),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Display for ServerAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerAddress::Tcp(socket_addr) => write!(f, "{socket_addr}"),
+            #[cfg(unix)]
+            ServerAddress::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl ServerAddress {
+    /// TCP socket address of server, if not listening on a unix socket
+    pub fn socket_addr(&self) -> Option<SocketAddrV4> {
+        match self {
+            ServerAddress::Tcp(socket_addr) => Some(*socket_addr),
+            #[cfg(unix)]
+            ServerAddress::Unix(_) => None,
+        }
+    }
+
+    /// Address of server the adb CLI would use, following `ADB_SERVER_SOCKET` and `ANDROID_ADB_SERVER_PORT`
+    /// environment variables, or `127.0.0.1:5037` if none is set.
+    pub fn from_env() -> Result<Self> {
+        if let Ok(spec) = std::env::var(ADB_SERVER_SOCKET_ENV) {
+            return spec.parse();
+        }
+        if let Ok(port) = std::env::var(ANDROID_ADB_SERVER_PORT_ENV) {
+            return Ok(Self::Tcp(SocketAddrV4::new(
+                DEFAULT_SERVER_IP,
+                port.trim().parse()?,
+            )));
+        }
+
+        Ok(Self::default())
+    }
+
+    /// Same as [`ServerAddress::from_env`], falling back to default address if environment is invalid
+    pub(crate) fn from_env_or_default() -> Self {
+        Self::from_env().unwrap_or_else(|e| {
+            log::warn!("ignoring adb server address from environment: {e}");
+            Self::default()
+        })
+    }
+
+    /// Whether server runs on this host, and can hence be started if not running yet
+    pub(crate) fn is_local(&self) -> bool {
+        match self {
+            ServerAddress::Tcp(socket_addr) => {
+                socket_addr.ip().is_loopback() || socket_addr.ip().is_unspecified()
+            }
+            #[cfg(unix)]
+            ServerAddress::Unix(_) => true,
+        }
+    }
+
+    /// Value of `ADB_SERVER_SOCKET` environment variable making a started server listen on this address
+    pub(crate) fn server_socket_spec(&self) -> String {
+        match self {
+            ServerAddress::Tcp(socket_addr) => format!("tcp:{socket_addr}"),
+            #[cfg(unix)]
+            ServerAddress::Unix(path) => format!("localfilesystem:{}", path.display()),
+        }
+    }
+
+    /// Connect to server, through `proxy` if set
+    pub(crate) fn connect(&self, proxy: Option<&TcpProxy>) -> Result<ServerStream> {
+        match self {
+            ServerAddress::Tcp(socket_addr) => {
+                let tcp_stream = match proxy {
+                    Some(proxy) => proxy.open_tunnel(
+                        TcpStream::connect(proxy.address())?,
+                        (*socket_addr).into(),
+                        None,
+                    )?,
+                    None => TcpStream::connect(socket_addr)?,
+                };
+                tcp_stream.set_nodelay(true)?;
+                Ok(ServerStream::Tcp(tcp_stream))
+            }
+            #[cfg(unix)]
+            ServerAddress::Unix(_) if proxy.is_some() => Err(RustADBError::ProxyError(
+                "unix sockets cannot be reached through a proxy".into(),
+            )),
+            #[cfg(unix)]
+            ServerAddress::Unix(path) => Ok(ServerStream::Unix(UnixStream::connect(path)?)),
+        }
+    }
+}
+
+/// Connection to an ADB server, over TCP or a unix domain socket
+#[derive(Debug)]
+pub(crate) enum ServerStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl ServerStream {
+    pub(crate) fn try_clone(&self) -> std::io::Result<Self> {
+        match self {
+            ServerStream::Tcp(tcp_stream) => tcp_stream.try_clone().map(ServerStream::Tcp),
+            #[cfg(unix)]
+            ServerStream::Unix(unix_stream) => unix_stream.try_clone().map(ServerStream::Unix),
+        }
+    }
+
+    pub(crate) fn shutdown(&self, how: Shutdown) -> std::io::Result<()> {
+        match self {
+            ServerStream::Tcp(tcp_stream) => tcp_stream.shutdown(how),
+            #[cfg(unix)]
+            ServerStream::Unix(unix_stream) => unix_stream.shutdown(how),
+        }
+    }
+
+    pub(crate) fn set_read_timeout(
+        &self,
+        timeout: Option<std::time::Duration>,
+    ) -> std::io::Result<()> {
+        match self {
+            ServerStream::Tcp(tcp_stream) => tcp_stream.set_read_timeout(timeout),
+            #[cfg(unix)]
+            ServerStream::Unix(unix_stream) => unix_stream.set_read_timeout(timeout),
+        }
+    }
+}
+
+impl Read for &ServerStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            ServerStream::Tcp(tcp_stream) => (&*tcp_stream).read(buf),
+            #[cfg(unix)]
+            ServerStream::Unix(unix_stream) => (&*unix_stream).read(buf),
+        }
+    }
+}
+
+impl Write for &ServerStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            ServerStream::Tcp(tcp_stream) => (&*tcp_stream).write(buf),
+            #[cfg(unix)]
+            ServerStream::Unix(unix_stream) => (&*unix_stream).write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ServerStream::Tcp(tcp_stream) => (&*tcp_stream).flush(),
+            #[cfg(unix)]
+            ServerStream::Unix(unix_stream) => (&*unix_stream).flush(),
+        }
+    }
+}
+
+impl Read for ServerStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        (&*self).read(buf)
+    }
+}
+
+impl Write for ServerStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+#[test]
+fn test_parse_server_address() {
+    let local = |port| ServerAddress::Tcp(SocketAddrV4::new(DEFAULT_SERVER_IP, port));
+    assert_eq!("tcp:5038".parse::<ServerAddress>().ok(), Some(local(5038)));
+    assert_eq!(
+        "tcp:127.0.0.1:5039".parse::<ServerAddress>().ok(),
+        Some(local(5039))
+    );
+    assert_eq!(
+        "tcp:10.0.0.2:5037".parse::<ServerAddress>().ok(),
+        Some(ServerAddress::Tcp(SocketAddrV4::new(
+            Ipv4Addr::new(10, 0, 0, 2),
+            5037
+        )))
+    );
+    #[cfg(unix)]
+    {
+        let unix = Some(ServerAddress::Unix(PathBuf::from("/tmp/adb.sock")));
+        assert_eq!("unix:/tmp/adb.sock".parse::<ServerAddress>().ok(), unix);
+        assert_eq!(
+            "localfilesystem:/tmp/adb.sock"
+                .parse::<ServerAddress>()
+                .ok(),
+            unix
+        );
+    }
+    for invalid in ["5037", "tcp:", "tcp:host:port", "unix:", "vsock:3:5037"] {
+        assert!(invalid.parse::<ServerAddress>().is_err(), "{invalid}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix_server_stream() {
+    let path = std::env::temp_dir().join(format!("adb_client-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = std::os::unix::net::UnixListener::bind(&path).expect("cannot bind socket");
+
+    let address = ServerAddress::Unix(path.clone());
+    assert!(address.is_local());
+    assert_eq!(
+        address.server_socket_spec(),
+        format!("localfilesystem:{}", path.display())
+    );
+
+    let mut stream = address.connect(None).expect("cannot connect");
+    let (mut peer, _) = listener.accept().expect("cannot accept");
+    stream.write_all(b"000chost:version").expect("cannot write");
+    let mut request = [0; 16];
+    peer.read_exact(&mut request).expect("cannot read");
+    assert_eq!(&request, b"000chost:version");
+
+    let _ = std::fs::remove_file(&path);
+}
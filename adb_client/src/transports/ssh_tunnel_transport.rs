@@ -0,0 +1,379 @@
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{Ipv4Addr, Shutdown, SocketAddr, SocketAddrV4, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD_NO_PAD};
+use ssh2::{Channel, CheckResult, HashType, KnownHostFileKind, Session};
+
+use super::ADBTransport;
+use crate::{Result, RustADBError, constants::BUFFER_SIZE};
+
+/// Time given to SSH server to accept connection, and to answer each request while establishing it
+const SSH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Interval between two keepalive messages, keeping idle tunnels open through firewalls
+const SSH_KEEPALIVE_INTERVAL_SECS: u32 = 30;
+/// Time waited by forwarding thread when no data has been moved
+const SSH_FORWARD_POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Authentication method used by an [`SshTunnelTransport`]
+#[derive(Clone, Default)]
+pub enum SshAuth {
+    /// Keys held by running SSH agent
+    #[default]
+    Agent,
+    /// Password of user
+    Password(String),
+    /// Private key stored at `path`, encrypted with `passphrase` if any
+    PrivateKey {
+        /// Path of private key
+        path: PathBuf,
+        /// Passphrase decrypting private key
+        passphrase: Option<String>,
+    },
+}
+
+impl std::fmt::Debug for SshAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never log secrets
+        match self {
+            SshAuth::Agent => write!(f, "Agent"),
+            SshAuth::Password(_) => write!(f, "Password(..)"),
+            SshAuth::PrivateKey { path, .. } => write!(f, "PrivateKey({})", path.display()),
+        }
+    }
+}
+
+/// Tunnel forwarding a port reachable from an SSH server (e.g. a device lab jump host) to a local port.
+///
+/// Once connected, connections to [`SshTunnelTransport::local_address`] are forwarded to target through SSH server.
+/// [`crate::ADBServer::new_over_ssh`] and [`crate::ADBTcpDevice::new_over_ssh`] build a server or device reached
+/// through a tunnel, keeping it open as long as they live.
+#[derive(Debug)]
+pub struct SshTunnelTransport {
+    ssh_address: SocketAddr,
+    username: String,
+    auth: SshAuth,
+    known_hosts_path: Option<PathBuf>,
+    target_host: String,
+    target_port: u16,
+    tunnel: Option<RunningTunnel>,
+}
+
+#[derive(Debug)]
+struct RunningTunnel {
+    local_address: SocketAddrV4,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl SshTunnelTransport {
+    /// Instantiate a new [`SshTunnelTransport`] logging in as `username` to SSH server at `ssh_address`, and forwarding
+    /// `target_host:target_port` as resolved by this server (e.g. `localhost:5037` for its adb server).
+    pub fn new(
+        ssh_address: SocketAddr,
+        username: &str,
+        target_host: &str,
+        target_port: u16,
+    ) -> Self {
+        Self {
+            ssh_address,
+            username: username.to_string(),
+            auth: SshAuth::default(),
+            known_hosts_path: None,
+            target_host: target_host.to_string(),
+            target_port,
+            tunnel: None,
+        }
+    }
+
+    /// Authenticate using `auth` instead of SSH agent
+    pub fn with_auth(mut self, auth: SshAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Only accept SSH servers whose host key is listed in OpenSSH `known_hosts` file at `path`.
+    ///
+    /// Host key is otherwise accepted without verification, its fingerprint being logged.
+    pub fn with_known_hosts(mut self, path: PathBuf) -> Self {
+        self.known_hosts_path = Some(path);
+        self
+    }
+
+    /// Local address forwarded to target, only available once connected
+    pub fn local_address(&self) -> Result<SocketAddrV4> {
+        self.tunnel
+            .as_ref()
+            .map(|tunnel| tunnel.local_address)
+            .ok_or(RustADBError::IOError(std::io::Error::new(
+                ErrorKind::NotConnected,
+                "not connected",
+            )))
+    }
+
+    fn open_session(&self) -> Result<Session> {
+        let stream = TcpStream::connect_timeout(&self.ssh_address, SSH_TIMEOUT)?;
+        let mut session = Session::new()?;
+        session.set_timeout(u32::try_from(SSH_TIMEOUT.as_millis())?);
+        session.set_tcp_stream(stream);
+        session.handshake()?;
+        self.check_host_key(&session)?;
+
+        match &self.auth {
+            SshAuth::Agent => session.userauth_agent(&self.username)?,
+            SshAuth::Password(password) => session.userauth_password(&self.username, password)?,
+            SshAuth::PrivateKey { path, passphrase } => {
+                session.userauth_pubkey_file(&self.username, None, path, passphrase.as_deref())?
+            }
+        }
+        if !session.authenticated() {
+            return Err(RustADBError::SshTunnelError(format!(
+                "authentication of {} to {} failed",
+                self.username, self.ssh_address
+            )));
+        }
+
+        session.set_keepalive(true, SSH_KEEPALIVE_INTERVAL_SECS);
+        Ok(session)
+    }
+
+    fn check_host_key(&self, session: &Session) -> Result<()> {
+        let Some(known_hosts_path) = &self.known_hosts_path else {
+            let fingerprint = session
+                .host_key_hash(HashType::Sha256)
+                .map(|hash| STANDARD_NO_PAD.encode(hash))
+                .unwrap_or_default();
+            log::warn!(
+                "accepting unverified host key SHA256:{fingerprint} of {}",
+                self.ssh_address
+            );
+            return Ok(());
+        };
+
+        let (key, _) = session.host_key().ok_or_else(|| {
+            RustADBError::SshTunnelError(format!("{} sent no host key", self.ssh_address))
+        })?;
+        let mut known_hosts = session.known_hosts()?;
+        known_hosts.read_file(known_hosts_path, KnownHostFileKind::OpenSSH)?;
+        let host = self.ssh_address.ip().to_string();
+        match known_hosts.check_port(&host, self.ssh_address.port(), key) {
+            CheckResult::Match => Ok(()),
+            CheckResult::Mismatch => Err(RustADBError::SshTunnelError(format!(
+                "host key of {} does not match known hosts",
+                self.ssh_address
+            ))),
+            CheckResult::NotFound => Err(RustADBError::SshTunnelError(format!(
+                "host key of {} is not in known hosts",
+                self.ssh_address
+            ))),
+            CheckResult::Failure => Err(RustADBError::SshTunnelError(format!(
+                "cannot check host key of {}",
+                self.ssh_address
+            ))),
+        }
+    }
+}
+
+impl ADBTransport for SshTunnelTransport {
+    fn connect(&mut self) -> Result<()> {
+        self.disconnect()?;
+
+        let session = self.open_session()?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        listener.set_nonblocking(true)?;
+        let SocketAddr::V4(local_address) = listener.local_addr()? else {
+            return Err(RustADBError::ConversionError);
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let target = (self.target_host.clone(), self.target_port);
+        let handle = std::thread::spawn({
+            let stop = stop.clone();
+            move || forward_connections(session, listener, target, stop)
+        });
+        log::debug!(
+            "forwarding {local_address} to {}:{} through {}",
+            self.target_host,
+            self.target_port,
+            self.ssh_address
+        );
+
+        self.tunnel = Some(RunningTunnel {
+            local_address,
+            stop,
+            handle,
+        });
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        if let Some(tunnel) = self.tunnel.take() {
+            tunnel.stop.store(true, Ordering::Relaxed);
+            let _ = tunnel.handle.join();
+            log::debug!("closed tunnel of {}", tunnel.local_address);
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for SshTunnelTransport {
+    fn drop(&mut self) {
+        let _ = self.disconnect();
+    }
+}
+
+/// Forward connections accepted by `listener` to `target` over `session`, until `stop` is set
+fn forward_connections(
+    session: Session,
+    listener: TcpListener,
+    target: (String, u16),
+    stop: Arc<AtomicBool>,
+) {
+    // All channels share the session, polling them avoids blocking one while another has data
+    session.set_blocking(false);
+    let mut connections: Vec<ForwardedConnection> = Vec::new();
+    let mut buffer = vec![0; BUFFER_SIZE];
+
+    while !stop.load(Ordering::Relaxed) {
+        let mut progress = false;
+        match listener.accept() {
+            Ok((local, peer)) => {
+                progress = true;
+                match ForwardedConnection::open(&session, local, &target) {
+                    Ok(connection) => connections.push(connection),
+                    Err(e) => log::error!("cannot forward connection of {peer}: {e}"),
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => {
+                log::error!("cannot accept tunnel connection: {e}");
+                break;
+            }
+        }
+
+        connections.retain_mut(|connection| match connection.pump(&mut buffer) {
+            Ok(pumped) => {
+                progress |= pumped;
+                !connection.is_finished()
+            }
+            Err(e) => {
+                log::debug!("closing tunnel connection: {e}");
+                let _ = connection.channel.close();
+                false
+            }
+        });
+
+        // Only sends a message once interval has elapsed
+        let _ = session.keepalive_send();
+        if !progress {
+            std::thread::sleep(SSH_FORWARD_POLL_INTERVAL);
+        }
+    }
+
+    for mut connection in connections {
+        let _ = connection.channel.close();
+    }
+    let _ = session.disconnect(None, "tunnel closed", None);
+}
+
+/// Local connection forwarded over an SSH channel
+struct ForwardedConnection {
+    local: TcpStream,
+    channel: Channel,
+    /// Data read from local connection, not written to channel yet
+    to_remote: Vec<u8>,
+    /// Data read from channel, not written to local connection yet
+    to_local: Vec<u8>,
+    local_closed: bool,
+    eof_sent: bool,
+    remote_closed: bool,
+}
+
+impl ForwardedConnection {
+    fn open(session: &Session, local: TcpStream, target: &(String, u16)) -> Result<Self> {
+        session.set_blocking(true);
+        let channel = session.channel_direct_tcpip(&target.0, target.1, None);
+        session.set_blocking(false);
+
+        local.set_nonblocking(true)?;
+        local.set_nodelay(true)?;
+        Ok(Self {
+            local,
+            channel: channel?,
+            to_remote: Vec::new(),
+            to_local: Vec::new(),
+            local_closed: false,
+            eof_sent: false,
+            remote_closed: false,
+        })
+    }
+
+    /// Move data available in both directions, returning whether any has been moved
+    fn pump(&mut self, buffer: &mut [u8]) -> std::io::Result<bool> {
+        let mut progress = false;
+
+        if !self.local_closed && self.to_remote.is_empty() {
+            match self.local.read(buffer) {
+                Ok(0) => self.local_closed = true,
+                Ok(size) => self.to_remote.extend_from_slice(&buffer[..size]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            progress |= self.local_closed || !self.to_remote.is_empty();
+        }
+        if !self.to_remote.is_empty() {
+            progress |= drain_into(&mut self.to_remote, &mut self.channel)?;
+        }
+        if self.local_closed && self.to_remote.is_empty() && !self.eof_sent {
+            match self.channel.send_eof().map_err(std::io::Error::from) {
+                Ok(()) => self.eof_sent = true,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if !self.remote_closed && self.to_local.is_empty() {
+            match self.channel.read(buffer) {
+                Ok(0) => self.remote_closed = self.channel.eof(),
+                Ok(size) => self.to_local.extend_from_slice(&buffer[..size]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+            progress |= self.remote_closed || !self.to_local.is_empty();
+        }
+        if !self.to_local.is_empty() {
+            progress |= drain_into(&mut self.to_local, &mut self.local)?;
+        }
+        if self.remote_closed && self.to_local.is_empty() {
+            // Let local peer know that target closed connection
+            let _ = self.local.shutdown(Shutdown::Write);
+        }
+
+        Ok(progress)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.eof_sent && self.remote_closed && self.to_local.is_empty()
+    }
+}
+
+/// Write as much of `pending` as `writer` accepts without blocking, returning whether anything has been written
+fn drain_into<W: Write>(pending: &mut Vec<u8>, writer: &mut W) -> std::io::Result<bool> {
+    match writer.write(pending) {
+        Ok(size) => {
+            pending.drain(..size);
+            Ok(size > 0)
+        }
+        Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(false),
+        Err(e) => Err(e),
+    }
+}
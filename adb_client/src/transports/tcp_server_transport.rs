@@ -1,38 +1,46 @@
 use std::io::{Error, ErrorKind, Read, Write};
-use std::net::{Ipv4Addr, SocketAddrV4, TcpStream};
+use std::net::SocketAddrV4;
+#[cfg(unix)]
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use byteorder::{ByteOrder, LittleEndian};
 
+use super::TcpProxy;
+use super::server_address::{ServerAddress, ServerStream};
 use crate::models::{AdbRequestStatus, SyncCommand};
 use crate::{ADBTransport, models::AdbServerCommand};
 use crate::{Result, RustADBError};
 
-const DEFAULT_SERVER_IP: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
-const DEFAULT_SERVER_PORT: u16 = 5037;
-
-/// Server transport running on top on TCP
-#[derive(Debug)]
+/// Server transport running on top on TCP, or on a unix domain socket
+#[derive(Debug, Default)]
 pub struct TCPServerTransport {
-    socket_addr: SocketAddrV4,
-    tcp_stream: Option<TcpStream>,
-}
-
-impl Default for TCPServerTransport {
-    fn default() -> Self {
-        Self::new(SocketAddrV4::new(DEFAULT_SERVER_IP, DEFAULT_SERVER_PORT))
-    }
+    address: ServerAddress,
+    proxy: Option<TcpProxy>,
+    stream: Option<ServerStream>,
 }
 
 impl TCPServerTransport {
     /// Instantiates a new instance of [TCPServerTransport]
     pub fn new(socket_addr: SocketAddrV4) -> Self {
+        Self::new_with_address(socket_addr.into())
+    }
+
+    /// Instantiates a new instance of [TCPServerTransport] reaching server at given [ServerAddress]
+    pub fn new_with_address(address: ServerAddress) -> Self {
         Self {
-            socket_addr,
-            tcp_stream: None,
+            address,
+            proxy: None,
+            stream: None,
         }
     }
 
+    /// Instantiates a new instance of [TCPServerTransport] reaching server listening on unix socket at `path`
+    #[cfg(unix)]
+    pub fn new_unix(path: PathBuf) -> Self {
+        Self::new_with_address(ServerAddress::Unix(path))
+    }
+
     /// Instantiate a new instance of [TCPServerTransport] using given address, or default if not specified.
     pub fn new_or_default(socket_addr: Option<SocketAddrV4>) -> Self {
         match socket_addr {
@@ -41,9 +49,19 @@ impl TCPServerTransport {
         }
     }
 
-    /// Get underlying [SocketAddrV4]
-    pub fn get_socketaddr(&self) -> SocketAddrV4 {
-        self.socket_addr
+    /// Get underlying [ServerAddress]
+    pub fn get_address(&self) -> &ServerAddress {
+        &self.address
+    }
+
+    /// Set [TcpProxy] traversed by following connections to a server listening on TCP
+    pub fn set_proxy(&mut self, proxy: Option<TcpProxy>) {
+        self.proxy = proxy;
+    }
+
+    /// Get [TcpProxy] traversed by connections, if any
+    pub fn get_proxy(&self) -> Option<&TcpProxy> {
+        self.proxy.as_ref()
     }
 
     pub(crate) fn proxy_connection(
@@ -71,22 +89,24 @@ impl TCPServerTransport {
         }
     }
 
-    pub(crate) fn get_raw_connection(&self) -> Result<&TcpStream> {
-        self.tcp_stream
-            .as_ref()
-            .ok_or(RustADBError::IOError(Error::new(
-                ErrorKind::NotConnected,
-                "not connected",
-            )))
+    pub(crate) fn get_raw_connection(&self) -> Result<&ServerStream> {
+        self.stream.as_ref().ok_or(RustADBError::IOError(Error::new(
+            ErrorKind::NotConnected,
+            "not connected",
+        )))
+    }
+
+    /// Take ownership of current connection, leaving this transport disconnected
+    pub(crate) fn take_raw_connection(&mut self) -> Result<ServerStream> {
+        self.stream.take().ok_or(RustADBError::IOError(Error::new(
+            ErrorKind::NotConnected,
+            "not connected",
+        )))
     }
 
     /// Gets the body length from hexadecimal value
     pub(crate) fn get_hex_body_length(&mut self) -> Result<u32> {
-        let length_buffer = self.read_body_length()?;
-        Ok(u32::from_str_radix(
-            std::str::from_utf8(&length_buffer)?,
-            16,
-        )?)
+        read_hex_body_length(self.get_raw_connection()?)
     }
 
     /// Send the given [SyncCommand] to ADB server, and checks that the request has been taken in consideration.
@@ -126,51 +146,65 @@ impl TCPServerTransport {
 
     /// Read a response from ADB server
     pub(crate) fn read_adb_response(&mut self) -> Result<()> {
-        // Reads returned status code from ADB server
-        let mut request_status = [0; 4];
-        self.get_raw_connection()?.read_exact(&mut request_status)?;
-
-        match AdbRequestStatus::from_str(std::str::from_utf8(request_status.as_ref())?)? {
-            AdbRequestStatus::Fail => {
-                // We can keep reading to get further details
-                let length = self.get_hex_body_length()?;
-
-                let mut body = vec![
-                    0;
-                    length
-                        .try_into()
-                        .map_err(|_| RustADBError::ConversionError)?
-                ];
-                if length > 0 {
-                    self.get_raw_connection()?.read_exact(&mut body)?;
-                }
-
-                Err(RustADBError::ADBRequestFailed(String::from_utf8(body)?))
+        read_adb_response(self.get_raw_connection()?)
+    }
+}
+
+/// Read a 4 bytes hexadecimal body length from `reader`
+pub(crate) fn read_hex_body_length<R: Read>(mut reader: R) -> Result<u32> {
+    let mut length_buffer = [0; 4];
+    reader.read_exact(&mut length_buffer)?;
+
+    Ok(u32::from_str_radix(
+        std::str::from_utf8(&length_buffer)?,
+        16,
+    )?)
+}
+
+/// Read a smart-socket response status from `reader`, returning server error message if request failed
+pub(crate) fn read_adb_response<R: Read>(mut reader: R) -> Result<()> {
+    // Reads returned status code from ADB server
+    let mut request_status = [0; 4];
+    reader.read_exact(&mut request_status)?;
+
+    match AdbRequestStatus::from_str(std::str::from_utf8(request_status.as_ref())?)? {
+        AdbRequestStatus::Fail => {
+            // We can keep reading to get further details
+            let length = read_hex_body_length(&mut reader)?;
+
+            let mut body = vec![
+                0;
+                length
+                    .try_into()
+                    .map_err(|_| RustADBError::ConversionError)?
+            ];
+            if length > 0 {
+                reader.read_exact(&mut body)?;
             }
-            AdbRequestStatus::Okay => Ok(()),
+
+            Err(RustADBError::ADBRequestFailed(String::from_utf8(body)?))
         }
+        AdbRequestStatus::Okay => Ok(()),
     }
 }
 
 impl ADBTransport for TCPServerTransport {
     fn disconnect(&mut self) -> Result<()> {
-        if let Some(conn) = &mut self.tcp_stream {
+        if let Some(conn) = &mut self.stream {
             conn.shutdown(std::net::Shutdown::Both)?;
-            log::trace!("Disconnected from {}", conn.peer_addr()?);
+            log::trace!("Disconnected from {}", self.address);
         }
 
         Ok(())
     }
 
     fn connect(&mut self) -> Result<()> {
-        if let Some(previous) = &self.tcp_stream {
+        if let Some(previous) = &self.stream {
             // Ignoring underlying error, we will recreate a new connection
             let _ = previous.shutdown(std::net::Shutdown::Both);
         }
-        let tcp_stream = TcpStream::connect(self.socket_addr)?;
-        tcp_stream.set_nodelay(true)?;
-        self.tcp_stream = Some(tcp_stream);
-        log::trace!("Successfully connected to {}", self.socket_addr);
+        self.stream = Some(self.address.connect(self.proxy.as_ref())?);
+        log::trace!("Successfully connected to {}", self.address);
 
         Ok(())
     }
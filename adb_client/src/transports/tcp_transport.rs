@@ -5,19 +5,19 @@ use rustls::{
     pki_types::{CertificateDer, PrivatePkcs8KeyDer, pem::PemObject},
 };
 
-use super::{ADBMessageTransport, ADBTransport};
+use super::{ADBMessageTransport, ADBTransport, TcpProxy};
 use crate::{
     Result, RustADBError,
-    device::{
-        ADBTransportMessage, ADBTransportMessageHeader, MessageCommand, get_default_adb_key_path,
-    },
+    constants::BUFFER_SIZE,
+    device::{ADBTransportMessage, ADBTransportMessageHeader, get_default_adb_key_path},
 };
+use socket2::{Domain, Protocol, Socket, Type};
 use std::{
     fs::read_to_string,
     io::{Read, Write},
     net::{Shutdown, SocketAddr, TcpStream},
     ops::{Deref, DerefMut},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -40,6 +40,17 @@ impl CurrentConnection {
         }
     }
 
+    fn set_nonblocking(&self, nonblocking: bool) -> Result<()> {
+        Ok(self.tcp_stream().set_nonblocking(nonblocking)?)
+    }
+
+    fn tcp_stream(&self) -> &TcpStream {
+        match self {
+            CurrentConnection::Tcp(tcp_stream) => tcp_stream,
+            CurrentConnection::Tls(stream_owned) => &stream_owned.sock,
+        }
+    }
+
     fn set_write_timeout(&self, write_timeout: Duration) -> Result<()> {
         match self {
             CurrentConnection::Tcp(tcp_stream) => {
@@ -77,11 +88,25 @@ impl Write for CurrentConnection {
     }
 }
 
+/// Options used to establish connection of a [`TcpTransport`].
+#[derive(Clone, Debug, Default)]
+pub struct TcpConnectOptions {
+    /// Local address to bind to before connecting (e.g. one of a network interface reaching device), chosen by OS if `None`
+    pub bind_address: Option<SocketAddr>,
+    /// Maximum time to wait for connection to be established, OS default if `None`
+    pub connect_timeout: Option<Duration>,
+    /// Proxy to traverse to reach device, connecting directly if `None`
+    pub proxy: Option<TcpProxy>,
+}
+
 /// Transport running on USB
 #[derive(Clone, Debug)]
 pub struct TcpTransport {
     address: SocketAddr,
+    connect_options: TcpConnectOptions,
     current_connection: Option<Arc<Mutex<CurrentConnection>>>,
+    /// Data received while polling that does not form a complete message yet
+    read_buffer: Arc<Mutex<Vec<u8>>>,
     private_key_path: PathBuf,
 }
 
@@ -91,6 +116,29 @@ fn certificate_from_pk(key_pair: &KeyPair) -> Result<Vec<CertificateDer<'static>
     Ok(vec![certificate.der().to_owned()])
 }
 
+/// TLS configuration authenticating with a certificate generated from private key at `private_key_path`.
+///
+/// Device certificates are self-signed, hence not verified.
+pub(crate) fn tls_client_config(private_key_path: &Path) -> Result<ClientConfig> {
+    // TODO: Check if we cannot be more precise
+
+    let pk_content = read_to_string(private_key_path)?;
+
+    let key_pair = KeyPair::from_pkcs8_pem_and_sign_algo(&pk_content, &PKCS_RSA_SHA256)?;
+
+    let certificate = certificate_from_pk(&key_pair)?;
+    let private_key = PrivatePkcs8KeyDer::from_pem_file(private_key_path)?;
+
+    let mut client_config = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(NoCertificateVerification {}))
+        .with_client_auth_cert(certificate, private_key.into())?;
+
+    client_config.key_log = Arc::new(KeyLogFile::new());
+
+    Ok(client_config)
+}
+
 impl TcpTransport {
     /// Instantiate a new [`TcpTransport`]
     pub fn new(address: SocketAddr) -> Result<Self> {
@@ -104,11 +152,59 @@ impl TcpTransport {
     ) -> Result<Self> {
         Ok(Self {
             address,
+            connect_options: TcpConnectOptions::default(),
             current_connection: None,
+            read_buffer: Arc::new(Mutex::new(Vec::new())),
             private_key_path,
         })
     }
 
+    /// Set options used by following connections
+    pub fn set_connect_options(&mut self, connect_options: TcpConnectOptions) {
+        self.connect_options = connect_options;
+    }
+
+    fn connect_stream(&self) -> Result<TcpStream> {
+        let TcpConnectOptions {
+            bind_address,
+            connect_timeout,
+            proxy,
+        } = &self.connect_options;
+
+        let Some(proxy) = proxy else {
+            return Self::connect_to(self.address, *bind_address, *connect_timeout);
+        };
+        let stream = Self::connect_to(proxy.address(), *bind_address, *connect_timeout)?;
+        proxy.open_tunnel(stream, self.address, *connect_timeout)
+    }
+
+    fn connect_to(
+        address: SocketAddr,
+        bind_address: Option<SocketAddr>,
+        connect_timeout: Option<Duration>,
+    ) -> Result<TcpStream> {
+        let Some(bind_address) = bind_address else {
+            return Ok(match connect_timeout {
+                Some(connect_timeout) => TcpStream::connect_timeout(&address, connect_timeout)?,
+                None => TcpStream::connect(address)?,
+            });
+        };
+
+        // Standard library cannot bind a socket before connecting it
+        let socket = Socket::new(
+            Domain::for_address(address),
+            Type::STREAM,
+            Some(Protocol::TCP),
+        )?;
+        socket.bind(&bind_address.into())?;
+        match connect_timeout {
+            Some(connect_timeout) => socket.connect_timeout(&address.into(), connect_timeout)?,
+            None => socket.connect(&address.into())?,
+        }
+
+        Ok(socket.into())
+    }
+
     fn get_current_connection(&mut self) -> Result<Arc<Mutex<CurrentConnection>>> {
         self.current_connection
             .as_ref()
@@ -119,73 +215,70 @@ impl TcpTransport {
             .cloned()
     }
 
-    pub(crate) fn upgrade_connection(&mut self) -> Result<()> {
-        let current_connection = match self.current_connection.clone() {
-            Some(current_connection) => current_connection,
-            None => {
-                return Err(RustADBError::UpgradeError(
-                    "cannot upgrade a non-existing connection...".into(),
-                ));
-            }
-        };
-
-        {
-            let mut current_conn_locked = current_connection.lock()?;
-            match current_conn_locked.deref() {
-                CurrentConnection::Tcp(tcp_stream) => {
-                    // TODO: Check if we cannot be more precise
-
-                    let pk_content = read_to_string(&self.private_key_path)?;
-
-                    let key_pair =
-                        KeyPair::from_pkcs8_pem_and_sign_algo(&pk_content, &PKCS_RSA_SHA256)?;
-
-                    let certificate = certificate_from_pk(&key_pair)?;
-                    let private_key = PrivatePkcs8KeyDer::from_pem_file(&self.private_key_path)?;
+    /// Return the raw file descriptor of the underlying socket, to register it in an external reactor.
+    ///
+    /// Once it becomes readable, [`ADBMessageTransport::try_read_message`] should be called until it returns `None`,
+    /// as a single read may carry several messages.
+    #[cfg(unix)]
+    pub fn raw_fd(&mut self) -> Result<std::os::fd::RawFd> {
+        use std::os::fd::AsRawFd;
 
-                    let mut client_config = ClientConfig::builder()
-                        .dangerous()
-                        .with_custom_certificate_verifier(Arc::new(NoCertificateVerification {}))
-                        .with_client_auth_cert(certificate, private_key.into())?;
+        let raw_connection_lock = self.get_current_connection()?;
+        let raw_connection = raw_connection_lock.lock()?;
+        Ok(raw_connection.tcp_stream().as_raw_fd())
+    }
 
-                    client_config.key_log = Arc::new(KeyLogFile::new());
+    /// Return the raw socket handle of the underlying connection, to register it in an external reactor.
+    ///
+    /// Once it becomes readable, [`ADBMessageTransport::try_read_message`] should be called until it returns `None`,
+    /// as a single read may carry several messages.
+    #[cfg(windows)]
+    pub fn raw_socket(&mut self) -> Result<std::os::windows::io::RawSocket> {
+        use std::os::windows::io::AsRawSocket;
 
-                    let rc_config = Arc::new(client_config);
-                    let server_name = self.address.ip().into();
-                    let conn = ClientConnection::new(rc_config, server_name)?;
-                    let owned = tcp_stream.try_clone()?;
-                    let client = StreamOwned::new(conn, owned);
+        let raw_connection_lock = self.get_current_connection()?;
+        let raw_connection = raw_connection_lock.lock()?;
+        Ok(raw_connection.tcp_stream().as_raw_socket())
+    }
 
-                    // Update current connection state to now use TLS protocol
-                    *current_conn_locked = CurrentConnection::Tls(Box::new(client));
-                }
-                CurrentConnection::Tls(_) => {
-                    return Err(RustADBError::UpgradeError(
-                        "cannot upgrade a TLS connection...".into(),
-                    ));
-                }
+    /// Read all data currently available on `connection` into `read_buffer`, without blocking.
+    ///
+    /// Return `false` if connection has been closed by remote.
+    fn fill_read_buffer(
+        connection: &mut CurrentConnection,
+        read_buffer: &mut Vec<u8>,
+    ) -> std::io::Result<bool> {
+        let mut chunk = [0; BUFFER_SIZE];
+        loop {
+            match connection.read(&mut chunk) {
+                Ok(0) => return Ok(false),
+                Ok(size) => read_buffer.extend_from_slice(&chunk[..size]),
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => return Ok(true),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
             }
         }
+    }
 
-        let message = self.read_message()?;
-        match message.header().command() {
-            MessageCommand::Cnxn => {
-                let device_infos = String::from_utf8(message.into_payload())?;
-                log::debug!("received device info: {device_infos}");
-                Ok(())
-            }
-            c => Err(RustADBError::ADBRequestFailed(format!(
-                "Wrong command received {}",
-                c
-            ))),
-        }
+    /// Fill `buf` with previously polled data first, then with data read from `connection`.
+    fn read_exact_buffered(
+        connection: &mut CurrentConnection,
+        read_buffer: &mut Vec<u8>,
+        buf: &mut [u8],
+    ) -> std::io::Result<()> {
+        let buffered = read_buffer.len().min(buf.len());
+        buf[..buffered].copy_from_slice(&read_buffer[..buffered]);
+        read_buffer.drain(..buffered);
+
+        connection.read_exact(&mut buf[buffered..])
     }
 }
 
 impl ADBTransport for TcpTransport {
     fn connect(&mut self) -> Result<()> {
-        let stream = TcpStream::connect(self.address)?;
+        let stream = self.connect_stream()?;
         self.current_connection = Some(Arc::new(Mutex::new(CurrentConnection::Tcp(stream))));
+        self.read_buffer.lock()?.clear();
         Ok(())
     }
 
@@ -215,29 +308,18 @@ impl ADBMessageTransport for TcpTransport {
     ) -> Result<crate::device::ADBTransportMessage> {
         let raw_connection_lock = self.get_current_connection()?;
         let mut raw_connection = raw_connection_lock.lock()?;
+        let mut read_buffer = self.read_buffer.lock()?;
 
         raw_connection.set_read_timeout(read_timeout)?;
 
         let mut data = [0; 24];
-        let mut total_read = 0;
-        loop {
-            total_read += raw_connection.read(&mut data[total_read..])?;
-            if total_read == data.len() {
-                break;
-            }
-        }
+        Self::read_exact_buffered(&mut raw_connection, &mut read_buffer, &mut data)?;
 
         let header = ADBTransportMessageHeader::try_from(data)?;
 
         if header.data_length() != 0 {
             let mut msg_data = vec![0_u8; header.data_length() as usize];
-            let mut total_read = 0;
-            loop {
-                total_read += raw_connection.read(&mut msg_data[total_read..])?;
-                if total_read == msg_data.capacity() {
-                    break;
-                }
-            }
+            Self::read_exact_buffered(&mut raw_connection, &mut read_buffer, &mut msg_data)?;
 
             let message = ADBTransportMessage::from_header_and_payload(header, msg_data);
 
@@ -255,6 +337,46 @@ impl ADBMessageTransport for TcpTransport {
         Ok(ADBTransportMessage::from_header_and_payload(header, vec![]))
     }
 
+    fn try_read_message(&mut self) -> Result<Option<ADBTransportMessage>> {
+        let raw_connection_lock = self.get_current_connection()?;
+        let mut raw_connection = raw_connection_lock.lock()?;
+        let mut read_buffer = self.read_buffer.lock()?;
+
+        raw_connection.set_nonblocking(true)?;
+        let fill_result = Self::fill_read_buffer(&mut raw_connection, &mut read_buffer);
+        raw_connection.set_nonblocking(false)?;
+        let connection_open = fill_result?;
+
+        let header = match read_buffer.get(..24) {
+            Some(data) => ADBTransportMessageHeader::try_from(<[u8; 24]>::try_from(data)?)?,
+            None if connection_open => return Ok(None),
+            None => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+        };
+
+        let message_length = 24 + header.data_length() as usize;
+        if read_buffer.len() < message_length {
+            if connection_open {
+                return Ok(None);
+            }
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let payload = read_buffer[24..message_length].to_vec();
+        read_buffer.drain(..message_length);
+
+        let message = ADBTransportMessage::from_header_and_payload(header, payload);
+
+        // Check message integrity
+        if !message.check_message_integrity() {
+            return Err(RustADBError::InvalidIntegrity(
+                ADBTransportMessageHeader::compute_crc32(message.payload()),
+                message.header().data_crc32(),
+            ));
+        }
+
+        Ok(Some(message))
+    }
+
     fn write_message_with_timeout(
         &mut self,
         message: ADBTransportMessage,
@@ -289,6 +411,49 @@ impl ADBMessageTransport for TcpTransport {
 
         Ok(())
     }
+
+    fn upgrade_tls(&mut self) -> Result<()> {
+        let current_connection = match self.current_connection.clone() {
+            Some(current_connection) => current_connection,
+            None => {
+                return Err(RustADBError::UpgradeError(
+                    "cannot upgrade a non-existing connection...".into(),
+                ));
+            }
+        };
+
+        {
+            let mut current_conn_locked = current_connection.lock()?;
+            match current_conn_locked.deref() {
+                CurrentConnection::Tcp(tcp_stream) => {
+                    let rc_config = Arc::new(tls_client_config(&self.private_key_path)?);
+                    let server_name = self.address.ip().into();
+                    let conn = ClientConnection::new(rc_config, server_name)?;
+                    let owned = tcp_stream.try_clone()?;
+                    let mut client = StreamOwned::new(conn, owned);
+
+                    // Complete handshake now, as adbd rejects clients whose key has not been paired
+                    while client.conn.is_handshaking() {
+                        client.conn.complete_io(&mut client.sock).map_err(|e| {
+                            RustADBError::UpgradeError(format!(
+                                "TLS handshake failed, is this host paired with device? ({e})"
+                            ))
+                        })?;
+                    }
+
+                    // Update current connection state to now use TLS protocol
+                    *current_conn_locked = CurrentConnection::Tls(Box::new(client));
+                }
+                CurrentConnection::Tls(_) => {
+                    return Err(RustADBError::UpgradeError(
+                        "cannot upgrade a TLS connection...".into(),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
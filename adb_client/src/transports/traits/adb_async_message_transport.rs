@@ -0,0 +1,45 @@
+use std::future::Future;
+use std::time::Duration;
+
+use async_io::Timer;
+use futures_lite::FutureExt;
+
+use super::ADBAsyncTransport;
+use crate::{Result, device::ADBTransportMessage};
+
+/// Asynchronous counterpart of [`crate::ADBMessageTransport`].
+///
+/// Implementors only have to provide untimed reads and writes, timeouts are handled by this trait.
+pub trait ADBAsyncMessageTransport: ADBAsyncTransport + Clone + Send + Sync + 'static {
+    /// Read a message from the underlying connection
+    fn read_message(&mut self) -> impl Future<Output = Result<ADBTransportMessage>> + Send;
+
+    /// Write a message to the underlying connection
+    fn write_message(
+        &mut self,
+        message: ADBTransportMessage,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Read a message, failing with [`std::io::ErrorKind::TimedOut`] if none is received within `read_timeout`
+    fn read_message_with_timeout(
+        &mut self,
+        read_timeout: Duration,
+    ) -> impl Future<Output = Result<ADBTransportMessage>> + Send {
+        self.read_message().or(async move {
+            Timer::after(read_timeout).await;
+            Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into())
+        })
+    }
+
+    /// Write a message, failing with [`std::io::ErrorKind::TimedOut`] if it cannot be sent within `write_timeout`
+    fn write_message_with_timeout(
+        &mut self,
+        message: ADBTransportMessage,
+        write_timeout: Duration,
+    ) -> impl Future<Output = Result<()>> + Send {
+        self.write_message(message).or(async move {
+            Timer::after(write_timeout).await;
+            Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into())
+        })
+    }
+}
@@ -0,0 +1,12 @@
+use std::future::Future;
+
+use crate::Result;
+
+/// Asynchronous counterpart of [`crate::ADBTransport`].
+pub trait ADBAsyncTransport {
+    /// Initializes the connection to this transport.
+    fn connect(&mut self) -> impl Future<Output = Result<()>> + Send;
+
+    /// Shuts down the connection to this transport.
+    fn disconnect(&mut self) -> impl Future<Output = Result<()>> + Send;
+}
@@ -1,7 +1,8 @@
+use std::io::ErrorKind;
 use std::time::Duration;
 
 use super::ADBTransport;
-use crate::{Result, device::ADBTransportMessage};
+use crate::{Result, RustADBError, constants::POLL_READ_TIMEOUT, device::ADBTransportMessage};
 
 const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(u64::MAX);
 const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(2);
@@ -16,6 +17,21 @@ pub trait ADBMessageTransport: ADBTransport + Clone + Send + 'static {
         self.read_message_with_timeout(DEFAULT_READ_TIMEOUT)
     }
 
+    /// Try to read a message without waiting for one to be received.
+    ///
+    /// Return `Ok(None)` if no message is currently available. This allows event-loop based consumers to poll the transport instead of dedicating a thread to it.
+    fn try_read_message(&mut self) -> Result<Option<ADBTransportMessage>> {
+        match self.read_message_with_timeout(POLL_READ_TIMEOUT) {
+            Ok(message) => Ok(Some(message)),
+            Err(RustADBError::IOError(e))
+                if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     /// Write a message using given timeout on the underlying transport
     fn write_message_with_timeout(
         &mut self,
@@ -27,4 +43,13 @@ pub trait ADBMessageTransport: ADBTransport + Clone + Send + 'static {
     fn write_message(&mut self, message: ADBTransportMessage) -> Result<()> {
         self.write_message_with_timeout(message, DEFAULT_WRITE_TIMEOUT)
     }
+
+    /// Upgrade underlying connection to TLS, as requested by devices sending a `STLS` message when connecting.
+    ///
+    /// Transports which cannot be encrypted fail with [`RustADBError::UpgradeError`].
+    fn upgrade_tls(&mut self) -> Result<()> {
+        Err(RustADBError::UpgradeError(
+            "transport does not support TLS".into(),
+        ))
+    }
 }
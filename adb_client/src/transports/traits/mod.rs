@@ -1,5 +1,13 @@
+#[cfg(feature = "async")]
+mod adb_async_message_transport;
+#[cfg(feature = "async")]
+mod adb_async_transport;
 mod adb_message_transport;
 mod adb_transport;
 
+#[cfg(feature = "async")]
+pub use adb_async_message_transport::ADBAsyncMessageTransport;
+#[cfg(feature = "async")]
+pub use adb_async_transport::ADBAsyncTransport;
 pub use adb_message_transport::ADBMessageTransport;
 pub use adb_transport::ADBTransport;
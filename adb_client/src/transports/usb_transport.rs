@@ -1,3 +1,8 @@
+mod usb_device_strings;
+mod usb_link_info;
+pub use usb_device_strings::USBDeviceStrings;
+pub use usb_link_info::{USBLinkInfo, USBSpeed};
+
 #[cfg(all(feature = "trans-nusb"))]
 mod usb_transport_nusb;
 #[cfg(all(feature = "trans-nusb"))]
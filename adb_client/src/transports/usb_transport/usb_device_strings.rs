@@ -0,0 +1,12 @@
+/// String descriptors of a USB device, as returned by [`crate::USBTransport::device_strings`].
+///
+/// Each string is `None` when the device does not provide it or cannot be read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct USBDeviceStrings {
+    /// Manufacturer name (e.g. `Google`)
+    pub manufacturer: Option<String>,
+    /// Product name (e.g. `Pixel 7`)
+    pub product: Option<String>,
+    /// Serial number, as reported by `adb devices`
+    pub serial: Option<String>,
+}
@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+/// Speed negotiated between host and a USB device, ordered from slowest to fastest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum USBSpeed {
+    /// Low speed (1.5 Mbps)
+    Low,
+    /// Full speed (12 Mbps)
+    Full,
+    /// High speed, USB 2.0 (480 Mbps)
+    High,
+    /// SuperSpeed, USB 3.x Gen 1 (5 Gbps)
+    Super,
+    /// SuperSpeed+, USB 3.x Gen 2 (10 Gbps)
+    SuperPlus,
+}
+
+impl Display for USBSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            USBSpeed::Low => write!(f, "low speed (1.5 Mbps)"),
+            USBSpeed::Full => write!(f, "full speed (12 Mbps)"),
+            USBSpeed::High => write!(f, "high speed (480 Mbps)"),
+            USBSpeed::Super => write!(f, "super speed (5 Gbps)"),
+            USBSpeed::SuperPlus => write!(f, "super speed+ (10 Gbps)"),
+        }
+    }
+}
+
+/// Link to a connected USB device, as returned by [`crate::USBTransport::link_info`].
+///
+/// A device plugged into a USB 2.0 port is limited to [`USBSpeed::High`], e.g. making large pulls slow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct USBLinkInfo {
+    /// Negotiated speed, `None` when not reported by operating system
+    pub speed: Option<USBSpeed>,
+    /// Maximum packet size of bulk IN endpoint, in bytes
+    pub read_max_packet_size: usize,
+    /// Maximum packet size of bulk OUT endpoint, in bytes
+    pub write_max_packet_size: usize,
+}
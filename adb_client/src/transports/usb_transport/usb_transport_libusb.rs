@@ -1,13 +1,18 @@
-use std::{sync::Arc, time::Duration};
+use std::{io::ErrorKind, sync::Arc, time::Duration};
 
 use rusb::{
-    Device, DeviceDescriptor, DeviceHandle, Direction, GlobalContext, TransferType, UsbContext,
-    constants::LIBUSB_CLASS_VENDOR_SPEC,
+    Device, DeviceDescriptor, DeviceHandle, Direction, GlobalContext, Speed, TransferType,
+    UsbContext, constants::LIBUSB_CLASS_VENDOR_SPEC,
 };
 
 use super::super::{ADBMessageTransport, ADBTransport};
+use super::{USBDeviceStrings, USBLinkInfo, USBSpeed};
 use crate::{
     Result, RustADBError,
+    constants::{
+        ADB_INTERFACE_PROTOCOL, FASTBOOT_INTERFACE_PROTOCOL, PAYLOAD_READ_TIMEOUT,
+        POLL_READ_TIMEOUT, USB_STRING_DESCRIPTOR_TIMEOUT,
+    },
     device::{ADBTransportMessage, ADBTransportMessageHeader, MessageCommand},
 };
 
@@ -15,6 +20,7 @@ use crate::{
 struct Endpoint {
     iface: u8,
     address: u8,
+    max_packet_size: usize,
 }
 
 /// Transport running on USB
@@ -24,6 +30,7 @@ pub struct USBTransport {
     handle: Option<Arc<DeviceHandle<GlobalContext>>>,
     read_endpoint: Option<Endpoint>,
     write_endpoint: Option<Endpoint>,
+    interface_protocol: u8,
 }
 
 impl USBTransport {
@@ -44,6 +51,33 @@ impl USBTransport {
         )))
     }
 
+    /// Instantiate a new [`USBTransport`] for device with given USB serial number.
+    ///
+    /// Unlike vendor and product ids, serial numbers tell apart several identical devices.
+    pub fn new_from_serial(serial: &str) -> Result<Self> {
+        for device in rusb::devices()?.iter() {
+            let Ok(descriptor) = device.device_descriptor() else {
+                continue;
+            };
+            if descriptor.serial_number_string_index().is_none() {
+                continue;
+            }
+
+            // Devices which cannot be opened do not expose their serial number
+            let transport = Self::new_from_device(device);
+            if transport
+                .device_strings()
+                .is_ok_and(|strings| strings.serial.as_deref() == Some(serial))
+            {
+                return Ok(transport);
+            }
+        }
+
+        Err(RustADBError::DeviceNotFound(format!(
+            "cannot find USB device with serial={serial}"
+        )))
+    }
+
     /// Instantiate a new [`USBTransport`] from a [`rusb::Device`].
     ///
     /// Devices can be enumerated using [`rusb::devices()`] and then filtered out to get desired device.
@@ -53,9 +87,30 @@ impl USBTransport {
             handle: None,
             read_endpoint: None,
             write_endpoint: None,
+            interface_protocol: ADB_INTERFACE_PROTOCOL,
         }
     }
 
+    /// Use interface with `protocol` instead of ADB one when connecting (e.g. fastboot interface)
+    pub(crate) fn with_interface_protocol(mut self, protocol: u8) -> Self {
+        self.interface_protocol = protocol;
+        self
+    }
+
+    /// Write `buf` to bulk endpoint of connected interface, returning number of bytes written
+    pub(crate) fn bulk_write(&self, buf: &[u8], timeout: Duration) -> Result<usize> {
+        let endpoint = self.get_write_endpoint()?;
+        let handle = self.get_raw_connection()?;
+        Ok(handle.write_bulk(endpoint.address, buf, timeout)?)
+    }
+
+    /// Read a single transfer from bulk endpoint of connected interface into `buf`
+    pub(crate) fn bulk_read(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        let endpoint = self.get_read_endpoint()?;
+        let handle = self.get_raw_connection()?;
+        Ok(handle.read_bulk(endpoint.address, buf, timeout)?)
+    }
+
     /// Claim a USB interface if it is not already claimed by the read or write endpoint.
     pub fn claim_interface(&mut self, iface: u8) -> Result<()> {
         if self
@@ -114,6 +169,42 @@ impl USBTransport {
         Ok(handle.write_bulk(endpoint, buf, timeout)?)
     }
 
+    /// Read manufacturer, product and serial number string descriptors of device.
+    ///
+    /// Strings are read in US English when device supports it, in its first supported language otherwise.
+    /// Device is opened for the time of the request if not connected yet.
+    pub fn device_strings(&self) -> Result<USBDeviceStrings> {
+        let descriptor = self.device.device_descriptor()?;
+        match &self.handle {
+            Some(handle) => read_device_strings(handle, &descriptor),
+            None => read_device_strings(&self.device.open()?, &descriptor),
+        }
+    }
+
+    /// Negotiated speed and bulk endpoints packet sizes of connected device
+    pub fn link_info(&self) -> Result<USBLinkInfo> {
+        let speed = match self.device.speed() {
+            Speed::Low => Some(USBSpeed::Low),
+            Speed::Full => Some(USBSpeed::Full),
+            Speed::High => Some(USBSpeed::High),
+            Speed::Super => Some(USBSpeed::Super),
+            Speed::SuperPlus => Some(USBSpeed::SuperPlus),
+            _ => None,
+        };
+
+        Ok(USBLinkInfo {
+            speed,
+            read_max_packet_size: self.get_read_endpoint()?.max_packet_size,
+            write_max_packet_size: self.get_write_endpoint()?.max_packet_size,
+        })
+    }
+
+    /// Vendor and product identifiers of device
+    pub(crate) fn vendor_product_ids(&self) -> Result<(u16, u16)> {
+        let descriptor = self.device.device_descriptor()?;
+        Ok((descriptor.vendor_id(), descriptor.product_id()))
+    }
+
     pub(crate) fn get_raw_connection(&self) -> Result<Arc<DeviceHandle<GlobalContext>>> {
         self.handle
             .as_ref()
@@ -124,6 +215,61 @@ impl USBTransport {
             .cloned()
     }
 
+    fn read_header(&self, timeout: Duration) -> Result<ADBTransportMessageHeader> {
+        let endpoint = self.get_read_endpoint()?;
+        let handle = self.get_raw_connection()?;
+
+        let mut data = [0; 24];
+        let mut total_read = 0;
+        loop {
+            total_read += handle.read_bulk(endpoint.address, &mut data[total_read..], timeout)?;
+            if total_read == data.len() {
+                break;
+            }
+        }
+
+        let header = ADBTransportMessageHeader::try_from(data)?;
+
+        log::trace!("received header {header:?}");
+
+        Ok(header)
+    }
+
+    fn read_payload(
+        &self,
+        header: ADBTransportMessageHeader,
+        timeout: Duration,
+    ) -> Result<ADBTransportMessage> {
+        if header.data_length() != 0 {
+            let endpoint = self.get_read_endpoint()?;
+            let handle = self.get_raw_connection()?;
+
+            let mut msg_data = vec![0_u8; header.data_length() as usize];
+            let mut total_read = 0;
+            loop {
+                total_read +=
+                    handle.read_bulk(endpoint.address, &mut msg_data[total_read..], timeout)?;
+                if total_read == msg_data.capacity() {
+                    break;
+                }
+            }
+
+            let message = ADBTransportMessage::from_header_and_payload(header, msg_data);
+
+            // Check message integrity
+            if !message.check_message_integrity() {
+                return Err(RustADBError::InvalidIntegrity(
+                    ADBTransportMessageHeader::compute_crc32(message.payload()),
+                    message.header().data_crc32(),
+                ));
+            }
+
+            return Ok(message);
+        }
+
+        Ok(ADBTransportMessage::from_header_and_payload(header, vec![]))
+    }
+
     fn get_read_endpoint(&self) -> Result<Endpoint> {
         self.read_endpoint
             .as_ref()
@@ -164,11 +310,15 @@ impl USBTransport {
                         if endpoint_desc.transfer_type() == TransferType::Bulk
                             && interface_desc.class_code() == LIBUSB_CLASS_VENDOR_SPEC
                             && interface_desc.sub_class_code() == 0x42
-                            && interface_desc.protocol_code() == 0x01
+                            && interface_desc.protocol_code() == self.interface_protocol
                         {
                             let endpoint = Endpoint {
                                 iface: interface_desc.interface_number(),
                                 address: endpoint_desc.address(),
+                                // Bits 11-12 only count additional transactions of isochronous endpoints
+                                max_packet_size: usize::from(
+                                    endpoint_desc.max_packet_size() & 0x7ff,
+                                ),
                             };
                             match endpoint_desc.direction() {
                                 Direction::In => {
@@ -254,47 +404,19 @@ impl ADBMessageTransport for USBTransport {
     }
 
     fn read_message_with_timeout(&mut self, timeout: Duration) -> Result<ADBTransportMessage> {
-        let endpoint = self.get_read_endpoint()?;
-        let handle = self.get_raw_connection()?;
-
-        let mut data = [0; 24];
-        let mut total_read = 0;
-        loop {
-            total_read += handle.read_bulk(endpoint.address, &mut data[total_read..], timeout)?;
-            if total_read == data.len() {
-                break;
-            }
-        }
-
-        let header = ADBTransportMessageHeader::try_from(data)?;
-
-        log::trace!("received header {header:?}");
-
-        if header.data_length() != 0 {
-            let mut msg_data = vec![0_u8; header.data_length() as usize];
-            let mut total_read = 0;
-            loop {
-                total_read +=
-                    handle.read_bulk(endpoint.address, &mut msg_data[total_read..], timeout)?;
-                if total_read == msg_data.capacity() {
-                    break;
-                }
-            }
-
-            let message = ADBTransportMessage::from_header_and_payload(header, msg_data);
-
-            // Check message integrity
-            if !message.check_message_integrity() {
-                return Err(RustADBError::InvalidIntegrity(
-                    ADBTransportMessageHeader::compute_crc32(message.payload()),
-                    message.header().data_crc32(),
-                ));
-            }
+        let header = self.read_header(timeout)?;
+        self.read_payload(header, timeout)
+    }
 
-            return Ok(message);
-        }
+    fn try_read_message(&mut self) -> Result<Option<ADBTransportMessage>> {
+        let header = match self.read_header(POLL_READ_TIMEOUT) {
+            Ok(header) => header,
+            Err(RustADBError::IOError(e)) if e.kind() == ErrorKind::TimedOut => return Ok(None),
+            Err(e) => return Err(e),
+        };
 
-        Ok(ADBTransportMessage::from_header_and_payload(header, vec![]))
+        // Payload is sent right after its header, no need to poll for it
+        self.read_payload(header, PAYLOAD_READ_TIMEOUT).map(Some)
     }
 }
 
@@ -325,9 +447,120 @@ pub fn search_adb_devices() -> Result<Option<(u16, u16)>> {
     }
 }
 
+/// Search for devices in fastboot mode, e.g. after rebooting an adb device using [`crate::RebootType::Bootloader`]
+pub fn search_fastboot_devices() -> Result<Option<(u16, u16)>> {
+    let mut found_devices = vec![];
+    for device in rusb::devices()?.iter() {
+        let Ok(des) = device.device_descriptor() else {
+            continue;
+        };
+        if is_fastboot_device(&device, &des) {
+            log::debug!(
+                "Autodetect fastboot device {:04x}:{:04x}",
+                des.vendor_id(),
+                des.product_id()
+            );
+            found_devices.push((des.vendor_id(), des.product_id()));
+        }
+    }
+
+    match (found_devices.first(), found_devices.get(1)) {
+        (None, _) => Ok(None),
+        (Some(identifiers), None) => Ok(Some(*identifiers)),
+        (Some((vid1, pid1)), Some((vid2, pid2))) => Err(RustADBError::DeviceNotFound(format!(
+            "Found two fastboot devices {:04x}:{:04x} and {:04x}:{:04x}",
+            vid1, pid1, vid2, pid2
+        ))),
+    }
+}
+
+/// List serial numbers of all connected adb devices.
+///
+/// Devices not exposing a readable serial number are identified by their vendor and product ids.
+#[cfg(feature = "tcp")]
+pub(crate) fn list_adb_device_serials() -> Result<Vec<String>> {
+    let mut serials = vec![];
+    for device in rusb::devices()?.iter() {
+        let Ok(des) = device.device_descriptor() else {
+            continue;
+        };
+        if !is_adb_device(&device, &des) {
+            continue;
+        }
+
+        let serial = USBTransport::new_from_device(device)
+            .device_strings()
+            .ok()
+            .and_then(|strings| strings.serial)
+            .unwrap_or_else(|| format!("{:04x}:{:04x}", des.vendor_id(), des.product_id()));
+        serials.push(serial);
+    }
+
+    Ok(serials)
+}
+
+fn read_device_strings<T: UsbContext>(
+    handle: &DeviceHandle<T>,
+    descriptor: &DeviceDescriptor,
+) -> Result<USBDeviceStrings> {
+    // US English
+    const PREFERRED_LANGUAGE_ID: u16 = 0x0409;
+
+    // Devices without any string descriptor may not answer languages request
+    if descriptor.manufacturer_string_index().is_none()
+        && descriptor.product_string_index().is_none()
+        && descriptor.serial_number_string_index().is_none()
+    {
+        return Ok(USBDeviceStrings::default());
+    }
+
+    let languages = handle.read_languages(USB_STRING_DESCRIPTOR_TIMEOUT)?;
+    let Some(language) = languages
+        .iter()
+        .find(|language| language.lang_id() == PREFERRED_LANGUAGE_ID)
+        .or_else(|| languages.first())
+        .copied()
+    else {
+        return Ok(USBDeviceStrings::default());
+    };
+
+    Ok(USBDeviceStrings {
+        manufacturer: handle
+            .read_manufacturer_string(language, descriptor, USB_STRING_DESCRIPTOR_TIMEOUT)
+            .ok(),
+        product: handle
+            .read_product_string(language, descriptor, USB_STRING_DESCRIPTOR_TIMEOUT)
+            .ok(),
+        serial: handle
+            .read_serial_number_string(language, descriptor, USB_STRING_DESCRIPTOR_TIMEOUT)
+            .ok(),
+    })
+}
+
+fn is_fastboot_device<T: UsbContext>(device: &Device<T>, des: &DeviceDescriptor) -> bool {
+    const FASTBOOT_SUBCLASS: u8 = 0x42;
+
+    for n in 0..des.num_configurations() {
+        let Ok(config_des) = device.config_descriptor(n) else {
+            continue;
+        };
+        for interface in config_des.interfaces() {
+            for interface_des in interface.descriptors() {
+                if interface_des.class_code() == LIBUSB_CLASS_VENDOR_SPEC
+                    && interface_des.sub_class_code() == FASTBOOT_SUBCLASS
+                    && interface_des.protocol_code() == FASTBOOT_INTERFACE_PROTOCOL
+                {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
 fn is_adb_device<T: UsbContext>(device: &Device<T>, des: &DeviceDescriptor) -> bool {
     const ADB_SUBCLASS: u8 = 0x42;
-    const ADB_PROTOCOL: u8 = 0x1;
+    const ADB_PROTOCOL: u8 = ADB_INTERFACE_PROTOCOL;
 
     // Some devices require choosing the file transfer mode
     // for usb debugging to take effect.
@@ -1,14 +1,28 @@
-use std::{collections::HashMap, fmt::Debug, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    future::Future,
+    io::ErrorKind,
+    pin::pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker, ready},
+    thread::Thread,
+    time::{Duration, Instant},
+};
 
-use async_io::{Timer, block_on};
-use futures_lite::FutureExt;
 use nusb::{
-    transfer::{Control, ControlType, Direction, EndpointType, Recipient, RequestBuffer}, Device, DeviceInfo, Interface
+    Device, DeviceInfo, Interface,
+    transfer::{Control, ControlType, Direction, EndpointType, Queue, Recipient, RequestBuffer},
 };
 
 use super::super::{ADBMessageTransport, ADBTransport};
+use super::{USBDeviceStrings, USBLinkInfo, USBSpeed};
 use crate::{
     Result, RustADBError,
+    constants::{
+        ADB_INTERFACE_PROTOCOL, FASTBOOT_INTERFACE_PROTOCOL, POLL_READ_TIMEOUT, USB_QUEUE_DEPTH,
+        USB_TRANSFER_CHUNK_SIZE,
+    },
     device::{ADBTransportMessage, ADBTransportMessageHeader, MessageCommand},
 };
 
@@ -17,12 +31,172 @@ struct Endpoint {
     iface: Interface,
     iface_num: u8,
     address: u8,
+    max_packet_size: usize,
 }
 
 #[derive(Debug, Clone)]
 struct EndpointDesc {
     iface: u8,
     address: u8,
+    max_packet_size: usize,
+}
+
+/// Size of a message header
+const HEADER_SIZE: usize = 24;
+/// Time given to queued messages to be sent when disconnecting
+const CLOSE_WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Bulk IN endpoint of connected interface.
+///
+/// A transfer is never cancelled on timeout: it stays in flight, and its data is returned by following reads.
+///
+/// Endpoint is either read as messages or as raw packets, whichever is read first: transfers in flight and data
+/// received for one of them (e.g. next header requested when pipelining) would otherwise be consumed by the other.
+struct BulkReader {
+    queue: Queue<RequestBuffer>,
+    /// Maximum number of transfers in flight
+    depth: usize,
+    /// Sizes of transfers in flight, in submission order
+    requested: VecDeque<usize>,
+    /// Data received from device, not returned yet
+    buffer: Vec<u8>,
+    /// Set by first read, `true` if endpoint is read as messages
+    reads_messages: Option<bool>,
+}
+
+impl BulkReader {
+    fn new(endpoint: &Endpoint, depth: usize) -> Self {
+        Self {
+            queue: endpoint.iface.bulk_in_queue(endpoint.address),
+            depth,
+            requested: VecDeque::with_capacity(depth),
+            buffer: Vec::new(),
+            reads_messages: None,
+        }
+    }
+
+    /// Check that endpoint is read the same way as before, messages or raw packets not being mixed
+    fn check_read_mode(&mut self, reads_messages: bool) -> Result<()> {
+        if *self.reads_messages.get_or_insert(reads_messages) != reads_messages {
+            return Err(RustADBError::ADBRequestFailed(
+                "endpoint cannot be read both as messages and as raw packets".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn submit(&mut self, length: usize) {
+        self.queue.submit(RequestBuffer::new(length));
+        self.requested.push_back(length);
+    }
+
+    /// Submit transfers until `length` bytes are received or requested, or `depth` transfers are in flight.
+    ///
+    /// Transfers are sized to exactly match expected data, devices not always ending them with a zero-length packet.
+    fn request(&mut self, length: usize) {
+        let mut missing =
+            length.saturating_sub(self.buffer.len() + self.requested.iter().sum::<usize>());
+        while missing > 0 && self.requested.len() < self.depth {
+            let size = missing.min(USB_TRANSFER_CHUNK_SIZE);
+            self.submit(size);
+            missing -= size;
+        }
+    }
+
+    /// Poll next transfer completion, appending its data to received ones
+    fn poll_completion(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let completion = ready!(self.queue.poll_next(cx));
+        self.requested.pop_front();
+        self.buffer.extend_from_slice(&completion.data);
+        Poll::Ready(completion.status.map_err(RustADBError::from))
+    }
+
+    /// Poll until at least `length` bytes have been received, requesting missing ones from device
+    fn poll_fill(&mut self, cx: &mut Context<'_>, length: usize) -> Poll<Result<()>> {
+        while self.buffer.len() < length {
+            self.request(length);
+            ready!(self.poll_completion(cx))?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Poll for a whole message, only consuming received data once complete so that a pending read can be dropped
+    fn poll_read_message(&mut self, cx: &mut Context<'_>) -> Poll<Result<ADBTransportMessage>> {
+        self.check_read_mode(true)?;
+        ready!(self.poll_fill(cx, HEADER_SIZE))?;
+        let header = ADBTransportMessageHeader::try_from(<[u8; HEADER_SIZE]>::try_from(
+            &self.buffer[..HEADER_SIZE],
+        )?)?;
+
+        let length = HEADER_SIZE + header.data_length() as usize;
+        ready!(self.poll_fill(cx, length))?;
+        let payload = self.buffer[HEADER_SIZE..length].to_vec();
+        self.buffer.drain(..length);
+
+        // Next header is requested right away when pipelining, instead of once asked for
+        if self.depth > 1 {
+            self.request(HEADER_SIZE);
+        }
+
+        log::trace!("received header {header:?}");
+        let message = ADBTransportMessage::from_header_and_payload(header, payload);
+
+        // Check message integrity
+        if !message.check_message_integrity() {
+            return Poll::Ready(Err(RustADBError::InvalidIntegrity(
+                ADBTransportMessageHeader::compute_crc32(message.payload()),
+                message.header().data_crc32(),
+            )));
+        }
+
+        Poll::Ready(Ok(message))
+    }
+
+    /// Poll for data of a single transfer, of at most `max_length` bytes
+    fn poll_read_packet(
+        &mut self,
+        cx: &mut Context<'_>,
+        max_length: usize,
+    ) -> Poll<Result<Vec<u8>>> {
+        self.check_read_mode(false)?;
+        if self.buffer.is_empty() {
+            if self.requested.is_empty() {
+                self.submit(max_length);
+            }
+            ready!(self.poll_completion(cx))?;
+        }
+
+        let length = max_length.min(self.buffer.len());
+        Poll::Ready(Ok(self.buffer.drain(..length).collect()))
+    }
+}
+
+/// Bulk OUT endpoint of connected interface.
+///
+/// Transfers are queued back to back instead of waiting for each other, and are still sent if a write times out.
+struct BulkWriter {
+    queue: Queue<Vec<u8>>,
+    /// Maximum number of transfers left in flight once a write returns
+    depth: usize,
+}
+
+impl BulkWriter {
+    fn new(endpoint: &Endpoint, depth: usize) -> Self {
+        Self {
+            queue: endpoint.iface.bulk_out_queue(endpoint.address),
+            depth,
+        }
+    }
+
+    /// Poll until at most `pending` submitted transfers are still in flight
+    fn poll_drain(&mut self, cx: &mut Context<'_>, pending: usize) -> Poll<Result<()>> {
+        while self.queue.pending() > pending {
+            ready!(self.queue.poll_next(cx)).status?;
+        }
+
+        Poll::Ready(Ok(()))
+    }
 }
 
 /// Transport running on USB
@@ -32,7 +206,11 @@ pub struct USBTransport {
     device: Option<Device>,
     read_endpoint: Option<Endpoint>,
     write_endpoint: Option<Endpoint>,
+    reader: Option<Arc<Mutex<BulkReader>>>,
+    writer: Option<Arc<Mutex<BulkWriter>>>,
     other_interfaces: HashMap<u8, Interface>,
+    interface_protocol: u8,
+    queue_depth: usize,
 }
 
 impl USBTransport {
@@ -51,7 +229,23 @@ impl USBTransport {
         )))
     }
 
-    /// Instantiate a new [`USBTransport`] from a [`rusb::Device`].
+    /// Instantiate a new [`USBTransport`] for device with given USB serial number.
+    ///
+    /// Unlike vendor and product ids, serial numbers tell apart several identical devices.
+    pub fn new_from_serial(serial: &str) -> Result<Self> {
+        for device_info in nusb::list_devices()? {
+            if device_info.serial_number() == Some(serial) {
+                return Ok(Self::new_from_device_info(device_info));
+            }
+        }
+
+        Err(RustADBError::DeviceNotFound(format!(
+            "cannot find USB device with serial={serial}"
+        )))
+    }
+
+    /// Instantiate a new [`USBTransport`] from a [`nusb::DeviceInfo`], describing a device found while enumerating
+    /// without opening it.
     ///
     /// Devices can be enumerated using [`nusb::list_devices()`] and then filtered out to get desired device.
     pub fn new_from_device_info(nusb_device_info: DeviceInfo) -> Self {
@@ -60,10 +254,88 @@ impl USBTransport {
             device: None,
             read_endpoint: None,
             write_endpoint: None,
+            reader: None,
+            writer: None,
             other_interfaces: HashMap::new(),
+            interface_protocol: ADB_INTERFACE_PROTOCOL,
+            queue_depth: USB_QUEUE_DEPTH,
         }
     }
 
+    /// Vendor and product identifiers of device
+    pub(crate) fn vendor_product_ids(&self) -> Result<(u16, u16)> {
+        Ok((self.device_info.vendor_id(), self.device_info.product_id()))
+    }
+
+    /// Use interface with `protocol` instead of ADB one when connecting (e.g. fastboot interface)
+    pub(crate) fn with_interface_protocol(mut self, protocol: u8) -> Self {
+        self.interface_protocol = protocol;
+        self
+    }
+
+    /// Keep up to `depth` transfers in flight on each bulk endpoint, taking effect on next connection.
+    ///
+    /// Reading large payloads and writing consecutive messages are pipelined when greater than 1, to make use of
+    /// fast links. A depth of 1 waits for each transfer to complete before submitting the next one.
+    pub fn with_queue_depth(mut self, depth: usize) -> Self {
+        self.queue_depth = depth.max(1);
+        self
+    }
+
+    /// Write `buf` to bulk endpoint of connected interface, returning number of bytes written
+    pub(crate) fn bulk_write(&self, buf: &[u8], timeout: Duration) -> Result<usize> {
+        self.get_writer()?.lock()?.queue.submit(buf.to_vec());
+        wait_with_timeout(timeout, |cx| self.poll_flush(cx))?;
+        Ok(buf.len())
+    }
+
+    /// Read a single transfer from bulk endpoint of connected interface into `buf`.
+    ///
+    /// Transport must not be read as messages, e.g. for fastboot which exchanges raw packets.
+    pub(crate) fn bulk_read(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
+        let reader = self.get_reader()?;
+        let data = wait_with_timeout(timeout, |cx| reader.lock()?.poll_read_packet(cx, buf.len()))?;
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    /// Poll for next message received from device, shared by blocking and async frontends
+    pub(crate) fn poll_read_message(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<ADBTransportMessage>> {
+        self.get_reader()?.lock()?.poll_read_message(cx)
+    }
+
+    /// Queue `message` to be sent to device, to be followed by [`USBTransport::poll_write_pipeline`]
+    pub(crate) fn submit_message(&self, message: ADBTransportMessage) -> Result<()> {
+        let header = message.header().as_bytes()?;
+        let payload = message.into_payload();
+
+        // Both transfers are queued at once, so that header and payload stay contiguous
+        // even if a clone of this transport writes concurrently
+        let writer = self.get_writer()?;
+        let mut writer = writer.lock()?;
+        writer.queue.submit(header);
+        if !payload.is_empty() {
+            writer.queue.submit(payload);
+        }
+        Ok(())
+    }
+
+    /// Poll until write pipeline has room for another message, errors of previous writes being returned here
+    pub(crate) fn poll_write_pipeline(&self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let writer = self.get_writer()?;
+        let mut writer = writer.lock()?;
+        let pending = writer.depth - 1;
+        writer.poll_drain(cx, pending)
+    }
+
+    /// Poll until all queued data has been sent to device
+    pub(crate) fn poll_flush(&self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_writer()?.lock()?.poll_drain(cx, 0)
+    }
+
     /// Claim a USB interface if it is not already claimed by the read or write endpoint.
     pub fn claim_interface(&mut self, iface: u8) -> Result<()> {
         if self
@@ -119,7 +391,14 @@ impl USBTransport {
         timeout: Duration,
     ) -> Result<usize> {
         let interface = self.get_interface(iface)?;
-        Ok(interface.read_bulk(endpoint, buf, timeout)?)
+        // Dropping transfer on timeout cancels it
+        let mut transfer = pin!(interface.bulk_in(endpoint, RequestBuffer::new(buf.len())));
+        let completion = wait_with_timeout(timeout, |cx| transfer.as_mut().poll(cx).map(Ok))?;
+        completion.status?;
+
+        let n = completion.data.len();
+        buf[..n].copy_from_slice(&completion.data);
+        Ok(n)
     }
 
     /// Perform a raw USB bulk write.
@@ -131,7 +410,56 @@ impl USBTransport {
         timeout: Duration,
     ) -> Result<usize> {
         let interface = self.get_interface(iface)?;
-        Ok(interface.write_bulk(endpoint, buf, timeout)?)
+        // Dropping transfer on timeout cancels it
+        let mut transfer = pin!(interface.bulk_out(endpoint, buf.to_vec()));
+        let completion = wait_with_timeout(timeout, |cx| transfer.as_mut().poll(cx).map(Ok))?;
+        completion.status?;
+
+        Ok(completion.data.actual_length())
+    }
+
+    /// Read manufacturer, product and serial number string descriptors of device.
+    ///
+    /// Strings are the ones cached by the operating system when device enumerated, read in its language
+    /// selection, so device does not need to be opened.
+    pub fn device_strings(&self) -> Result<USBDeviceStrings> {
+        Ok(USBDeviceStrings {
+            manufacturer: self.device_info.manufacturer_string().map(str::to_string),
+            product: self.device_info.product_string().map(str::to_string),
+            serial: self.device_info.serial_number().map(str::to_string),
+        })
+    }
+
+    /// Negotiated speed and bulk endpoints packet sizes of connected device
+    pub fn link_info(&self) -> Result<USBLinkInfo> {
+        let speed = self.device_info.speed().and_then(|speed| match speed {
+            nusb::Speed::Low => Some(USBSpeed::Low),
+            nusb::Speed::Full => Some(USBSpeed::Full),
+            nusb::Speed::High => Some(USBSpeed::High),
+            nusb::Speed::Super => Some(USBSpeed::Super),
+            nusb::Speed::SuperPlus => Some(USBSpeed::SuperPlus),
+            _ => None,
+        });
+
+        let not_connected = || {
+            RustADBError::IOError(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "no endpoint setup",
+            ))
+        };
+        Ok(USBLinkInfo {
+            speed,
+            read_max_packet_size: self
+                .read_endpoint
+                .as_ref()
+                .ok_or_else(not_connected)?
+                .max_packet_size,
+            write_max_packet_size: self
+                .write_endpoint
+                .as_ref()
+                .ok_or_else(not_connected)?
+                .max_packet_size,
+        })
     }
 
     fn get_device(&self) -> Result<Device> {
@@ -144,8 +472,8 @@ impl USBTransport {
             .cloned()
     }
 
-    fn get_read_endpoint(&self) -> Result<Endpoint> {
-        self.read_endpoint
+    fn get_reader(&self) -> Result<Arc<Mutex<BulkReader>>> {
+        self.reader
             .as_ref()
             .ok_or(RustADBError::IOError(std::io::Error::new(
                 std::io::ErrorKind::NotConnected,
@@ -154,13 +482,14 @@ impl USBTransport {
             .cloned()
     }
 
-    fn get_write_endpoint(&self) -> Result<&Endpoint> {
-        self.write_endpoint
+    fn get_writer(&self) -> Result<Arc<Mutex<BulkWriter>>> {
+        self.writer
             .as_ref()
             .ok_or(RustADBError::IOError(std::io::Error::new(
                 std::io::ErrorKind::NotConnected,
                 "no write endpoint setup",
             )))
+            .cloned()
     }
 
     fn get_interface(&self, iface: u8) -> Result<Interface> {
@@ -189,6 +518,7 @@ impl USBTransport {
             iface,
             iface_num: endpoint_desc.iface,
             address: endpoint_desc.address,
+            max_packet_size: endpoint_desc.max_packet_size,
         })
     }
 
@@ -203,11 +533,12 @@ impl USBTransport {
                         if endpoint_desc.transfer_type() == EndpointType::Bulk
                             && interface_desc.class() == 0xff
                             && interface_desc.subclass() == 0x42
-                            && interface_desc.protocol() == 0x01
+                            && interface_desc.protocol() == self.interface_protocol
                         {
                             let endpoint = EndpointDesc {
                                 iface: interface_desc.interface_number(),
                                 address: endpoint_desc.address(),
+                                max_packet_size: endpoint_desc.max_packet_size(),
                             };
                             match endpoint_desc.direction() {
                                 Direction::In => {
@@ -241,9 +572,19 @@ impl ADBTransport for USBTransport {
 
         let (read_endpoint, write_endpoint) = self.find_endpoints(&device)?;
 
-        self.read_endpoint = Some(Self::configure_endpoint(&device, &read_endpoint)?);
+        let read_endpoint = Self::configure_endpoint(&device, &read_endpoint)?;
+        self.reader = Some(Arc::new(Mutex::new(BulkReader::new(
+            &read_endpoint,
+            self.queue_depth,
+        ))));
+        self.read_endpoint = Some(read_endpoint);
 
-        self.write_endpoint = Some(Self::configure_endpoint(&device, &write_endpoint)?);
+        let write_endpoint = Self::configure_endpoint(&device, &write_endpoint)?;
+        self.writer = Some(Arc::new(Mutex::new(BulkWriter::new(
+            &write_endpoint,
+            self.queue_depth,
+        ))));
+        self.write_endpoint = Some(write_endpoint);
 
         self.device = Some(device);
 
@@ -252,7 +593,9 @@ impl ADBTransport for USBTransport {
 
     fn disconnect(&mut self) -> crate::Result<()> {
         let message = ADBTransportMessage::new(MessageCommand::Clse, 0, 0, &[]);
-        self.write_message(message)
+        // Whole queue is flushed, pipelined writes being cancelled once transport is dropped
+        self.submit_message(message)?;
+        wait_with_timeout(CLOSE_WRITE_TIMEOUT, |cx| self.poll_flush(cx))
     }
 }
 
@@ -262,119 +605,51 @@ impl ADBMessageTransport for USBTransport {
         message: ADBTransportMessage,
         timeout: Duration,
     ) -> Result<()> {
-        let endpoint = self.get_write_endpoint()?;
-
-        let message_bytes = message.header().as_bytes()?;
-        let mut total_written = 0;
-        loop {
-            total_written += endpoint.write_bulk(&message_bytes[total_written..], timeout)?;
-            if total_written == message_bytes.len() {
-                break;
-            }
-        }
-
-        let payload = message.into_payload();
-        if !payload.is_empty() {
-            let mut total_written = 0;
-            loop {
-                total_written += endpoint.write_bulk(&payload[total_written..], timeout)?;
-                if total_written == payload.len() {
-                    break;
-                }
-            }
-        }
-
-        Ok(())
+        self.submit_message(message)?;
+        wait_with_timeout(timeout, |cx| self.poll_write_pipeline(cx))
     }
 
     fn read_message_with_timeout(&mut self, timeout: Duration) -> Result<ADBTransportMessage> {
-        let endpoint = self.get_read_endpoint()?;
-
-        let mut data = [0; 24];
-        let mut total_read = 0;
-        loop {
-            total_read += endpoint.read_bulk(&mut data[total_read..], timeout)?;
-            if total_read == data.len() {
-                break;
-            }
-        }
-
-        let header = ADBTransportMessageHeader::try_from(data)?;
-
-        log::trace!("received header {header:?}");
-
-        if header.data_length() != 0 {
-            let mut msg_data = vec![0_u8; header.data_length() as usize];
-            let mut total_read = 0;
-            loop {
-                total_read += endpoint.read_bulk(&mut msg_data[total_read..], timeout)?;
-                if total_read == msg_data.capacity() {
-                    break;
-                }
-            }
-
-            let message = ADBTransportMessage::from_header_and_payload(header, msg_data);
-
-            // Check message integrity
-            if !message.check_message_integrity() {
-                return Err(RustADBError::InvalidIntegrity(
-                    ADBTransportMessageHeader::compute_crc32(message.payload()),
-                    message.header().data_crc32(),
-                ));
-            }
+        wait_with_timeout(timeout, |cx| self.poll_read_message(cx))
+    }
 
-            return Ok(message);
+    fn try_read_message(&mut self) -> Result<Option<ADBTransportMessage>> {
+        // A message partially received is kept, and returned by a following call
+        match self.read_message_with_timeout(POLL_READ_TIMEOUT) {
+            Ok(message) => Ok(Some(message)),
+            Err(RustADBError::IOError(e)) if e.kind() == ErrorKind::TimedOut => Ok(None),
+            Err(e) => Err(e),
         }
-
-        Ok(ADBTransportMessage::from_header_and_payload(header, vec![]))
     }
 }
 
-trait InterfaceHelpers {
-    fn write_bulk(&self, address: u8, buf: &[u8], timeout: Duration) -> Result<usize>;
-    fn read_bulk(&self, address: u8, buf: &mut [u8], timeout: Duration) -> Result<usize>;
-}
-
-impl InterfaceHelpers for Interface {
-    fn write_bulk(&self, address: u8, buf: &[u8], timeout: Duration) -> Result<usize> {
-        let fut = async {
-            let comp = self.bulk_out(address, buf.to_vec()).await;
-            comp.status?;
-
-            let n = comp.data.actual_length();
-            Ok(n)
-        };
-
-        block_on(fut.or(async {
-            Timer::after(timeout).await;
-            Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into())
-        }))
-    }
-
-    fn read_bulk(&self, address: u8, buf: &mut [u8], timeout: Duration) -> Result<usize> {
-        let fut = async {
-            let comp = self.bulk_in(address, RequestBuffer::new(buf.len())).await;
-            comp.status?;
-
-            let n = comp.data.len();
-            buf[..n].copy_from_slice(&comp.data);
-            Ok(n)
-        };
+/// Wakes a thread parked while waiting for a transfer
+struct ThreadWaker(Thread);
 
-        block_on(fut.or(async {
-            Timer::after(timeout).await;
-            Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into())
-        }))
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
     }
 }
 
-impl Endpoint {
-    fn write_bulk(&self, buf: &[u8], timeout: Duration) -> Result<usize> {
-        self.iface.write_bulk(self.address, buf, timeout)
-    }
+/// Poll `poll` from current thread until it is ready, or fail with [`ErrorKind::TimedOut`] once `timeout` elapsed
+fn wait_with_timeout<T>(
+    timeout: Duration,
+    mut poll: impl FnMut(&mut Context<'_>) -> Poll<Result<T>>,
+) -> Result<T> {
+    let deadline = Instant::now() + timeout;
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(result) = poll(&mut cx) {
+            return result;
+        }
 
-    fn read_bulk(&self, buf: &mut [u8], timeout: Duration) -> Result<usize> {
-        self.iface.read_bulk(self.address, buf, timeout)
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::Error::from(ErrorKind::TimedOut).into());
+        }
+        std::thread::park_timeout(remaining);
     }
 }
 
@@ -383,6 +658,7 @@ impl Debug for Endpoint {
         f.debug_struct("Endpoint")
             .field("iface", &self.iface.interface_number())
             .field("address", &self.address)
+            .field("max_packet_size", &self.max_packet_size)
             .finish()
     }
 }
@@ -393,6 +669,7 @@ impl Debug for USBTransport {
             .field("device_info", &self.device_info)
             .field("read_endpoint", &self.read_endpoint)
             .field("write_endpoint", &self.write_endpoint)
+            .field("queue_depth", &self.queue_depth)
             .finish()
     }
 }
@@ -424,9 +701,83 @@ pub fn search_adb_devices() -> Result<Option<(u16, u16)>> {
     }
 }
 
+/// Search for devices in fastboot mode, e.g. after rebooting an adb device using [`crate::RebootType::Bootloader`]
+pub fn search_fastboot_devices() -> Result<Option<(u16, u16)>> {
+    let mut found_devices = vec![];
+    for device_info in nusb::list_devices()? {
+        let Ok(device) = device_info.open() else {
+            continue;
+        };
+        if is_fastboot_device(&device) {
+            log::debug!(
+                "Autodetect fastboot device {:04x}:{:04x}",
+                device_info.vendor_id(),
+                device_info.product_id()
+            );
+            found_devices.push((device_info.vendor_id(), device_info.product_id()));
+        }
+    }
+
+    match (found_devices.first(), found_devices.get(1)) {
+        (None, _) => Ok(None),
+        (Some(identifiers), None) => Ok(Some(*identifiers)),
+        (Some((vid1, pid1)), Some((vid2, pid2))) => Err(RustADBError::DeviceNotFound(format!(
+            "Found two fastboot devices {:04x}:{:04x} and {:04x}:{:04x}",
+            vid1, pid1, vid2, pid2
+        ))),
+    }
+}
+
+/// List serial numbers of all connected adb devices.
+///
+/// Devices not exposing a serial number are identified by their vendor and product ids.
+#[cfg(feature = "tcp")]
+pub(crate) fn list_adb_device_serials() -> Result<Vec<String>> {
+    let mut serials = vec![];
+    for device_info in nusb::list_devices()? {
+        let Ok(device) = device_info.open() else {
+            continue;
+        };
+        if !is_adb_device(&device) {
+            continue;
+        }
+
+        let vendor_product = format!(
+            "{:04x}:{:04x}",
+            device_info.vendor_id(),
+            device_info.product_id()
+        );
+        let serial = USBTransport::new_from_device_info(device_info)
+            .device_strings()?
+            .serial
+            .unwrap_or(vendor_product);
+        serials.push(serial);
+    }
+
+    Ok(serials)
+}
+
+fn is_fastboot_device(device: &Device) -> bool {
+    const FASTBOOT_SUBCLASS: u8 = 0x42;
+
+    for config_desc in device.configurations() {
+        for interface in config_desc.interfaces() {
+            for interface_desc in interface.alt_settings() {
+                if interface_desc.class() == 0xff
+                    && interface_desc.subclass() == FASTBOOT_SUBCLASS
+                    && interface_desc.protocol() == FASTBOOT_INTERFACE_PROTOCOL
+                {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
 fn is_adb_device(device: &Device) -> bool {
     const ADB_SUBCLASS: u8 = 0x42;
-    const ADB_PROTOCOL: u8 = 0x1;
+    const ADB_PROTOCOL: u8 = ADB_INTERFACE_PROTOCOL;
 
     // Some devices require choosing the file transfer mode
     // for usb debugging to take effect.
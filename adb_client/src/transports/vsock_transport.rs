@@ -0,0 +1,109 @@
+use std::{
+    io::{Read, Write},
+    mem::MaybeUninit,
+    net::Shutdown,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use socket2::{Domain, SockAddr, Socket, Type};
+
+use super::{ADBMessageTransport, ADBTransport};
+use crate::{
+    Result, RustADBError,
+    device::{ADBTransportMessage, ADBTransportMessageHeader},
+};
+
+/// Transport running on a vsock socket, used by virtual machines (e.g. Cuttlefish) exposing adbd to their host.
+#[derive(Clone, Debug)]
+pub struct VsockTransport {
+    cid: u32,
+    port: u32,
+    current_connection: Option<Arc<Mutex<Socket>>>,
+}
+
+impl VsockTransport {
+    /// Instantiate a new [`VsockTransport`] reaching `port` of virtual machine with context identifier `cid`
+    pub fn new(cid: u32, port: u32) -> Self {
+        Self {
+            cid,
+            port,
+            current_connection: None,
+        }
+    }
+
+    fn get_current_connection(&self) -> Result<Arc<Mutex<Socket>>> {
+        self.current_connection
+            .as_ref()
+            .ok_or(RustADBError::IOError(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "not connected",
+            )))
+            .cloned()
+    }
+}
+
+impl ADBTransport for VsockTransport {
+    fn connect(&mut self) -> Result<()> {
+        let socket = Socket::new(Domain::VSOCK, Type::STREAM, None)?;
+        socket.connect(&SockAddr::vsock(self.cid, self.port))?;
+        self.current_connection = Some(Arc::new(Mutex::new(socket)));
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<()> {
+        log::debug!("disconnecting...");
+        if let Some(current_connection) = &self.current_connection {
+            let _ = current_connection.lock()?.shutdown(Shutdown::Both);
+        }
+
+        Ok(())
+    }
+}
+
+impl ADBMessageTransport for VsockTransport {
+    fn read_message_with_timeout(&mut self, read_timeout: Duration) -> Result<ADBTransportMessage> {
+        let connection_lock = self.get_current_connection()?;
+        let mut connection = connection_lock.lock()?;
+
+        // Only wait for message to start within timeout, a partially read message would corrupt the stream
+        connection.set_read_timeout(Some(read_timeout))?;
+        connection.peek(&mut [MaybeUninit::uninit()])?;
+        connection.set_read_timeout(None)?;
+
+        let mut data = [0; 24];
+        connection.read_exact(&mut data)?;
+        let header = ADBTransportMessageHeader::try_from(data)?;
+
+        let mut payload = vec![0_u8; header.data_length() as usize];
+        connection.read_exact(&mut payload)?;
+        let message = ADBTransportMessage::from_header_and_payload(header, payload);
+
+        // Check message integrity
+        if !message.check_message_integrity() {
+            return Err(RustADBError::InvalidIntegrity(
+                ADBTransportMessageHeader::compute_crc32(message.payload()),
+                message.header().data_crc32(),
+            ));
+        }
+
+        Ok(message)
+    }
+
+    fn write_message_with_timeout(
+        &mut self,
+        message: ADBTransportMessage,
+        write_timeout: Duration,
+    ) -> Result<()> {
+        let message_bytes = message.header().as_bytes()?;
+        let connection_lock = self.get_current_connection()?;
+        let mut connection = connection_lock.lock()?;
+
+        connection.set_write_timeout(Some(write_timeout))?;
+        connection.write_all(&message_bytes)?;
+        connection.write_all(&message.into_payload())?;
+        connection.flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use std::time::{Duration, Instant};
+
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Interval between two hierarchy dumps while waiting for a node
+const UI_WAIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Temporary file on device receiving hierarchy dumped by `uiautomator`
+const UI_DUMP_PATH: &str = "/data/local/tmp/adb_client_ui_dump.xml";
+
+/// Node of UI hierarchy, as dumped by `uiautomator`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UiNode {
+    /// Class of view (e.g. `android.widget.Button`)
+    pub class: String,
+    /// Package owning view
+    pub package: String,
+    /// Resource identifier of view (e.g. `com.example:id/login`), empty if none
+    pub resource_id: String,
+    /// Displayed text, empty if none
+    pub text: String,
+    /// Content description, as read by accessibility services
+    pub content_desc: String,
+    /// Bounds on screen, as `(left, top, right, bottom)` in pixels
+    pub bounds: (i32, i32, i32, i32),
+}
+
+impl UiNode {
+    /// Center of node bounds, where it is tapped
+    pub fn center(&self) -> (i32, i32) {
+        let (left, top, right, bottom) = self.bounds;
+        ((left + right) / 2, (top + bottom) / 2)
+    }
+}
+
+/// Criterion selecting a [`UiNode`], values being matched exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiSelector {
+    /// Match on resource identifier (e.g. `com.example:id/login`)
+    ResourceId(String),
+    /// Match on displayed text
+    Text(String),
+    /// Match on content description
+    ContentDesc(String),
+}
+
+impl UiSelector {
+    /// Return `true` if `node` is selected
+    pub fn matches(&self, node: &UiNode) -> bool {
+        match self {
+            UiSelector::ResourceId(resource_id) => node.resource_id == *resource_id,
+            UiSelector::Text(text) => node.text == *text,
+            UiSelector::ContentDesc(content_desc) => node.content_desc == *content_desc,
+        }
+    }
+}
+
+/// Replace XML entities of attribute `value`
+fn unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&#10;", "\n")
+        .replace("&amp;", "&")
+}
+
+/// Parse bounds written as `[left,top][right,bottom]`
+fn parse_bounds(bounds: &str) -> Option<(i32, i32, i32, i32)> {
+    let mut numbers = bounds
+        .split(['[', ']', ','])
+        .filter(|number| !number.is_empty())
+        .map(str::parse);
+    Some((
+        numbers.next()?.ok()?,
+        numbers.next()?.ok()?,
+        numbers.next()?.ok()?,
+        numbers.next()?.ok()?,
+    ))
+}
+
+/// Parse nodes of hierarchy dumped by `uiautomator`, in document order.
+///
+/// Dump only uses `<node>` elements with double-quoted attributes, nesting being ignored.
+pub(crate) fn parse_ui_dump(dump: &str) -> Vec<UiNode> {
+    let mut nodes = Vec::new();
+    let mut remaining = dump;
+
+    while let Some(start) = remaining.find("<node ") {
+        remaining = &remaining[start + "<node ".len()..];
+        let mut node = UiNode::default();
+
+        // Read `name="value"` attributes until end of tag
+        loop {
+            remaining = remaining.trim_start();
+            if remaining.is_empty() || remaining.starts_with(['>', '/']) {
+                break;
+            }
+            let Some((name, rest)) = remaining.split_once("=\"") else {
+                break;
+            };
+            let Some((value, rest)) = rest.split_once('"') else {
+                break;
+            };
+            remaining = rest;
+
+            let value = unescape(value);
+            match name.trim() {
+                "class" => node.class = value,
+                "package" => node.package = value,
+                "resource-id" => node.resource_id = value,
+                "text" => node.text = value,
+                "content-desc" => node.content_desc = value,
+                "bounds" => node.bounds = parse_bounds(&value).unwrap_or_default(),
+                _ => {}
+            }
+        }
+
+        nodes.push(node);
+    }
+
+    nodes
+}
+
+/// Dump UI hierarchy of `device`, see [`ADBDeviceExt::ui_dump`].
+pub(crate) fn ui_dump<D: ADBDeviceExt + ?Sized>(device: &mut D) -> Result<Vec<UiNode>> {
+    let mut output = Vec::new();
+    device.shell_command(
+        &[
+            &format!("uiautomator dump {UI_DUMP_PATH} >/dev/null"),
+            &format!("&& cat {UI_DUMP_PATH};"),
+            &format!("rm -f {UI_DUMP_PATH}"),
+        ],
+        &mut output,
+    )?;
+
+    let output = String::from_utf8(output)?;
+    if !output.contains("<hierarchy") {
+        return Err(RustADBError::ADBRequestFailed(format!(
+            "cannot dump UI hierarchy: {}",
+            output.trim()
+        )));
+    }
+    Ok(parse_ui_dump(&output))
+}
+
+/// Wait for a node selected by `selector` on `device`, see [`ADBDeviceExt::wait_for`].
+pub(crate) fn wait_for<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    selector: &UiSelector,
+    timeout: Duration,
+) -> Result<UiNode> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(node) = ui_dump(device)?
+            .into_iter()
+            .find(|node| selector.matches(node))
+        {
+            return Ok(node);
+        }
+        if Instant::now() >= deadline {
+            return Err(RustADBError::UiNodeNotFound(format!("{selector:?}")));
+        }
+        std::thread::sleep(
+            UI_WAIT_POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())),
+        );
+    }
+}
+
+#[test]
+fn test_parse_ui_dump() {
+    let dump = r#"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><hierarchy rotation="0"><node index="0" text="" resource-id="" class="android.widget.FrameLayout" package="com.example" content-desc="" bounds="[0,0][1080,2400]"><node index="0" text="Sign &quot;in&quot; &amp; go" resource-id="com.example:id/login" class="android.widget.Button" package="com.example" content-desc="Log in" bounds="[100,200][300,260]" /></node></hierarchy>"#;
+
+    let nodes = parse_ui_dump(dump);
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(
+        nodes[1],
+        UiNode {
+            class: "android.widget.Button".to_string(),
+            package: "com.example".to_string(),
+            resource_id: "com.example:id/login".to_string(),
+            text: "Sign \"in\" & go".to_string(),
+            content_desc: "Log in".to_string(),
+            bounds: (100, 200, 300, 260),
+        }
+    );
+    assert_eq!(nodes[1].center(), (200, 230));
+    assert!(UiSelector::ContentDesc("Log in".to_string()).matches(&nodes[1]));
+}
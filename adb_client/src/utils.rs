@@ -1,7 +1,10 @@
-use std::{ffi::OsStr, path::Path};
+#[cfg(any(feature = "tcp", feature = "usb"))]
+use std::{ffi::OsStr, io::Write, path::Path, time::SystemTime};
 
-use crate::{Result, RustADBError};
+#[cfg(any(feature = "tcp", feature = "usb"))]
+use crate::{InstallPhase, Result, RustADBError, observer::ObserverSlot};
 
+#[cfg(any(feature = "tcp", feature = "usb"))]
 pub fn check_extension_is_apk<P: AsRef<Path>>(path: P) -> Result<()> {
     if let Some(extension) = path.as_ref().extension() {
         if ![OsStr::new("apk")].contains(&extension) {
@@ -14,3 +17,186 @@ pub fn check_extension_is_apk<P: AsRef<Path>>(path: P) -> Result<()> {
 
     Ok(())
 }
+
+/// Fail unless `copied` bytes match the `size` announced to device, which would otherwise wait for missing ones
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub fn check_copied_size(copied: u64, size: u64) -> Result<()> {
+    if copied != size {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!("reader ended after {copied} of {size} announced bytes"),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// `time` as sent in a sync `DONE` message, in seconds since Unix epoch
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub fn sync_mod_time(time: SystemTime) -> u32 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as u32)
+}
+
+/// Incremental parser of `cmd package install` output, reporting installation progress until final status is received.
+#[cfg(any(feature = "tcp", feature = "usb"))]
+#[derive(Debug, Default)]
+pub struct InstallOutput {
+    /// Current line, not complete yet
+    line: Vec<u8>,
+}
+
+#[cfg(any(feature = "tcp", feature = "usb"))]
+impl InstallOutput {
+    /// Parse `data` received from package manager, and return final status of installation once received.
+    pub fn feed(&mut self, data: &[u8], observer: &ObserverSlot) -> Option<Result<()>> {
+        for byte in data {
+            // Progress may be rewritten on the same line
+            if *byte != b'\n' && *byte != b'\r' {
+                self.line.push(*byte);
+                continue;
+            }
+
+            let line = std::mem::take(&mut self.line);
+            if let Some(status) = Self::parse_line(&line, observer) {
+                return Some(status);
+            }
+        }
+
+        None
+    }
+
+    /// Parse output left once package manager closed its stream, which must hold final status.
+    pub fn finish(self, observer: &ObserverSlot) -> Result<()> {
+        Self::parse_line(&self.line, observer).unwrap_or_else(|| {
+            Err(RustADBError::ADBRequestFailed(
+                "package manager exited without reporting installation status".to_string(),
+            ))
+        })
+    }
+
+    fn parse_line(line: &[u8], observer: &ObserverSlot) -> Option<Result<()>> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        if line.starts_with("Success") {
+            return Some(Ok(()));
+        }
+        if line.starts_with("Failure") || line.starts_with("Error") {
+            return Some(Err(RustADBError::ADBRequestFailed(line.to_string())));
+        }
+
+        if let Some(percent) = line.find('%').and_then(|end| {
+            let start = line[..end]
+                .rfind(|c: char| !c.is_ascii_digit())
+                .map_or(0, |position| position + 1);
+            line[start..end].parse::<u8>().ok()
+        }) {
+            observer.install_progress(InstallPhase::Installing, percent.min(100));
+        }
+
+        None
+    }
+}
+
+/// Quote `value` to be used as a single device shell word
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Wrap `command` so that remote shell prints its PID on a first dedicated line before running it.
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub fn with_remote_pid(command: &[&str]) -> String {
+    format!("echo $$; {}", command.join(" "))
+}
+
+/// Command killing the process group of a shell whose PID has been printed by [`with_remote_pid`].
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub fn kill_remote_pid(pid: u32) -> String {
+    // Shell is usually its own group leader, otherwise at least kill it
+    format!("kill -9 -- -{pid} 2>/dev/null || kill -9 {pid}")
+}
+
+/// [`Write`] implementation extracting the PID line printed by [`with_remote_pid`], forwarding the remaining output.
+#[cfg(any(feature = "tcp", feature = "usb"))]
+pub struct RemotePidFilter<W: Write> {
+    inner: W,
+    /// First line being received, `None` once complete
+    pid_line: Option<Vec<u8>>,
+    pid: Option<u32>,
+}
+
+#[cfg(any(feature = "tcp", feature = "usb"))]
+impl<W: Write> RemotePidFilter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pid_line: Some(Vec::new()),
+            pid: None,
+        }
+    }
+
+    /// PID of the remote shell, if already received
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+}
+
+#[cfg(any(feature = "tcp", feature = "usb"))]
+impl<W: Write> Write for RemotePidFilter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let Some(pid_line) = &mut self.pid_line else {
+            return self.inner.write(buf);
+        };
+
+        match buf.iter().position(|byte| *byte == b'\n') {
+            Some(end) => {
+                pid_line.extend_from_slice(&buf[..end]);
+                self.pid = std::str::from_utf8(pid_line)
+                    .ok()
+                    .and_then(|pid| pid.trim().parse().ok());
+                self.pid_line = None;
+                self.inner.write_all(&buf[end + 1..])?;
+            }
+            None => pid_line.extend_from_slice(buf),
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+#[cfg(any(feature = "tcp", feature = "usb"))]
+fn test_install_output_reports_progress() {
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Progress(Mutex<Vec<u8>>);
+    impl crate::AdbObserver for Progress {
+        fn on_install_progress(&self, _phase: InstallPhase, percent: u8) {
+            self.0.lock().expect("poisoned lock").push(percent);
+        }
+    }
+
+    let progress = Arc::new(Progress::default());
+    let mut observer = ObserverSlot::default();
+    observer.set(progress.clone());
+
+    let mut output = InstallOutput::default();
+    assert!(output.feed(b"[ 12%]\r[ 5", &observer).is_none());
+    assert!(output.feed(b"0%]\r", &observer).is_none());
+    assert!(matches!(output.feed(b"Success\n", &observer), Some(Ok(()))));
+    assert_eq!(*progress.0.lock().expect("poisoned lock"), [12, 50]);
+
+    let mut output = InstallOutput::default();
+    assert!(
+        output
+            .feed(b"Failure [INSTALL_FAILED_INVALID_APK]", &observer)
+            .is_none()
+    );
+    assert!(output.finish(&observer).is_err());
+}
@@ -0,0 +1,49 @@
+use std::io::Read;
+
+use crate::{ADBDeviceExt, Result, RustADBError};
+
+/// Open `service` (`remount:`, `disable-verity:` or `enable-verity:`) on `device`, returning `true` if device must
+/// be rebooted for it to take effect.
+pub(crate) fn run_verity_service<D: ADBDeviceExt + ?Sized>(
+    device: &mut D,
+    service: &str,
+) -> Result<bool> {
+    let mut output = String::new();
+    device.open_service(service)?.read_to_string(&mut output)?;
+
+    reboot_required(&output)
+}
+
+/// Return `true` if `output` of a remount or verity service asks for a reboot, failing if it reports an error.
+///
+/// These services only report their result as text, which wording changed across Android versions.
+fn reboot_required(output: &str) -> Result<bool> {
+    let lowercase = output.to_lowercase();
+    // e.g. "remount failed", "verity cannot be disabled/enabled - USER build",
+    // "Device must be bootloader unlocked"
+    if ["failed", "cannot", "must be"]
+        .iter()
+        .any(|error| lowercase.contains(error))
+    {
+        return Err(RustADBError::ADBRequestFailed(output.trim().to_string()));
+    }
+
+    // e.g. "Now reboot your device for settings to take effect"
+    Ok(lowercase.contains("reboot"))
+}
+
+#[test]
+fn test_reboot_required() {
+    assert!(
+        reboot_required(
+            "Successfully disabled verity\nNow reboot your device for settings to take effect\n"
+        )
+        .expect("cannot parse output")
+    );
+    assert!(
+        !reboot_required("Verity already disabled on /system\nremount succeeded\n")
+            .expect("cannot parse output")
+    );
+    assert!(reboot_required("verity cannot be disabled/enabled - USER build\n").is_err());
+    assert!(reboot_required("Device must be bootloader unlocked\n").is_err());
+}
@@ -0,0 +1,259 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use rand::Rng;
+use rand::distr::Alphanumeric;
+
+use crate::constants::TCP_WAIT_POLL_INTERVAL;
+use crate::{
+    ADBDeviceExt, ADBPairing, ADBTcpDevice, MDNSBrowser, MDNSDevice, MDNSEvent, MDNSServiceType,
+    Result, RustADBError,
+};
+
+const PAIRING_SERVICE_TYPE: &str = "_adb-tls-pairing._tcp.local.";
+
+/// Pairing information to display as a QR code, for device to scan in "Pair device with QR code" dialog.
+///
+/// Once QR code is scanned, device advertises a pairing service over mDNS using given service name, which
+/// [`QrPairing::pair`] waits for to complete pairing, as Android Studio does.
+#[derive(Debug, Clone)]
+pub struct QrPairing {
+    service_name: String,
+    password: String,
+}
+
+impl Default for QrPairing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QrPairing {
+    /// Instantiate a new [`QrPairing`] with a random service name and password
+    pub fn new() -> Self {
+        let random = |len| {
+            rand::rng()
+                .sample_iter(Alphanumeric)
+                .take(len)
+                .map(char::from)
+                .collect::<String>()
+        };
+        Self {
+            service_name: format!("adb_client-{}", random(10)),
+            password: random(12),
+        }
+    }
+
+    /// Instance name of the pairing service device will advertise
+    pub fn service_name(&self) -> &str {
+        &self.service_name
+    }
+
+    /// Payload to display as a QR code, `WIFI:T:ADB;S:<name>;P:<password>;;`
+    pub fn payload(&self) -> String {
+        format!("WIFI:T:ADB;S:{};P:{};;", self.service_name, self.password)
+    }
+
+    /// Wait for device to scan QR code and pair with it without ADB server, returning device GUID.
+    ///
+    /// Fails if device does not advertise its pairing service within `timeout`.
+    pub fn pair(&self, timeout: Duration) -> Result<String> {
+        let deadline = Instant::now() + timeout;
+        let daemon = ServiceDaemon::new()?;
+        let service_fullname = format!("{}.{PAIRING_SERVICE_TYPE}", self.service_name);
+        let result = resolve_service(&daemon, PAIRING_SERVICE_TYPE, deadline, |service| {
+            service.get_fullname() == service_fullname
+        });
+        // Best effort, discovery is over anyway
+        let _ = daemon.shutdown();
+        let pairing_service = result?;
+
+        // Prefer IPv4 addresses, as ADB server does
+        let address = pairing_service
+            .get_addresses()
+            .iter()
+            .min_by_key(|address| address.is_ipv6())
+            .map(|address| SocketAddr::new(*address, pairing_service.get_port()))
+            .ok_or_else(|| {
+                RustADBError::DeviceNotFound(format!("{service_fullname} has no address"))
+            })?;
+
+        log::debug!("pairing with {address}");
+        ADBPairing::new(address)?.pair(&self.password)
+    }
+}
+
+/// Discover a device in wireless debugging mode over mDNS, pair with it if needed, and return it once connected.
+///
+/// Devices advertising their connect service are connected to as soon as found, which only succeeds once they have
+/// been paired with default key (`~/.android/adbkey`). `pairing_code_provider` is called with each device
+/// advertising its pairing service, i.e. showing "Pair device with pairing code" dialog, and returns the code it
+/// displays, or `None` to leave this device alone. Pairing is performed without ADB server, paired device being
+/// connected to once its connect service is known.
+///
+/// Fails if no device can be connected to within `timeout`.
+pub fn connect_wireless<F>(mut pairing_code_provider: F, timeout: Duration) -> Result<ADBTcpDevice>
+where
+    F: FnMut(&MDNSDevice) -> Option<String>,
+{
+    let events = MDNSBrowser::new()
+        .with_service_types(&[MDNSServiceType::TlsConnect, MDNSServiceType::TlsPairing])
+        .with_timeout(timeout)
+        .browse()?;
+
+    // Connect services of devices which refused connection, until they are paired
+    let mut unpaired: Vec<MDNSDevice> = Vec::new();
+    for event in events {
+        let service = match event? {
+            MDNSEvent::Added(service) | MDNSEvent::Updated(service) => service,
+            MDNSEvent::Removed(_, fullname) => {
+                unpaired.retain(|service| service.fullname != fullname);
+                continue;
+            }
+        };
+
+        match service.service_type {
+            MDNSServiceType::TlsConnect => match ADBTcpDevice::new(service_address(&service)?) {
+                Ok(device) => return Ok(device),
+                Err(e) => {
+                    log::debug!("cannot connect to {}: {e}", service.instance_name);
+                    unpaired.retain(|other| other.fullname != service.fullname);
+                    unpaired.push(service);
+                }
+            },
+            MDNSServiceType::TlsPairing => {
+                let Some(pairing_code) = pairing_code_provider(&service) else {
+                    continue;
+                };
+                let guid = ADBPairing::new(service_address(&service)?)?.pair(&pairing_code)?;
+                log::info!("paired with {guid}");
+
+                // Connect service is named after device GUID, if it was already advertised
+                if let Some(connect_service) = unpaired.iter().find(|connect_service| {
+                    connect_service.instance_name == guid
+                        || !connect_service.addresses.is_disjoint(&service.addresses)
+                }) {
+                    return ADBTcpDevice::new(service_address(connect_service)?);
+                }
+            }
+            MDNSServiceType::Adb => {}
+        }
+    }
+
+    Err(RustADBError::DeviceNotFound(
+        "no wireless device connected in time".to_string(),
+    ))
+}
+
+/// Switch `device` (e.g. connected over USB) to TCP mode on `port`, and connect to it over Wi-Fi once adbd restarted.
+///
+/// Device is reached at the IPv4 address of its `wlan0` interface, host being expected on the same network.
+/// Connection is not authenticated, hence only devices accepting it are reached this way (e.g. emulators and
+/// insecure builds). Other devices can be connected through ADB server using [`crate::ADBServer::connect_device`].
+///
+/// Fails if device cannot be connected to within `timeout`.
+pub fn switch_to_tcp(
+    device: &mut dyn ADBDeviceExt,
+    port: u16,
+    timeout: Duration,
+) -> Result<ADBTcpDevice> {
+    let deadline = Instant::now() + timeout;
+
+    let mut output = Vec::new();
+    device.shell_command(&["ip", "-f", "inet", "addr", "show", "wlan0"], &mut output)?;
+    let address = parse_inet_address(&String::from_utf8_lossy(&output)).ok_or_else(|| {
+        RustADBError::DeviceNotFound("device has no Wi-Fi IPv4 address".to_string())
+    })?;
+
+    let address = SocketAddr::new(IpAddr::V4(address), device.tcpip(port)?);
+    loop {
+        match ADBTcpDevice::new(address) {
+            Ok(device) => return Ok(device),
+            Err(e) if Instant::now() >= deadline => return Err(e),
+            Err(e) => log::debug!("waiting for adbd to listen on {address}: {e}"),
+        }
+
+        std::thread::sleep(TCP_WAIT_POLL_INTERVAL);
+    }
+}
+
+/// Parse first IPv4 address printed by `ip addr show`, e.g. `inet 192.168.1.23/24 brd ...`
+fn parse_inet_address(output: &str) -> Option<Ipv4Addr> {
+    output.lines().find_map(|line| {
+        let address = line
+            .trim()
+            .strip_prefix("inet ")?
+            .split(['/', ' '])
+            .next()?;
+        address.parse().ok()
+    })
+}
+
+/// Address of an advertised service, preferring IPv4 as ADB server does
+fn service_address(service: &MDNSDevice) -> Result<SocketAddr> {
+    service
+        .addresses
+        .iter()
+        .min_by_key(|address| address.is_ipv6())
+        .map(|address| SocketAddr::new(*address, service.port))
+        .ok_or_else(|| RustADBError::DeviceNotFound(format!("{} has no address", service.fullname)))
+}
+
+/// Browse `service_type` services until one matching `filter` is resolved
+fn resolve_service(
+    daemon: &ServiceDaemon,
+    service_type: &str,
+    deadline: Instant,
+    filter: impl Fn(&ServiceInfo) -> bool,
+) -> Result<ServiceInfo> {
+    let receiver = daemon.browse(service_type)?;
+
+    let result = loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(remaining) {
+            Ok(ServiceEvent::ServiceResolved(service)) if filter(&service) => break Ok(service),
+            Ok(_) => {}
+            Err(_) => {
+                break Err(RustADBError::DeviceNotFound(format!(
+                    "no matching {service_type} service found in time"
+                )));
+            }
+        }
+    };
+
+    // Best effort, daemon will be shut down soon anyway
+    let _ = daemon.stop_browse(service_type);
+
+    result
+}
+
+#[test]
+fn test_qr_pairing_payload() {
+    let pairing = QrPairing::new();
+    assert_eq!(
+        pairing.payload(),
+        format!(
+            "WIFI:T:ADB;S:{};P:{};;",
+            pairing.service_name(),
+            pairing.password
+        )
+    );
+    assert!(pairing.service_name().starts_with("adb_client-"));
+    assert!(pairing.password.chars().all(|c| c.is_ascii_alphanumeric()));
+}
+
+#[test]
+fn test_parse_inet_address() {
+    let output = "30: wlan0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 qdisc mq state UP group default qlen 3000\n    \
+        inet 192.168.1.23/24 brd 192.168.1.255 scope global wlan0\n       \
+        valid_lft forever preferred_lft forever\n";
+    assert_eq!(
+        parse_inet_address(output),
+        Some(Ipv4Addr::new(192, 168, 1, 23))
+    );
+    assert_eq!(
+        parse_inet_address("Device \"wlan0\" does not exist.\n"),
+        None
+    );
+}
@@ -0,0 +1,420 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use byteorder::{ByteOrder, LittleEndian};
+use flate2::read::DeflateDecoder;
+
+use crate::{Result, RustADBError};
+
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const CENTRAL_DIRECTORY_ENTRY_SIGNATURE: u32 = 0x0201_4b50;
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x0807_4b50;
+/// Extra field holding 64 bits sizes and offset of an entry
+const ZIP64_EXTRA_FIELD: u16 = 0x0001;
+/// Version needed to extract ZIP64 archives
+const ZIP64_VERSION: u16 = 45;
+/// Flag telling that sizes and CRC of an entry follow its data
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+
+/// Entry of a [`ZipArchive`].
+#[derive(Debug, Clone)]
+pub(crate) struct ZipEntry {
+    pub(crate) name: String,
+    /// Size of entry once extracted
+    pub(crate) size: u64,
+    method: u16,
+    compressed_size: u64,
+    local_header_offset: u64,
+}
+
+/// Minimal reader of ZIP archives, as needed to extract APKs from app bundles.
+///
+/// Only stored and deflated entries are supported, which is all Android tooling produces. Archives larger than 4GB
+/// (ZIP64) are supported, as bundles may hold large expansion files.
+#[derive(Debug)]
+pub(crate) struct ZipArchive<R: Read + Seek> {
+    reader: R,
+    entries: Vec<ZipEntry>,
+}
+
+fn invalid(reason: &str) -> RustADBError {
+    RustADBError::InvalidBundle(reason.to_string())
+}
+
+impl<R: Read + Seek> ZipArchive<R> {
+    /// Read central directory of archive read from `reader`
+    pub(crate) fn new(mut reader: R) -> Result<Self> {
+        let archive_size = reader.seek(SeekFrom::End(0))?;
+
+        // End of central directory record is followed by a comment of at most 65535 bytes
+        let tail_size = archive_size.min(22 + 65535);
+        let mut tail = vec![0; tail_size as usize];
+        reader.seek(SeekFrom::Start(archive_size - tail_size))?;
+        reader.read_exact(&mut tail)?;
+        let end_position = (0..tail.len().saturating_sub(21))
+            .rev()
+            .find(|position| {
+                LittleEndian::read_u32(&tail[*position..]) == END_OF_CENTRAL_DIRECTORY_SIGNATURE
+            })
+            .ok_or_else(|| invalid("not a ZIP archive"))?;
+        let end = &tail[end_position..];
+
+        let mut entry_count = u64::from(LittleEndian::read_u16(&end[10..]));
+        let mut directory_offset = u64::from(LittleEndian::read_u32(&end[16..]));
+
+        // ZIP64 locator is right before end of central directory record
+        if end_position >= 20
+            && LittleEndian::read_u32(&tail[end_position - 20..]) == ZIP64_LOCATOR_SIGNATURE
+        {
+            let zip64_end_offset = LittleEndian::read_u64(&tail[end_position - 12..]);
+            let mut zip64_end = [0; 56];
+            reader.seek(SeekFrom::Start(zip64_end_offset))?;
+            reader.read_exact(&mut zip64_end)?;
+            if LittleEndian::read_u32(&zip64_end) != ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE {
+                return Err(invalid("invalid ZIP64 end of central directory"));
+            }
+            entry_count = LittleEndian::read_u64(&zip64_end[32..]);
+            directory_offset = LittleEndian::read_u64(&zip64_end[48..]);
+        }
+
+        reader.seek(SeekFrom::Start(directory_offset))?;
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let mut header = [0; 46];
+            reader.read_exact(&mut header)?;
+            if LittleEndian::read_u32(&header) != CENTRAL_DIRECTORY_ENTRY_SIGNATURE {
+                return Err(invalid("invalid central directory entry"));
+            }
+
+            let mut name = vec![0; usize::from(LittleEndian::read_u16(&header[28..]))];
+            let mut extra = vec![0; usize::from(LittleEndian::read_u16(&header[30..]))];
+            let comment_length = LittleEndian::read_u16(&header[32..]);
+            reader.read_exact(&mut name)?;
+            reader.read_exact(&mut extra)?;
+            reader.seek(SeekFrom::Current(i64::from(comment_length)))?;
+
+            let mut entry = ZipEntry {
+                name: String::from_utf8_lossy(&name).into_owned(),
+                size: u64::from(LittleEndian::read_u32(&header[24..])),
+                method: LittleEndian::read_u16(&header[10..]),
+                compressed_size: u64::from(LittleEndian::read_u32(&header[20..])),
+                local_header_offset: u64::from(LittleEndian::read_u32(&header[42..])),
+            };
+            Self::apply_zip64_extra(&mut entry, &extra);
+            entries.push(entry);
+        }
+
+        Ok(Self { reader, entries })
+    }
+
+    /// Replace saturated 32 bits values of `entry` by the ones of its ZIP64 extra field, if any
+    fn apply_zip64_extra(entry: &mut ZipEntry, mut extra: &[u8]) {
+        while extra.len() >= 4 {
+            let id = LittleEndian::read_u16(extra);
+            let length = usize::from(LittleEndian::read_u16(&extra[2..])).min(extra.len() - 4);
+            let mut field = &extra[4..4 + length];
+            if id == ZIP64_EXTRA_FIELD {
+                // Only saturated values are present, in this order
+                for value in [
+                    &mut entry.size,
+                    &mut entry.compressed_size,
+                    &mut entry.local_header_offset,
+                ] {
+                    if *value == u64::from(u32::MAX) && field.len() >= 8 {
+                        *value = LittleEndian::read_u64(field);
+                        field = &field[8..];
+                    }
+                }
+            }
+            extra = &extra[4 + length..];
+        }
+    }
+
+    /// Entries of archive
+    pub(crate) fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    /// Open `entry` for reading its extracted content
+    pub(crate) fn open(&mut self, entry: &ZipEntry) -> Result<Box<dyn Read + '_>> {
+        let mut header = [0; 30];
+        self.reader
+            .seek(SeekFrom::Start(entry.local_header_offset))?;
+        self.reader.read_exact(&mut header)?;
+        if LittleEndian::read_u32(&header) != LOCAL_HEADER_SIGNATURE {
+            return Err(invalid("invalid local file header"));
+        }
+        let skipped = i64::from(LittleEndian::read_u16(&header[26..]))
+            + i64::from(LittleEndian::read_u16(&header[28..]));
+        self.reader.seek(SeekFrom::Current(skipped))?;
+
+        let data = (&mut self.reader).take(entry.compressed_size);
+        match entry.method {
+            METHOD_STORED => Ok(Box::new(data)),
+            METHOD_DEFLATED => Ok(Box::new(DeflateDecoder::new(data))),
+            method => Err(RustADBError::InvalidBundle(format!(
+                "unsupported compression method {method} for {}",
+                entry.name
+            ))),
+        }
+    }
+}
+
+/// Entry written by a [`ZipWriter`], kept for central directory
+#[derive(Debug)]
+struct WrittenEntry {
+    name: String,
+    crc: u32,
+    size: u64,
+    local_header_offset: u64,
+}
+
+/// [`Write`] implementation computing CRC and size of data written to an entry
+struct EntryWriter<'a, W: Write> {
+    inner: &'a mut W,
+    hasher: crc32fast::Hasher,
+    size: u64,
+}
+
+impl<W: Write> Write for EntryWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let amount = self.inner.write(buf)?;
+        self.hasher.update(&buf[..amount]);
+        self.size += amount as u64;
+        Ok(amount)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Minimal streaming writer of ZIP archives, whose entries are stored without compression.
+///
+/// Entries are written while their size is still unknown, so all sizes are written as ZIP64 values.
+#[derive(Debug)]
+pub(crate) struct ZipWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    entries: Vec<WrittenEntry>,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self {
+            writer,
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    fn write_bytes(&mut self, parts: &[&[u8]]) -> Result<()> {
+        for part in parts {
+            self.writer.write_all(part)?;
+            self.offset += part.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Add entry `name`, whose content is written by `f`
+    pub(crate) fn add_entry<F>(&mut self, name: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut dyn Write) -> Result<()>,
+    {
+        let local_header_offset = self.offset;
+        // Sizes are saturated, actual ones being in data descriptor
+        let zip64_extra = [
+            &ZIP64_EXTRA_FIELD.to_le_bytes()[..],
+            &16_u16.to_le_bytes(),
+            &[0; 16],
+        ]
+        .concat();
+        self.write_bytes(&[
+            &LOCAL_HEADER_SIGNATURE.to_le_bytes(),
+            &ZIP64_VERSION.to_le_bytes(),
+            &FLAG_DATA_DESCRIPTOR.to_le_bytes(),
+            &METHOD_STORED.to_le_bytes(),
+            &[0; 8],
+            &u32::MAX.to_le_bytes(),
+            &u32::MAX.to_le_bytes(),
+            &(name.len() as u16).to_le_bytes(),
+            &(zip64_extra.len() as u16).to_le_bytes(),
+            name.as_bytes(),
+            &zip64_extra,
+        ])?;
+
+        let mut entry_writer = EntryWriter {
+            inner: &mut self.writer,
+            hasher: crc32fast::Hasher::new(),
+            size: 0,
+        };
+        f(&mut entry_writer)?;
+        let size = entry_writer.size;
+        let crc = entry_writer.hasher.finalize();
+        self.offset += size;
+
+        self.write_bytes(&[
+            &DATA_DESCRIPTOR_SIGNATURE.to_le_bytes(),
+            &crc.to_le_bytes(),
+            &size.to_le_bytes(),
+            &size.to_le_bytes(),
+        ])?;
+
+        self.entries.push(WrittenEntry {
+            name: name.to_string(),
+            crc,
+            size,
+            local_header_offset,
+        });
+        Ok(())
+    }
+
+    /// Write central directory, and return underlying writer
+    pub(crate) fn finish(mut self) -> Result<W> {
+        let directory_offset = self.offset;
+        let entries = std::mem::take(&mut self.entries);
+        let entry_count = entries.len() as u64;
+        for entry in entries {
+            let zip64_extra = [
+                &ZIP64_EXTRA_FIELD.to_le_bytes()[..],
+                &24_u16.to_le_bytes(),
+                &entry.size.to_le_bytes(),
+                &entry.size.to_le_bytes(),
+                &entry.local_header_offset.to_le_bytes(),
+            ]
+            .concat();
+            self.write_bytes(&[
+                &CENTRAL_DIRECTORY_ENTRY_SIGNATURE.to_le_bytes(),
+                &ZIP64_VERSION.to_le_bytes(),
+                &ZIP64_VERSION.to_le_bytes(),
+                &FLAG_DATA_DESCRIPTOR.to_le_bytes(),
+                &METHOD_STORED.to_le_bytes(),
+                &[0; 4],
+                &entry.crc.to_le_bytes(),
+                &u32::MAX.to_le_bytes(),
+                &u32::MAX.to_le_bytes(),
+                &(entry.name.len() as u16).to_le_bytes(),
+                &(zip64_extra.len() as u16).to_le_bytes(),
+                &[0; 10],
+                &u32::MAX.to_le_bytes(),
+                entry.name.as_bytes(),
+                &zip64_extra,
+            ])?;
+        }
+
+        let directory_size = self.offset - directory_offset;
+        let zip64_end_offset = self.offset;
+        self.write_bytes(&[
+            &ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes(),
+            &44_u64.to_le_bytes(),
+            &ZIP64_VERSION.to_le_bytes(),
+            &ZIP64_VERSION.to_le_bytes(),
+            &[0; 8],
+            &entry_count.to_le_bytes(),
+            &entry_count.to_le_bytes(),
+            &directory_size.to_le_bytes(),
+            &directory_offset.to_le_bytes(),
+            &ZIP64_LOCATOR_SIGNATURE.to_le_bytes(),
+            &0_u32.to_le_bytes(),
+            &zip64_end_offset.to_le_bytes(),
+            &1_u32.to_le_bytes(),
+            &END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes(),
+            &[0; 4],
+            &u16::MAX.to_le_bytes(),
+            &u16::MAX.to_le_bytes(),
+            &u32::MAX.to_le_bytes(),
+            &u32::MAX.to_le_bytes(),
+            &[0; 2],
+        ])?;
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[test]
+fn test_zip_archive_stored_entry() {
+    // Archive holding a single stored `a.txt` file, containing `hello`
+    let mut archive = Vec::new();
+    let local_header = [
+        &LOCAL_HEADER_SIGNATURE.to_le_bytes()[..],
+        &[20, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        &0x3610_a686_u32.to_le_bytes(),
+        &5_u32.to_le_bytes(),
+        &5_u32.to_le_bytes(),
+        &5_u16.to_le_bytes(),
+        &0_u16.to_le_bytes(),
+        b"a.txt",
+        b"hello",
+    ]
+    .concat();
+    let directory_offset = local_header.len() as u32;
+    archive.extend(local_header);
+
+    let directory_entry = [
+        &CENTRAL_DIRECTORY_ENTRY_SIGNATURE.to_le_bytes()[..],
+        &[20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        &0x3610_a686_u32.to_le_bytes(),
+        &5_u32.to_le_bytes(),
+        &5_u32.to_le_bytes(),
+        &5_u16.to_le_bytes(),
+        &[0; 12],
+        &0_u32.to_le_bytes(),
+        b"a.txt",
+    ]
+    .concat();
+    let directory_size = directory_entry.len() as u32;
+    archive.extend(directory_entry);
+    archive.extend(
+        [
+            &END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes()[..],
+            &[0, 0, 0, 0, 1, 0, 1, 0],
+            &directory_size.to_le_bytes(),
+            &directory_offset.to_le_bytes(),
+            &[0, 0],
+        ]
+        .concat(),
+    );
+
+    let mut archive = ZipArchive::new(std::io::Cursor::new(archive)).expect("cannot read archive");
+    let entry = archive.entries()[0].clone();
+    assert_eq!((entry.name.as_str(), entry.size), ("a.txt", 5));
+
+    let mut content = String::new();
+    archive
+        .open(&entry)
+        .expect("cannot open entry")
+        .read_to_string(&mut content)
+        .expect("cannot read entry");
+    assert_eq!(content, "hello");
+}
+
+#[test]
+fn test_zip_writer_round_trip() {
+    let mut writer = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, content) in [("apk/base.apk", &b"base"[..]), ("data.tar", b"")] {
+        writer
+            .add_entry(name, |output| Ok(output.write_all(content)?))
+            .expect("cannot write entry");
+    }
+    let mut cursor = writer.finish().expect("cannot finish archive");
+    cursor.set_position(0);
+
+    let mut archive = ZipArchive::new(cursor).expect("cannot read archive");
+    let entries = archive.entries().to_vec();
+    assert_eq!(entries.len(), 2);
+    assert_eq!((entries[1].name.as_str(), entries[1].size), ("data.tar", 0));
+
+    let mut content = Vec::new();
+    archive
+        .open(&entries[0])
+        .expect("cannot open entry")
+        .read_to_end(&mut content)
+        .expect("cannot read entry");
+    assert_eq!(content, b"base");
+}
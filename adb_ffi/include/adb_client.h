@@ -0,0 +1,66 @@
+/*
+ * C bindings for adb_client library.
+ *
+ * Functions returning an int return 0 on success and -1 on error,
+ * functions returning a pointer return NULL on error.
+ * Use adb_last_error() to get details about last error of current thread,
+ * panics of library being reported as errors too.
+ */
+
+#ifndef ADB_CLIENT_H
+#define ADB_CLIENT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque handle to a device, released using adb_device_free(). */
+typedef struct AdbDevice AdbDevice;
+
+/* Byte buffer allocated by this library, released using adb_buffer_free(). */
+typedef struct AdbBuffer {
+    uint8_t *data;
+    size_t len;
+} AdbBuffer;
+
+/* Last error of current thread, or NULL. Valid until next failing call on this thread. */
+const char *adb_last_error(void);
+
+/* Release a buffer filled by this library. */
+void adb_buffer_free(AdbBuffer buffer);
+
+/* Device reachable through ADB server. Both arguments may be NULL to use defaults. */
+AdbDevice *adb_server_device_new(const char *serial, const char *server_addr);
+
+/* Device reachable over TCP at "ip:port". */
+AdbDevice *adb_tcp_device_new(const char *address);
+
+/* Only Android device plugged over USB. */
+AdbDevice *adb_usb_device_autodetect(void);
+
+/* Release a device. NULL is ignored. */
+void adb_device_free(AdbDevice *device);
+
+/* Run command in a shell, filling output with its output and error streams. */
+int adb_shell_command(AdbDevice *device, const char *command, AdbBuffer *output);
+
+/* Push local file to remote path. */
+int adb_push(AdbDevice *device, const char *local_path, const char *remote_path);
+
+/* Pull remote path into local file. */
+int adb_pull(AdbDevice *device, const char *remote_path, const char *local_path);
+
+/* Install local APK on device. */
+int adb_install(AdbDevice *device, const char *apk_path);
+
+/* List devices known by ADB server (NULL for default address), one "<serial>\t<state>" line each. */
+int adb_list_devices(const char *server_addr, AdbBuffer *output);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ADB_CLIENT_H */
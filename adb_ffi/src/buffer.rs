@@ -0,0 +1,55 @@
+use adb_client::{Result, RustADBError};
+
+use crate::error::into_unit;
+
+/// Byte buffer allocated by this library, to be released using [`adb_buffer_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct AdbBuffer {
+    /// Pointer to buffer content
+    pub data: *mut u8,
+    /// Length of buffer content, in bytes
+    pub len: usize,
+}
+
+impl From<Vec<u8>> for AdbBuffer {
+    fn from(value: Vec<u8>) -> Self {
+        let len = value.len();
+        let data = Box::into_raw(value.into_boxed_slice()).cast::<u8>();
+        Self { data, len }
+    }
+}
+
+/// Write `value` into buffer pointed to by `output`
+///
+/// # Safety
+///
+/// `output` must either be null or be valid for writes.
+pub(crate) unsafe fn write_buffer(output: *mut AdbBuffer, value: Vec<u8>) -> Result<()> {
+    if output.is_null() {
+        return Err(RustADBError::ADBRequestFailed(
+            "unexpected null output buffer".to_string(),
+        ));
+    }
+
+    // SAFETY: checked non-null above, validity is guaranteed by caller
+    unsafe { output.write(AdbBuffer::from(value)) };
+    Ok(())
+}
+
+/// Release a buffer previously filled by this library.
+///
+/// # Safety
+///
+/// `buffer` must have been filled by this library and not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_buffer_free(buffer: AdbBuffer) {
+    into_unit(|| {
+        if !buffer.data.is_null() {
+            let data = std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len);
+            // SAFETY: buffer has been allocated as a boxed slice of this length in `AdbBuffer::from`
+            drop(unsafe { Box::from_raw(data) });
+        }
+        Ok(())
+    })
+}
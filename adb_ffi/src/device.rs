@@ -0,0 +1,180 @@
+use std::ffi::{c_char, c_int};
+use std::fs::File;
+use std::net::{SocketAddr, SocketAddrV4};
+
+use adb_client::{ADBDeviceExt, ADBServerDevice, ADBTcpDevice, ADBUSBDevice, Result, RustADBError};
+
+use crate::buffer::{AdbBuffer, write_buffer};
+use crate::error::{into_pointer, into_status, into_unit};
+use crate::{c_str, optional_c_str};
+
+/// Opaque handle to a device, to be released using [`adb_device_free`].
+pub struct AdbDevice {
+    inner: Box<dyn ADBDeviceExt>,
+}
+
+impl std::fmt::Debug for AdbDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdbDevice").finish_non_exhaustive()
+    }
+}
+
+/// Get a mutable reference to device pointed to by `device`
+///
+/// # Safety
+///
+/// `device` must either be null or have been returned by this library and not released yet.
+unsafe fn device_mut<'a>(device: *mut AdbDevice) -> Result<&'a mut dyn ADBDeviceExt> {
+    // SAFETY: guaranteed by caller
+    match unsafe { device.as_mut() } {
+        Some(device) => Ok(device.inner.as_mut()),
+        None => Err(RustADBError::ADBRequestFailed(
+            "unexpected null device".to_string(),
+        )),
+    }
+}
+
+/// Instantiate a device reachable through ADB server listening on `server_addr` (e.g. `127.0.0.1:5037`).
+///
+/// `serial` can be `NULL` if only one device is connected, `server_addr` can be `NULL` to use default server address.
+///
+/// # Safety
+///
+/// `serial` and `server_addr` must either be `NULL` or NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_server_device_new(
+    serial: *const c_char,
+    server_addr: *const c_char,
+) -> *mut AdbDevice {
+    // SAFETY: guaranteed by caller
+    into_pointer(|| unsafe {
+        let server_addr = optional_c_str(server_addr)?
+            .map(str::parse::<SocketAddrV4>)
+            .transpose()?;
+        let device = match optional_c_str(serial)? {
+            Some(serial) => ADBServerDevice::new(serial.to_string(), server_addr),
+            None => ADBServerDevice::autodetect(server_addr),
+        };
+
+        Ok(AdbDevice {
+            inner: device.boxed(),
+        })
+    })
+}
+
+/// Connect to a device over TCP at `address` (e.g. `192.168.0.10:5555`).
+///
+/// # Safety
+///
+/// `address` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_tcp_device_new(address: *const c_char) -> *mut AdbDevice {
+    // SAFETY: guaranteed by caller
+    into_pointer(|| unsafe {
+        let address = c_str(address)?.parse::<SocketAddr>()?;
+
+        Ok(AdbDevice {
+            inner: ADBTcpDevice::new(address)?.boxed(),
+        })
+    })
+}
+
+/// Connect to the only Android device plugged over USB.
+#[unsafe(no_mangle)]
+pub extern "C" fn adb_usb_device_autodetect() -> *mut AdbDevice {
+    into_pointer(|| {
+        ADBUSBDevice::autodetect().map(|device| AdbDevice {
+            inner: device.boxed(),
+        })
+    })
+}
+
+/// Release a device previously returned by this library. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `device` must either be `NULL` or have been returned by this library and not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_device_free(device: *mut AdbDevice) {
+    into_unit(|| {
+        if !device.is_null() {
+            // SAFETY: guaranteed by caller
+            drop(unsafe { Box::from_raw(device) });
+        }
+        Ok(())
+    })
+}
+
+/// Run `command` in a shell on the device, filling `output` with its output and error streams.
+///
+/// # Safety
+///
+/// `device` must be a valid device, `command` a NUL-terminated string and `output` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_shell_command(
+    device: *mut AdbDevice,
+    command: *const c_char,
+    output: *mut AdbBuffer,
+) -> c_int {
+    // SAFETY: guaranteed by caller
+    into_status(|| unsafe {
+        let device = device_mut(device)?;
+        let mut buffer = Vec::new();
+        device.shell_command(&[c_str(command)?], &mut buffer)?;
+        write_buffer(output, buffer)
+    })
+}
+
+/// Push local file at `local_path` to `remote_path` on the device.
+///
+/// # Safety
+///
+/// `device` must be a valid device, `local_path` and `remote_path` NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_push(
+    device: *mut AdbDevice,
+    local_path: *const c_char,
+    remote_path: *const c_char,
+) -> c_int {
+    // SAFETY: guaranteed by caller
+    into_status(|| unsafe {
+        let device = device_mut(device)?;
+        let mut input = File::open(c_str(local_path)?)?;
+        device.push(&mut input, &c_str(remote_path)?)?;
+        Ok(())
+    })
+}
+
+/// Pull `remote_path` from the device into local file at `local_path`.
+///
+/// # Safety
+///
+/// `device` must be a valid device, `remote_path` and `local_path` NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_pull(
+    device: *mut AdbDevice,
+    remote_path: *const c_char,
+    local_path: *const c_char,
+) -> c_int {
+    // SAFETY: guaranteed by caller
+    into_status(|| unsafe {
+        let device = device_mut(device)?;
+        let mut output = File::create(c_str(local_path)?)?;
+        device.pull(&c_str(remote_path)?, &mut output)?;
+        Ok(())
+    })
+}
+
+/// Install APK at local `apk_path` on the device.
+///
+/// # Safety
+///
+/// `device` must be a valid device, `apk_path` a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_install(device: *mut AdbDevice, apk_path: *const c_char) -> c_int {
+    // SAFETY: guaranteed by caller
+    into_status(|| unsafe {
+        let device = device_mut(device)?;
+        device.install(&c_str(apk_path)?)
+    })
+}
@@ -0,0 +1,88 @@
+use std::cell::RefCell;
+use std::ffi::{CString, c_char, c_int};
+use std::panic::AssertUnwindSafe;
+
+use adb_client::{Result, RustADBError};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Store `error` as last error of current thread
+pub(crate) fn set_last_error(error: RustADBError) {
+    // Interior NUL bytes cannot be represented in a C string
+    let message = CString::new(error.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Run `f`, turning a panic into an error as it must not unwind into caller
+fn catch_panic<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        Err(RustADBError::ADBRequestFailed(format!(
+            "library panicked: {message}"
+        )))
+    })
+}
+
+/// Run `f` and convert its result into a status code, saving error if any
+pub(crate) fn into_status(f: impl FnOnce() -> Result<()>) -> c_int {
+    match catch_panic(f) {
+        Ok(()) => 0,
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Run `f` and convert its result into an owned pointer, null on error
+pub(crate) fn into_pointer<T>(f: impl FnOnce() -> Result<T>) -> *mut T {
+    match catch_panic(f) {
+        Ok(value) => Box::into_raw(Box::new(value)),
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Run `f` for a function without return value, saving error if any
+pub(crate) fn into_unit(f: impl FnOnce() -> Result<()>) {
+    if let Err(e) = catch_panic(f) {
+        set_last_error(e);
+    }
+}
+
+/// Return a description of last error that occurred on current thread, or `NULL` if none.
+///
+/// Returned string is owned by this library and stays valid until next failing call on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn adb_last_error() -> *const c_char {
+    std::panic::catch_unwind(|| {
+        LAST_ERROR.with(|last_error| {
+            last_error
+                .borrow()
+                .as_ref()
+                .map_or(std::ptr::null(), |message| message.as_ptr())
+        })
+    })
+    .unwrap_or(std::ptr::null())
+}
+
+#[test]
+fn test_panic_is_reported_as_error() {
+    assert_eq!(into_status(|| panic!("unexpected state")), -1);
+    assert!(into_pointer::<()>(|| panic!("{} state", "unexpected")).is_null());
+
+    // SAFETY: pointer returned by `adb_last_error` is a valid C string until next failing call
+    let message = unsafe { std::ffi::CStr::from_ptr(adb_last_error()) };
+    assert_eq!(
+        message.to_str().expect("invalid last error"),
+        "ADB request failed - library panicked: unexpected state"
+    );
+}
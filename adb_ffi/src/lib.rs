@@ -0,0 +1,46 @@
+#![forbid(missing_docs)]
+#![forbid(missing_debug_implementations)]
+#![doc = include_str!("../README.md")]
+
+mod buffer;
+mod device;
+mod error;
+mod server;
+pub use buffer::*;
+pub use device::*;
+pub use error::*;
+pub use server::*;
+
+use std::ffi::{CStr, c_char};
+
+use adb_client::{Result, RustADBError};
+
+/// Convert a C string given by caller into a Rust string slice
+///
+/// # Safety
+///
+/// `value` must either be null or point to a NUL-terminated string valid for `'a`.
+unsafe fn c_str<'a>(value: *const c_char) -> Result<&'a str> {
+    if value.is_null() {
+        return Err(RustADBError::ADBRequestFailed(
+            "unexpected null string argument".to_string(),
+        ));
+    }
+
+    // SAFETY: checked non-null above, validity is guaranteed by caller
+    Ok(unsafe { CStr::from_ptr(value) }.to_str()?)
+}
+
+/// Same as [`c_str`], but mapping a null pointer to `None`
+///
+/// # Safety
+///
+/// Same as [`c_str`].
+unsafe fn optional_c_str<'a>(value: *const c_char) -> Result<Option<&'a str>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+
+    // SAFETY: guaranteed by caller
+    unsafe { c_str(value) }.map(Some)
+}
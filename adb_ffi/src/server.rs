@@ -0,0 +1,36 @@
+use std::ffi::{c_char, c_int};
+use std::net::SocketAddrV4;
+
+use adb_client::ADBServer;
+
+use crate::buffer::{AdbBuffer, write_buffer};
+use crate::error::into_status;
+use crate::optional_c_str;
+
+/// List devices known by ADB server listening on `server_addr`, or on default address if `NULL`.
+///
+/// `output` is filled with one `<serial>\t<state>` line per device.
+///
+/// # Safety
+///
+/// `server_addr` must either be `NULL` or a NUL-terminated string, `output` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn adb_list_devices(
+    server_addr: *const c_char,
+    output: *mut AdbBuffer,
+) -> c_int {
+    // SAFETY: guaranteed by caller
+    into_status(|| unsafe {
+        let mut server = match optional_c_str(server_addr)? {
+            Some(server_addr) => ADBServer::new(server_addr.parse::<SocketAddrV4>()?),
+            None => ADBServer::default(),
+        };
+
+        let devices: String = server
+            .devices()?
+            .iter()
+            .map(|device| format!("{device}\n"))
+            .collect();
+        write_buffer(output, devices.into_bytes())
+    })
+}
@@ -0,0 +1,105 @@
+use std::fs::File;
+use std::net::{SocketAddr, SocketAddrV4};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use adb_client::{ADBDeviceExt, ADBServerDevice, ADBTcpDevice, ADBUSBDevice};
+
+use crate::AdbError;
+
+/// Android device, reachable either over USB, TCP or through ADB server.
+#[derive(uniffi::Object)]
+pub struct AdbDevice {
+    inner: Mutex<Box<dyn ADBDeviceExt + Send>>,
+}
+
+impl std::fmt::Debug for AdbDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdbDevice").finish_non_exhaustive()
+    }
+}
+
+impl AdbDevice {
+    fn new<D: ADBDeviceExt + Send + 'static>(device: D) -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(Box::new(device)),
+        })
+    }
+
+    fn device(&self) -> MutexGuard<'_, Box<dyn ADBDeviceExt + Send>> {
+        // A panic in a previous call cannot leave device handle in an inconsistent state
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[uniffi::export]
+impl AdbDevice {
+    /// Device reachable through ADB server listening on `server_addr` (default address if not set).
+    /// If `serial` is not set, only one device must be connected.
+    #[uniffi::constructor]
+    pub fn server(
+        serial: Option<String>,
+        server_addr: Option<String>,
+    ) -> Result<Arc<Self>, AdbError> {
+        let server_addr = server_addr
+            .map(|server_addr| server_addr.parse::<SocketAddrV4>())
+            .transpose()?;
+
+        Ok(Self::new(match serial {
+            Some(serial) => ADBServerDevice::new(serial, server_addr),
+            None => ADBServerDevice::autodetect(server_addr),
+        }))
+    }
+
+    /// Device reachable over TCP at `address` (e.g. `192.168.0.10:5555`).
+    #[uniffi::constructor]
+    pub fn tcp(address: String) -> Result<Arc<Self>, AdbError> {
+        Ok(Self::new(ADBTcpDevice::new(
+            address.parse::<SocketAddr>()?,
+        )?))
+    }
+
+    /// Only device plugged over USB.
+    #[uniffi::constructor]
+    pub fn usb_autodetect() -> Result<Arc<Self>, AdbError> {
+        Ok(Self::new(ADBUSBDevice::autodetect()?))
+    }
+
+    /// Run shell commands on device and return the output (stdout + stderr merged)
+    pub fn shell_command(&self, commands: Vec<String>) -> Result<Vec<u8>, AdbError> {
+        let commands: Vec<&str> = commands.iter().map(String::as_str).collect();
+        let mut output = Vec::new();
+        self.device().shell_command(&commands, &mut output)?;
+        Ok(output)
+    }
+
+    /// Push local file at `input` to `dest` on device
+    pub fn push(&self, input: String, dest: String) -> Result<(), AdbError> {
+        let mut reader = File::open(input)?;
+        self.device().push(&mut reader, &dest)?;
+        Ok(())
+    }
+
+    /// Pull file located at `input` on device to local `dest`
+    pub fn pull(&self, input: String, dest: String) -> Result<(), AdbError> {
+        let mut writer = File::create(dest)?;
+        self.device().pull(&input, &mut writer)?;
+        Ok(())
+    }
+
+    /// Install local APK located at `apk_path` on device
+    pub fn install(&self, apk_path: String) -> Result<(), AdbError> {
+        Ok(self.device().install(&apk_path)?)
+    }
+
+    /// Uninstall `package` from device
+    pub fn uninstall(&self, package: String) -> Result<(), AdbError> {
+        Ok(self.device().uninstall(&package)?)
+    }
+
+    /// Dump device framebuffer as `PNG` bytes
+    pub fn framebuffer_bytes(&self) -> Result<Vec<u8>, AdbError> {
+        Ok(self.device().framebuffer_bytes()?)
+    }
+}
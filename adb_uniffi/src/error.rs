@@ -0,0 +1,16 @@
+use adb_client::RustADBError;
+
+/// Error raised by all exported functions.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum AdbError {
+    /// Error returned by underlying library
+    #[error(transparent)]
+    Adb(#[from] RustADBError),
+    /// Local file could not be accessed
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Given address could not be parsed
+    #[error(transparent)]
+    InvalidAddress(#[from] std::net::AddrParseError),
+}
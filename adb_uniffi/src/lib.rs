@@ -0,0 +1,11 @@
+#![forbid(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod device;
+mod error;
+mod server;
+pub use device::*;
+pub use error::*;
+pub use server::*;
+
+uniffi::setup_scaffolding!();
@@ -0,0 +1,32 @@
+use std::net::SocketAddrV4;
+
+use adb_client::ADBServer;
+
+use crate::AdbError;
+
+/// Device known by ADB server.
+#[derive(Debug, uniffi::Record)]
+pub struct DeviceInfo {
+    /// Unique device identifier
+    pub identifier: String,
+    /// Connection state of the device (e.g. `device`, `offline`...)
+    pub state: String,
+}
+
+/// List devices known by ADB server listening on `server_addr`, or on default address if not set.
+#[uniffi::export]
+pub fn list_devices(server_addr: Option<String>) -> Result<Vec<DeviceInfo>, AdbError> {
+    let mut server = match server_addr {
+        Some(server_addr) => ADBServer::new(server_addr.parse::<SocketAddrV4>()?),
+        None => ADBServer::default(),
+    };
+
+    Ok(server
+        .devices()?
+        .into_iter()
+        .map(|device| DeviceInfo {
+            identifier: device.identifier,
+            state: device.state.to_string(),
+        })
+        .collect())
+}
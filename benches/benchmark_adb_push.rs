@@ -35,7 +35,8 @@ fn bench_adb_client_push() -> Result<()> {
     let mut client = ADBServer::default();
     let mut device = client.get_device()?;
     let f = File::open(LOCAL_TEST_FILE_PATH)?;
-    Ok(device.push(f, REMOTE_TEST_FILE_PATH)?)
+    device.push(f, REMOTE_TEST_FILE_PATH)?;
+    Ok(())
 }
 
 /// Use standard `adb` command ti push a file on device
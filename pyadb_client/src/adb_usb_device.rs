@@ -31,13 +31,15 @@ impl PyADBUSBDevice {
     /// Push a local file from input to dest
     pub fn push(&mut self, input: PathBuf, dest: PathBuf) -> Result<()> {
         let mut reader = File::open(input)?;
-        Ok(self.0.push(&mut reader, &dest.to_string_lossy())?)
+        self.0.push(&mut reader, &dest.to_string_lossy())?;
+        Ok(())
     }
 
     /// Pull a file from device located at input, and drop it to dest
     pub fn pull(&mut self, input: PathBuf, dest: PathBuf) -> Result<()> {
         let mut writer = File::create(dest)?;
-        Ok(self.0.pull(&input.to_string_lossy(), &mut writer)?)
+        self.0.pull(&input.to_string_lossy(), &mut writer)?;
+        Ok(())
     }
 
     /// Install a package installed on the device